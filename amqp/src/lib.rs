@@ -3,6 +3,7 @@ mod handler;
 mod manager;
 mod publisher;
 
+pub use crate::error::MessageBrokerError;
 pub use crate::handler::IncomingMessageHandler;
 pub use crate::manager::RabbitMqManager;
 pub use crate::publisher::Publisher;
@@ -44,13 +44,15 @@ impl RabbitMqManager {
                         continue;
                     }
                 };
-                let message = String::from_utf8_lossy(&delivery.data).to_string();
-                log::info!("Received message from queue [{}]:[{}]", consumer.queue(), message);
-                message_handler.handle_message(message).await;
-                delivery
-                    .ack(BasicAckOptions::default())
-                    .await
-                    .expect("Failed to ack message");
+                log::info!(
+                    "Received message from queue [{}]: {} bytes",
+                    consumer.queue(),
+                    delivery.data.len()
+                );
+                message_handler.handle_message(delivery.data.clone()).await;
+                if let Err(error) = delivery.ack(BasicAckOptions::default()).await {
+                    log::error!("Failed to ack message: {}", error);
+                }
             }
         });
         Ok(())
@@ -13,7 +13,7 @@ impl Publisher {
         &self,
         exchange: &str,
         rooting_key: &str,
-        message: String,
+        message: Vec<u8>,
     ) -> Result<(), MessageBrokerError> {
         let publisher_confirm = match self
             .channel
@@ -21,7 +21,7 @@ impl Publisher {
                 exchange,
                 rooting_key,
                 BasicPublishOptions::default(),
-                message.as_bytes(),
+                &message,
                 BasicProperties::default(),
             )
             .await
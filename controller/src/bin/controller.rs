@@ -1,61 +1,114 @@
-use controller::{Config, ControllerService, MessageHandler};
+use controller::{
+    Channels, Config, ControllerService, DeliveryQueue, DeliveryScheduler, MessageHandler, Moderation,
+    RateLimiter, RoutingTable,
+};
 use dotenv::dotenv;
 use amqp::RabbitMqManager;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 
+const PENDING_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const RATE_LIMITS_SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     pretty_env_logger::init();
 
     let config = envy::from_env::<Config>().unwrap();
+    let routes = RoutingTable::from_config(&config).unwrap();
+    let limiter = RateLimiter::from_config(&config).unwrap();
+    let moderation = Moderation::from_config(&config).unwrap();
 
     let mut manager = RabbitMqManager::builder()
         .build(&config.amqp_address)
         .await
         .unwrap();
     let publisher = manager.get_publisher().await.unwrap();
+    let (delivery, fan_outs) = DeliveryQueue::new();
     let service = Arc::new(Mutex::new(ControllerService::new(
         config.clone(),
+        routes,
+        limiter,
+        moderation,
+        delivery,
         publisher,
     )));
+    let channels = Channels::from_config(&config, service.clone()).unwrap();
+    tokio::spawn(DeliveryScheduler::new(&config, service.clone(), channels, fan_outs).run());
+    let expiry = service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PENDING_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            expiry.lock().await.expire_pending_requests().await;
+        }
+    });
+    let rate_limits = service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RATE_LIMITS_SAVE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let snapshot = rate_limits.lock().await.rate_limits_snapshot();
+            if let Some(snapshot) = snapshot {
+                snapshot.save().await;
+            }
+        }
+    });
 
     manager
         .add_consumer(
             &config.client_request_queue,
-            MessageHandler::client_request(service.clone()),
+            MessageHandler::client_request(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
     manager
         .add_consumer(
             &config.customer_request_queue,
-            MessageHandler::customer_request(service.clone()),
+            MessageHandler::customer_request(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
     manager
         .add_consumer(
             &config.client_repository_response_queue,
-            MessageHandler::client_response_from_repository(service.clone()),
+            MessageHandler::client_response_from_repository(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
     manager
         .add_consumer(
             &config.customer_repository_response_queue,
-            MessageHandler::customer_response_from_repository(service.clone()),
+            MessageHandler::customer_response_from_repository(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
     manager
         .add_consumer(
             &config.repository_response_queue,
-            MessageHandler::response_from_repository(service.clone()),
+            MessageHandler::response_from_repository(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
+    if let Some(moderation_queue) = &config.moderation_queue {
+        manager
+            .add_consumer(
+                moderation_queue,
+                MessageHandler::moderation_decision(service.clone(), config.wire_format),
+            )
+            .await
+            .unwrap();
+    }
+    if let Some(webhook_response_queue) = &config.webhook_response_queue {
+        manager
+            .add_consumer(
+                webhook_response_queue,
+                MessageHandler::webhook_response(service.clone(), config.wire_format),
+            )
+            .await
+            .unwrap();
+    }
 
     manager.run();
 }
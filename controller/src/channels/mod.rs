@@ -0,0 +1,94 @@
+mod email;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::models::{DeliveryChannel, Notification};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+pub use email::EmailChannel;
+
+use crate::{Config, ControllerError, ControllerService, Transformer};
+
+#[derive(Debug, Error)]
+pub enum ChannelError {
+    #[error("Telegram delivery error: [{0}]")]
+    TelegramFailure(#[from] ControllerError),
+    #[error("Email delivery error: [{0}]")]
+    EmailFailure(String),
+    #[error("Invalid email address: [{0}]")]
+    InvalidAddress(String),
+    #[error("Channel configuration error: [{0}]")]
+    ConfigFailure(String),
+    #[error("No configured channel for: [{0:?}]")]
+    Unavailable(DeliveryChannel),
+}
+
+/// A way to get a notification to its subscriber.
+#[async_trait]
+pub trait Channel: Send + Sync {
+    /// Whether the subscriber asked for the notification through this channel.
+    fn accepts(&self, notification: &Notification) -> bool;
+    async fn deliver(&self, notification: &Notification) -> Result<(), ChannelError>;
+}
+
+/// Publishes the notification to the client bot through the routing table.
+pub struct TelegramChannel {
+    service: Arc<Mutex<ControllerService>>,
+}
+
+impl TelegramChannel {
+    pub fn new(service: Arc<Mutex<ControllerService>>) -> Self {
+        TelegramChannel { service }
+    }
+}
+
+#[async_trait]
+impl Channel for TelegramChannel {
+    fn accepts(&self, notification: &Notification) -> bool {
+        notification.channel.telegram()
+    }
+
+    async fn deliver(&self, notification: &Notification) -> Result<(), ChannelError> {
+        let response = Transformer::notification_to_client_response(notification);
+        self.service.lock().await.route(&response).await?;
+        Ok(())
+    }
+}
+
+/// Delivery channels of the controller. Telegram is always available, email
+/// only when `smtp_url` is set.
+pub struct Channels {
+    channels: Vec<Box<dyn Channel>>,
+}
+
+impl Channels {
+    pub fn from_config(
+        config: &Config,
+        service: Arc<Mutex<ControllerService>>,
+    ) -> Result<Self, ChannelError> {
+        let mut channels: Vec<Box<dyn Channel>> = vec![Box::new(TelegramChannel::new(service))];
+        if let Some(email) = EmailChannel::from_config(config)? {
+            channels.push(Box::new(email));
+        }
+        Ok(Channels { channels })
+    }
+
+    /// Delivers the notification through every channel the subscriber asked for.
+    /// Fails if none of them is configured or any of them failed.
+    pub async fn deliver(&self, notification: &Notification) -> Result<(), ChannelError> {
+        let mut result = Err(ChannelError::Unavailable(notification.channel));
+        for channel in &self.channels {
+            if !channel.accepts(notification) {
+                continue;
+            }
+            match (channel.deliver(notification).await, &result) {
+                (Err(error), _) => result = Err(error),
+                (Ok(()), Err(ChannelError::Unavailable(_))) => result = Ok(()),
+                (Ok(()), _) => {}
+            }
+        }
+        result
+    }
+}
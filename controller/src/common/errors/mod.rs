@@ -0,0 +1,17 @@
+use amqp::MessageBrokerError;
+use domain::codec::DecodeError;
+use thiserror::Error;
+
+use crate::MessageType;
+
+#[derive(Debug, Error)]
+pub enum ControllerError {
+    #[error("Decode message error: [{0}]")]
+    DecodeFailure(#[from] DecodeError),
+    #[error("Publish message error: [{0}]")]
+    PublishFailure(#[from] MessageBrokerError),
+    #[error("No route for message: [{0:?}]")]
+    NoRoute(MessageType),
+    #[error("No held notification with id: [{0}]")]
+    UnknownHeldNotification(u64),
+}
@@ -1,3 +1,6 @@
+pub mod errors;
+
+use domain::codec::WireFormat;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone)]
@@ -15,4 +18,46 @@ pub struct Config {
     pub client_repository_response_queue: String,
     pub customer_repository_response_queue: String,
     pub repository_response_queue: String,
+    /// JSON routing table, replaces the routes built from the queues above for
+    /// the message types it mentions.
+    #[serde(default)]
+    pub routing_table_path: Option<String>,
+    /// JSON file with notification quotas of the customers, unlimited if not set.
+    #[serde(default)]
+    pub quotas_path: Option<String>,
+    /// File keeping the rate limiter state between restarts.
+    #[serde(default)]
+    pub rate_limits_path: Option<String>,
+    /// JSON file with the moderation rules for notifications, nothing is checked if not set.
+    #[serde(default)]
+    pub moderation_path: Option<String>,
+    /// File keeping the notifications held for manual review between restarts.
+    #[serde(default)]
+    pub moderation_hold_path: Option<String>,
+    /// Queue with the decisions of moderators on held notifications.
+    #[serde(default)]
+    pub moderation_queue: Option<String>,
+    /// Seconds to wait for a repository response before the user gets a failure, 30 by default.
+    #[serde(default)]
+    pub repository_timeout_secs: Option<u64>,
+    /// SMTP server of the email channel, e.g. `smtp://localhost:1025`. Email is disabled if not set.
+    #[serde(default)]
+    pub smtp_url: Option<String>,
+    /// Sender address of notification emails, required with `smtp_url`.
+    #[serde(default)]
+    pub smtp_from: Option<String>,
+    /// Queue of the webhook service, webhooks are disabled if not set.
+    #[serde(default)]
+    pub webhook_queue: Option<String>,
+    /// Queue with the responses of the webhook service.
+    #[serde(default)]
+    pub webhook_response_queue: Option<String>,
+    /// Client notifications published per second, 30 by default.
+    #[serde(default)]
+    pub delivery_rate: Option<u32>,
+    /// Number of published notifications between progress reports, 100 by default.
+    #[serde(default)]
+    pub delivery_progress_step: Option<u32>,
+    #[serde(default)]
+    pub wire_format: WireFormat,
 }
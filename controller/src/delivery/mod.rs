@@ -0,0 +1,192 @@
+use std::{collections::VecDeque, sync::Arc, time::Duration, vec};
+
+use domain::{
+    models::{CustomerName, Notification, ProductName, Timestamp, UserId, WebhookEvent},
+    requests::WebhookRequest,
+    responses::CustomerResponse,
+};
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        Mutex,
+    },
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{Channels, Config, ControllerService};
+
+const DEFAULT_DELIVERY_RATE: u32 = 30;
+const DEFAULT_PROGRESS_STEP: u32 = 100;
+
+/// Notifications of one customer message for its subscribers, `user_id` is
+/// the customer user who receives the progress reports.
+#[derive(Debug)]
+pub struct FanOut {
+    pub user_id: u32,
+    pub notifications: Vec<Notification>,
+}
+
+/// Queue of fan-outs waiting for the `DeliveryScheduler`.
+#[derive(Clone)]
+pub struct DeliveryQueue {
+    sender: UnboundedSender<FanOut>,
+}
+
+impl DeliveryQueue {
+    pub fn new() -> (Self, UnboundedReceiver<FanOut>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (DeliveryQueue { sender }, receiver)
+    }
+    pub fn push(&self, fan_out: FanOut) {
+        if let Err(error) = self.sender.send(fan_out) {
+            log::error!(
+                "Delivery scheduler is stopped, dropping {} notifications",
+                error.0.notifications.len()
+            );
+        }
+    }
+}
+
+struct ActiveFanOut {
+    user_id: u32,
+    source: Option<(CustomerName, ProductName)>,
+    pending: vec::IntoIter<Notification>,
+    /// Handed over by every channel: published to the client bot or accepted
+    /// by the SMTP server. The bot may still fail to send them.
+    published: u32,
+    failed: u32,
+    total: u32,
+}
+
+impl ActiveFanOut {
+    fn new(fan_out: FanOut) -> Self {
+        let source = fan_out
+            .notifications
+            .first()
+            .map(|notification| (notification.customer.clone(), notification.product.clone()));
+        ActiveFanOut {
+            user_id: fan_out.user_id,
+            source,
+            total: fan_out.notifications.len() as u32,
+            pending: fan_out.notifications.into_iter(),
+            published: 0,
+            failed: 0,
+        }
+    }
+    fn done(&self) -> u32 {
+        self.published + self.failed
+    }
+}
+
+/// Delivers client notifications through their `Channels` at `delivery_rate`
+/// per second so a large fan-out is spread over time instead of flooding the
+/// client bot. Active fan-outs take turns, and every `delivery_progress_step`
+/// notifications and at the end the sender gets `CustomerResponse::DeliveryProgress`.
+/// A finished fan-out is also posted to the webhook of the customer.
+pub struct DeliveryScheduler {
+    service: Arc<Mutex<ControllerService>>,
+    channels: Channels,
+    fan_outs: UnboundedReceiver<FanOut>,
+    period: Duration,
+    progress_step: u32,
+}
+
+impl DeliveryScheduler {
+    pub fn new(
+        config: &Config,
+        service: Arc<Mutex<ControllerService>>,
+        channels: Channels,
+        fan_outs: UnboundedReceiver<FanOut>,
+    ) -> Self {
+        let rate = config.delivery_rate.unwrap_or(DEFAULT_DELIVERY_RATE).max(1);
+        let progress_step = config
+            .delivery_progress_step
+            .unwrap_or(DEFAULT_PROGRESS_STEP)
+            .max(1);
+        DeliveryScheduler {
+            service,
+            channels,
+            fan_outs,
+            period: Duration::from_secs(1) / rate,
+            progress_step,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut ticker = time::interval(self.period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut active: VecDeque<ActiveFanOut> = VecDeque::new();
+        loop {
+            if active.is_empty() {
+                match self.fan_outs.recv().await {
+                    Some(fan_out) => active.push_back(ActiveFanOut::new(fan_out)),
+                    None => return,
+                }
+            }
+            while let Ok(fan_out) = self.fan_outs.try_recv() {
+                active.push_back(ActiveFanOut::new(fan_out));
+            }
+
+            let mut fan_out = active.pop_front().unwrap();
+            if let Some(notification) = fan_out.pending.next() {
+                ticker.tick().await;
+                self.deliver(&mut fan_out, notification).await;
+            }
+            if fan_out.done() == fan_out.total {
+                self.report(&fan_out).await;
+                self.finish(&fan_out).await;
+            } else {
+                if fan_out.done().is_multiple_of(self.progress_step) {
+                    self.report(&fan_out).await;
+                }
+                active.push_back(fan_out);
+            }
+        }
+    }
+
+    async fn deliver(&self, fan_out: &mut ActiveFanOut, notification: Notification) {
+        match self.channels.deliver(&notification).await {
+            Ok(()) => fan_out.published += 1,
+            Err(error) => {
+                fan_out.failed += 1;
+                log::error!(
+                    "Failed to deliver notification to user [{}]: {}",
+                    notification.user_id,
+                    error
+                );
+            }
+        }
+    }
+
+    async fn report(&self, fan_out: &ActiveFanOut) {
+        let response = CustomerResponse::DeliveryProgress {
+            user_id: UserId::from(fan_out.user_id),
+            published: fan_out.published,
+            failed: fan_out.failed,
+            total: fan_out.total,
+        };
+        if let Err(error) = self.service.lock().await.route(&response).await {
+            log::error!(
+                "Failed to report delivery progress to user [{}]: {}",
+                fan_out.user_id,
+                error
+            );
+        }
+    }
+
+    async fn finish(&self, fan_out: &ActiveFanOut) {
+        if let Some((customer, product)) = &fan_out.source {
+            let request = WebhookRequest::Event {
+                customer: customer.clone(),
+                event: WebhookEvent::DeliveryFinished {
+                    product: product.clone(),
+                    published: fan_out.published,
+                    failed: fan_out.failed,
+                    total: fan_out.total,
+                },
+                timestamp: Timestamp::now(),
+            };
+            self.service.lock().await.notify_webhook(request).await;
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use std::sync::Arc;
 
+use amqp::IncomingMessageHandler;
 use domain::{
-    requests::{ClientRequest, CustomerRequest},
+    codec::{Correlated, WireFormat, WireMessage},
+    requests::{ClientRequest, CustomerRequest, ModerationDecision},
     responses::{
         ClientResponseFromRepository, CustomerResponseFromRepository, ResponseFromRepository,
+        WebhookResponse,
     },
 };
-use amqp::IncomingMessageHandler;
 use tokio::sync::Mutex;
 
 use crate::ControllerService;
@@ -15,74 +17,161 @@ pub struct MessageHandler {}
 impl MessageHandler {
     pub fn client_request(
         service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let request: ClientRequest = serde_json::from_str(&message).unwrap();
-                service.lock().await.handle_client_request(request).await;
+                let result = match ClientRequest::decode(&message, wire_format) {
+                    Ok(request) => service.lock().await.handle_client_request(request).await,
+                    Err(error) => Err(error.into()),
+                };
+                if let Err(error) = result {
+                    log::error!("Message handler client request error: {}", error);
+                }
             }
         }
     }
 
     pub fn customer_request(
         service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let request: CustomerRequest = serde_json::from_str(&message).unwrap();
-                service.lock().await.handle_customer_request(request).await;
+                let result = match CustomerRequest::decode(&message, wire_format) {
+                    Ok(request) => service.lock().await.handle_customer_request(request).await,
+                    Err(error) => Err(error.into()),
+                };
+                if let Err(error) = result {
+                    log::error!("Message handler customer request error: {}", error);
+                }
             }
         }
     }
 
     pub fn client_response_from_repository(
         service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let response: ClientResponseFromRepository =
-                    serde_json::from_str(&message).unwrap();
-                service
-                    .lock()
-                    .await
-                    .handle_client_response_from_repository(response)
-                    .await;
+                let result =
+                    match Correlated::<ClientResponseFromRepository>::decode(&message, wire_format)
+                    {
+                        Ok(response) => {
+                            service
+                                .lock()
+                                .await
+                                .handle_client_response_from_repository(response)
+                                .await
+                        }
+                        Err(error) => Err(error.into()),
+                    };
+                if let Err(error) = result {
+                    log::error!(
+                        "Message handler client response from repository error: {}",
+                        error
+                    );
+                }
             }
         }
     }
 
     pub fn customer_response_from_repository(
         service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let response: CustomerResponseFromRepository =
-                    serde_json::from_str(&message).unwrap();
-                service
-                    .lock()
-                    .await
-                    .handle_customer_response_from_repository(response)
-                    .await;
+                let result = match Correlated::<CustomerResponseFromRepository>::decode(
+                    &message,
+                    wire_format,
+                ) {
+                    Ok(response) => {
+                        service
+                            .lock()
+                            .await
+                            .handle_customer_response_from_repository(response)
+                            .await
+                    }
+                    Err(error) => Err(error.into()),
+                };
+                if let Err(error) = result {
+                    log::error!(
+                        "Message handler customer response from repository error: {}",
+                        error
+                    );
+                }
             }
         }
     }
 
     pub fn response_from_repository(
         service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
+    ) -> impl IncomingMessageHandler + 'static {
+        move |message: Vec<u8>| {
+            let service = service.clone();
+            async move {
+                let result = match ResponseFromRepository::decode(&message, wire_format) {
+                    Ok(response) => {
+                        service
+                            .lock()
+                            .await
+                            .handle_response_from_repository(response)
+                            .await
+                    }
+                    Err(error) => Err(error.into()),
+                };
+                if let Err(error) = result {
+                    log::error!("Message handler response from repository error: {}", error);
+                }
+            }
+        }
+    }
+
+    pub fn moderation_decision(
+        service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
+    ) -> impl IncomingMessageHandler + 'static {
+        move |message: Vec<u8>| {
+            let service = service.clone();
+            async move {
+                let result = match ModerationDecision::decode(&message, wire_format) {
+                    Ok(decision) => {
+                        service
+                            .lock()
+                            .await
+                            .handle_moderation_decision(decision)
+                            .await
+                    }
+                    Err(error) => Err(error.into()),
+                };
+                if let Err(error) = result {
+                    log::error!("Message handler moderation decision error: {}", error);
+                }
+            }
+        }
+    }
+
+    pub fn webhook_response(
+        service: Arc<Mutex<ControllerService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let response: ResponseFromRepository = serde_json::from_str(&message).unwrap();
-                service
-                    .lock()
-                    .await
-                    .handle_response_from_repository(response)
-                    .await;
+                let result = match Correlated::<WebhookResponse>::decode(&message, wire_format) {
+                    Ok(response) => service.lock().await.handle_webhook_response(response).await,
+                    Err(error) => Err(error.into()),
+                };
+                if let Err(error) = result {
+                    log::error!("Message handler webhook response error: {}", error);
+                }
             }
         }
     }
@@ -1,9 +1,22 @@
+mod channels;
 mod common;
+mod delivery;
 mod handler;
+mod limits;
+mod moderation;
+mod pending;
+mod routing;
 mod service;
 mod transform;
 
+pub use channels::{Channel, ChannelError, Channels, EmailChannel, TelegramChannel};
+pub use common::errors::ControllerError;
 pub use common::Config;
+pub use delivery::{DeliveryQueue, DeliveryScheduler, FanOut};
 pub use handler::MessageHandler;
+pub use limits::{Limits, QuotaExceeded, Quotas, QuotasError, RateLimiter, RateLimitsSnapshot};
+pub use moderation::{Moderation, ModerationError, ModerationRules, Verdict};
+pub use pending::PendingRequests;
+pub use routing::{Destination, MessageType, Route, Routed, RoutingTable, RoutingTableError};
 pub use service::ControllerService;
 pub use transform::Transformer;
@@ -0,0 +1,419 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use domain::models::{CustomerName, Quota, Timestamp};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Config;
+
+const HOUR_MILLIS: i64 = 60 * 60 * 1000;
+const DAY_MILLIS: i64 = 24 * HOUR_MILLIS;
+
+#[derive(Debug, Error)]
+pub enum QuotasError {
+    #[error("Read quotas error: [{0}]")]
+    ReadFailure(String),
+    #[error("Parse quotas error: [{0}]")]
+    ParseFailure(String),
+}
+
+/// Limits of one customer, a missing limit is unlimited.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Limits {
+    #[serde(default)]
+    pub notifications_per_hour: Option<u32>,
+    #[serde(default)]
+    pub notifications_per_day: Option<u32>,
+    #[serde(default)]
+    pub recipients_per_day: Option<u32>,
+}
+
+impl Limits {
+    fn limit(&self, quota: Quota) -> Option<u32> {
+        match quota {
+            Quota::NotificationsPerHour => self.notifications_per_hour,
+            Quota::NotificationsPerDay => self.notifications_per_day,
+            Quota::RecipientsPerDay => self.recipients_per_day,
+        }
+    }
+}
+
+/// Content of the `quotas_path` file: default limits and per-customer overrides.
+/// Limits missing from an override are taken from the default.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Quotas {
+    #[serde(default)]
+    pub default: Limits,
+    #[serde(default)]
+    pub customers: HashMap<CustomerName, Limits>,
+}
+
+impl Quotas {
+    fn limit(&self, customer: &CustomerName, quota: Quota) -> Option<u32> {
+        self.customers
+            .get(customer)
+            .and_then(|limits| limits.limit(quota))
+            .or_else(|| self.default.limit(quota))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub quota: Quota,
+    pub limit: u32,
+    pub retry_after_secs: u64,
+}
+
+/// Token bucket holding up to `limit` tokens, refilled evenly over the period.
+/// Recipients are charged after delivery, so the bucket may go below zero.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Timestamp,
+}
+
+impl Bucket {
+    fn full(limit: u32, now: Timestamp) -> Self {
+        Bucket {
+            tokens: limit as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, limit: u32, period_millis: i64, now: Timestamp) {
+        let elapsed = now.as_millis() - self.updated_at.as_millis();
+        if elapsed > 0 {
+            let rate = limit as f64 / period_millis as f64;
+            self.tokens = (self.tokens + elapsed as f64 * rate).min(limit as f64);
+            self.updated_at = now;
+        }
+    }
+
+    fn retry_after_secs(&self, limit: u32, period_millis: i64) -> u64 {
+        let missing = 1.0 - self.tokens;
+        let rate = limit as f64 / period_millis as f64;
+        (missing / rate / 1000.0).ceil().max(1.0) as u64
+    }
+}
+
+fn period_millis(quota: Quota) -> i64 {
+    match quota {
+        Quota::NotificationsPerHour => HOUR_MILLIS,
+        Quota::NotificationsPerDay | Quota::RecipientsPerDay => DAY_MILLIS,
+    }
+}
+
+/// Bucket state waiting to be written to `rate_limits_path`.
+pub struct RateLimitsSnapshot {
+    path: PathBuf,
+    data: String,
+}
+
+impl RateLimitsSnapshot {
+    pub async fn save(self) {
+        if let Err(error) = tokio::fs::write(&self.path, self.data).await {
+            log::error!("Failed to save rate limits to {:?}: {}", self.path, error);
+        }
+    }
+}
+
+/// Per-customer rate limiter. The buckets are saved to `rate_limits_path`
+/// periodically, see [`RateLimiter::snapshot`], so the limits survive restarts.
+pub struct RateLimiter {
+    quotas: Quotas,
+    path: Option<PathBuf>,
+    buckets: HashMap<CustomerName, HashMap<Quota, Bucket>>,
+    changed: bool,
+}
+
+impl RateLimiter {
+    pub fn from_config(config: &Config) -> Result<Self, QuotasError> {
+        let quotas = match &config.quotas_path {
+            Some(path) => {
+                let data = fs::read_to_string(path)
+                    .map_err(|error| QuotasError::ReadFailure(error.to_string()))?;
+                serde_json::from_str(&data)
+                    .map_err(|error| QuotasError::ParseFailure(error.to_string()))?
+            }
+            None => Quotas::default(),
+        };
+        let path = config.rate_limits_path.as_ref().map(PathBuf::from);
+        let buckets = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(data)) => serde_json::from_str(&data).unwrap_or_else(|error| {
+                log::error!("Failed to parse rate limits file {:?}: {}", path, error);
+                HashMap::new()
+            }),
+            _ => HashMap::new(),
+        };
+        Ok(RateLimiter {
+            quotas,
+            path,
+            buckets,
+            changed: false,
+        })
+    }
+
+    /// Checks that the customer has a token for a new notification, or returns
+    /// the quota that is used up. The token is taken by `charge_notification`.
+    pub fn check_notification(&mut self, customer: &CustomerName) -> Result<(), QuotaExceeded> {
+        self.check_notification_at(customer, Timestamp::now())
+    }
+
+    fn check_notification_at(
+        &mut self,
+        customer: &CustomerName,
+        now: Timestamp,
+    ) -> Result<(), QuotaExceeded> {
+        let quotas = [
+            Quota::NotificationsPerHour,
+            Quota::NotificationsPerDay,
+            Quota::RecipientsPerDay,
+        ];
+        for quota in quotas {
+            if let Some((bucket, limit)) = self.bucket(customer, quota, now) {
+                if bucket.tokens < 1.0 {
+                    return Err(QuotaExceeded {
+                        quota,
+                        limit,
+                        retry_after_secs: bucket.retry_after_secs(limit, period_millis(quota)),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes a token of the notification quotas for a notification that is
+    /// yet to be accepted, so concurrent requests can not overdraw the quotas.
+    /// The token goes back with `refund_notification` if it is not accepted.
+    pub fn reserve_notification(&mut self, customer: &CustomerName) -> Result<(), QuotaExceeded> {
+        self.reserve_notification_at(customer, Timestamp::now())
+    }
+
+    fn reserve_notification_at(
+        &mut self,
+        customer: &CustomerName,
+        now: Timestamp,
+    ) -> Result<(), QuotaExceeded> {
+        self.check_notification_at(customer, now)?;
+        self.charge_notification_at(customer, now);
+        Ok(())
+    }
+
+    /// Returns the token of a reserved notification the repository did not accept.
+    pub fn refund_notification(&mut self, customer: &CustomerName) {
+        self.refund_notification_at(customer, Timestamp::now())
+    }
+
+    fn refund_notification_at(&mut self, customer: &CustomerName, now: Timestamp) {
+        for quota in [Quota::NotificationsPerHour, Quota::NotificationsPerDay] {
+            if let Some((bucket, limit)) = self.bucket(customer, quota, now) {
+                bucket.tokens = (bucket.tokens + 1.0).min(limit as f64);
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Takes a token of the notification quotas for an accepted notification.
+    pub fn charge_notification(&mut self, customer: &CustomerName) {
+        self.charge_notification_at(customer, Timestamp::now())
+    }
+
+    fn charge_notification_at(&mut self, customer: &CustomerName, now: Timestamp) {
+        for quota in [Quota::NotificationsPerHour, Quota::NotificationsPerDay] {
+            if let Some((bucket, _)) = self.bucket(customer, quota, now) {
+                bucket.tokens -= 1.0;
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Charges delivered notifications to the recipients quota of the customer.
+    pub fn charge_recipients(&mut self, customer: &CustomerName, recipients: usize) {
+        let now = Timestamp::now();
+        if let Some((bucket, _)) = self.bucket(customer, Quota::RecipientsPerDay, now) {
+            bucket.tokens -= recipients as f64;
+            self.changed = true;
+        }
+    }
+
+    fn bucket(
+        &mut self,
+        customer: &CustomerName,
+        quota: Quota,
+        now: Timestamp,
+    ) -> Option<(&mut Bucket, u32)> {
+        let limit = self.quotas.limit(customer, quota)?;
+        let bucket = self
+            .buckets
+            .entry(customer.clone())
+            .or_default()
+            .entry(quota)
+            .or_insert_with(|| Bucket::full(limit, now));
+        bucket.refill(limit, period_millis(quota), now);
+        Some((bucket, limit))
+    }
+
+    /// Bucket state to save if it changed since the last snapshot. Saving is
+    /// left to the caller, so the file is not written while the limiter is locked.
+    pub fn snapshot(&mut self) -> Option<RateLimitsSnapshot> {
+        let path = self.path.clone()?;
+        if !self.changed {
+            return None;
+        }
+        self.changed = false;
+        match serde_json::to_string(&self.buckets) {
+            Ok(data) => Some(RateLimitsSnapshot { path, data }),
+            Err(error) => {
+                log::error!("Failed to serialize rate limits: {}", error);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: i64) -> Timestamp {
+        Timestamp::from_millis(1_671_000_000_000 + millis).unwrap()
+    }
+
+    fn limiter(quotas: &str) -> RateLimiter {
+        RateLimiter {
+            quotas: serde_json::from_str(quotas).unwrap(),
+            path: None,
+            buckets: HashMap::new(),
+            changed: false,
+        }
+    }
+
+    fn customer(name: &str) -> CustomerName {
+        CustomerName::parse(name).unwrap()
+    }
+
+    #[test]
+    fn bucket_refills_evenly_up_to_limit() {
+        let mut bucket = Bucket {
+            tokens: 0.0,
+            updated_at: at(0),
+        };
+        bucket.refill(24, DAY_MILLIS, at(HOUR_MILLIS));
+        assert!((bucket.tokens - 1.0).abs() < 1e-9);
+        assert_eq!(bucket.updated_at, at(HOUR_MILLIS));
+
+        bucket.refill(24, DAY_MILLIS, at(3 * DAY_MILLIS));
+        assert_eq!(bucket.tokens, 24.0);
+    }
+
+    #[test]
+    fn bucket_ignores_clock_going_back() {
+        let mut bucket = Bucket {
+            tokens: 2.0,
+            updated_at: at(HOUR_MILLIS),
+        };
+        bucket.refill(10, HOUR_MILLIS, at(0));
+        assert_eq!(bucket.tokens, 2.0);
+        assert_eq!(bucket.updated_at, at(HOUR_MILLIS));
+    }
+
+    #[test]
+    fn retry_after_waits_for_one_token() {
+        let bucket = Bucket {
+            tokens: 0.0,
+            updated_at: at(0),
+        };
+        assert_eq!(bucket.retry_after_secs(60, HOUR_MILLIS), 60);
+        let overdrawn = Bucket {
+            tokens: -1.0,
+            updated_at: at(0),
+        };
+        assert_eq!(overdrawn.retry_after_secs(60, HOUR_MILLIS), 120);
+    }
+
+    #[test]
+    fn check_does_not_take_tokens() {
+        let mut limiter = limiter(r#"{ "default": { "notifications_per_hour": 1 } }"#);
+        let shop = customer("shop");
+        assert!(limiter.check_notification_at(&shop, at(0)).is_ok());
+        assert!(limiter.check_notification_at(&shop, at(0)).is_ok());
+
+        limiter.charge_notification_at(&shop, at(0));
+        let exceeded = limiter.check_notification_at(&shop, at(0)).unwrap_err();
+        assert_eq!(exceeded.quota, Quota::NotificationsPerHour);
+        assert_eq!(exceeded.limit, 1);
+        assert!((3600..=3601).contains(&exceeded.retry_after_secs));
+
+        assert!(limiter
+            .check_notification_at(&shop, at(HOUR_MILLIS + 1000))
+            .is_ok());
+    }
+
+    #[test]
+    fn reservations_are_taken_before_responses_arrive() {
+        let mut limiter = limiter(r#"{ "default": { "notifications_per_hour": 1 } }"#);
+        let shop = customer("shop");
+        assert!(limiter.reserve_notification_at(&shop, at(0)).is_ok());
+        let exceeded = limiter.reserve_notification_at(&shop, at(0)).unwrap_err();
+        assert_eq!(exceeded.quota, Quota::NotificationsPerHour);
+
+        limiter.refund_notification_at(&shop, at(0));
+        assert!(limiter.reserve_notification_at(&shop, at(0)).is_ok());
+        assert!(limiter.reserve_notification_at(&shop, at(0)).is_err());
+    }
+
+    #[test]
+    fn refunds_do_not_exceed_the_limit() {
+        let mut limiter = limiter(r#"{ "default": { "notifications_per_day": 2 } }"#);
+        let shop = customer("shop");
+        limiter.refund_notification_at(&shop, at(0));
+        limiter.refund_notification_at(&shop, at(0));
+        assert!(limiter.reserve_notification_at(&shop, at(0)).is_ok());
+        assert!(limiter.reserve_notification_at(&shop, at(0)).is_ok());
+        assert!(limiter.reserve_notification_at(&shop, at(0)).is_err());
+    }
+
+    #[test]
+    fn customer_limits_override_default() {
+        let mut limiter = limiter(
+            r#"{
+                "default": { "notifications_per_day": 1 },
+                "customers": { "big": { "notifications_per_day": 2 } }
+            }"#,
+        );
+        let (small, big) = (customer("small"), customer("big"));
+        limiter.charge_notification_at(&small, at(0));
+        limiter.charge_notification_at(&big, at(0));
+        assert!(limiter.check_notification_at(&small, at(0)).is_err());
+        assert!(limiter.check_notification_at(&big, at(0)).is_ok());
+    }
+
+    #[test]
+    fn used_up_recipients_block_notifications() {
+        let mut limiter = limiter(r#"{ "default": { "recipients_per_day": 10 } }"#);
+        let shop = customer("shop");
+        limiter.charge_recipients(&shop, 25);
+        let exceeded = limiter.check_notification(&shop).unwrap_err();
+        assert_eq!(exceeded.quota, Quota::RecipientsPerDay);
+    }
+
+    #[test]
+    fn unlimited_customer_is_not_tracked() {
+        let mut limiter = limiter("{}");
+        let shop = customer("shop");
+        limiter.charge_notification(&shop);
+        assert!(limiter.check_notification(&shop).is_ok());
+        assert!(limiter.buckets.get(&shop).is_none_or(HashMap::is_empty));
+    }
+
+    #[test]
+    fn snapshot_only_after_changes() {
+        let mut limiter = limiter(r#"{ "default": { "notifications_per_hour": 5 } }"#);
+        limiter.path = Some(PathBuf::from("rate_limits.json"));
+        assert!(limiter.snapshot().is_none());
+        limiter.charge_notification(&customer("shop"));
+        assert!(limiter.snapshot().is_some());
+        assert!(limiter.snapshot().is_none());
+    }
+}
@@ -0,0 +1,333 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use domain::{
+    models::{CustomerName, ModerationReason, NotificationContent},
+    requests::CustomerRequest,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+use crate::Config;
+
+const LINK_SCHEMES: [&str; 2] = ["http://", "https://"];
+const LINK_TERMINATORS: [char; 6] = ['"', '\'', '<', '>', '(', ')'];
+
+#[derive(Debug, Error)]
+pub enum ModerationError {
+    #[error("Read moderation rules error: [{0}]")]
+    ReadFailure(String),
+    #[error("Parse moderation rules error: [{0}]")]
+    ParseFailure(String),
+}
+
+/// Content of the `moderation_path` file. Words are matched case-insensitively
+/// as whole words, domains match themselves and their subdomains. Links are
+/// only checked against `allowed_domains` if the list is not empty.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ModerationRules {
+    #[serde(default)]
+    pub max_length: Option<u32>,
+    #[serde(default)]
+    pub banned_words: Vec<String>,
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub denied_domains: Vec<String>,
+    /// Every notification is held for manual review.
+    #[serde(default)]
+    pub review_all: bool,
+    /// Customers whose notifications are held for manual review.
+    #[serde(default)]
+    pub review_customers: Vec<CustomerName>,
+    /// Words that send a notification to manual review.
+    #[serde(default)]
+    pub review_words: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Accept,
+    Reject(ModerationReason),
+    Hold,
+}
+
+impl ModerationRules {
+    pub fn check(&self, customer: &CustomerName, content: &NotificationContent) -> Verdict {
+        let length = content.text.chars().count() as u32;
+        if let Some(max_length) = self.max_length {
+            if length > max_length {
+                return Verdict::Reject(ModerationReason::TooLong { length, max_length });
+            }
+        }
+
+        let words = words(&content.text);
+        if let Some(word) = find_word(&words, &self.banned_words) {
+            return Verdict::Reject(ModerationReason::BannedWord { word });
+        }
+
+        let hosts = links(&content.text)
+            .into_iter()
+            .chain(content.buttons.iter().map(|button| button.url.clone()))
+            .filter_map(|link| Url::parse(&link).ok())
+            .filter_map(|url| url.host_str().map(str::to_lowercase));
+        for host in hosts {
+            let denied = self
+                .denied_domains
+                .iter()
+                .any(|domain| matches_domain(&host, domain));
+            let allowed = self.allowed_domains.is_empty()
+                || self
+                    .allowed_domains
+                    .iter()
+                    .any(|domain| matches_domain(&host, domain));
+            if denied || !allowed {
+                return Verdict::Reject(ModerationReason::DeniedLink { domain: host });
+            }
+        }
+
+        if self.review_all
+            || self.review_customers.contains(customer)
+            || find_word(&words, &self.review_words).is_some()
+        {
+            return Verdict::Hold;
+        }
+        Verdict::Accept
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn find_word(words: &[String], list: &[String]) -> Option<String> {
+    list.iter()
+        .find(|word| words.contains(&word.to_lowercase()))
+        .cloned()
+}
+
+/// Finds the http(s) links in a plain, Markdown or HTML text.
+fn links(text: &str) -> Vec<String> {
+    let mut links = vec![];
+    for scheme in LINK_SCHEMES {
+        let mut rest = text;
+        while let Some(start) = rest.find(scheme) {
+            let link = &rest[start..];
+            let end = link
+                .find(|c: char| c.is_whitespace() || LINK_TERMINATORS.contains(&c))
+                .unwrap_or(link.len());
+            links.push(link[..end].to_string());
+            rest = &link[end..];
+        }
+    }
+    links
+}
+
+fn matches_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches('.').to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct HoldQueue {
+    next_id: u64,
+    requests: BTreeMap<u64, CustomerRequest>,
+}
+
+/// Moderation stage of the controller: checks notifications against the
+/// rules and keeps the ones waiting for manual review. The hold queue is
+/// saved to `moderation_hold_path` after every change.
+pub struct Moderation {
+    rules: ModerationRules,
+    path: Option<PathBuf>,
+    queue: HoldQueue,
+}
+
+impl Moderation {
+    pub fn from_config(config: &Config) -> Result<Self, ModerationError> {
+        let rules = match &config.moderation_path {
+            Some(path) => {
+                let data = fs::read_to_string(path)
+                    .map_err(|error| ModerationError::ReadFailure(error.to_string()))?;
+                serde_json::from_str(&data)
+                    .map_err(|error| ModerationError::ParseFailure(error.to_string()))?
+            }
+            None => ModerationRules::default(),
+        };
+        let path = config.moderation_hold_path.as_ref().map(PathBuf::from);
+        let queue = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(data)) => serde_json::from_str(&data).unwrap_or_else(|error| {
+                log::error!("Failed to parse moderation hold file {:?}: {}", path, error);
+                HoldQueue::default()
+            }),
+            _ => HoldQueue::default(),
+        };
+        Ok(Moderation { rules, path, queue })
+    }
+
+    pub fn check(&self, customer: &CustomerName, content: &NotificationContent) -> Verdict {
+        self.rules.check(customer, content)
+    }
+
+    /// Puts the request on hold and returns its id in the queue.
+    pub fn hold(&mut self, request: CustomerRequest) -> u64 {
+        self.queue.next_id += 1;
+        let id = self.queue.next_id;
+        self.queue.requests.insert(id, request);
+        self.save();
+        id
+    }
+
+    /// Takes a held request out of the queue.
+    pub fn release(&mut self, id: u64) -> Option<CustomerRequest> {
+        let request = self.queue.requests.remove(&id);
+        if request.is_some() {
+            self.save();
+        }
+        request
+    }
+
+    fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let result = serde_json::to_string(&self.queue)
+            .map_err(|error| error.to_string())
+            .and_then(|data| fs::write(path, data).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            log::error!(
+                "Failed to save moderation hold queue to {:?}: {}",
+                path,
+                error
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::models::LinkButton;
+
+    use super::*;
+
+    fn rules(data: &str) -> ModerationRules {
+        serde_json::from_str(data).unwrap()
+    }
+
+    fn shop() -> CustomerName {
+        CustomerName::parse("shop").unwrap()
+    }
+
+    fn check(rules: &ModerationRules, text: &str) -> Verdict {
+        rules.check(&shop(), &NotificationContent::from(text.to_string()))
+    }
+
+    #[test]
+    fn empty_rules_accept_everything() {
+        let rules = ModerationRules::default();
+        assert_eq!(check(&rules, "Visit https://bit.ly/x"), Verdict::Accept);
+    }
+
+    #[test]
+    fn too_long_text_is_rejected() {
+        let rules = rules(r#"{ "max_length": 5 }"#);
+        assert_eq!(check(&rules, "hello"), Verdict::Accept);
+        assert_eq!(
+            check(&rules, "привет"),
+            Verdict::Reject(ModerationReason::TooLong {
+                length: 6,
+                max_length: 5
+            })
+        );
+    }
+
+    #[test]
+    fn banned_words_match_whole_words_ignoring_case() {
+        let rules = rules(r#"{ "banned_words": ["Casino"] }"#);
+        assert_eq!(
+            check(&rules, "Best CASINO in town!"),
+            Verdict::Reject(ModerationReason::BannedWord {
+                word: "Casino".to_string()
+            })
+        );
+        assert_eq!(check(&rules, "casinos and casinoland"), Verdict::Accept);
+    }
+
+    #[test]
+    fn denied_domains_match_subdomains() {
+        let rules = rules(r#"{ "denied_domains": ["bit.ly"] }"#);
+        let denied = |domain: &str| {
+            Verdict::Reject(ModerationReason::DeniedLink {
+                domain: domain.to_string(),
+            })
+        };
+        assert_eq!(check(&rules, "see https://bit.ly/abc"), denied("bit.ly"));
+        assert_eq!(
+            check(&rules, "<a href=\"http://go.BIT.ly/x\">link</a>"),
+            denied("go.bit.ly")
+        );
+        assert_eq!(check(&rules, "[x](https://notbit.ly/y)"), Verdict::Accept);
+    }
+
+    #[test]
+    fn allowed_domains_restrict_links_and_buttons() {
+        let rules = rules(r#"{ "allowed_domains": ["example.com"] }"#);
+        assert_eq!(
+            check(&rules, "https://shop.example.com/sale"),
+            Verdict::Accept
+        );
+        assert_eq!(
+            check(&rules, "https://other.org"),
+            Verdict::Reject(ModerationReason::DeniedLink {
+                domain: "other.org".to_string()
+            })
+        );
+        let content = NotificationContent {
+            buttons: vec![LinkButton {
+                text: "Open".to_string(),
+                url: "https://other.org/page".to_string(),
+            }],
+            ..NotificationContent::from("no links".to_string())
+        };
+        assert!(matches!(
+            rules.check(&shop(), &content),
+            Verdict::Reject(ModerationReason::DeniedLink { .. })
+        ));
+    }
+
+    #[test]
+    fn review_rules_hold_notifications() {
+        let rules = rules(r#"{ "review_customers": ["shop"], "review_words": ["free"] }"#);
+        assert_eq!(check(&rules, "new boots"), Verdict::Hold);
+        let other = CustomerName::parse("other").unwrap();
+        let content = |text: &str| NotificationContent::from(text.to_string());
+        assert_eq!(rules.check(&other, &content("new boots")), Verdict::Accept);
+        assert_eq!(rules.check(&other, &content("FREE boots")), Verdict::Hold);
+        assert_eq!(
+            ModerationRules {
+                review_all: true,
+                ..ModerationRules::default()
+            }
+            .check(&other, &content("new boots")),
+            Verdict::Hold
+        );
+    }
+
+    #[test]
+    fn rejection_wins_over_review() {
+        let rules = rules(r#"{ "banned_words": ["casino"], "review_all": true }"#);
+        assert!(matches!(check(&rules, "casino"), Verdict::Reject(_)));
+    }
+
+    #[test]
+    fn links_stop_at_markup() {
+        assert_eq!(
+            links("a https://a.com/x) b <http://b.org>'"),
+            vec!["http://b.org".to_string(), "https://a.com/x".to_string()]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::{collections::HashMap, time::Duration};
+
+use domain::models::Timestamp;
+use tokio::time::Instant;
+
+/// How long an expired request waits for a late response before it is forgotten.
+const EXPIRED_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+struct PendingRequest<R> {
+    deadline: Instant,
+    /// Taken when the request expires.
+    failure: Option<R>,
+}
+
+/// Requests forwarded to the repository or the webhook service and still
+/// waiting for a response. Every forwarded request carries an id the
+/// responder copies into its response, see `Correlated`. Expired requests are
+/// kept for a while, so a late response is told apart from an unknown one.
+/// `R` holds the failure response sent to the user on expiry.
+pub struct PendingRequests<R> {
+    timeout: Duration,
+    next_id: u64,
+    pending: HashMap<u64, PendingRequest<R>>,
+}
+
+impl<R> PendingRequests<R> {
+    pub fn new(timeout: Duration) -> Self {
+        PendingRequests {
+            timeout,
+            // Ids start from the current time, so responses to requests sent
+            // before a restart do not match new requests.
+            next_id: Timestamp::now().as_millis().max(0) as u64,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Id for the next request, tracked with `track` once it is sent.
+    pub fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn track(&mut self, request_id: u64, failure: R) {
+        self.track_at(request_id, failure, Instant::now());
+    }
+
+    fn track_at(&mut self, request_id: u64, failure: R, now: Instant) {
+        let request = PendingRequest {
+            deadline: now + self.timeout,
+            failure: Some(failure),
+        };
+        self.pending.insert(request_id, request);
+    }
+
+    /// Returns what was tracked for the request, or `None` if the response
+    /// came for a request that already expired or is not tracked.
+    pub fn resolve(&mut self, request_id: u64) -> Option<R> {
+        self.pending
+            .remove(&request_id)
+            .and_then(|request| request.failure)
+    }
+
+    /// Returns the failure responses of the requests past their deadline.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<R> {
+        let mut expired = vec![];
+        for request in self.pending.values_mut() {
+            if request.deadline <= now {
+                if let Some(failure) = request.failure.take() {
+                    expired.push(failure);
+                }
+            }
+        }
+        self.pending
+            .retain(|_, request| request.deadline + EXPIRED_RETENTION > now);
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn responses_resolve_their_own_request() {
+        let mut pending = PendingRequests::new(TIMEOUT);
+        let (first, second) = (pending.next_id(), pending.next_id());
+        assert_ne!(first, second);
+        pending.track(first, "first");
+        pending.track(second, "second");
+
+        assert_eq!(pending.resolve(second), Some("second"));
+        assert_eq!(pending.resolve(second), None);
+        assert_eq!(pending.resolve(first), Some("first"));
+        assert_eq!(pending.resolve(first + 100), None);
+    }
+
+    #[test]
+    fn expired_requests_fail_once() {
+        let mut pending = PendingRequests::new(TIMEOUT);
+        let now = Instant::now();
+        pending.track_at(1, "late", now);
+        pending.track_at(2, "on time", now + TIMEOUT);
+
+        assert!(pending.take_expired(now).is_empty());
+        assert_eq!(pending.take_expired(now + TIMEOUT), vec!["late"]);
+        assert!(pending.take_expired(now + TIMEOUT).is_empty());
+
+        assert_eq!(pending.resolve(1), None);
+        assert_eq!(pending.resolve(2), Some("on time"));
+    }
+
+    #[test]
+    fn expired_requests_are_forgotten() {
+        let mut pending = PendingRequests::new(TIMEOUT);
+        let now = Instant::now();
+        pending.track_at(1, "late", now);
+        pending.take_expired(now + TIMEOUT + EXPIRED_RETENTION);
+        assert!(pending.pending.is_empty());
+    }
+}
@@ -0,0 +1,371 @@
+use std::fs;
+
+use domain::{
+    codec::{Correlated, WireMessage},
+    records::Record,
+    requests::{
+        ClientRequest, ClientRequestToRepository, CustomerRequest, CustomerRequestToRepository,
+        RequestToRepository, WebhookRequest,
+    },
+    responses::{
+        ClientResponse, ClientResponseFromRepository, CustomerResponse,
+        CustomerResponseFromRepository, ResponseFromRepository, WebhookResponse,
+    },
+};
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::Config;
+
+#[derive(Debug, Error)]
+pub enum RoutingTableError {
+    #[error("Read routing table error: [{0}]")]
+    ReadFailure(String),
+    #[error("Parse routing table error: [{0}]")]
+    ParseFailure(String),
+}
+
+/// Every message type the controller receives or publishes.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    ClientRequest,
+    CustomerRequest,
+    ClientResponseFromRepository,
+    CustomerResponseFromRepository,
+    ResponseFromRepository,
+    Record,
+    ClientRequestToRepository,
+    CustomerRequestToRepository,
+    RequestToRepository,
+    ClientResponse,
+    CustomerResponse,
+    WebhookRequest,
+    WebhookResponse,
+}
+
+pub trait Routed: WireMessage {
+    const TYPE: MessageType;
+
+    /// The message as seen by the `variants` and `customers` filters.
+    fn filter_value(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Destination {
+    /// Defaults to the `exchange` of the config.
+    #[serde(default)]
+    pub exchange: Option<String>,
+    pub queue: String,
+}
+
+/// Sends messages of one type to the destinations. Empty `variants` and
+/// `customers` filters match every message.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Route {
+    pub message: MessageType,
+    #[serde(default)]
+    pub variants: Vec<String>,
+    #[serde(default)]
+    pub customers: Vec<String>,
+    pub destinations: Vec<Destination>,
+}
+
+impl Route {
+    fn has_filters(&self) -> bool {
+        !self.variants.is_empty() || !self.customers.is_empty()
+    }
+
+    fn matches(&self, message_type: MessageType, message: &Value) -> bool {
+        if self.message != message_type {
+            return false;
+        }
+        if !self.variants.is_empty() {
+            match variant(message) {
+                Some(variant) if self.variants.iter().any(|name| name == variant) => {}
+                _ => return false,
+            }
+        }
+        if !self.customers.is_empty() {
+            match customer(message) {
+                Some(customer) if self.customers.iter().any(|name| name == customer) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Routing table of the controller, built from the queues of the config.
+/// Routes of the JSON file at `routing_table_path` replace the built routes of
+/// the message types they mention, other message types keep the built routes.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RoutingTable {
+    routes: Vec<Route>,
+}
+
+impl RoutingTable {
+    pub fn from_config(config: &Config) -> Result<Self, RoutingTableError> {
+        match &config.routing_table_path {
+            Some(path) => {
+                let data = fs::read_to_string(path)
+                    .map_err(|error| RoutingTableError::ReadFailure(error.to_string()))?;
+                let table: RoutingTable = serde_json::from_str(&data)
+                    .map_err(|error| RoutingTableError::ParseFailure(error.to_string()))?;
+                Ok(table.merge(Self::default_routes(config)))
+            }
+            None => Ok(Self::default_routes(config)),
+        }
+    }
+
+    /// Adds the `defaults` of the message types without a route in this table.
+    fn merge(mut self, defaults: RoutingTable) -> Self {
+        let (kept, replaced): (Vec<Route>, Vec<Route>) =
+            defaults.routes.into_iter().partition(|default| {
+                !self
+                    .routes
+                    .iter()
+                    .any(|route| route.message == default.message)
+            });
+        for route in &replaced {
+            log::info!(
+                "Routing table replaces default routes of {:?}",
+                route.message
+            );
+        }
+        self.routes.extend(kept);
+        self
+    }
+
+    fn default_routes(config: &Config) -> Self {
+        let route = |message, queue: &String| Route {
+            message,
+            variants: Vec::new(),
+            customers: Vec::new(),
+            destinations: vec![Destination {
+                exchange: None,
+                queue: queue.clone(),
+            }],
+        };
+        let mut routes = vec![
+            route(MessageType::Record, &config.history_queue),
+            route(
+                MessageType::ClientRequestToRepository,
+                &config.client_repository_request_queue,
+            ),
+            route(
+                MessageType::CustomerRequestToRepository,
+                &config.customer_repository_request_queue,
+            ),
+            route(
+                MessageType::RequestToRepository,
+                &config.repository_request_queue,
+            ),
+            route(MessageType::ClientResponse, &config.client_response_queue),
+            route(
+                MessageType::CustomerResponse,
+                &config.customer_response_queue,
+            ),
+        ];
+        if let Some(queue) = &config.webhook_queue {
+            routes.push(route(MessageType::WebhookRequest, queue));
+        }
+        RoutingTable { routes }
+    }
+
+    pub fn destinations<M: Routed>(&self, message: &M) -> Vec<&Destination> {
+        // Only filters look into the message, skip serializing it otherwise.
+        let filtered = self
+            .routes
+            .iter()
+            .any(|route| route.message == M::TYPE && route.has_filters());
+        let value = if filtered {
+            match message.filter_value() {
+                Ok(value) => value,
+                Err(error) => {
+                    log::error!("Failed to inspect message for routing: {}", error);
+                    return Vec::new();
+                }
+            }
+        } else {
+            Value::Null
+        };
+        self.routes
+            .iter()
+            .filter(|route| route.matches(M::TYPE, &value))
+            .flat_map(|route| route.destinations.iter())
+            .collect()
+    }
+}
+
+/// Name of the enum variant of a serialized message.
+fn variant(message: &Value) -> Option<&str> {
+    match message {
+        Value::String(variant) => Some(variant),
+        Value::Object(map) => map.keys().next().map(String::as_str),
+        _ => None,
+    }
+}
+
+/// Customer name of a serialized message, either a name or a `Customer`.
+fn customer(message: &Value) -> Option<&str> {
+    let fields = message.as_object()?.values().next()?;
+    match fields.get("customer")? {
+        Value::String(name) => Some(name),
+        Value::Object(customer) => customer.get("name")?.as_str(),
+        _ => None,
+    }
+}
+
+/// Routed like the message it carries.
+impl<T: Routed> Routed for Correlated<T>
+where
+    Correlated<T>: WireMessage,
+{
+    const TYPE: MessageType = T::TYPE;
+
+    fn filter_value(&self) -> serde_json::Result<Value> {
+        self.message.filter_value()
+    }
+}
+
+impl Routed for ClientRequest {
+    const TYPE: MessageType = MessageType::ClientRequest;
+}
+
+impl Routed for CustomerRequest {
+    const TYPE: MessageType = MessageType::CustomerRequest;
+}
+
+impl Routed for ClientResponseFromRepository {
+    const TYPE: MessageType = MessageType::ClientResponseFromRepository;
+}
+
+impl Routed for CustomerResponseFromRepository {
+    const TYPE: MessageType = MessageType::CustomerResponseFromRepository;
+}
+
+impl Routed for ResponseFromRepository {
+    const TYPE: MessageType = MessageType::ResponseFromRepository;
+}
+
+impl Routed for Record {
+    const TYPE: MessageType = MessageType::Record;
+}
+
+impl Routed for ClientRequestToRepository {
+    const TYPE: MessageType = MessageType::ClientRequestToRepository;
+}
+
+impl Routed for CustomerRequestToRepository {
+    const TYPE: MessageType = MessageType::CustomerRequestToRepository;
+}
+
+impl Routed for RequestToRepository {
+    const TYPE: MessageType = MessageType::RequestToRepository;
+}
+
+impl Routed for ClientResponse {
+    const TYPE: MessageType = MessageType::ClientResponse;
+}
+
+impl Routed for CustomerResponse {
+    const TYPE: MessageType = MessageType::CustomerResponse;
+}
+
+impl Routed for WebhookRequest {
+    const TYPE: MessageType = MessageType::WebhookRequest;
+}
+
+impl Routed for WebhookResponse {
+    const TYPE: MessageType = MessageType::WebhookResponse;
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::models::{CustomerName, ProductName, Timestamp, UserEventRecord, UserId};
+
+    use super::*;
+
+    fn table(data: &str) -> RoutingTable {
+        serde_json::from_str(data).unwrap()
+    }
+
+    fn queues(destinations: Vec<&Destination>) -> Vec<&str> {
+        destinations
+            .iter()
+            .map(|destination| destination.queue.as_str())
+            .collect()
+    }
+
+    fn notification(customer: &str) -> CustomerRequest {
+        CustomerRequest::NewNotification {
+            user_id: UserId(1),
+            customer: CustomerName::parse(customer).unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            notification: "sale".to_string().into(),
+            send_at: None,
+            timestamp: Timestamp::now(),
+        }
+    }
+
+    #[test]
+    fn merge_keeps_defaults_of_missing_message_types() {
+        let defaults = table(
+            r#"{ "routes": [
+                { "message": "Record", "destinations": [{ "queue": "history" }] },
+                { "message": "ClientResponse", "destinations": [{ "queue": "client_response" }] }
+            ] }"#,
+        );
+        let merged = table(
+            r#"{ "routes": [
+                { "message": "Record", "destinations": [{ "queue": "audit" }] }
+            ] }"#,
+        )
+        .merge(defaults);
+        let record = Record::UserEvent(UserEventRecord {
+            sent_at: None,
+            received_at: Timestamp::now(),
+            user_id: 1,
+            event: "Customers".to_string(),
+            data: String::new(),
+        });
+        assert_eq!(queues(merged.destinations(&record)), vec!["audit"]);
+        let response = ClientResponse::SubscriptionSuccess { user_id: UserId(1) };
+        assert_eq!(
+            queues(merged.destinations(&response)),
+            vec!["client_response"]
+        );
+    }
+
+    #[test]
+    fn filters_match_variant_and_customer() {
+        let table = table(
+            r#"{ "routes": [
+                { "message": "CustomerRequest", "destinations": [{ "queue": "all" }] },
+                {
+                    "message": "CustomerRequest",
+                    "variants": ["NewNotification"],
+                    "customers": ["shop"],
+                    "destinations": [{ "queue": "shop" }]
+                }
+            ] }"#,
+        );
+        assert_eq!(
+            queues(table.destinations(&notification("shop"))),
+            vec!["all", "shop"]
+        );
+        assert_eq!(
+            queues(table.destinations(&notification("other"))),
+            vec!["all"]
+        );
+        let authorization = CustomerRequest::Authorization {
+            user_id: UserId(1),
+            key: "key".to_string(),
+            timestamp: Timestamp::now(),
+        };
+        assert_eq!(queues(table.destinations(&authorization)), vec!["all"]);
+    }
+}
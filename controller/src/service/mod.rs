@@ -1,217 +1,564 @@
+use std::{collections::HashMap, time::Duration};
+
+use amqp::Publisher;
 use domain::{
-    models::UserId,
-    requests::{ClientRequest, CustomerRequest},
+    codec::Correlated,
+    models::{CustomerName, ModerationReason, Timestamp, UserId, WebhookEvent},
+    requests::{
+        ClientRequest, CustomerRequest, ModerationDecision, RequestToRepository, WebhookRequest,
+    },
     responses::{
-        ClientResponseFromRepository, CustomerResponse, CustomerResponseFromRepository,
-        ResponseFromRepository,
+        ClientResponse, ClientResponseFromRepository, CustomerResponse,
+        CustomerResponseFromRepository, ResponseFromRepository, WebhookResponse,
     },
 };
-use amqp::Publisher;
+use tokio::time::Instant;
 
-use crate::{Config, Transformer};
+use crate::{
+    Config, ControllerError, DeliveryQueue, FanOut, Moderation, PendingRequests, QuotaExceeded,
+    RateLimiter, RateLimitsSnapshot, Routed, RoutingTable, Transformer, Verdict,
+};
+
+const DEFAULT_REPOSITORY_TIMEOUT_SECS: u64 = 30;
+
+/// Customer request waiting for a response, with the customer whose
+/// notification token was reserved for it.
+struct PendingCustomerRequest {
+    failure: CustomerResponse,
+    reserved: Option<CustomerName>,
+}
 
 pub struct ControllerService {
     config: Config,
+    routes: RoutingTable,
+    limiter: RateLimiter,
+    moderation: Moderation,
+    delivery: DeliveryQueue,
     publisher: Publisher,
+    client_requests: PendingRequests<ClientResponse>,
+    customer_requests: PendingRequests<PendingCustomerRequest>,
 }
 
 impl ControllerService {
-    pub fn new(config: Config, publisher: Publisher) -> Self {
-        Self { config, publisher }
+    pub fn new(
+        config: Config,
+        routes: RoutingTable,
+        limiter: RateLimiter,
+        moderation: Moderation,
+        delivery: DeliveryQueue,
+        publisher: Publisher,
+    ) -> Self {
+        let timeout = Duration::from_secs(
+            config
+                .repository_timeout_secs
+                .unwrap_or(DEFAULT_REPOSITORY_TIMEOUT_SECS),
+        );
+        Self {
+            config,
+            routes,
+            limiter,
+            moderation,
+            delivery,
+            publisher,
+            client_requests: PendingRequests::new(timeout),
+            customer_requests: PendingRequests::new(timeout),
+        }
     }
 
-    pub async fn handle_client_request(&mut self, request: ClientRequest) {
+    pub async fn handle_client_request(
+        &mut self,
+        request: ClientRequest,
+    ) -> Result<(), ControllerError> {
         let record = Transformer::client_request_to_record(&request);
+        if let Some(latency) = record.latency() {
+            log::info!("Client request latency: {} ms", latency.num_milliseconds());
+        }
+        self.notify(&request).await;
+        self.notify(&record).await;
+
+        if let Err(error) = self.forward_client_request(&request).await {
+            let response = Transformer::client_request_to_failure_response(&request);
+            self.route(&response).await?;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    async fn forward_client_request(
+        &mut self,
+        request: &ClientRequest,
+    ) -> Result<(), ControllerError> {
+        let request_id = self.client_requests.next_id();
         let repository_request =
-            Transformer::client_request_to_repository_to_client_request(&request);
-
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.history_queue,
-                record.to_string(),
-            )
-            .await
-            .unwrap();
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.client_repository_request_queue,
-                repository_request.to_string(),
-            )
-            .await
-            .unwrap();
+            Transformer::client_request_to_repository_to_client_request(request);
+        self.route(&Correlated::new(request_id, repository_request))
+            .await?;
+        self.client_requests.track(
+            request_id,
+            Transformer::client_request_to_timeout_response(request),
+        );
 
         if let Some(request_to_repository) =
-            Transformer::client_request_to_repository_request(&request)
+            Transformer::client_request_to_repository_request(request)
         {
             let record = Transformer::request_to_repository_to_record(&request_to_repository);
-            self.publisher
-                .publish_message(
-                    &self.config.exchange,
-                    &self.config.history_queue,
-                    record.to_string(),
-                )
-                .await
-                .unwrap();
-            self.publisher
-                .publish_message(
-                    &self.config.exchange,
-                    &self.config.repository_request_queue,
-                    request_to_repository.to_string(),
-                )
-                .await
-                .unwrap();
+            self.notify(&record).await;
+            self.route(&request_to_repository).await?;
         }
+        Ok(())
     }
 
-    pub async fn handle_customer_request(&mut self, request: CustomerRequest) {
+    pub async fn handle_customer_request(
+        &mut self,
+        request: CustomerRequest,
+    ) -> Result<(), ControllerError> {
         let record = Transformer::customer_request_to_record(&request);
-        let repository_request =
-            Transformer::customer_request_to_repository_to_customer_request(&request);
-
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.history_queue,
-                record.to_string(),
-            )
-            .await
-            .unwrap();
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.customer_repository_request_queue,
-                repository_request.to_string(),
-            )
+        if let Some(latency) = record.latency() {
+            log::info!(
+                "Customer request latency: {} ms",
+                latency.num_milliseconds()
+            );
+        }
+        self.notify(&request).await;
+        self.notify(&record).await;
+
+        if let CustomerRequest::NewNotification {
+            user_id,
+            customer,
+            notification,
+            ..
+        }
+        | CustomerRequest::NewRecurringNotification {
+            user_id,
+            customer,
+            notification,
+            ..
+        } = &request
+        {
+            let user_id = user_id.clone();
+            match self.moderation.check(customer, notification) {
+                Verdict::Accept => {}
+                Verdict::Reject(reason) => {
+                    log::info!(
+                        "Notification of customer [{}] rejected: {:?}",
+                        customer,
+                        reason
+                    );
+                    let response = CustomerResponse::NotificationRejected { user_id, reason };
+                    return self.route(&response).await;
+                }
+                Verdict::Hold => {
+                    let id = self.moderation.hold(request);
+                    log::info!("Notification held for review with id {}", id);
+                    let response = CustomerResponse::NotificationHeld { user_id, id };
+                    return self.route(&response).await;
+                }
+            }
+        }
+        self.accept_customer_request(request).await
+    }
+
+    pub async fn handle_moderation_decision(
+        &mut self,
+        decision: ModerationDecision,
+    ) -> Result<(), ControllerError> {
+        log::info!("Moderation decision: {}", decision);
+        match decision {
+            ModerationDecision::Approve { id } => match self.moderation.release(id) {
+                Some(request) => self.accept_customer_request(request).await,
+                None => Err(ControllerError::UnknownHeldNotification(id)),
+            },
+            ModerationDecision::Reject { id, comment } => match self.moderation.release(id) {
+                Some(
+                    CustomerRequest::NewNotification { user_id, .. }
+                    | CustomerRequest::NewRecurringNotification { user_id, .. },
+                ) => {
+                    let reason = ModerationReason::Rejected { comment };
+                    let response = CustomerResponse::NotificationRejected { user_id, reason };
+                    self.route(&response).await
+                }
+                Some(_) | None => Err(ControllerError::UnknownHeldNotification(id)),
+            },
+        }
+    }
+
+    /// Passes a request that cleared moderation on to the repository,
+    /// unless the customer is out of notification quota. A notification token
+    /// is reserved up front and refunded unless the repository sends the
+    /// notification right away.
+    async fn accept_customer_request(
+        &mut self,
+        request: CustomerRequest,
+    ) -> Result<(), ControllerError> {
+        let mut reserved = None;
+        if let CustomerRequest::NewNotification {
+            user_id, customer, ..
+        } = &request
+        {
+            if let Err(exceeded) = self.limiter.reserve_notification(customer) {
+                return self
+                    .reject_over_quota(user_id.clone(), customer, exceeded)
+                    .await;
+            }
+            reserved = Some(customer.clone());
+        }
+
+        if let Err(error) = self
+            .forward_customer_request(&request, reserved.clone())
             .await
-            .unwrap();
+        {
+            if let Some(customer) = &reserved {
+                self.limiter.refund_notification(customer);
+            }
+            let response = Transformer::customer_request_to_failure_response(&request);
+            self.route(&response).await?;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    async fn reject_over_quota(
+        &self,
+        user_id: UserId,
+        customer: &CustomerName,
+        exceeded: QuotaExceeded,
+    ) -> Result<(), ControllerError> {
+        log::info!(
+            "Customer [{}] exceeded quota {:?} of {}",
+            customer,
+            exceeded.quota,
+            exceeded.limit
+        );
+        let response = CustomerResponse::QuotaExceeded {
+            user_id,
+            quota: exceeded.quota,
+            limit: exceeded.limit,
+            retry_after_secs: exceeded.retry_after_secs,
+        };
+        self.route(&response).await
+    }
+
+    /// Puts a due notification back in the schedule of the repository until
+    /// the customer has quota again and tells the user when it is sent.
+    async fn defer_over_quota(
+        &self,
+        id: i64,
+        user_id: u32,
+        customer: CustomerName,
+        exceeded: QuotaExceeded,
+    ) -> Result<(), ControllerError> {
+        let now = Timestamp::now();
+        let retry_after_millis = exceeded.retry_after_secs as i64 * 1000;
+        let send_at = Timestamp::from_millis(now.as_millis() + retry_after_millis).unwrap_or(now);
+        log::info!(
+            "Customer [{}] exceeded quota {:?} of {}, deferring notification [{}]",
+            customer,
+            exceeded.quota,
+            exceeded.limit,
+            id
+        );
+        let request = RequestToRepository::DeferNotification {
+            user_id,
+            customer,
+            id,
+            send_at,
+        };
+        let record = Transformer::request_to_repository_to_record(&request);
+        self.notify(&record).await;
+        self.route(&request).await?;
+        let response = CustomerResponse::NotificationDeferred {
+            user_id: UserId::from(user_id),
+            id,
+            quota: exceeded.quota,
+            limit: exceeded.limit,
+            send_at,
+        };
+        self.route(&response).await
+    }
+
+    async fn forward_customer_request(
+        &mut self,
+        request: &CustomerRequest,
+        reserved: Option<CustomerName>,
+    ) -> Result<(), ControllerError> {
+        let request_id = self.customer_requests.next_id();
+        if let Some(repository_request) =
+            Transformer::customer_request_to_repository_to_customer_request(request)
+        {
+            self.route(&Correlated::new(request_id, repository_request))
+                .await?;
+            let pending = PendingCustomerRequest {
+                failure: Transformer::customer_request_to_timeout_response(request),
+                reserved,
+            };
+            self.customer_requests.track(request_id, pending);
+        }
 
         if let Some(request_to_repository) =
-            Transformer::customer_request_to_repository_request(&request)
+            Transformer::customer_request_to_repository_request(request)
         {
             let record = Transformer::request_to_repository_to_record(&request_to_repository);
-            self.publisher
-                .publish_message(
-                    &self.config.exchange,
-                    &self.config.history_queue,
-                    record.to_string(),
-                )
-                .await
-                .unwrap();
-            self.publisher
-                .publish_message(
-                    &self.config.exchange,
-                    &self.config.repository_request_queue,
-                    request_to_repository.to_string(),
-                )
-                .await
-                .unwrap();
+            self.notify(&record).await;
+            self.route(&request_to_repository).await?;
         }
+        Ok(())
     }
 
     pub async fn handle_client_response_from_repository(
         &mut self,
-        repository_response: ClientResponseFromRepository,
-    ) {
+        repository_response: Correlated<ClientResponseFromRepository>,
+    ) -> Result<(), ControllerError> {
+        let request_id = repository_response.request_id;
+        let repository_response = repository_response.message;
         let record = Transformer::client_response_from_repository_to_record(&repository_response);
         let response =
             Transformer::client_response_from_repository_to_client_response(&repository_response);
 
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.history_queue,
-                record.to_string(),
-            )
-            .await
-            .unwrap();
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.client_response_queue,
-                response.to_string(),
-            )
-            .await
-            .unwrap();
+        self.notify(&repository_response).await;
+        self.notify(&record).await;
+        if self.client_requests.resolve(request_id).is_none() {
+            log::warn!(
+                "Late or unexpected repository response: {}",
+                repository_response
+            );
+        }
+        self.route(&response).await
     }
 
     pub async fn handle_customer_response_from_repository(
         &mut self,
-        repository_response: CustomerResponseFromRepository,
-    ) {
+        repository_response: Correlated<CustomerResponseFromRepository>,
+    ) -> Result<(), ControllerError> {
+        let request_id = repository_response.request_id;
+        let repository_response = repository_response.message;
         let record = Transformer::customer_response_from_repository_to_record(&repository_response);
         let response = Transformer::customer_response_from_repository_to_customer_response(
             &repository_response,
         );
 
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.history_queue,
-                record.to_string(),
-            )
-            .await
-            .unwrap();
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.customer_response_queue,
-                response.to_string(),
-            )
-            .await
-            .unwrap();
+        self.notify(&repository_response).await;
+        self.notify(&record).await;
+        if let Some(webhook_request) =
+            Transformer::customer_response_from_repository_to_webhook_request(&repository_response)
+        {
+            return self
+                .forward_webhook_request(request_id, webhook_request)
+                .await;
+        }
+        match self.customer_requests.resolve(request_id) {
+            // Scheduled notifications are charged when they are due.
+            Some(PendingCustomerRequest {
+                reserved: Some(customer),
+                ..
+            }) if !matches!(
+                repository_response,
+                CustomerResponseFromRepository::NewNotification { success: true, .. }
+            ) =>
+            {
+                self.limiter.refund_notification(&customer)
+            }
+            Some(_) => {}
+            None => log::warn!(
+                "Late or unexpected repository response: {}",
+                repository_response
+            ),
+        }
+        self.route(&response).await?;
+
+        if let CustomerResponseFromRepository::OperatorRevoked {
+            customer,
+            operator_id,
+            success: true,
+            ..
+        } = repository_response
+        {
+            let response = CustomerResponse::OperatorAccessRevoked {
+                user_id: UserId::from(operator_id),
+                customer,
+            };
+            self.route(&response).await?;
+        }
+        Ok(())
+    }
+
+    /// Forwards a registration the repository allowed under the same request
+    /// id, so the webhooks service answers the pending customer request.
+    async fn forward_webhook_request(
+        &mut self,
+        request_id: u64,
+        request: WebhookRequest,
+    ) -> Result<(), ControllerError> {
+        match self.customer_requests.resolve(request_id) {
+            Some(pending) => self.customer_requests.track(request_id, pending),
+            None => {
+                log::warn!("Late or unexpected webhook permission: {}", request);
+                return Ok(());
+            }
+        }
+        if let Err(error) = self.route(&Correlated::new(request_id, request)).await {
+            if let Some(pending) = self.customer_requests.resolve(request_id) {
+                self.route(&pending.failure).await?;
+            }
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    pub async fn handle_webhook_response(
+        &mut self,
+        webhook_response: Correlated<WebhookResponse>,
+    ) -> Result<(), ControllerError> {
+        let request_id = webhook_response.request_id;
+        let webhook_response = webhook_response.message;
+        let response = Transformer::webhook_response_to_customer_response(&webhook_response);
+
+        self.notify(&webhook_response).await;
+        if self.customer_requests.resolve(request_id).is_none() {
+            log::warn!("Late or unexpected webhook response: {}", webhook_response);
+        }
+        self.route(&response).await
+    }
+
+    /// Rate limiter state to save if it changed since the last call.
+    pub fn rate_limits_snapshot(&mut self) -> Option<RateLimitsSnapshot> {
+        self.limiter.snapshot()
+    }
+
+    /// Sends the failure responses of the repository requests past their deadline.
+    pub async fn expire_pending_requests(&mut self) {
+        let now = Instant::now();
+        for response in self.client_requests.take_expired(now) {
+            log::warn!("Repository request timed out: {}", response);
+            let _ = self.route(&response).await;
+        }
+        // Tokens reserved for expired requests are kept, the repository may
+        // still have sent the notification.
+        for pending in self.customer_requests.take_expired(now) {
+            log::warn!("Repository request timed out: {}", pending.failure);
+            let _ = self.route(&pending.failure).await;
+        }
     }
 
     pub async fn handle_response_from_repository(
         &mut self,
         repository_response: ResponseFromRepository,
-    ) {
+    ) -> Result<(), ControllerError> {
         let record = Transformer::response_from_repository_to_record(&repository_response);
 
-        self.publisher
-            .publish_message(
-                &self.config.exchange,
-                &self.config.history_queue,
-                record.to_string(),
-            )
-            .await
-            .unwrap();
+        self.notify(&repository_response).await;
+        self.notify(&record).await;
 
         match repository_response {
-            ResponseFromRepository::Notifications(notifications) => {
-                for notification in notifications {
-                    let response = Transformer::notification_to_client_response(&notification);
-                    self.publisher
-                        .publish_message(
-                            &self.config.exchange,
-                            &self.config.client_response_queue,
-                            response.to_string(),
-                        )
-                        .await
-                        .unwrap();
+            ResponseFromRepository::Notifications {
+                user_id,
+                notifications,
+            } => {
+                let mut recipients: HashMap<CustomerName, usize> = HashMap::new();
+                for notification in &notifications {
+                    *recipients.entry(notification.customer.clone()).or_default() += 1;
+                }
+                for (customer, count) in recipients {
+                    self.limiter.charge_recipients(&customer, count);
                 }
+                self.delivery.push(FanOut {
+                    user_id,
+                    notifications,
+                });
+                Ok(())
             }
             ResponseFromRepository::Subscription {
+                operators,
+                customer,
+                product,
+            } => {
+                let request = WebhookRequest::Event {
+                    customer: customer.clone(),
+                    event: WebhookEvent::NewSubscriber {
+                        product: product.clone(),
+                    },
+                    timestamp: Timestamp::now(),
+                };
+                self.notify_webhook(request).await;
+
+                let mut result = Ok(());
+                for user_id in operators {
+                    let user_id = UserId::from(user_id);
+                    let response = CustomerResponse::ClientSubscription {
+                        user_id,
+                        customer: customer.clone(),
+                        product: product.clone(),
+                    };
+                    if let Err(error) = self.route(&response).await {
+                        result = Err(error);
+                    }
+                }
+                result
+            }
+            ResponseFromRepository::DueNotification {
+                id,
                 user_id,
                 customer,
                 product,
+                notification,
+                ..
             } => {
-                let user_id = UserId::from(user_id);
-                let response = CustomerResponse::ClientSubscription {
+                if let Err(exceeded) = self.limiter.check_notification(&customer) {
+                    return self.defer_over_quota(id, user_id, customer, exceeded).await;
+                }
+                self.limiter.charge_notification(&customer);
+                let request = RequestToRepository::NotificationForClients {
                     user_id,
                     customer,
                     product,
+                    notification,
                 };
-                self.publisher
-                    .publish_message(
-                        &self.config.exchange,
-                        &self.config.customer_response_queue,
-                        response.to_string(),
-                    )
-                    .await
-                    .unwrap();
+                let record = Transformer::request_to_repository_to_record(&request);
+                self.notify(&record).await;
+                self.route(&request).await
+            }
+        }
+    }
+
+    /// Publishes the message to every destination of the routing table.
+    /// Fails if the message has no route or any destination failed.
+    pub(crate) async fn route<M: Routed>(&self, message: &M) -> Result<(), ControllerError> {
+        let destinations = self.routes.destinations(message);
+        if destinations.is_empty() {
+            return Err(ControllerError::NoRoute(M::TYPE));
+        }
+        let data = message.encode(self.config.wire_format);
+        let mut result = Ok(());
+        for destination in destinations {
+            let exchange = destination
+                .exchange
+                .as_deref()
+                .unwrap_or(&self.config.exchange);
+            if let Err(error) = self
+                .publisher
+                .publish_message(exchange, &destination.queue, data.clone())
+                .await
+            {
+                log::error!(
+                    "Failed to publish {:?} to queue [{}]: {}",
+                    M::TYPE,
+                    destination.queue,
+                    error
+                );
+                result = Err(error.into());
             }
         }
+        result
+    }
+
+    /// Publishes a webhook event. Events get no response, so they are not tracked.
+    pub(crate) async fn notify_webhook(&mut self, request: WebhookRequest) {
+        let request_id = self.customer_requests.next_id();
+        self.notify(&Correlated::new(request_id, request)).await;
+    }
+
+    /// Publishes a message nobody waits for, such as a history record.
+    /// A missing route is not an error, publish failures are logged by `route`.
+    pub(crate) async fn notify<M: Routed>(&self, message: &M) {
+        let _ = self.route(message).await;
     }
 }
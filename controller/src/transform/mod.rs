@@ -1,30 +1,71 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use domain::{
-    models::{CustomerEventRecord, Notification, UserEventRecord, UserId},
+    models::{CustomerEventRecord, Notification, Timestamp, UserEventRecord, UserId},
     records::Record,
     requests::{
         ClientRequest, ClientRequestToRepository, CustomerRequest, CustomerRequestToRepository,
-        RequestToRepository,
+        RequestToRepository, WebhookRequest,
     },
     responses::{
         ClientResponse, ClientResponseFromRepository, CustomerResponse,
-        CustomerResponseFromRepository, ResponseFromRepository,
+        CustomerResponseFromRepository, ResponseFromRepository, WebhookResponse,
     },
 };
 
+/// Stored in the history instead of authorization keys.
+const REDACTED: &str = "<redacted>";
+
 pub struct Transformer {}
 impl Transformer {
+    /// Record data of the request without the authorization key.
+    fn redacted_customer_request(request: &CustomerRequest) -> String {
+        match request {
+            CustomerRequest::Authorization {
+                user_id, timestamp, ..
+            } => CustomerRequest::Authorization {
+                user_id: user_id.clone(),
+                key: REDACTED.to_string(),
+                timestamp: *timestamp,
+            }
+            .to_string(),
+            _ => request.to_string(),
+        }
+    }
+
+    /// Record data of the response without the invite key.
+    fn redacted_customer_response_from_repository(
+        response: &CustomerResponseFromRepository,
+    ) -> String {
+        match response {
+            CustomerResponseFromRepository::OperatorInvited {
+                user_id,
+                customer,
+                role,
+                expires_at,
+                ..
+            } => CustomerResponseFromRepository::OperatorInvited {
+                user_id: *user_id,
+                customer: customer.clone(),
+                role: *role,
+                key: REDACTED.to_string(),
+                expires_at: *expires_at,
+            }
+            .to_string(),
+            _ => response.to_string(),
+        }
+    }
+
     pub fn client_request_to_record(request: &ClientRequest) -> Record {
+        let received_at = Timestamp::now();
         let data = request.to_string();
 
         match request {
             ClientRequest::Customers { user_id, timestamp } => {
                 let user_id = user_id.0;
-                let timestamp = *timestamp;
+                let sent_at = Some(*timestamp);
                 let event = "Request for customers".to_string();
                 let record = UserEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     data,
                     event,
@@ -35,10 +76,11 @@ impl Transformer {
                 user_id, timestamp, ..
             } => {
                 let user_id = user_id.0;
-                let timestamp = *timestamp;
+                let sent_at = Some(*timestamp);
                 let event = "Request for products".to_string();
                 let record = UserEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     data,
                     event,
@@ -49,10 +91,71 @@ impl Transformer {
                 user_id, timestamp, ..
             } => {
                 let user_id = user_id.0;
-                let timestamp = *timestamp;
+                let sent_at = Some(*timestamp);
                 let event = "Request for new subscription".to_string();
                 let record = UserEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientRequest::Subscriptions {
+                user_id, timestamp, ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let event = "Request for subscriptions".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientRequest::CancelSubscription {
+                user_id, timestamp, ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let event = "Request for subscription cancellation".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientRequest::CancelAllSubscriptions {
+                user_id, timestamp, ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let event = "Request for cancellation of all subscriptions".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientRequest::NotificationHistory {
+                user_id, timestamp, ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let event = "Request for notification history".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
                     user_id,
                     data,
                     event,
@@ -65,10 +168,8 @@ impl Transformer {
     pub fn client_response_from_repository_to_record(
         response: &ClientResponseFromRepository,
     ) -> Record {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let sent_at = None;
+        let received_at = Timestamp::now();
         let data = response.to_string();
 
         match response {
@@ -76,7 +177,8 @@ impl Transformer {
                 let user_id = *user_id;
                 let event = "Response for customers".to_string();
                 let record = UserEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     data,
                     event,
@@ -87,7 +189,8 @@ impl Transformer {
                 let user_id = *user_id;
                 let event = "Response for products".to_string();
                 let record = UserEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     data,
                     event,
@@ -98,7 +201,80 @@ impl Transformer {
                 let user_id = *user_id;
                 let event = "Response for new subscription".to_string();
                 let record = UserEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientResponseFromRepository::UnknownCustomer { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for unknown customer".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientResponseFromRepository::UnknownProduct { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for unknown product".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientResponseFromRepository::Subscriptions { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for subscriptions".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientResponseFromRepository::SubscriptionCancelled { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for subscription cancellation".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientResponseFromRepository::SubscriptionsCancelled { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for cancellation of all subscriptions".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ClientResponseFromRepository::NotificationHistory { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for notification history".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
                     user_id,
                     data,
                     event,
@@ -109,18 +285,20 @@ impl Transformer {
     }
 
     pub fn customer_request_to_record(request: &CustomerRequest) -> Record {
-        let data = request.to_string();
+        let received_at = Timestamp::now();
+        let data = Self::redacted_customer_request(request);
 
         match request {
             CustomerRequest::Authorization {
                 user_id, timestamp, ..
             } => {
                 let user_id = user_id.0;
-                let timestamp = *timestamp;
+                let sent_at = Some(*timestamp);
                 let event = "Request for customer authorization".to_string();
                 let customer = None;
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -134,11 +312,12 @@ impl Transformer {
                 timestamp,
             } => {
                 let user_id = user_id.0;
-                let timestamp = *timestamp;
-                let customer = Some(customer.clone());
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
                 let event = "Request for products for notification".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -153,11 +332,12 @@ impl Transformer {
                 ..
             } => {
                 let user_id = user_id.0;
-                let timestamp = *timestamp;
-                let customer = Some(customer.clone());
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
                 let event = "Request for new notification".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -165,27 +345,18 @@ impl Transformer {
                 };
                 Record::CustomerEvent(record)
             }
-        }
-    }
-
-    pub fn customer_response_from_repository_to_record(
-        response: &CustomerResponseFromRepository,
-    ) -> Record {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let data = response.to_string();
-
-        match response {
-            CustomerResponseFromRepository::Authorization {
-                user_id, customer, ..
+            CustomerRequest::ScheduledNotifications {
+                user_id,
+                customer,
+                timestamp,
             } => {
-                let user_id = *user_id;
-                let event = "Response for customer authorization".to_string();
-                let customer = customer.clone().map(|customer| customer.name);
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for scheduled notifications".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -193,14 +364,19 @@ impl Transformer {
                 };
                 Record::CustomerEvent(record)
             }
-            CustomerResponseFromRepository::ProductsForNotification {
-                user_id, customer, ..
+            CustomerRequest::CancelScheduledNotification {
+                user_id,
+                customer,
+                timestamp,
+                ..
             } => {
-                let user_id = *user_id;
-                let event = "Response for products for notification".to_string();
-                let customer = Some(customer.clone());
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for scheduled notification cancellation".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -208,14 +384,19 @@ impl Transformer {
                 };
                 Record::CustomerEvent(record)
             }
-            CustomerResponseFromRepository::NewNotification {
-                user_id, customer, ..
+            CustomerRequest::NewRecurringNotification {
+                user_id,
+                customer,
+                timestamp,
+                ..
             } => {
-                let user_id = *user_id;
-                let event = "Response for new notification".to_string();
-                let customer = Some(customer.clone());
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for new recurring notification".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -223,25 +404,18 @@ impl Transformer {
                 };
                 Record::CustomerEvent(record)
             }
-        }
-    }
-
-    pub fn request_to_repository_to_record(request: &RequestToRepository) -> Record {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let data = request.to_string();
-
-        match request {
-            RequestToRepository::NotificationForClients {
-                user_id, customer, ..
+            CustomerRequest::RecurringNotifications {
+                user_id,
+                customer,
+                timestamp,
             } => {
-                let user_id = *user_id;
-                let event = "Request for notification for clients".to_string();
-                let customer = Some(customer.clone());
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for recurring notifications".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -249,47 +423,59 @@ impl Transformer {
                 };
                 Record::CustomerEvent(record)
             }
-            RequestToRepository::SubscriptionForCustomer { user_id, .. } => {
-                let user_id = *user_id;
-                let event = "Request for subscription for customer".to_string();
-                let record = UserEventRecord {
-                    timestamp,
+            CustomerRequest::PauseRecurringNotification {
+                user_id,
+                customer,
+                timestamp,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for recurring notification pause".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
                     user_id,
+                    customer,
                     data,
                     event,
                 };
-                Record::UserEvent(record)
+                Record::CustomerEvent(record)
             }
-        }
-    }
-
-    pub fn response_from_repository_to_record(response: &ResponseFromRepository) -> Record {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let data = response.to_string();
-
-        match response {
-            ResponseFromRepository::Notifications(_) => {
-                let user_id = 0;
-                let event = "Response for notifications for clients".to_string();
-                let record = UserEventRecord {
-                    timestamp,
+            CustomerRequest::DeleteRecurringNotification {
+                user_id,
+                customer,
+                timestamp,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for recurring notification deletion".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
                     user_id,
+                    customer,
                     data,
                     event,
                 };
-                Record::UserEvent(record)
+                Record::CustomerEvent(record)
             }
-            ResponseFromRepository::Subscription {
-                user_id, customer, ..
+            CustomerRequest::SetWebhook {
+                user_id,
+                customer,
+                timestamp,
+                ..
             } => {
-                let user_id = *user_id;
-                let event = "Response for subscription for customer".to_string();
-                let customer = Some(customer.clone());
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for webhook change".to_string();
                 let record = CustomerEventRecord {
-                    timestamp,
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
                     data,
@@ -297,48 +483,598 @@ impl Transformer {
                 };
                 Record::CustomerEvent(record)
             }
-        }
-    }
-
-    pub fn client_request_to_repository_to_client_request(
-        request: &ClientRequest,
-    ) -> ClientRequestToRepository {
-        match request {
-            ClientRequest::Customers { user_id, .. } => {
+            CustomerRequest::Operators {
+                user_id,
+                customer,
+                timestamp,
+            } => {
                 let user_id = user_id.0;
-                ClientRequestToRepository::Customers { user_id }
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for operators".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
             }
-            ClientRequest::Products {
-                user_id, customer, ..
+            CustomerRequest::InviteOperator {
+                user_id,
+                customer,
+                timestamp,
+                ..
             } => {
                 let user_id = user_id.0;
-                let customer = customer.clone();
-                ClientRequestToRepository::Products { user_id, customer }
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for operator invite".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
             }
-            ClientRequest::NewSubscription {
+            CustomerRequest::RevokeOperator {
                 user_id,
                 customer,
-                product,
+                timestamp,
                 ..
             } => {
                 let user_id = user_id.0;
-                let customer = customer.clone();
-                let product = product.clone();
-                ClientRequestToRepository::NewSubscription {
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for operator revocation".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
                     user_id,
                     customer,
-                    product,
-                }
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
             }
-        }
-    }
-
-    pub fn client_response_from_repository_to_client_response(
-        response: &ClientResponseFromRepository,
-    ) -> ClientResponse {
-        match response {
-            ClientResponseFromRepository::Customers { user_id, customers } => {
-                let user_id = UserId::from(*user_id);
+            CustomerRequest::NotificationHistory {
+                user_id,
+                customer,
+                timestamp,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let sent_at = Some(*timestamp);
+                let customer = Some(customer.to_string());
+                let event = "Request for notification history".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+        }
+    }
+
+    pub fn customer_response_from_repository_to_record(
+        response: &CustomerResponseFromRepository,
+    ) -> Record {
+        let sent_at = None;
+        let received_at = Timestamp::now();
+        let data = Self::redacted_customer_response_from_repository(response);
+
+        match response {
+            CustomerResponseFromRepository::Authorization {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for customer authorization".to_string();
+                let customer = customer.clone().map(|customer| customer.name.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::ProductsForNotification {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for products for notification".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::NewNotification {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for new notification".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::UnknownCustomer { user_id, customer } => {
+                let user_id = *user_id;
+                let event = "Response for unknown customer".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::UnknownProduct {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for unknown product".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::NotificationScheduled {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for scheduled notification".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::ScheduledNotifications {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for scheduled notifications".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::ScheduledNotificationCancelled {
+                user_id,
+                customer,
+                ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for scheduled notification cancellation".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::RecurringNotificationCreated {
+                user_id,
+                customer,
+                ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for new recurring notification".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::RecurringNotifications {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for recurring notifications".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::RecurringNotificationPaused {
+                user_id,
+                customer,
+                ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for recurring notification pause".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::RecurringNotificationDeleted {
+                user_id,
+                customer,
+                ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for recurring notification deletion".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::InvalidSchedule {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for invalid schedule".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::AuthorizationLocked { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for locked customer authorization".to_string();
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer: None,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::Operators {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for operators".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::OperatorInvited {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for operator invite".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::OperatorRevoked {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for operator revocation".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::WebhookAllowed {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for webhook permission".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::PermissionDenied { user_id, customer } => {
+                let user_id = *user_id;
+                let event = "Response for denied permission".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            CustomerResponseFromRepository::NotificationHistory {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Response for notification history".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+        }
+    }
+
+    pub fn request_to_repository_to_record(request: &RequestToRepository) -> Record {
+        let sent_at = None;
+        let received_at = Timestamp::now();
+        let data = request.to_string();
+
+        match request {
+            RequestToRepository::NotificationForClients {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Request for notification for clients".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            RequestToRepository::SubscriptionForCustomer { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Request for subscription for customer".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            RequestToRepository::DeferNotification {
+                user_id, customer, ..
+            } => {
+                let user_id = *user_id;
+                let event = "Request to defer notification".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+        }
+    }
+
+    pub fn response_from_repository_to_record(response: &ResponseFromRepository) -> Record {
+        let sent_at = None;
+        let received_at = Timestamp::now();
+        let data = response.to_string();
+
+        match response {
+            ResponseFromRepository::Notifications { user_id, .. } => {
+                let user_id = *user_id;
+                let event = "Response for notifications for clients".to_string();
+                let record = UserEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    data,
+                    event,
+                };
+                Record::UserEvent(record)
+            }
+            ResponseFromRepository::Subscription {
+                operators,
+                customer,
+                ..
+            } => {
+                let user_id = operators.first().copied().unwrap_or_default();
+                let event = "Response for subscription for customer".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+            ResponseFromRepository::DueNotification {
+                user_id,
+                customer,
+                send_at,
+                ..
+            } => {
+                let sent_at = Some(*send_at);
+                let user_id = *user_id;
+                let event = "Due notification".to_string();
+                let customer = Some(customer.to_string());
+                let record = CustomerEventRecord {
+                    sent_at,
+                    received_at,
+                    user_id,
+                    customer,
+                    data,
+                    event,
+                };
+                Record::CustomerEvent(record)
+            }
+        }
+    }
+
+    pub fn client_request_to_repository_to_client_request(
+        request: &ClientRequest,
+    ) -> ClientRequestToRepository {
+        match request {
+            ClientRequest::Customers { user_id, .. } => {
+                let user_id = user_id.0;
+                ClientRequestToRepository::Customers { user_id }
+            }
+            ClientRequest::Products {
+                user_id, customer, ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                ClientRequestToRepository::Products { user_id, customer }
+            }
+            ClientRequest::NewSubscription {
+                user_id,
+                customer,
+                product,
+                channel,
+                email,
+                mode,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let product = product.clone();
+                let channel = *channel;
+                let email = email.clone();
+                let mode = *mode;
+                ClientRequestToRepository::NewSubscription {
+                    user_id,
+                    customer,
+                    product,
+                    channel,
+                    email,
+                    mode,
+                }
+            }
+            ClientRequest::Subscriptions { user_id, .. } => {
+                let user_id = user_id.0;
+                ClientRequestToRepository::Subscriptions { user_id }
+            }
+            ClientRequest::CancelSubscription { user_id, id, .. } => {
+                let user_id = user_id.0;
+                let id = *id;
+                ClientRequestToRepository::CancelSubscription { user_id, id }
+            }
+            ClientRequest::CancelAllSubscriptions { user_id, .. } => {
+                let user_id = user_id.0;
+                ClientRequestToRepository::CancelAllSubscriptions { user_id }
+            }
+            ClientRequest::NotificationHistory { user_id, page, .. } => {
+                let user_id = user_id.0;
+                let page = *page;
+                ClientRequestToRepository::NotificationHistory { user_id, page }
+            }
+        }
+    }
+
+    pub fn client_response_from_repository_to_client_response(
+        response: &ClientResponseFromRepository,
+    ) -> ClientResponse {
+        match response {
+            ClientResponseFromRepository::Customers { user_id, customers } => {
+                let user_id = UserId::from(*user_id);
                 let customers = customers.clone();
                 ClientResponse::Customers { user_id, customers }
             }
@@ -347,63 +1083,360 @@ impl Transformer {
                 let products = products.clone();
                 ClientResponse::Products { user_id, products }
             }
-            ClientResponseFromRepository::NewSubscription { user_id, success } => {
-                let user_id = UserId::from(*user_id);
-                match *success {
-                    true => ClientResponse::SubscriptionSuccess { user_id },
-                    false => ClientResponse::SubscriptionFailure { user_id },
+            ClientResponseFromRepository::NewSubscription { user_id, success } => {
+                let user_id = UserId::from(*user_id);
+                match *success {
+                    true => ClientResponse::SubscriptionSuccess { user_id },
+                    false => ClientResponse::SubscriptionFailure { user_id },
+                }
+            }
+            ClientResponseFromRepository::UnknownCustomer { user_id, customer } => {
+                let user_id = UserId::from(*user_id);
+                let customer = customer.clone();
+                ClientResponse::UnknownCustomer { user_id, customer }
+            }
+            ClientResponseFromRepository::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => {
+                let user_id = UserId::from(*user_id);
+                let customer = customer.clone();
+                let product = product.clone();
+                ClientResponse::UnknownProduct {
+                    user_id,
+                    customer,
+                    product,
+                }
+            }
+            ClientResponseFromRepository::Subscriptions {
+                user_id,
+                subscriptions,
+            } => {
+                let user_id = UserId::from(*user_id);
+                let subscriptions = subscriptions.clone();
+                ClientResponse::Subscriptions {
+                    user_id,
+                    subscriptions,
+                }
+            }
+            ClientResponseFromRepository::SubscriptionCancelled {
+                user_id,
+                id,
+                success,
+            } => {
+                let user_id = UserId::from(*user_id);
+                let id = *id;
+                let success = *success;
+                ClientResponse::SubscriptionCancelled {
+                    user_id,
+                    id,
+                    success,
+                }
+            }
+            ClientResponseFromRepository::SubscriptionsCancelled { user_id, count } => {
+                let user_id = UserId::from(*user_id);
+                let count = *count;
+                ClientResponse::SubscriptionsCancelled { user_id, count }
+            }
+            ClientResponseFromRepository::NotificationHistory {
+                user_id,
+                page,
+                notifications,
+                has_more,
+            } => {
+                let user_id = UserId::from(*user_id);
+                let page = *page;
+                let notifications = notifications.clone();
+                let has_more = *has_more;
+                ClientResponse::NotificationHistory {
+                    user_id,
+                    page,
+                    notifications,
+                    has_more,
+                }
+            }
+        }
+    }
+
+    /// `None` for the requests served by other services, such as webhooks.
+    pub fn customer_request_to_repository_to_customer_request(
+        request: &CustomerRequest,
+    ) -> Option<CustomerRequestToRepository> {
+        let request = match request {
+            CustomerRequest::Authorization { user_id, key, .. } => {
+                let user_id = user_id.0;
+                let key = key.clone();
+                CustomerRequestToRepository::Authorization { user_id, key }
+            }
+            CustomerRequest::ProductsForNotification {
+                user_id, customer, ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                CustomerRequestToRepository::ProductsForNotification { user_id, customer }
+            }
+            CustomerRequest::NewNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                send_at,
+                timestamp,
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let product = product.clone();
+                let notification = notification.clone();
+                let send_at = Self::scheduled_send_at(send_at, timestamp);
+                CustomerRequestToRepository::NewNotification {
+                    user_id,
+                    customer,
+                    product,
+                    notification,
+                    send_at,
+                }
+            }
+            CustomerRequest::ScheduledNotifications {
+                user_id, customer, ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                CustomerRequestToRepository::ScheduledNotifications { user_id, customer }
+            }
+            CustomerRequest::CancelScheduledNotification {
+                user_id,
+                customer,
+                id,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let id = *id;
+                CustomerRequestToRepository::CancelScheduledNotification {
+                    user_id,
+                    customer,
+                    id,
+                }
+            }
+            CustomerRequest::NewRecurringNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                schedule,
+                timezone,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let product = product.clone();
+                let notification = notification.clone();
+                let schedule = schedule.clone();
+                let timezone = timezone.clone();
+                CustomerRequestToRepository::NewRecurringNotification {
+                    user_id,
+                    customer,
+                    product,
+                    notification,
+                    schedule,
+                    timezone,
+                }
+            }
+            CustomerRequest::RecurringNotifications {
+                user_id, customer, ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                CustomerRequestToRepository::RecurringNotifications { user_id, customer }
+            }
+            CustomerRequest::PauseRecurringNotification {
+                user_id,
+                customer,
+                id,
+                paused,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let id = *id;
+                let paused = *paused;
+                CustomerRequestToRepository::PauseRecurringNotification {
+                    user_id,
+                    customer,
+                    id,
+                    paused,
                 }
             }
-        }
-    }
-
-    pub fn customer_request_to_repository_to_customer_request(
-        request: &CustomerRequest,
-    ) -> CustomerRequestToRepository {
-        match request {
-            CustomerRequest::Authorization { user_id, key, .. } => {
+            CustomerRequest::DeleteRecurringNotification {
+                user_id,
+                customer,
+                id,
+                ..
+            } => {
                 let user_id = user_id.0;
-                let key = key.clone();
-                CustomerRequestToRepository::Authorization { user_id, key }
+                let customer = customer.clone();
+                let id = *id;
+                CustomerRequestToRepository::DeleteRecurringNotification {
+                    user_id,
+                    customer,
+                    id,
+                }
             }
-            CustomerRequest::ProductsForNotification {
+            CustomerRequest::Operators {
                 user_id, customer, ..
             } => {
                 let user_id = user_id.0;
                 let customer = customer.clone();
-                CustomerRequestToRepository::ProductsForNotification { user_id, customer }
+                CustomerRequestToRepository::Operators { user_id, customer }
             }
-            CustomerRequest::NewNotification {
+            CustomerRequest::InviteOperator {
+                user_id,
+                customer,
+                role,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let role = *role;
+                CustomerRequestToRepository::InviteOperator {
+                    user_id,
+                    customer,
+                    role,
+                }
+            }
+            CustomerRequest::RevokeOperator {
+                user_id,
+                customer,
+                operator_id,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let operator_id = *operator_id;
+                CustomerRequestToRepository::RevokeOperator {
+                    user_id,
+                    customer,
+                    operator_id,
+                }
+            }
+            CustomerRequest::NotificationHistory {
                 user_id,
                 customer,
                 product,
-                notification,
+                page,
                 ..
             } => {
                 let user_id = user_id.0;
                 let customer = customer.clone();
                 let product = product.clone();
-                let notification = notification.clone();
-                CustomerRequestToRepository::NewNotification {
+                let page = *page;
+                CustomerRequestToRepository::NotificationHistory {
                     user_id,
                     customer,
                     product,
-                    notification,
+                    page,
+                }
+            }
+            CustomerRequest::SetWebhook {
+                user_id,
+                customer,
+                url,
+                ..
+            } => {
+                let user_id = user_id.0;
+                let customer = customer.clone();
+                let url = url.clone();
+                CustomerRequestToRepository::SetWebhook {
+                    user_id,
+                    customer,
+                    url,
+                }
+            }
+        };
+        Some(request)
+    }
+
+    /// Webhook registration the repository allowed, to forward to the webhooks service.
+    pub fn customer_response_from_repository_to_webhook_request(
+        response: &CustomerResponseFromRepository,
+    ) -> Option<WebhookRequest> {
+        match response {
+            CustomerResponseFromRepository::WebhookAllowed {
+                user_id,
+                customer,
+                url,
+            } => {
+                let user_id = *user_id;
+                let customer = customer.clone();
+                let url = url.clone();
+                Some(WebhookRequest::Register {
+                    user_id,
+                    customer,
+                    url,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn webhook_response_to_customer_response(response: &WebhookResponse) -> CustomerResponse {
+        match response {
+            WebhookResponse::Registered {
+                user_id,
+                url,
+                secret,
+            } => {
+                let user_id = UserId::from(*user_id);
+                let url = url.clone();
+                let secret = secret.clone();
+                CustomerResponse::WebhookRegistered {
+                    user_id,
+                    url,
+                    secret,
                 }
             }
+            WebhookResponse::Removed { user_id } => {
+                let user_id = UserId::from(*user_id);
+                CustomerResponse::WebhookRemoved { user_id }
+            }
+            WebhookResponse::InvalidUrl { user_id, url } => {
+                let user_id = UserId::from(*user_id);
+                let url = url.clone();
+                CustomerResponse::InvalidWebhookUrl { user_id, url }
+            }
+            WebhookResponse::Failure { user_id } => {
+                let user_id = UserId::from(*user_id);
+                CustomerResponse::RequestFailure { user_id }
+            }
         }
     }
 
+    /// Send time of a notification, `None` if it must be sent right away.
+    /// Compared with the request time, so every transform agrees on it.
+    fn scheduled_send_at(send_at: &Option<Timestamp>, timestamp: &Timestamp) -> Option<Timestamp> {
+        send_at.filter(|send_at| send_at > timestamp)
+    }
+
     pub fn customer_response_from_repository_to_customer_response(
         response: &CustomerResponseFromRepository,
     ) -> CustomerResponse {
         match response {
-            CustomerResponseFromRepository::Authorization { user_id, customer } => {
+            CustomerResponseFromRepository::Authorization {
+                user_id,
+                customer,
+                role,
+            } => {
                 let user_id = UserId::from(*user_id);
                 match customer {
                     Some(customer) => {
                         let customer = customer.clone();
-                        CustomerResponse::AuthorizationSuccess { user_id, customer }
+                        let role = *role;
+                        CustomerResponse::AuthorizationSuccess {
+                            user_id,
+                            customer,
+                            role,
+                        }
                     }
                     None => CustomerResponse::AuthorizationFailure { user_id },
                 }
@@ -431,6 +1464,211 @@ impl Transformer {
                     false => CustomerResponse::NotificationFailure { user_id },
                 }
             }
+            CustomerResponseFromRepository::UnknownCustomer { user_id, customer } => {
+                let user_id = UserId::from(*user_id);
+                let customer = customer.clone();
+                CustomerResponse::UnknownCustomer { user_id, customer }
+            }
+            CustomerResponseFromRepository::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => {
+                let user_id = UserId::from(*user_id);
+                let customer = customer.clone();
+                let product = product.clone();
+                CustomerResponse::UnknownProduct {
+                    user_id,
+                    customer,
+                    product,
+                }
+            }
+            CustomerResponseFromRepository::NotificationScheduled {
+                user_id,
+                notification,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let notification = notification.clone();
+                CustomerResponse::NotificationScheduled {
+                    user_id,
+                    notification,
+                }
+            }
+            CustomerResponseFromRepository::ScheduledNotifications {
+                user_id,
+                notifications,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let notifications = notifications.clone();
+                CustomerResponse::ScheduledNotifications {
+                    user_id,
+                    notifications,
+                }
+            }
+            CustomerResponseFromRepository::ScheduledNotificationCancelled {
+                user_id,
+                id,
+                success,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let id = *id;
+                let success = *success;
+                CustomerResponse::ScheduledNotificationCancelled {
+                    user_id,
+                    id,
+                    success,
+                }
+            }
+            CustomerResponseFromRepository::RecurringNotificationCreated {
+                user_id,
+                notification,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let notification = notification.clone();
+                CustomerResponse::RecurringNotificationCreated {
+                    user_id,
+                    notification,
+                }
+            }
+            CustomerResponseFromRepository::RecurringNotifications {
+                user_id,
+                notifications,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let notifications = notifications.clone();
+                CustomerResponse::RecurringNotifications {
+                    user_id,
+                    notifications,
+                }
+            }
+            CustomerResponseFromRepository::RecurringNotificationPaused {
+                user_id,
+                id,
+                paused,
+                success,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let id = *id;
+                let paused = *paused;
+                let success = *success;
+                CustomerResponse::RecurringNotificationPaused {
+                    user_id,
+                    id,
+                    paused,
+                    success,
+                }
+            }
+            CustomerResponseFromRepository::RecurringNotificationDeleted {
+                user_id,
+                id,
+                success,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let id = *id;
+                let success = *success;
+                CustomerResponse::RecurringNotificationDeleted {
+                    user_id,
+                    id,
+                    success,
+                }
+            }
+            CustomerResponseFromRepository::InvalidSchedule {
+                user_id,
+                schedule,
+                timezone,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let schedule = schedule.clone();
+                let timezone = timezone.clone();
+                CustomerResponse::InvalidSchedule {
+                    user_id,
+                    schedule,
+                    timezone,
+                }
+            }
+            CustomerResponseFromRepository::AuthorizationLocked { user_id, until } => {
+                let user_id = UserId::from(*user_id);
+                let until = *until;
+                CustomerResponse::AuthorizationLocked { user_id, until }
+            }
+            CustomerResponseFromRepository::Operators {
+                user_id, operators, ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let operators = operators.clone();
+                CustomerResponse::Operators { user_id, operators }
+            }
+            CustomerResponseFromRepository::OperatorInvited {
+                user_id,
+                role,
+                key,
+                expires_at,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let role = *role;
+                let key = key.clone();
+                let expires_at = *expires_at;
+                CustomerResponse::OperatorInvited {
+                    user_id,
+                    role,
+                    key,
+                    expires_at,
+                }
+            }
+            CustomerResponseFromRepository::OperatorRevoked {
+                user_id,
+                operator_id,
+                success,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let operator_id = *operator_id;
+                let success = *success;
+                CustomerResponse::OperatorRevoked {
+                    user_id,
+                    operator_id,
+                    success,
+                }
+            }
+            CustomerResponseFromRepository::PermissionDenied { user_id, .. } => {
+                let user_id = UserId::from(*user_id);
+                CustomerResponse::PermissionDenied { user_id }
+            }
+            // Answered by the webhooks service once the registration is forwarded.
+            CustomerResponseFromRepository::WebhookAllowed { user_id, .. } => {
+                let user_id = UserId::from(*user_id);
+                CustomerResponse::RequestFailure { user_id }
+            }
+            CustomerResponseFromRepository::NotificationHistory {
+                user_id,
+                product,
+                page,
+                notifications,
+                has_more,
+                ..
+            } => {
+                let user_id = UserId::from(*user_id);
+                let product = product.clone();
+                let page = *page;
+                let notifications = notifications.clone();
+                let has_more = *has_more;
+                CustomerResponse::NotificationHistory {
+                    user_id,
+                    product,
+                    page,
+                    notifications,
+                    has_more,
+                }
+            }
         }
     }
 
@@ -466,8 +1704,9 @@ impl Transformer {
                 customer,
                 product,
                 notification,
-                ..
-            } => {
+                send_at,
+                timestamp,
+            } if Self::scheduled_send_at(send_at, timestamp).is_none() => {
                 let user_id = user_id.0;
                 let customer = customer.clone();
                 let product = product.clone();
@@ -487,7 +1726,7 @@ impl Transformer {
         let user_id = UserId::from(notification.user_id);
         let customer = notification.customer.clone();
         let product = notification.product.clone();
-        let notification = notification.text.clone();
+        let notification = notification.content.clone();
 
         ClientResponse::CustomerNotification {
             user_id,
@@ -496,4 +1735,131 @@ impl Transformer {
             notification,
         }
     }
+
+    pub fn client_request_to_failure_response(request: &ClientRequest) -> ClientResponse {
+        match request {
+            ClientRequest::NewSubscription { user_id, .. } => ClientResponse::SubscriptionFailure {
+                user_id: user_id.clone(),
+            },
+            ClientRequest::Customers { user_id, .. }
+            | ClientRequest::Products { user_id, .. }
+            | ClientRequest::Subscriptions { user_id, .. }
+            | ClientRequest::CancelSubscription { user_id, .. }
+            | ClientRequest::CancelAllSubscriptions { user_id, .. }
+            | ClientRequest::NotificationHistory { user_id, .. } => {
+                ClientResponse::RequestFailure {
+                    user_id: user_id.clone(),
+                }
+            }
+        }
+    }
+
+    pub fn customer_request_to_failure_response(request: &CustomerRequest) -> CustomerResponse {
+        match request {
+            CustomerRequest::NewNotification { user_id, .. }
+            | CustomerRequest::NewRecurringNotification { user_id, .. } => {
+                CustomerResponse::NotificationFailure {
+                    user_id: user_id.clone(),
+                }
+            }
+            CustomerRequest::Authorization { user_id, .. }
+            | CustomerRequest::ProductsForNotification { user_id, .. }
+            | CustomerRequest::ScheduledNotifications { user_id, .. }
+            | CustomerRequest::CancelScheduledNotification { user_id, .. }
+            | CustomerRequest::RecurringNotifications { user_id, .. }
+            | CustomerRequest::PauseRecurringNotification { user_id, .. }
+            | CustomerRequest::DeleteRecurringNotification { user_id, .. }
+            | CustomerRequest::Operators { user_id, .. }
+            | CustomerRequest::InviteOperator { user_id, .. }
+            | CustomerRequest::RevokeOperator { user_id, .. }
+            | CustomerRequest::NotificationHistory { user_id, .. }
+            | CustomerRequest::SetWebhook { user_id, .. } => CustomerResponse::RequestFailure {
+                user_id: user_id.clone(),
+            },
+        }
+    }
+
+    pub fn client_request_to_timeout_response(request: &ClientRequest) -> ClientResponse {
+        let user_id = request.user_id().clone();
+        match request {
+            ClientRequest::NewSubscription { .. } => {
+                ClientResponse::SubscriptionFailure { user_id }
+            }
+            ClientRequest::Customers { .. }
+            | ClientRequest::Products { .. }
+            | ClientRequest::Subscriptions { .. }
+            | ClientRequest::CancelSubscription { .. }
+            | ClientRequest::CancelAllSubscriptions { .. }
+            | ClientRequest::NotificationHistory { .. } => {
+                ClientResponse::ServiceUnavailable { user_id }
+            }
+        }
+    }
+
+    pub fn customer_request_to_timeout_response(request: &CustomerRequest) -> CustomerResponse {
+        let user_id = request.user_id().clone();
+        match request {
+            CustomerRequest::NewNotification { .. }
+            | CustomerRequest::NewRecurringNotification { .. } => {
+                CustomerResponse::NotificationFailure { user_id }
+            }
+            CustomerRequest::Authorization { .. }
+            | CustomerRequest::ProductsForNotification { .. }
+            | CustomerRequest::ScheduledNotifications { .. }
+            | CustomerRequest::CancelScheduledNotification { .. }
+            | CustomerRequest::RecurringNotifications { .. }
+            | CustomerRequest::PauseRecurringNotification { .. }
+            | CustomerRequest::DeleteRecurringNotification { .. }
+            | CustomerRequest::Operators { .. }
+            | CustomerRequest::InviteOperator { .. }
+            | CustomerRequest::RevokeOperator { .. }
+            | CustomerRequest::NotificationHistory { .. }
+            | CustomerRequest::SetWebhook { .. } => {
+                CustomerResponse::ServiceUnavailable { user_id }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::models::{CustomerName, OperatorRole};
+
+    const KEY: &str = "abcdef01.0123456789abcdef0123456789abcdef";
+
+    fn record_data(record: Record) -> String {
+        match record {
+            Record::UserEvent(record) => record.data,
+            Record::CustomerEvent(record) => record.data,
+        }
+    }
+
+    #[test]
+    fn authorization_records_have_no_key() {
+        let request = CustomerRequest::Authorization {
+            user_id: UserId(7),
+            key: KEY.to_string(),
+            timestamp: Timestamp::now(),
+        };
+        let data = record_data(Transformer::customer_request_to_record(&request));
+        assert!(!data.contains(KEY));
+        assert!(data.contains(REDACTED));
+    }
+
+    #[test]
+    fn operator_invite_records_have_no_key() {
+        let response = CustomerResponseFromRepository::OperatorInvited {
+            user_id: 7,
+            customer: CustomerName::parse("shop").unwrap(),
+            role: OperatorRole::Editor,
+            key: KEY.to_string(),
+            expires_at: Timestamp::now(),
+        };
+        let data = record_data(Transformer::customer_response_from_repository_to_record(
+            &response,
+        ));
+        assert!(!data.contains(KEY));
+        assert!(data.contains(REDACTED));
+    }
 }
@@ -0,0 +1,386 @@
+//! Encoding of the messages exchanged through the message broker.
+
+use std::fmt::{self, Display};
+
+use prost::Message;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::proto::{self, ProtoError};
+use crate::records::Record;
+use crate::requests::{
+    AdminRequestToRepository, ClientRequest, ClientRequestToRepository, CustomerRequest,
+    CustomerRequestToRepository, ModerationDecision, RequestToRepository, WebhookRequest,
+};
+use crate::responses::{
+    ClientResponse, ClientResponseFromRepository, CustomerResponse, CustomerResponseFromRepository,
+    ResponseFromRepository, WebhookResponse,
+};
+
+/// Wire format of the queue messages. All services connected to the same
+/// queues must use the same format.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WireFormat {
+    #[default]
+    Json,
+    Protobuf,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Json(serde_json::Error),
+    Protobuf(prost::DecodeError),
+    Invalid(ProtoError),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Json(error) => write!(f, "invalid json message: {}", error),
+            DecodeError::Protobuf(error) => write!(f, "invalid protobuf message: {}", error),
+            DecodeError::Invalid(error) => write!(f, "invalid message content: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Message of a request/response exchange between services. The responder
+/// copies `request_id` of the request into its response, so the requester can
+/// tell which request a response belongs to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Correlated<T> {
+    pub request_id: u64,
+    pub message: T,
+}
+
+impl<T> Correlated<T> {
+    pub fn new(request_id: u64, message: T) -> Self {
+        Correlated {
+            request_id,
+            message,
+        }
+    }
+
+    /// Response to this request, with the same `request_id`.
+    pub fn reply<R>(&self, message: R) -> Correlated<R> {
+        Correlated::new(self.request_id, message)
+    }
+}
+
+impl<T: Display> Display for Correlated<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.request_id, self.message)
+    }
+}
+
+pub trait WireMessage: Serialize + DeserializeOwned + Sized {
+    type Proto: Message + Default + for<'a> From<&'a Self>;
+
+    fn encode(&self, format: WireFormat) -> Vec<u8> {
+        match format {
+            WireFormat::Json => serde_json::to_vec(self).unwrap(),
+            WireFormat::Protobuf => Self::Proto::from(self).encode_to_vec(),
+        }
+    }
+
+    fn decode(message: &[u8], format: WireFormat) -> Result<Self, DecodeError>
+    where
+        Self: TryFrom<Self::Proto, Error = ProtoError>,
+    {
+        match format {
+            WireFormat::Json => serde_json::from_slice(message).map_err(DecodeError::Json),
+            WireFormat::Protobuf => Self::Proto::decode(message)
+                .map_err(DecodeError::Protobuf)?
+                .try_into()
+                .map_err(DecodeError::Invalid),
+        }
+    }
+}
+
+impl WireMessage for ClientRequest {
+    type Proto = proto::notifyme::ClientRequest;
+}
+
+impl WireMessage for CustomerRequest {
+    type Proto = proto::notifyme::CustomerRequest;
+}
+
+impl WireMessage for ModerationDecision {
+    type Proto = proto::notifyme::ModerationDecision;
+}
+
+impl WireMessage for ClientRequestToRepository {
+    type Proto = proto::notifyme::ClientRequestToRepository;
+}
+
+impl WireMessage for CustomerRequestToRepository {
+    type Proto = proto::notifyme::CustomerRequestToRepository;
+}
+
+impl WireMessage for RequestToRepository {
+    type Proto = proto::notifyme::RequestToRepository;
+}
+
+impl WireMessage for AdminRequestToRepository {
+    type Proto = proto::notifyme::AdminRequestToRepository;
+}
+
+impl WireMessage for ClientResponse {
+    type Proto = proto::notifyme::ClientResponse;
+}
+
+impl WireMessage for CustomerResponse {
+    type Proto = proto::notifyme::CustomerResponse;
+}
+
+impl WireMessage for ClientResponseFromRepository {
+    type Proto = proto::notifyme::ClientResponseFromRepository;
+}
+
+impl WireMessage for CustomerResponseFromRepository {
+    type Proto = proto::notifyme::CustomerResponseFromRepository;
+}
+
+impl WireMessage for ResponseFromRepository {
+    type Proto = proto::notifyme::ResponseFromRepository;
+}
+
+impl WireMessage for WebhookRequest {
+    type Proto = proto::notifyme::WebhookRequest;
+}
+
+impl WireMessage for WebhookResponse {
+    type Proto = proto::notifyme::WebhookResponse;
+}
+
+impl WireMessage for Record {
+    type Proto = proto::notifyme::Record;
+}
+
+/// Wire format of `Correlated` messages: the protobuf message of `$message`
+/// with its `request_id` field set.
+macro_rules! correlated {
+    ($($message:ident),* $(,)?) => {
+        $(
+            impl From<&Correlated<$message>> for proto::notifyme::$message {
+                fn from(value: &Correlated<$message>) -> Self {
+                    proto::notifyme::$message {
+                        request_id: value.request_id,
+                        ..(&value.message).into()
+                    }
+                }
+            }
+
+            impl TryFrom<proto::notifyme::$message> for Correlated<$message> {
+                type Error = ProtoError;
+
+                fn try_from(value: proto::notifyme::$message) -> Result<Self, Self::Error> {
+                    let request_id = value.request_id;
+                    Ok(Correlated::new(request_id, value.try_into()?))
+                }
+            }
+
+            impl WireMessage for Correlated<$message> {
+                type Proto = proto::notifyme::$message;
+            }
+        )*
+    };
+}
+
+correlated!(
+    ClientRequestToRepository,
+    CustomerRequestToRepository,
+    WebhookRequest,
+    ClientResponseFromRepository,
+    CustomerResponseFromRepository,
+    WebhookResponse,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        Attachment, CustomerName, DeliveryChannel, LinkButton, NotificationContent, ParseMode,
+        ProductName, Quota, SubscriptionMode, Timestamp, UserId,
+    };
+    use crate::requests::ModerationDecision;
+    use crate::responses::ClientResponse;
+
+    const FORMATS: [WireFormat; 2] = [WireFormat::Json, WireFormat::Protobuf];
+
+    fn timestamp() -> Timestamp {
+        Timestamp::from_millis(1_671_450_000_123).unwrap()
+    }
+
+    fn content() -> NotificationContent {
+        NotificationContent {
+            text: "*Sale* today".to_string(),
+            parse_mode: ParseMode::MarkdownV2,
+            attachment: Some(Attachment::Photo {
+                file_id: "photo-id".to_string(),
+            }),
+            buttons: vec![LinkButton {
+                text: "Open".to_string(),
+                url: "https://example.com".to_string(),
+            }],
+        }
+    }
+
+    /// Encodes and decodes `message` in every format, comparing the JSON values.
+    fn assert_round_trip<T>(message: T)
+    where
+        T: WireMessage + TryFrom<T::Proto, Error = ProtoError>,
+    {
+        let expected = serde_json::to_value(&message).unwrap();
+        for format in FORMATS {
+            let decoded = T::decode(&message.encode(format), format).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                expected,
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn client_request_round_trip() {
+        assert_round_trip(ClientRequest::NewSubscription {
+            user_id: UserId(42),
+            customer: CustomerName::parse("shop").unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            channel: DeliveryChannel::Both,
+            email: Some("user@example.com".to_string()),
+            timestamp: timestamp(),
+            mode: SubscriptionMode::Times { count: 3 },
+        });
+        assert_round_trip(ClientRequest::NewSubscription {
+            user_id: UserId(42),
+            customer: CustomerName::parse("shop").unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            channel: DeliveryChannel::Telegram,
+            email: None,
+            timestamp: timestamp(),
+            mode: SubscriptionMode::Until { until: timestamp() },
+        });
+        assert_round_trip(ClientRequest::NotificationHistory {
+            user_id: UserId(42),
+            page: 2,
+            timestamp: timestamp(),
+        });
+    }
+
+    #[test]
+    fn customer_request_round_trip() {
+        assert_round_trip(CustomerRequest::NewNotification {
+            user_id: UserId(7),
+            customer: CustomerName::parse("shop").unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            notification: content(),
+            send_at: Some(timestamp()),
+            timestamp: timestamp(),
+        });
+        assert_round_trip(CustomerRequest::NewNotification {
+            user_id: UserId(7),
+            customer: CustomerName::parse("shop").unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            notification: NotificationContent::from("plain".to_string()),
+            send_at: None,
+            timestamp: timestamp(),
+        });
+    }
+
+    #[test]
+    fn moderation_decision_round_trip() {
+        assert_round_trip(ModerationDecision::Approve { id: 5 });
+        assert_round_trip(ModerationDecision::Reject {
+            id: 6,
+            comment: "spam".to_string(),
+        });
+    }
+
+    #[test]
+    fn client_response_round_trip() {
+        assert_round_trip(ClientResponse::CustomerNotification {
+            user_id: UserId(42),
+            customer: CustomerName::parse("shop").unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            notification: content(),
+        });
+    }
+
+    #[test]
+    fn response_from_repository_round_trip() {
+        assert_round_trip(ResponseFromRepository::DueNotification {
+            id: 3,
+            user_id: 7,
+            customer: CustomerName::parse("shop").unwrap(),
+            product: ProductName::parse("boots").unwrap(),
+            notification: content(),
+            send_at: timestamp(),
+        });
+    }
+
+    #[test]
+    fn webhook_authorization_round_trip() {
+        assert_round_trip(CustomerRequestToRepository::SetWebhook {
+            user_id: 7,
+            customer: CustomerName::parse("shop").unwrap(),
+            url: Some("https://example.com/hook".to_string()),
+        });
+        assert_round_trip(CustomerResponseFromRepository::WebhookAllowed {
+            user_id: 7,
+            customer: CustomerName::parse("shop").unwrap(),
+            url: None,
+        });
+    }
+
+    #[test]
+    fn deferred_notification_round_trip() {
+        assert_round_trip(RequestToRepository::DeferNotification {
+            user_id: 7,
+            customer: CustomerName::parse("shop").unwrap(),
+            id: 3,
+            send_at: timestamp(),
+        });
+        assert_round_trip(CustomerResponse::NotificationDeferred {
+            user_id: UserId(42),
+            id: 3,
+            quota: Quota::NotificationsPerDay,
+            limit: 50,
+            send_at: timestamp(),
+        });
+    }
+
+    #[test]
+    fn correlated_round_trip() {
+        let request = Correlated::new(
+            1_671_450_000_123,
+            WebhookRequest::Register {
+                user_id: 7,
+                customer: CustomerName::parse("shop").unwrap(),
+                url: Some("https://example.com/hook".to_string()),
+            },
+        );
+        assert_round_trip(request.clone());
+        assert_round_trip(request.reply(WebhookResponse::Removed { user_id: 7 }));
+        for format in FORMATS {
+            let data = request.reply(ClientResponseFromRepository::SubscriptionsCancelled {
+                user_id: 7,
+                count: 2,
+            });
+            let decoded =
+                Correlated::<ClientResponseFromRepository>::decode(&data.encode(format), format)
+                    .unwrap();
+            assert_eq!(decoded.request_id, request.request_id);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_garbage() {
+        for format in FORMATS {
+            assert!(ClientRequest::decode(&[0xff, 0x01, 0x02], format).is_err());
+        }
+    }
+}
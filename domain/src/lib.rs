@@ -1,4 +1,6 @@
+pub mod codec;
 pub mod models;
+pub mod proto;
 pub mod records;
 pub mod requests;
 pub mod responses;
@@ -1,5 +1,10 @@
+use std::fmt::{self, Display};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 
+pub const MAX_NAME_LENGTH: usize = 64;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserId(pub u32);
 
@@ -14,36 +19,510 @@ impl From<i64> for UserId {
     }
 }
 
+/// UTC point in time with millisecond precision, serialized as milliseconds since the epoch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(#[serde(with = "chrono::serde::ts_milliseconds")] DateTime<Utc>);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Timestamp::from(Utc::now())
+    }
+    pub fn from_millis(millis: i64) -> Option<Self> {
+        Utc.timestamp_millis_opt(millis).single().map(Timestamp)
+    }
+    pub fn as_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+    pub fn as_datetime(&self) -> DateTime<Utc> {
+        self.0
+    }
+    pub fn duration_since(&self, earlier: Timestamp) -> Duration {
+        self.0 - earlier.0
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(datetime: DateTime<Utc>) -> Self {
+        let millis = datetime.timestamp_millis();
+        Timestamp(Utc.timestamp_millis_opt(millis).unwrap())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    TooLong(usize),
+    ControlCharacter,
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "name is empty"),
+            NameError::TooLong(length) => write!(
+                f,
+                "name is {} characters long, maximum is {}",
+                length, MAX_NAME_LENGTH
+            ),
+            NameError::ControlCharacter => write!(f, "name contains control characters"),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+fn validate_name(name: &str) -> Result<String, NameError> {
+    let name = name.trim();
+    let length = name.chars().count();
+    if length == 0 {
+        return Err(NameError::Empty);
+    }
+    if length > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong(length));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(NameError::ControlCharacter);
+    }
+    Ok(name.to_string())
+}
+
+/// Name of a customer (supplier), trimmed and validated on construction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct CustomerName(String);
+
+impl CustomerName {
+    pub fn parse(name: &str) -> Result<Self, NameError> {
+        validate_name(name).map(CustomerName)
+    }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Name of a product of a customer, trimmed and validated on construction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProductName(String);
+
+impl ProductName {
+    pub fn parse(name: &str) -> Result<Self, NameError> {
+        validate_name(name).map(ProductName)
+    }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for CustomerName {
+    type Error = NameError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        CustomerName::parse(&name)
+    }
+}
+impl From<CustomerName> for String {
+    fn from(name: CustomerName) -> Self {
+        name.0
+    }
+}
+impl Display for CustomerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for ProductName {
+    type Error = NameError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        ProductName::parse(&name)
+    }
+}
+impl From<ProductName> for String {
+    fn from(name: ProductName) -> Self {
+        name.0
+    }
+}
+impl Display for ProductName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Customer {
-    pub name: String,
+    pub name: CustomerName,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Product {
-    pub name: String,
+    pub name: ProductName,
+}
+
+/// Notification quota of a customer, enforced by the controller.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quota {
+    NotificationsPerHour,
+    NotificationsPerDay,
+    RecipientsPerDay,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    #[default]
+    Plain,
+    MarkdownV2,
+    Html,
+}
+
+impl ParseMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseMode::Plain => "plain",
+            ParseMode::MarkdownV2 => "markdown_v2",
+            ParseMode::Html => "html",
+        }
+    }
+}
+
+impl From<&str> for ParseMode {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "markdown_v2" => ParseMode::MarkdownV2,
+            "html" => ParseMode::Html,
+            _ => ParseMode::Plain,
+        }
+    }
+}
+
+/// Where a subscriber wants to receive the notifications of a subscription.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeliveryChannel {
+    #[default]
+    Telegram,
+    Email,
+    Both,
+}
+
+impl DeliveryChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryChannel::Telegram => "telegram",
+            DeliveryChannel::Email => "email",
+            DeliveryChannel::Both => "both",
+        }
+    }
+    pub fn telegram(&self) -> bool {
+        matches!(self, DeliveryChannel::Telegram | DeliveryChannel::Both)
+    }
+    pub fn email(&self) -> bool {
+        matches!(self, DeliveryChannel::Email | DeliveryChannel::Both)
+    }
+}
+
+impl From<&str> for DeliveryChannel {
+    fn from(channel: &str) -> Self {
+        match channel {
+            "email" => DeliveryChannel::Email,
+            "both" => DeliveryChannel::Both,
+            _ => DeliveryChannel::Telegram,
+        }
+    }
+}
+
+/// Which notifications a subscription receives before it becomes inactive.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubscriptionMode {
+    /// Only the next notification.
+    #[default]
+    Once,
+    /// Every notification until the subscription is cancelled.
+    Always,
+    /// The next `count` notifications.
+    Times { count: u32 },
+    /// Every notification sent before `until`.
+    Until { until: Timestamp },
+}
+
+impl SubscriptionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionMode::Once => "once",
+            SubscriptionMode::Always => "always",
+            SubscriptionMode::Times { .. } => "times",
+            SubscriptionMode::Until { .. } => "until",
+        }
+    }
+    pub fn count(&self) -> Option<u32> {
+        match self {
+            SubscriptionMode::Times { count } => Some(*count),
+            _ => None,
+        }
+    }
+    pub fn until(&self) -> Option<Timestamp> {
+        match self {
+            SubscriptionMode::Until { until } => Some(*until),
+            _ => None,
+        }
+    }
+    /// Unknown or incomplete stored modes fall back to `Once`.
+    pub fn from_parts(mode: &str, count: Option<u32>, until: Option<Timestamp>) -> Self {
+        match (mode, count, until) {
+            ("always", _, _) => SubscriptionMode::Always,
+            ("times", Some(count), _) => SubscriptionMode::Times { count },
+            ("until", _, Some(until)) => SubscriptionMode::Until { until },
+            _ => SubscriptionMode::Once,
+        }
+    }
+    /// `false` if a subscription with the mode could never be notified.
+    pub fn is_valid(&self, now: Timestamp) -> bool {
+        match self {
+            SubscriptionMode::Times { count } => *count > 0,
+            SubscriptionMode::Until { until } => *until > now,
+            SubscriptionMode::Once | SubscriptionMode::Always => true,
+        }
+    }
+}
+
+/// Permissions of an operator of a customer. Owners manage the operators,
+/// editors send notifications and viewers only look at them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OperatorRole {
+    #[default]
+    Owner,
+    Editor,
+    Viewer,
+}
+
+impl OperatorRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperatorRole::Owner => "owner",
+            OperatorRole::Editor => "editor",
+            OperatorRole::Viewer => "viewer",
+        }
+    }
+    /// Whether the role grants everything `required` does.
+    pub fn allows(&self, required: OperatorRole) -> bool {
+        self.rank() >= required.rank()
+    }
+    fn rank(&self) -> u8 {
+        match self {
+            OperatorRole::Owner => 2,
+            OperatorRole::Editor => 1,
+            OperatorRole::Viewer => 0,
+        }
+    }
+}
+
+impl From<&str> for OperatorRole {
+    fn from(role: &str) -> Self {
+        match role {
+            "owner" => OperatorRole::Owner,
+            "editor" => OperatorRole::Editor,
+            _ => OperatorRole::Viewer,
+        }
+    }
+}
+
+/// User acting for a customer in the customer bot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Operator {
+    pub user_id: u32,
+    pub role: OperatorRole,
+}
+
+/// Subscription of a client to a product. It stays listed once delivered,
+/// but only an `active` one is still waiting for a notification.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Subscription {
+    pub id: i64,
+    pub customer: CustomerName,
+    pub product: ProductName,
+    pub channel: DeliveryChannel,
+    pub active: bool,
+    /// Mode of the subscription, `Times` holds the notifications left.
+    #[serde(default)]
+    pub mode: SubscriptionMode,
+}
+
+/// File attached to a notification. `file_id` is a Telegram file id
+/// (or an HTTP URL) that the client bot is able to send.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Attachment {
+    Photo { file_id: String },
+    Document { file_id: String },
+}
+
+impl Attachment {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Attachment::Photo { .. } => "photo",
+            Attachment::Document { .. } => "document",
+        }
+    }
+    pub fn file_id(&self) -> &str {
+        match self {
+            Attachment::Photo { file_id } | Attachment::Document { file_id } => file_id,
+        }
+    }
+    pub fn from_parts(kind: &str, file_id: String) -> Option<Self> {
+        match kind {
+            "photo" => Some(Attachment::Photo { file_id }),
+            "document" => Some(Attachment::Document { file_id }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LinkButton {
+    pub text: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NotificationContent {
+    pub text: String,
+    #[serde(default)]
+    pub parse_mode: ParseMode,
+    #[serde(default)]
+    pub attachment: Option<Attachment>,
+    #[serde(default)]
+    pub buttons: Vec<LinkButton>,
+}
+
+impl From<String> for NotificationContent {
+    fn from(text: String) -> Self {
+        NotificationContent {
+            text,
+            parse_mode: ParseMode::Plain,
+            attachment: None,
+            buttons: vec![],
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Notification {
     pub user_id: u32,
-    pub customer: String,
-    pub product: String,
-    pub text: String,
+    pub customer: CustomerName,
+    pub product: ProductName,
+    pub content: NotificationContent,
+    #[serde(default)]
+    pub channel: DeliveryChannel,
+    /// Address for the email channel.
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+/// Event of a customer posted to its webhook.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    NewSubscriber {
+        product: ProductName,
+    },
+    DeliveryFinished {
+        product: ProductName,
+        published: u32,
+        failed: u32,
+        total: u32,
+    },
 }
 
+impl WebhookEvent {
+    /// Name of the event in the webhook payload.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::NewSubscriber { .. } => "subscription.created",
+            WebhookEvent::DeliveryFinished { .. } => "delivery.finished",
+        }
+    }
+}
+
+/// Notification of a customer waiting in the repository to be sent at `send_at`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledNotification {
+    pub id: i64,
+    pub product: ProductName,
+    pub send_at: Timestamp,
+    pub content: NotificationContent,
+}
+
+/// Notification of a customer that the repository sends on every occurrence
+/// of the cron `schedule` in the IANA `timezone`, unless it is paused.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecurringNotification {
+    pub id: i64,
+    pub product: ProductName,
+    pub schedule: String,
+    pub timezone: String,
+    pub paused: bool,
+    pub next_send_at: Option<Timestamp>,
+    pub content: NotificationContent,
+}
+
+/// Notification already sent by a customer, as kept in the history.
+/// `sent_at` is unknown for notifications sent before it was recorded.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PastNotification {
+    pub id: i64,
+    pub customer: CustomerName,
+    pub product: ProductName,
+    pub sent_at: Option<Timestamp>,
+    pub content: NotificationContent,
+}
+
+/// Why the controller rejected a notification of a customer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ModerationReason {
+    TooLong {
+        length: u32,
+        max_length: u32,
+    },
+    BannedWord {
+        word: String,
+    },
+    DeniedLink {
+        domain: String,
+    },
+    /// Rejected by a moderator after manual review.
+    Rejected {
+        comment: String,
+    },
+}
+
+/// `sent_at` is the time the user sent the request (known only for requests
+/// coming from the bots), `received_at` is the time the controller got the message.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserEventRecord {
-    pub timestamp: i64,
+    pub sent_at: Option<Timestamp>,
+    pub received_at: Timestamp,
     pub user_id: u32,
     pub event: String,
     pub data: String,
 }
 
+impl UserEventRecord {
+    pub fn latency(&self) -> Option<Duration> {
+        self.sent_at
+            .map(|sent_at| self.received_at.duration_since(sent_at))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CustomerEventRecord {
-    pub timestamp: i64,
+    pub sent_at: Option<Timestamp>,
+    pub received_at: Timestamp,
     pub user_id: u32,
     pub customer: Option<String>,
     pub event: String,
     pub data: String,
 }
+
+impl CustomerEventRecord {
+    pub fn latency(&self) -> Option<Duration> {
+        self.sent_at
+            .map(|sent_at| self.received_at.duration_since(sent_at))
+    }
+}
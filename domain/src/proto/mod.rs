@@ -0,0 +1,3232 @@
+//! Protobuf representation of the domain types, see `proto/notifyme.proto`.
+//!
+//! Every domain type converts into its protobuf message with `From<&T>` and
+//! back with `TryFrom`, which validates names, timestamps and required fields.
+
+use std::fmt::{self, Display};
+
+use crate::models::{self, CustomerName, NameError, ProductName, Timestamp, UserId};
+use crate::{records, requests, responses};
+
+/// Messages generated by `prost-build` from `proto/notifyme.proto`.
+pub mod notifyme {
+    include!(concat!(env!("OUT_DIR"), "/notifyme.rs"));
+}
+
+use self::notifyme as proto;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtoError {
+    MissingField(&'static str),
+    InvalidEnum(&'static str, i32),
+    InvalidTimestamp(i64),
+    InvalidName(NameError),
+}
+
+impl Display for ProtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoError::MissingField(field) => write!(f, "missing field `{}`", field),
+            ProtoError::InvalidEnum(field, value) => {
+                write!(f, "invalid value {} of field `{}`", value, field)
+            }
+            ProtoError::InvalidTimestamp(millis) => write!(f, "invalid timestamp {}", millis),
+            ProtoError::InvalidName(error) => write!(f, "invalid name: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ProtoError {}
+
+impl From<NameError> for ProtoError {
+    fn from(error: NameError) -> Self {
+        ProtoError::InvalidName(error)
+    }
+}
+
+fn required<T>(value: Option<T>, field: &'static str) -> Result<T, ProtoError> {
+    value.ok_or(ProtoError::MissingField(field))
+}
+
+fn timestamp(millis: i64) -> Result<Timestamp, ProtoError> {
+    Timestamp::from_millis(millis).ok_or(ProtoError::InvalidTimestamp(millis))
+}
+
+// Conversions between the domain types and their protobuf messages.
+
+impl From<models::ParseMode> for proto::ParseMode {
+    fn from(value: models::ParseMode) -> Self {
+        match value {
+            models::ParseMode::Plain => proto::ParseMode::Plain,
+            models::ParseMode::MarkdownV2 => proto::ParseMode::MarkdownV2,
+            models::ParseMode::Html => proto::ParseMode::Html,
+        }
+    }
+}
+
+impl From<proto::ParseMode> for models::ParseMode {
+    fn from(value: proto::ParseMode) -> Self {
+        match value {
+            proto::ParseMode::Plain => models::ParseMode::Plain,
+            proto::ParseMode::MarkdownV2 => models::ParseMode::MarkdownV2,
+            proto::ParseMode::Html => models::ParseMode::Html,
+        }
+    }
+}
+
+impl From<models::Quota> for proto::Quota {
+    fn from(value: models::Quota) -> Self {
+        match value {
+            models::Quota::NotificationsPerHour => proto::Quota::NotificationsPerHour,
+            models::Quota::NotificationsPerDay => proto::Quota::NotificationsPerDay,
+            models::Quota::RecipientsPerDay => proto::Quota::RecipientsPerDay,
+        }
+    }
+}
+
+impl From<proto::Quota> for models::Quota {
+    fn from(value: proto::Quota) -> Self {
+        match value {
+            proto::Quota::NotificationsPerHour => models::Quota::NotificationsPerHour,
+            proto::Quota::NotificationsPerDay => models::Quota::NotificationsPerDay,
+            proto::Quota::RecipientsPerDay => models::Quota::RecipientsPerDay,
+        }
+    }
+}
+
+impl From<models::DeliveryChannel> for proto::DeliveryChannel {
+    fn from(value: models::DeliveryChannel) -> Self {
+        match value {
+            models::DeliveryChannel::Telegram => proto::DeliveryChannel::Telegram,
+            models::DeliveryChannel::Email => proto::DeliveryChannel::Email,
+            models::DeliveryChannel::Both => proto::DeliveryChannel::Both,
+        }
+    }
+}
+
+impl From<proto::DeliveryChannel> for models::DeliveryChannel {
+    fn from(value: proto::DeliveryChannel) -> Self {
+        match value {
+            proto::DeliveryChannel::Telegram => models::DeliveryChannel::Telegram,
+            proto::DeliveryChannel::Email => models::DeliveryChannel::Email,
+            proto::DeliveryChannel::Both => models::DeliveryChannel::Both,
+        }
+    }
+}
+
+impl From<models::OperatorRole> for proto::OperatorRole {
+    fn from(value: models::OperatorRole) -> Self {
+        match value {
+            models::OperatorRole::Owner => proto::OperatorRole::Owner,
+            models::OperatorRole::Editor => proto::OperatorRole::Editor,
+            models::OperatorRole::Viewer => proto::OperatorRole::Viewer,
+        }
+    }
+}
+
+impl From<proto::OperatorRole> for models::OperatorRole {
+    fn from(value: proto::OperatorRole) -> Self {
+        match value {
+            proto::OperatorRole::Owner => models::OperatorRole::Owner,
+            proto::OperatorRole::Editor => models::OperatorRole::Editor,
+            proto::OperatorRole::Viewer => models::OperatorRole::Viewer,
+        }
+    }
+}
+
+impl From<&models::Customer> for proto::Customer {
+    fn from(value: &models::Customer) -> Self {
+        proto::Customer {
+            name: value.name.to_string(),
+        }
+    }
+}
+
+impl TryFrom<proto::Customer> for models::Customer {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Customer) -> Result<Self, Self::Error> {
+        Ok(models::Customer {
+            name: CustomerName::parse(&value.name)?,
+        })
+    }
+}
+
+impl From<&models::Product> for proto::Product {
+    fn from(value: &models::Product) -> Self {
+        proto::Product {
+            name: value.name.to_string(),
+        }
+    }
+}
+
+impl TryFrom<proto::Product> for models::Product {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Product) -> Result<Self, Self::Error> {
+        Ok(models::Product {
+            name: ProductName::parse(&value.name)?,
+        })
+    }
+}
+
+impl From<&models::LinkButton> for proto::LinkButton {
+    fn from(value: &models::LinkButton) -> Self {
+        proto::LinkButton {
+            text: value.text.clone(),
+            url: value.url.clone(),
+        }
+    }
+}
+
+impl TryFrom<proto::LinkButton> for models::LinkButton {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::LinkButton) -> Result<Self, Self::Error> {
+        Ok(models::LinkButton {
+            text: value.text,
+            url: value.url,
+        })
+    }
+}
+
+impl From<&models::NotificationContent> for proto::NotificationContent {
+    fn from(value: &models::NotificationContent) -> Self {
+        proto::NotificationContent {
+            text: value.text.clone(),
+            parse_mode: proto::ParseMode::from(value.parse_mode) as i32,
+            attachment: value.attachment.as_ref().map(Into::into),
+            buttons: value.buttons.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::NotificationContent> for models::NotificationContent {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::NotificationContent) -> Result<Self, Self::Error> {
+        Ok(models::NotificationContent {
+            text: value.text,
+            parse_mode: proto::ParseMode::from_i32(value.parse_mode)
+                .ok_or(ProtoError::InvalidEnum("parse_mode", value.parse_mode))?
+                .into(),
+            attachment: value.attachment.map(TryInto::try_into).transpose()?,
+            buttons: value
+                .buttons
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&models::Notification> for proto::Notification {
+    fn from(value: &models::Notification) -> Self {
+        proto::Notification {
+            user_id: value.user_id,
+            customer: value.customer.to_string(),
+            product: value.product.to_string(),
+            content: Some((&value.content).into()),
+            channel: proto::DeliveryChannel::from(value.channel) as i32,
+            email: value.email.clone(),
+        }
+    }
+}
+
+impl TryFrom<proto::Notification> for models::Notification {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Notification) -> Result<Self, Self::Error> {
+        Ok(models::Notification {
+            user_id: value.user_id,
+            customer: CustomerName::parse(&value.customer)?,
+            product: ProductName::parse(&value.product)?,
+            content: required(value.content, "content")?.try_into()?,
+            channel: proto::DeliveryChannel::from_i32(value.channel)
+                .ok_or(ProtoError::InvalidEnum("channel", value.channel))?
+                .into(),
+            email: value.email,
+        })
+    }
+}
+
+impl From<&models::ScheduledNotification> for proto::ScheduledNotification {
+    fn from(value: &models::ScheduledNotification) -> Self {
+        proto::ScheduledNotification {
+            id: value.id,
+            product: value.product.to_string(),
+            send_at: value.send_at.as_millis(),
+            content: Some((&value.content).into()),
+        }
+    }
+}
+
+impl TryFrom<proto::ScheduledNotification> for models::ScheduledNotification {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ScheduledNotification) -> Result<Self, Self::Error> {
+        Ok(models::ScheduledNotification {
+            id: value.id,
+            product: ProductName::parse(&value.product)?,
+            send_at: timestamp(value.send_at)?,
+            content: required(value.content, "content")?.try_into()?,
+        })
+    }
+}
+
+impl From<&models::RecurringNotification> for proto::RecurringNotification {
+    fn from(value: &models::RecurringNotification) -> Self {
+        proto::RecurringNotification {
+            id: value.id,
+            product: value.product.to_string(),
+            schedule: value.schedule.clone(),
+            timezone: value.timezone.clone(),
+            paused: value.paused,
+            next_send_at: value.next_send_at.map(|timestamp| timestamp.as_millis()),
+            content: Some((&value.content).into()),
+        }
+    }
+}
+
+impl TryFrom<proto::RecurringNotification> for models::RecurringNotification {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::RecurringNotification) -> Result<Self, Self::Error> {
+        Ok(models::RecurringNotification {
+            id: value.id,
+            product: ProductName::parse(&value.product)?,
+            schedule: value.schedule,
+            timezone: value.timezone,
+            paused: value.paused,
+            next_send_at: value.next_send_at.map(timestamp).transpose()?,
+            content: required(value.content, "content")?.try_into()?,
+        })
+    }
+}
+
+impl From<&models::UserEventRecord> for proto::UserEventRecord {
+    fn from(value: &models::UserEventRecord) -> Self {
+        proto::UserEventRecord {
+            sent_at: value.sent_at.map(|timestamp| timestamp.as_millis()),
+            received_at: value.received_at.as_millis(),
+            user_id: value.user_id,
+            event: value.event.clone(),
+            data: value.data.clone(),
+        }
+    }
+}
+
+impl TryFrom<proto::UserEventRecord> for models::UserEventRecord {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::UserEventRecord) -> Result<Self, Self::Error> {
+        Ok(models::UserEventRecord {
+            sent_at: value.sent_at.map(timestamp).transpose()?,
+            received_at: timestamp(value.received_at)?,
+            user_id: value.user_id,
+            event: value.event,
+            data: value.data,
+        })
+    }
+}
+
+impl From<&models::CustomerEventRecord> for proto::CustomerEventRecord {
+    fn from(value: &models::CustomerEventRecord) -> Self {
+        proto::CustomerEventRecord {
+            sent_at: value.sent_at.map(|timestamp| timestamp.as_millis()),
+            received_at: value.received_at.as_millis(),
+            user_id: value.user_id,
+            customer: value.customer.clone(),
+            event: value.event.clone(),
+            data: value.data.clone(),
+        }
+    }
+}
+
+impl TryFrom<proto::CustomerEventRecord> for models::CustomerEventRecord {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::CustomerEventRecord) -> Result<Self, Self::Error> {
+        Ok(models::CustomerEventRecord {
+            sent_at: value.sent_at.map(timestamp).transpose()?,
+            received_at: timestamp(value.received_at)?,
+            user_id: value.user_id,
+            customer: value.customer,
+            event: value.event,
+            data: value.data,
+        })
+    }
+}
+
+impl From<&models::Operator> for proto::Operator {
+    fn from(value: &models::Operator) -> Self {
+        proto::Operator {
+            user_id: value.user_id,
+            role: proto::OperatorRole::from(value.role) as i32,
+        }
+    }
+}
+
+impl TryFrom<proto::Operator> for models::Operator {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Operator) -> Result<Self, Self::Error> {
+        Ok(models::Operator {
+            user_id: value.user_id,
+            role: proto::OperatorRole::from_i32(value.role)
+                .ok_or(ProtoError::InvalidEnum("role", value.role))?
+                .into(),
+        })
+    }
+}
+
+impl From<&models::Subscription> for proto::Subscription {
+    fn from(value: &models::Subscription) -> Self {
+        proto::Subscription {
+            id: value.id,
+            customer: value.customer.to_string(),
+            product: value.product.to_string(),
+            channel: proto::DeliveryChannel::from(value.channel) as i32,
+            active: value.active,
+            mode: Some((&value.mode).into()),
+        }
+    }
+}
+
+impl TryFrom<proto::Subscription> for models::Subscription {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Subscription) -> Result<Self, Self::Error> {
+        Ok(models::Subscription {
+            id: value.id,
+            customer: CustomerName::parse(&value.customer)?,
+            product: ProductName::parse(&value.product)?,
+            channel: proto::DeliveryChannel::from_i32(value.channel)
+                .ok_or(ProtoError::InvalidEnum("channel", value.channel))?
+                .into(),
+            active: value.active,
+            mode: required(value.mode, "mode")?.try_into()?,
+        })
+    }
+}
+
+impl From<&models::PastNotification> for proto::PastNotification {
+    fn from(value: &models::PastNotification) -> Self {
+        proto::PastNotification {
+            id: value.id,
+            customer: value.customer.to_string(),
+            product: value.product.to_string(),
+            sent_at: value.sent_at.map(|timestamp| timestamp.as_millis()),
+            content: Some((&value.content).into()),
+        }
+    }
+}
+
+impl TryFrom<proto::PastNotification> for models::PastNotification {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::PastNotification) -> Result<Self, Self::Error> {
+        Ok(models::PastNotification {
+            id: value.id,
+            customer: CustomerName::parse(&value.customer)?,
+            product: ProductName::parse(&value.product)?,
+            sent_at: value.sent_at.map(timestamp).transpose()?,
+            content: required(value.content, "content")?.try_into()?,
+        })
+    }
+}
+
+impl From<&models::Attachment> for proto::Attachment {
+    fn from(value: &models::Attachment) -> Self {
+        let kind = match value {
+            models::Attachment::Photo { file_id } => {
+                proto::attachment::Kind::Photo(proto::attachment::Photo {
+                    file_id: file_id.clone(),
+                })
+            }
+            models::Attachment::Document { file_id } => {
+                proto::attachment::Kind::Document(proto::attachment::Document {
+                    file_id: file_id.clone(),
+                })
+            }
+        };
+        proto::Attachment { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::Attachment> for models::Attachment {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Attachment) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::attachment::Kind::Photo(message) => models::Attachment::Photo {
+                file_id: message.file_id,
+            },
+            proto::attachment::Kind::Document(message) => models::Attachment::Document {
+                file_id: message.file_id,
+            },
+        })
+    }
+}
+
+impl From<&models::ModerationReason> for proto::ModerationReason {
+    fn from(value: &models::ModerationReason) -> Self {
+        let kind = match value {
+            models::ModerationReason::TooLong { length, max_length } => {
+                proto::moderation_reason::Kind::TooLong(proto::moderation_reason::TooLong {
+                    length: *length,
+                    max_length: *max_length,
+                })
+            }
+            models::ModerationReason::BannedWord { word } => {
+                proto::moderation_reason::Kind::BannedWord(proto::moderation_reason::BannedWord {
+                    word: word.clone(),
+                })
+            }
+            models::ModerationReason::DeniedLink { domain } => {
+                proto::moderation_reason::Kind::DeniedLink(proto::moderation_reason::DeniedLink {
+                    domain: domain.clone(),
+                })
+            }
+            models::ModerationReason::Rejected { comment } => {
+                proto::moderation_reason::Kind::Rejected(proto::moderation_reason::Rejected {
+                    comment: comment.clone(),
+                })
+            }
+        };
+        proto::ModerationReason { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::ModerationReason> for models::ModerationReason {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ModerationReason) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::moderation_reason::Kind::TooLong(message) => models::ModerationReason::TooLong {
+                length: message.length,
+                max_length: message.max_length,
+            },
+            proto::moderation_reason::Kind::BannedWord(message) => {
+                models::ModerationReason::BannedWord { word: message.word }
+            }
+            proto::moderation_reason::Kind::DeniedLink(message) => {
+                models::ModerationReason::DeniedLink {
+                    domain: message.domain,
+                }
+            }
+            proto::moderation_reason::Kind::Rejected(message) => {
+                models::ModerationReason::Rejected {
+                    comment: message.comment,
+                }
+            }
+        })
+    }
+}
+
+impl From<&models::SubscriptionMode> for proto::SubscriptionMode {
+    fn from(value: &models::SubscriptionMode) -> Self {
+        let kind = match value {
+            models::SubscriptionMode::Once => {
+                proto::subscription_mode::Kind::Once(proto::subscription_mode::Once {})
+            }
+            models::SubscriptionMode::Always => {
+                proto::subscription_mode::Kind::Always(proto::subscription_mode::Always {})
+            }
+            models::SubscriptionMode::Times { count } => {
+                proto::subscription_mode::Kind::Times(proto::subscription_mode::Times {
+                    count: *count,
+                })
+            }
+            models::SubscriptionMode::Until { until } => {
+                proto::subscription_mode::Kind::Until(proto::subscription_mode::Until {
+                    until: until.as_millis(),
+                })
+            }
+        };
+        proto::SubscriptionMode { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::SubscriptionMode> for models::SubscriptionMode {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::SubscriptionMode) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::subscription_mode::Kind::Once(_) => models::SubscriptionMode::Once,
+            proto::subscription_mode::Kind::Always(_) => models::SubscriptionMode::Always,
+            proto::subscription_mode::Kind::Times(message) => models::SubscriptionMode::Times {
+                count: message.count,
+            },
+            proto::subscription_mode::Kind::Until(message) => models::SubscriptionMode::Until {
+                until: timestamp(message.until)?,
+            },
+        })
+    }
+}
+
+impl From<&requests::ModerationDecision> for proto::ModerationDecision {
+    fn from(value: &requests::ModerationDecision) -> Self {
+        let kind = match value {
+            requests::ModerationDecision::Approve { id } => {
+                proto::moderation_decision::Kind::Approve(proto::moderation_decision::Approve {
+                    id: *id,
+                })
+            }
+            requests::ModerationDecision::Reject { id, comment } => {
+                proto::moderation_decision::Kind::Reject(proto::moderation_decision::Reject {
+                    id: *id,
+                    comment: comment.clone(),
+                })
+            }
+        };
+        proto::ModerationDecision { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::ModerationDecision> for requests::ModerationDecision {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ModerationDecision) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::moderation_decision::Kind::Approve(message) => {
+                requests::ModerationDecision::Approve { id: message.id }
+            }
+            proto::moderation_decision::Kind::Reject(message) => {
+                requests::ModerationDecision::Reject {
+                    id: message.id,
+                    comment: message.comment,
+                }
+            }
+        })
+    }
+}
+
+impl From<&requests::ClientRequest> for proto::ClientRequest {
+    fn from(value: &requests::ClientRequest) -> Self {
+        let kind = match value {
+            requests::ClientRequest::Customers { user_id, timestamp } => {
+                proto::client_request::Kind::Customers(proto::client_request::Customers {
+                    user_id: user_id.0,
+                    timestamp: timestamp.as_millis(),
+                })
+            }
+            requests::ClientRequest::Products {
+                user_id,
+                customer,
+                timestamp,
+            } => proto::client_request::Kind::Products(proto::client_request::Products {
+                user_id: user_id.0,
+                customer: customer.to_string(),
+                timestamp: timestamp.as_millis(),
+            }),
+            requests::ClientRequest::NewSubscription {
+                user_id,
+                customer,
+                product,
+                timestamp,
+                channel,
+                email,
+                mode,
+            } => proto::client_request::Kind::NewSubscription(
+                proto::client_request::NewSubscription {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    timestamp: timestamp.as_millis(),
+                    channel: proto::DeliveryChannel::from(*channel) as i32,
+                    email: email.clone(),
+                    mode: Some(mode.into()),
+                },
+            ),
+            requests::ClientRequest::Subscriptions { user_id, timestamp } => {
+                proto::client_request::Kind::Subscriptions(proto::client_request::Subscriptions {
+                    user_id: user_id.0,
+                    timestamp: timestamp.as_millis(),
+                })
+            }
+            requests::ClientRequest::CancelSubscription {
+                user_id,
+                id,
+                timestamp,
+            } => proto::client_request::Kind::CancelSubscription(
+                proto::client_request::CancelSubscription {
+                    user_id: user_id.0,
+                    id: *id,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::ClientRequest::CancelAllSubscriptions { user_id, timestamp } => {
+                proto::client_request::Kind::CancelAllSubscriptions(
+                    proto::client_request::CancelAllSubscriptions {
+                        user_id: user_id.0,
+                        timestamp: timestamp.as_millis(),
+                    },
+                )
+            }
+            requests::ClientRequest::NotificationHistory {
+                user_id,
+                page,
+                timestamp,
+            } => proto::client_request::Kind::NotificationHistory(
+                proto::client_request::NotificationHistory {
+                    user_id: user_id.0,
+                    page: *page,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+        };
+        proto::ClientRequest { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::ClientRequest> for requests::ClientRequest {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ClientRequest) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::client_request::Kind::Customers(message) => requests::ClientRequest::Customers {
+                user_id: UserId(message.user_id),
+                timestamp: timestamp(message.timestamp)?,
+            },
+            proto::client_request::Kind::Products(message) => requests::ClientRequest::Products {
+                user_id: UserId(message.user_id),
+                customer: CustomerName::parse(&message.customer)?,
+                timestamp: timestamp(message.timestamp)?,
+            },
+            proto::client_request::Kind::NewSubscription(message) => {
+                requests::ClientRequest::NewSubscription {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    timestamp: timestamp(message.timestamp)?,
+                    channel: proto::DeliveryChannel::from_i32(message.channel)
+                        .ok_or(ProtoError::InvalidEnum("channel", message.channel))?
+                        .into(),
+                    email: message.email,
+                    mode: required(message.mode, "mode")?.try_into()?,
+                }
+            }
+            proto::client_request::Kind::Subscriptions(message) => {
+                requests::ClientRequest::Subscriptions {
+                    user_id: UserId(message.user_id),
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::client_request::Kind::CancelSubscription(message) => {
+                requests::ClientRequest::CancelSubscription {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::client_request::Kind::CancelAllSubscriptions(message) => {
+                requests::ClientRequest::CancelAllSubscriptions {
+                    user_id: UserId(message.user_id),
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::client_request::Kind::NotificationHistory(message) => {
+                requests::ClientRequest::NotificationHistory {
+                    user_id: UserId(message.user_id),
+                    page: message.page,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+        })
+    }
+}
+
+impl From<&requests::CustomerRequest> for proto::CustomerRequest {
+    fn from(value: &requests::CustomerRequest) -> Self {
+        let kind = match value {
+            requests::CustomerRequest::Authorization {
+                user_id,
+                key,
+                timestamp,
+            } => proto::customer_request::Kind::Authorization(
+                proto::customer_request::Authorization {
+                    user_id: user_id.0,
+                    key: key.clone(),
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::ProductsForNotification {
+                user_id,
+                customer,
+                timestamp,
+            } => proto::customer_request::Kind::ProductsForNotification(
+                proto::customer_request::ProductsForNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::NewNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                send_at,
+                timestamp,
+            } => proto::customer_request::Kind::NewNotification(
+                proto::customer_request::NewNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                    send_at: send_at.map(|timestamp| timestamp.as_millis()),
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::ScheduledNotifications {
+                user_id,
+                customer,
+                timestamp,
+            } => proto::customer_request::Kind::ScheduledNotifications(
+                proto::customer_request::ScheduledNotifications {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::CancelScheduledNotification {
+                user_id,
+                customer,
+                id,
+                timestamp,
+            } => proto::customer_request::Kind::CancelScheduledNotification(
+                proto::customer_request::CancelScheduledNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    id: *id,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::NewRecurringNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                schedule,
+                timezone,
+                timestamp,
+            } => proto::customer_request::Kind::NewRecurringNotification(
+                proto::customer_request::NewRecurringNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                    schedule: schedule.clone(),
+                    timezone: timezone.clone(),
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::RecurringNotifications {
+                user_id,
+                customer,
+                timestamp,
+            } => proto::customer_request::Kind::RecurringNotifications(
+                proto::customer_request::RecurringNotifications {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::PauseRecurringNotification {
+                user_id,
+                customer,
+                id,
+                paused,
+                timestamp,
+            } => proto::customer_request::Kind::PauseRecurringNotification(
+                proto::customer_request::PauseRecurringNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    id: *id,
+                    paused: *paused,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::DeleteRecurringNotification {
+                user_id,
+                customer,
+                id,
+                timestamp,
+            } => proto::customer_request::Kind::DeleteRecurringNotification(
+                proto::customer_request::DeleteRecurringNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    id: *id,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::SetWebhook {
+                user_id,
+                customer,
+                url,
+                timestamp,
+            } => proto::customer_request::Kind::SetWebhook(proto::customer_request::SetWebhook {
+                user_id: user_id.0,
+                customer: customer.to_string(),
+                url: url.clone(),
+                timestamp: timestamp.as_millis(),
+            }),
+            requests::CustomerRequest::Operators {
+                user_id,
+                customer,
+                timestamp,
+            } => proto::customer_request::Kind::Operators(proto::customer_request::Operators {
+                user_id: user_id.0,
+                customer: customer.to_string(),
+                timestamp: timestamp.as_millis(),
+            }),
+            requests::CustomerRequest::InviteOperator {
+                user_id,
+                customer,
+                role,
+                timestamp,
+            } => proto::customer_request::Kind::InviteOperator(
+                proto::customer_request::InviteOperator {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    role: proto::OperatorRole::from(*role) as i32,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::RevokeOperator {
+                user_id,
+                customer,
+                operator_id,
+                timestamp,
+            } => proto::customer_request::Kind::RevokeOperator(
+                proto::customer_request::RevokeOperator {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    operator_id: *operator_id,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+            requests::CustomerRequest::NotificationHistory {
+                user_id,
+                customer,
+                product,
+                page,
+                timestamp,
+            } => proto::customer_request::Kind::NotificationHistory(
+                proto::customer_request::NotificationHistory {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.as_ref().map(ToString::to_string),
+                    page: *page,
+                    timestamp: timestamp.as_millis(),
+                },
+            ),
+        };
+        proto::CustomerRequest { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::CustomerRequest> for requests::CustomerRequest {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::CustomerRequest) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::customer_request::Kind::Authorization(message) => {
+                requests::CustomerRequest::Authorization {
+                    user_id: UserId(message.user_id),
+                    key: message.key,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::ProductsForNotification(message) => {
+                requests::CustomerRequest::ProductsForNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::NewNotification(message) => {
+                requests::CustomerRequest::NewNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                    send_at: message.send_at.map(timestamp).transpose()?,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::ScheduledNotifications(message) => {
+                requests::CustomerRequest::ScheduledNotifications {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::CancelScheduledNotification(message) => {
+                requests::CustomerRequest::CancelScheduledNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::NewRecurringNotification(message) => {
+                requests::CustomerRequest::NewRecurringNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                    schedule: message.schedule,
+                    timezone: message.timezone,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::RecurringNotifications(message) => {
+                requests::CustomerRequest::RecurringNotifications {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::PauseRecurringNotification(message) => {
+                requests::CustomerRequest::PauseRecurringNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                    paused: message.paused,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::DeleteRecurringNotification(message) => {
+                requests::CustomerRequest::DeleteRecurringNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::SetWebhook(message) => {
+                requests::CustomerRequest::SetWebhook {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    url: message.url,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::Operators(message) => {
+                requests::CustomerRequest::Operators {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::InviteOperator(message) => {
+                requests::CustomerRequest::InviteOperator {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    role: proto::OperatorRole::from_i32(message.role)
+                        .ok_or(ProtoError::InvalidEnum("role", message.role))?
+                        .into(),
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::RevokeOperator(message) => {
+                requests::CustomerRequest::RevokeOperator {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    operator_id: message.operator_id,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+            proto::customer_request::Kind::NotificationHistory(message) => {
+                requests::CustomerRequest::NotificationHistory {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: message
+                        .product
+                        .map(|name| ProductName::parse(&name))
+                        .transpose()?,
+                    page: message.page,
+                    timestamp: timestamp(message.timestamp)?,
+                }
+            }
+        })
+    }
+}
+
+impl From<&models::WebhookEvent> for proto::WebhookEvent {
+    fn from(value: &models::WebhookEvent) -> Self {
+        let kind = match value {
+            models::WebhookEvent::NewSubscriber { product } => {
+                proto::webhook_event::Kind::NewSubscriber(proto::webhook_event::NewSubscriber {
+                    product: product.to_string(),
+                })
+            }
+            models::WebhookEvent::DeliveryFinished {
+                product,
+                published,
+                failed,
+                total,
+            } => proto::webhook_event::Kind::DeliveryFinished(
+                proto::webhook_event::DeliveryFinished {
+                    product: product.to_string(),
+                    published: *published,
+                    failed: *failed,
+                    total: *total,
+                },
+            ),
+        };
+        proto::WebhookEvent { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::WebhookEvent> for models::WebhookEvent {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::WebhookEvent) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::webhook_event::Kind::NewSubscriber(message) => {
+                models::WebhookEvent::NewSubscriber {
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::webhook_event::Kind::DeliveryFinished(message) => {
+                models::WebhookEvent::DeliveryFinished {
+                    product: ProductName::parse(&message.product)?,
+                    published: message.published,
+                    failed: message.failed,
+                    total: message.total,
+                }
+            }
+        })
+    }
+}
+
+impl From<&requests::WebhookRequest> for proto::WebhookRequest {
+    fn from(value: &requests::WebhookRequest) -> Self {
+        let kind = match value {
+            requests::WebhookRequest::Register {
+                user_id,
+                customer,
+                url,
+            } => proto::webhook_request::Kind::Register(proto::webhook_request::Register {
+                user_id: *user_id,
+                customer: customer.to_string(),
+                url: url.clone(),
+            }),
+            requests::WebhookRequest::Event {
+                customer,
+                event,
+                timestamp,
+            } => proto::webhook_request::Kind::Event(proto::webhook_request::Event {
+                customer: customer.to_string(),
+                event: Some(event.into()),
+                timestamp: timestamp.as_millis(),
+            }),
+        };
+        proto::WebhookRequest {
+            request_id: 0,
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::WebhookRequest> for requests::WebhookRequest {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::WebhookRequest) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::webhook_request::Kind::Register(message) => requests::WebhookRequest::Register {
+                user_id: message.user_id,
+                customer: CustomerName::parse(&message.customer)?,
+                url: message.url,
+            },
+            proto::webhook_request::Kind::Event(message) => requests::WebhookRequest::Event {
+                customer: CustomerName::parse(&message.customer)?,
+                event: required(message.event, "event")?.try_into()?,
+                timestamp: timestamp(message.timestamp)?,
+            },
+        })
+    }
+}
+
+impl From<&responses::WebhookResponse> for proto::WebhookResponse {
+    fn from(value: &responses::WebhookResponse) -> Self {
+        let kind = match value {
+            responses::WebhookResponse::Registered {
+                user_id,
+                url,
+                secret,
+            } => proto::webhook_response::Kind::Registered(proto::webhook_response::Registered {
+                user_id: *user_id,
+                url: url.clone(),
+                secret: secret.clone(),
+            }),
+            responses::WebhookResponse::Removed { user_id } => {
+                proto::webhook_response::Kind::Removed(proto::webhook_response::Removed {
+                    user_id: *user_id,
+                })
+            }
+            responses::WebhookResponse::InvalidUrl { user_id, url } => {
+                proto::webhook_response::Kind::InvalidUrl(proto::webhook_response::InvalidUrl {
+                    user_id: *user_id,
+                    url: url.clone(),
+                })
+            }
+            responses::WebhookResponse::Failure { user_id } => {
+                proto::webhook_response::Kind::Failure(proto::webhook_response::Failure {
+                    user_id: *user_id,
+                })
+            }
+        };
+        proto::WebhookResponse {
+            request_id: 0,
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::WebhookResponse> for responses::WebhookResponse {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::WebhookResponse) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::webhook_response::Kind::Registered(message) => {
+                responses::WebhookResponse::Registered {
+                    user_id: message.user_id,
+                    url: message.url,
+                    secret: message.secret,
+                }
+            }
+            proto::webhook_response::Kind::Removed(message) => {
+                responses::WebhookResponse::Removed {
+                    user_id: message.user_id,
+                }
+            }
+            proto::webhook_response::Kind::InvalidUrl(message) => {
+                responses::WebhookResponse::InvalidUrl {
+                    user_id: message.user_id,
+                    url: message.url,
+                }
+            }
+            proto::webhook_response::Kind::Failure(message) => {
+                responses::WebhookResponse::Failure {
+                    user_id: message.user_id,
+                }
+            }
+        })
+    }
+}
+
+impl From<&requests::ClientRequestToRepository> for proto::ClientRequestToRepository {
+    fn from(value: &requests::ClientRequestToRepository) -> Self {
+        let kind = match value {
+            requests::ClientRequestToRepository::Customers { user_id } => {
+                proto::client_request_to_repository::Kind::Customers(
+                    proto::client_request_to_repository::Customers { user_id: *user_id },
+                )
+            }
+            requests::ClientRequestToRepository::Products { user_id, customer } => {
+                proto::client_request_to_repository::Kind::Products(
+                    proto::client_request_to_repository::Products {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            requests::ClientRequestToRepository::NewSubscription {
+                user_id,
+                customer,
+                product,
+                channel,
+                email,
+                mode,
+            } => proto::client_request_to_repository::Kind::NewSubscription(
+                proto::client_request_to_repository::NewSubscription {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    channel: proto::DeliveryChannel::from(*channel) as i32,
+                    email: email.clone(),
+                    mode: Some(mode.into()),
+                },
+            ),
+            requests::ClientRequestToRepository::Subscriptions { user_id } => {
+                proto::client_request_to_repository::Kind::Subscriptions(
+                    proto::client_request_to_repository::Subscriptions { user_id: *user_id },
+                )
+            }
+            requests::ClientRequestToRepository::CancelSubscription { user_id, id } => {
+                proto::client_request_to_repository::Kind::CancelSubscription(
+                    proto::client_request_to_repository::CancelSubscription {
+                        user_id: *user_id,
+                        id: *id,
+                    },
+                )
+            }
+            requests::ClientRequestToRepository::CancelAllSubscriptions { user_id } => {
+                proto::client_request_to_repository::Kind::CancelAllSubscriptions(
+                    proto::client_request_to_repository::CancelAllSubscriptions {
+                        user_id: *user_id,
+                    },
+                )
+            }
+            requests::ClientRequestToRepository::NotificationHistory { user_id, page } => {
+                proto::client_request_to_repository::Kind::NotificationHistory(
+                    proto::client_request_to_repository::NotificationHistory {
+                        user_id: *user_id,
+                        page: *page,
+                    },
+                )
+            }
+        };
+        proto::ClientRequestToRepository {
+            request_id: 0,
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::ClientRequestToRepository> for requests::ClientRequestToRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ClientRequestToRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::client_request_to_repository::Kind::Customers(message) => {
+                requests::ClientRequestToRepository::Customers {
+                    user_id: message.user_id,
+                }
+            }
+            proto::client_request_to_repository::Kind::Products(message) => {
+                requests::ClientRequestToRepository::Products {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::client_request_to_repository::Kind::NewSubscription(message) => {
+                requests::ClientRequestToRepository::NewSubscription {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    channel: proto::DeliveryChannel::from_i32(message.channel)
+                        .ok_or(ProtoError::InvalidEnum("channel", message.channel))?
+                        .into(),
+                    email: message.email,
+                    mode: required(message.mode, "mode")?.try_into()?,
+                }
+            }
+            proto::client_request_to_repository::Kind::Subscriptions(message) => {
+                requests::ClientRequestToRepository::Subscriptions {
+                    user_id: message.user_id,
+                }
+            }
+            proto::client_request_to_repository::Kind::CancelSubscription(message) => {
+                requests::ClientRequestToRepository::CancelSubscription {
+                    user_id: message.user_id,
+                    id: message.id,
+                }
+            }
+            proto::client_request_to_repository::Kind::CancelAllSubscriptions(message) => {
+                requests::ClientRequestToRepository::CancelAllSubscriptions {
+                    user_id: message.user_id,
+                }
+            }
+            proto::client_request_to_repository::Kind::NotificationHistory(message) => {
+                requests::ClientRequestToRepository::NotificationHistory {
+                    user_id: message.user_id,
+                    page: message.page,
+                }
+            }
+        })
+    }
+}
+
+impl From<&requests::CustomerRequestToRepository> for proto::CustomerRequestToRepository {
+    fn from(value: &requests::CustomerRequestToRepository) -> Self {
+        let kind = match value {
+            requests::CustomerRequestToRepository::Authorization { user_id, key } => {
+                proto::customer_request_to_repository::Kind::Authorization(
+                    proto::customer_request_to_repository::Authorization {
+                        user_id: *user_id,
+                        key: key.clone(),
+                    },
+                )
+            }
+            requests::CustomerRequestToRepository::ProductsForNotification {
+                user_id,
+                customer,
+            } => proto::customer_request_to_repository::Kind::ProductsForNotification(
+                proto::customer_request_to_repository::ProductsForNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                },
+            ),
+            requests::CustomerRequestToRepository::NewNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                send_at,
+            } => proto::customer_request_to_repository::Kind::NewNotification(
+                proto::customer_request_to_repository::NewNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                    send_at: send_at.map(|timestamp| timestamp.as_millis()),
+                },
+            ),
+            requests::CustomerRequestToRepository::ScheduledNotifications { user_id, customer } => {
+                proto::customer_request_to_repository::Kind::ScheduledNotifications(
+                    proto::customer_request_to_repository::ScheduledNotifications {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            requests::CustomerRequestToRepository::CancelScheduledNotification {
+                user_id,
+                customer,
+                id,
+            } => proto::customer_request_to_repository::Kind::CancelScheduledNotification(
+                proto::customer_request_to_repository::CancelScheduledNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                },
+            ),
+            requests::CustomerRequestToRepository::NewRecurringNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                schedule,
+                timezone,
+            } => proto::customer_request_to_repository::Kind::NewRecurringNotification(
+                proto::customer_request_to_repository::NewRecurringNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                    schedule: schedule.clone(),
+                    timezone: timezone.clone(),
+                },
+            ),
+            requests::CustomerRequestToRepository::RecurringNotifications { user_id, customer } => {
+                proto::customer_request_to_repository::Kind::RecurringNotifications(
+                    proto::customer_request_to_repository::RecurringNotifications {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            requests::CustomerRequestToRepository::PauseRecurringNotification {
+                user_id,
+                customer,
+                id,
+                paused,
+            } => proto::customer_request_to_repository::Kind::PauseRecurringNotification(
+                proto::customer_request_to_repository::PauseRecurringNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                    paused: *paused,
+                },
+            ),
+            requests::CustomerRequestToRepository::DeleteRecurringNotification {
+                user_id,
+                customer,
+                id,
+            } => proto::customer_request_to_repository::Kind::DeleteRecurringNotification(
+                proto::customer_request_to_repository::DeleteRecurringNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                },
+            ),
+            requests::CustomerRequestToRepository::Operators { user_id, customer } => {
+                proto::customer_request_to_repository::Kind::Operators(
+                    proto::customer_request_to_repository::Operators {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            requests::CustomerRequestToRepository::InviteOperator {
+                user_id,
+                customer,
+                role,
+            } => proto::customer_request_to_repository::Kind::InviteOperator(
+                proto::customer_request_to_repository::InviteOperator {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    role: proto::OperatorRole::from(*role) as i32,
+                },
+            ),
+            requests::CustomerRequestToRepository::RevokeOperator {
+                user_id,
+                customer,
+                operator_id,
+            } => proto::customer_request_to_repository::Kind::RevokeOperator(
+                proto::customer_request_to_repository::RevokeOperator {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    operator_id: *operator_id,
+                },
+            ),
+            requests::CustomerRequestToRepository::SetWebhook {
+                user_id,
+                customer,
+                url,
+            } => proto::customer_request_to_repository::Kind::SetWebhook(
+                proto::customer_request_to_repository::SetWebhook {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    url: url.clone(),
+                },
+            ),
+            requests::CustomerRequestToRepository::NotificationHistory {
+                user_id,
+                customer,
+                product,
+                page,
+            } => proto::customer_request_to_repository::Kind::NotificationHistory(
+                proto::customer_request_to_repository::NotificationHistory {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.as_ref().map(ToString::to_string),
+                    page: *page,
+                },
+            ),
+        };
+        proto::CustomerRequestToRepository {
+            request_id: 0,
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::CustomerRequestToRepository> for requests::CustomerRequestToRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::CustomerRequestToRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::customer_request_to_repository::Kind::Authorization(message) => {
+                requests::CustomerRequestToRepository::Authorization {
+                    user_id: message.user_id,
+                    key: message.key,
+                }
+            }
+            proto::customer_request_to_repository::Kind::ProductsForNotification(message) => {
+                requests::CustomerRequestToRepository::ProductsForNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_request_to_repository::Kind::NewNotification(message) => {
+                requests::CustomerRequestToRepository::NewNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                    send_at: message.send_at.map(timestamp).transpose()?,
+                }
+            }
+            proto::customer_request_to_repository::Kind::ScheduledNotifications(message) => {
+                requests::CustomerRequestToRepository::ScheduledNotifications {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_request_to_repository::Kind::CancelScheduledNotification(message) => {
+                requests::CustomerRequestToRepository::CancelScheduledNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                }
+            }
+            proto::customer_request_to_repository::Kind::NewRecurringNotification(message) => {
+                requests::CustomerRequestToRepository::NewRecurringNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                    schedule: message.schedule,
+                    timezone: message.timezone,
+                }
+            }
+            proto::customer_request_to_repository::Kind::RecurringNotifications(message) => {
+                requests::CustomerRequestToRepository::RecurringNotifications {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_request_to_repository::Kind::PauseRecurringNotification(message) => {
+                requests::CustomerRequestToRepository::PauseRecurringNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                    paused: message.paused,
+                }
+            }
+            proto::customer_request_to_repository::Kind::DeleteRecurringNotification(message) => {
+                requests::CustomerRequestToRepository::DeleteRecurringNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                }
+            }
+            proto::customer_request_to_repository::Kind::Operators(message) => {
+                requests::CustomerRequestToRepository::Operators {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_request_to_repository::Kind::InviteOperator(message) => {
+                requests::CustomerRequestToRepository::InviteOperator {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    role: proto::OperatorRole::from_i32(message.role)
+                        .ok_or(ProtoError::InvalidEnum("role", message.role))?
+                        .into(),
+                }
+            }
+            proto::customer_request_to_repository::Kind::RevokeOperator(message) => {
+                requests::CustomerRequestToRepository::RevokeOperator {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    operator_id: message.operator_id,
+                }
+            }
+            proto::customer_request_to_repository::Kind::SetWebhook(message) => {
+                requests::CustomerRequestToRepository::SetWebhook {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    url: message.url,
+                }
+            }
+            proto::customer_request_to_repository::Kind::NotificationHistory(message) => {
+                requests::CustomerRequestToRepository::NotificationHistory {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: message
+                        .product
+                        .map(|name| ProductName::parse(&name))
+                        .transpose()?,
+                    page: message.page,
+                }
+            }
+        })
+    }
+}
+
+impl From<&requests::RequestToRepository> for proto::RequestToRepository {
+    fn from(value: &requests::RequestToRepository) -> Self {
+        let kind = match value {
+            requests::RequestToRepository::NotificationForClients {
+                user_id,
+                customer,
+                product,
+                notification,
+            } => proto::request_to_repository::Kind::NotificationForClients(
+                proto::request_to_repository::NotificationForClients {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                },
+            ),
+            requests::RequestToRepository::SubscriptionForCustomer {
+                user_id,
+                customer,
+                product,
+            } => proto::request_to_repository::Kind::SubscriptionForCustomer(
+                proto::request_to_repository::SubscriptionForCustomer {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            requests::RequestToRepository::DeferNotification {
+                user_id,
+                customer,
+                id,
+                send_at,
+            } => proto::request_to_repository::Kind::DeferNotification(
+                proto::request_to_repository::DeferNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                    send_at: send_at.as_millis(),
+                },
+            ),
+        };
+        proto::RequestToRepository { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::RequestToRepository> for requests::RequestToRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::RequestToRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::request_to_repository::Kind::NotificationForClients(message) => {
+                requests::RequestToRepository::NotificationForClients {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                }
+            }
+            proto::request_to_repository::Kind::SubscriptionForCustomer(message) => {
+                requests::RequestToRepository::SubscriptionForCustomer {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::request_to_repository::Kind::DeferNotification(message) => {
+                requests::RequestToRepository::DeferNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    id: message.id,
+                    send_at: timestamp(message.send_at)?,
+                }
+            }
+        })
+    }
+}
+
+impl From<&responses::ClientResponse> for proto::ClientResponse {
+    fn from(value: &responses::ClientResponse) -> Self {
+        let kind = match value {
+            responses::ClientResponse::Customers { user_id, customers } => {
+                proto::client_response::Kind::Customers(proto::client_response::Customers {
+                    user_id: user_id.0,
+                    customers: customers.iter().map(Into::into).collect(),
+                })
+            }
+            responses::ClientResponse::Products { user_id, products } => {
+                proto::client_response::Kind::Products(proto::client_response::Products {
+                    user_id: user_id.0,
+                    products: products.iter().map(Into::into).collect(),
+                })
+            }
+            responses::ClientResponse::SubscriptionSuccess { user_id } => {
+                proto::client_response::Kind::SubscriptionSuccess(
+                    proto::client_response::SubscriptionSuccess { user_id: user_id.0 },
+                )
+            }
+            responses::ClientResponse::SubscriptionFailure { user_id } => {
+                proto::client_response::Kind::SubscriptionFailure(
+                    proto::client_response::SubscriptionFailure { user_id: user_id.0 },
+                )
+            }
+            responses::ClientResponse::CustomerNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+            } => proto::client_response::Kind::CustomerNotification(
+                proto::client_response::CustomerNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                },
+            ),
+            responses::ClientResponse::UnknownCustomer { user_id, customer } => {
+                proto::client_response::Kind::UnknownCustomer(
+                    proto::client_response::UnknownCustomer {
+                        user_id: user_id.0,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            responses::ClientResponse::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => proto::client_response::Kind::UnknownProduct(
+                proto::client_response::UnknownProduct {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            responses::ClientResponse::RequestFailure { user_id } => {
+                proto::client_response::Kind::RequestFailure(
+                    proto::client_response::RequestFailure { user_id: user_id.0 },
+                )
+            }
+            responses::ClientResponse::ServiceUnavailable { user_id } => {
+                proto::client_response::Kind::ServiceUnavailable(
+                    proto::client_response::ServiceUnavailable { user_id: user_id.0 },
+                )
+            }
+            responses::ClientResponse::Subscriptions {
+                user_id,
+                subscriptions,
+            } => {
+                proto::client_response::Kind::Subscriptions(proto::client_response::Subscriptions {
+                    user_id: user_id.0,
+                    subscriptions: subscriptions.iter().map(Into::into).collect(),
+                })
+            }
+            responses::ClientResponse::SubscriptionCancelled {
+                user_id,
+                id,
+                success,
+            } => proto::client_response::Kind::SubscriptionCancelled(
+                proto::client_response::SubscriptionCancelled {
+                    user_id: user_id.0,
+                    id: *id,
+                    success: *success,
+                },
+            ),
+            responses::ClientResponse::SubscriptionsCancelled { user_id, count } => {
+                proto::client_response::Kind::SubscriptionsCancelled(
+                    proto::client_response::SubscriptionsCancelled {
+                        user_id: user_id.0,
+                        count: *count,
+                    },
+                )
+            }
+            responses::ClientResponse::NotificationHistory {
+                user_id,
+                page,
+                notifications,
+                has_more,
+            } => proto::client_response::Kind::NotificationHistory(
+                proto::client_response::NotificationHistory {
+                    user_id: user_id.0,
+                    page: *page,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                    has_more: *has_more,
+                },
+            ),
+        };
+        proto::ClientResponse { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::ClientResponse> for responses::ClientResponse {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ClientResponse) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::client_response::Kind::Customers(message) => {
+                responses::ClientResponse::Customers {
+                    user_id: UserId(message.user_id),
+                    customers: message
+                        .customers
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::client_response::Kind::Products(message) => {
+                responses::ClientResponse::Products {
+                    user_id: UserId(message.user_id),
+                    products: message
+                        .products
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::client_response::Kind::SubscriptionSuccess(message) => {
+                responses::ClientResponse::SubscriptionSuccess {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::client_response::Kind::SubscriptionFailure(message) => {
+                responses::ClientResponse::SubscriptionFailure {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::client_response::Kind::CustomerNotification(message) => {
+                responses::ClientResponse::CustomerNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                }
+            }
+            proto::client_response::Kind::UnknownCustomer(message) => {
+                responses::ClientResponse::UnknownCustomer {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::client_response::Kind::UnknownProduct(message) => {
+                responses::ClientResponse::UnknownProduct {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::client_response::Kind::RequestFailure(message) => {
+                responses::ClientResponse::RequestFailure {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::client_response::Kind::ServiceUnavailable(message) => {
+                responses::ClientResponse::ServiceUnavailable {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::client_response::Kind::Subscriptions(message) => {
+                responses::ClientResponse::Subscriptions {
+                    user_id: UserId(message.user_id),
+                    subscriptions: message
+                        .subscriptions
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::client_response::Kind::SubscriptionCancelled(message) => {
+                responses::ClientResponse::SubscriptionCancelled {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                    success: message.success,
+                }
+            }
+            proto::client_response::Kind::SubscriptionsCancelled(message) => {
+                responses::ClientResponse::SubscriptionsCancelled {
+                    user_id: UserId(message.user_id),
+                    count: message.count,
+                }
+            }
+            proto::client_response::Kind::NotificationHistory(message) => {
+                responses::ClientResponse::NotificationHistory {
+                    user_id: UserId(message.user_id),
+                    page: message.page,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                    has_more: message.has_more,
+                }
+            }
+        })
+    }
+}
+
+impl From<&responses::CustomerResponse> for proto::CustomerResponse {
+    fn from(value: &responses::CustomerResponse) -> Self {
+        let kind = match value {
+            responses::CustomerResponse::AuthorizationSuccess {
+                user_id,
+                customer,
+                role,
+            } => proto::customer_response::Kind::AuthorizationSuccess(
+                proto::customer_response::AuthorizationSuccess {
+                    user_id: user_id.0,
+                    customer: Some(customer.into()),
+                    role: proto::OperatorRole::from(*role) as i32,
+                },
+            ),
+            responses::CustomerResponse::AuthorizationFailure { user_id } => {
+                proto::customer_response::Kind::AuthorizationFailure(
+                    proto::customer_response::AuthorizationFailure { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::ProductsForNotification {
+                user_id,
+                customer,
+                products,
+            } => proto::customer_response::Kind::ProductsForNotification(
+                proto::customer_response::ProductsForNotification {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    products: products.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponse::NotificationSuccess { user_id } => {
+                proto::customer_response::Kind::NotificationSuccess(
+                    proto::customer_response::NotificationSuccess { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::NotificationFailure { user_id } => {
+                proto::customer_response::Kind::NotificationFailure(
+                    proto::customer_response::NotificationFailure { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::ClientSubscription {
+                user_id,
+                customer,
+                product,
+            } => proto::customer_response::Kind::ClientSubscription(
+                proto::customer_response::ClientSubscription {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            responses::CustomerResponse::UnknownCustomer { user_id, customer } => {
+                proto::customer_response::Kind::UnknownCustomer(
+                    proto::customer_response::UnknownCustomer {
+                        user_id: user_id.0,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            responses::CustomerResponse::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => proto::customer_response::Kind::UnknownProduct(
+                proto::customer_response::UnknownProduct {
+                    user_id: user_id.0,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            responses::CustomerResponse::RequestFailure { user_id } => {
+                proto::customer_response::Kind::RequestFailure(
+                    proto::customer_response::RequestFailure { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::NotificationScheduled {
+                user_id,
+                notification,
+            } => proto::customer_response::Kind::NotificationScheduled(
+                proto::customer_response::NotificationScheduled {
+                    user_id: user_id.0,
+                    notification: Some(notification.into()),
+                },
+            ),
+            responses::CustomerResponse::ScheduledNotifications {
+                user_id,
+                notifications,
+            } => proto::customer_response::Kind::ScheduledNotifications(
+                proto::customer_response::ScheduledNotifications {
+                    user_id: user_id.0,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponse::ScheduledNotificationCancelled {
+                user_id,
+                id,
+                success,
+            } => proto::customer_response::Kind::ScheduledNotificationCancelled(
+                proto::customer_response::ScheduledNotificationCancelled {
+                    user_id: user_id.0,
+                    id: *id,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponse::QuotaExceeded {
+                user_id,
+                quota,
+                limit,
+                retry_after_secs,
+            } => proto::customer_response::Kind::QuotaExceeded(
+                proto::customer_response::QuotaExceeded {
+                    user_id: user_id.0,
+                    quota: proto::Quota::from(*quota) as i32,
+                    limit: *limit,
+                    retry_after_secs: *retry_after_secs,
+                },
+            ),
+            responses::CustomerResponse::RecurringNotificationCreated {
+                user_id,
+                notification,
+            } => proto::customer_response::Kind::RecurringNotificationCreated(
+                proto::customer_response::RecurringNotificationCreated {
+                    user_id: user_id.0,
+                    notification: Some(notification.into()),
+                },
+            ),
+            responses::CustomerResponse::RecurringNotifications {
+                user_id,
+                notifications,
+            } => proto::customer_response::Kind::RecurringNotifications(
+                proto::customer_response::RecurringNotifications {
+                    user_id: user_id.0,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponse::RecurringNotificationPaused {
+                user_id,
+                id,
+                paused,
+                success,
+            } => proto::customer_response::Kind::RecurringNotificationPaused(
+                proto::customer_response::RecurringNotificationPaused {
+                    user_id: user_id.0,
+                    id: *id,
+                    paused: *paused,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponse::RecurringNotificationDeleted {
+                user_id,
+                id,
+                success,
+            } => proto::customer_response::Kind::RecurringNotificationDeleted(
+                proto::customer_response::RecurringNotificationDeleted {
+                    user_id: user_id.0,
+                    id: *id,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponse::InvalidSchedule {
+                user_id,
+                schedule,
+                timezone,
+            } => proto::customer_response::Kind::InvalidSchedule(
+                proto::customer_response::InvalidSchedule {
+                    user_id: user_id.0,
+                    schedule: schedule.clone(),
+                    timezone: timezone.clone(),
+                },
+            ),
+            responses::CustomerResponse::DeliveryProgress {
+                user_id,
+                published,
+                failed,
+                total,
+            } => proto::customer_response::Kind::DeliveryProgress(
+                proto::customer_response::DeliveryProgress {
+                    user_id: user_id.0,
+                    published: *published,
+                    failed: *failed,
+                    total: *total,
+                },
+            ),
+            responses::CustomerResponse::NotificationRejected { user_id, reason } => {
+                proto::customer_response::Kind::NotificationRejected(
+                    proto::customer_response::NotificationRejected {
+                        user_id: user_id.0,
+                        reason: Some(reason.into()),
+                    },
+                )
+            }
+            responses::CustomerResponse::NotificationHeld { user_id, id } => {
+                proto::customer_response::Kind::NotificationHeld(
+                    proto::customer_response::NotificationHeld {
+                        user_id: user_id.0,
+                        id: *id,
+                    },
+                )
+            }
+            responses::CustomerResponse::NotificationDeferred {
+                user_id,
+                id,
+                quota,
+                limit,
+                send_at,
+            } => proto::customer_response::Kind::NotificationDeferred(
+                proto::customer_response::NotificationDeferred {
+                    user_id: user_id.0,
+                    id: *id,
+                    quota: proto::Quota::from(*quota) as i32,
+                    limit: *limit,
+                    send_at: send_at.as_millis(),
+                },
+            ),
+            responses::CustomerResponse::ServiceUnavailable { user_id } => {
+                proto::customer_response::Kind::ServiceUnavailable(
+                    proto::customer_response::ServiceUnavailable { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::WebhookRegistered {
+                user_id,
+                url,
+                secret,
+            } => proto::customer_response::Kind::WebhookRegistered(
+                proto::customer_response::WebhookRegistered {
+                    user_id: user_id.0,
+                    url: url.clone(),
+                    secret: secret.clone(),
+                },
+            ),
+            responses::CustomerResponse::WebhookRemoved { user_id } => {
+                proto::customer_response::Kind::WebhookRemoved(
+                    proto::customer_response::WebhookRemoved { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::InvalidWebhookUrl { user_id, url } => {
+                proto::customer_response::Kind::InvalidWebhookUrl(
+                    proto::customer_response::InvalidWebhookUrl {
+                        user_id: user_id.0,
+                        url: url.clone(),
+                    },
+                )
+            }
+            responses::CustomerResponse::AuthorizationLocked { user_id, until } => {
+                proto::customer_response::Kind::AuthorizationLocked(
+                    proto::customer_response::AuthorizationLocked {
+                        user_id: user_id.0,
+                        until: until.as_millis(),
+                    },
+                )
+            }
+            responses::CustomerResponse::Operators { user_id, operators } => {
+                proto::customer_response::Kind::Operators(proto::customer_response::Operators {
+                    user_id: user_id.0,
+                    operators: operators.iter().map(Into::into).collect(),
+                })
+            }
+            responses::CustomerResponse::OperatorInvited {
+                user_id,
+                role,
+                key,
+                expires_at,
+            } => proto::customer_response::Kind::OperatorInvited(
+                proto::customer_response::OperatorInvited {
+                    user_id: user_id.0,
+                    role: proto::OperatorRole::from(*role) as i32,
+                    key: key.clone(),
+                    expires_at: expires_at.as_millis(),
+                },
+            ),
+            responses::CustomerResponse::OperatorRevoked {
+                user_id,
+                operator_id,
+                success,
+            } => proto::customer_response::Kind::OperatorRevoked(
+                proto::customer_response::OperatorRevoked {
+                    user_id: user_id.0,
+                    operator_id: *operator_id,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponse::OperatorAccessRevoked { user_id, customer } => {
+                proto::customer_response::Kind::OperatorAccessRevoked(
+                    proto::customer_response::OperatorAccessRevoked {
+                        user_id: user_id.0,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            responses::CustomerResponse::PermissionDenied { user_id } => {
+                proto::customer_response::Kind::PermissionDenied(
+                    proto::customer_response::PermissionDenied { user_id: user_id.0 },
+                )
+            }
+            responses::CustomerResponse::NotificationHistory {
+                user_id,
+                product,
+                page,
+                notifications,
+                has_more,
+            } => proto::customer_response::Kind::NotificationHistory(
+                proto::customer_response::NotificationHistory {
+                    user_id: user_id.0,
+                    product: product.as_ref().map(ToString::to_string),
+                    page: *page,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                    has_more: *has_more,
+                },
+            ),
+        };
+        proto::CustomerResponse { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::CustomerResponse> for responses::CustomerResponse {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::CustomerResponse) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::customer_response::Kind::AuthorizationSuccess(message) => {
+                responses::CustomerResponse::AuthorizationSuccess {
+                    user_id: UserId(message.user_id),
+                    customer: required(message.customer, "customer")?.try_into()?,
+                    role: proto::OperatorRole::from_i32(message.role)
+                        .ok_or(ProtoError::InvalidEnum("role", message.role))?
+                        .into(),
+                }
+            }
+            proto::customer_response::Kind::AuthorizationFailure(message) => {
+                responses::CustomerResponse::AuthorizationFailure {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::ProductsForNotification(message) => {
+                responses::CustomerResponse::ProductsForNotification {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    products: message
+                        .products
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response::Kind::NotificationSuccess(message) => {
+                responses::CustomerResponse::NotificationSuccess {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::NotificationFailure(message) => {
+                responses::CustomerResponse::NotificationFailure {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::ClientSubscription(message) => {
+                responses::CustomerResponse::ClientSubscription {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::customer_response::Kind::UnknownCustomer(message) => {
+                responses::CustomerResponse::UnknownCustomer {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_response::Kind::UnknownProduct(message) => {
+                responses::CustomerResponse::UnknownProduct {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::customer_response::Kind::RequestFailure(message) => {
+                responses::CustomerResponse::RequestFailure {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::NotificationScheduled(message) => {
+                responses::CustomerResponse::NotificationScheduled {
+                    user_id: UserId(message.user_id),
+                    notification: required(message.notification, "notification")?.try_into()?,
+                }
+            }
+            proto::customer_response::Kind::ScheduledNotifications(message) => {
+                responses::CustomerResponse::ScheduledNotifications {
+                    user_id: UserId(message.user_id),
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response::Kind::ScheduledNotificationCancelled(message) => {
+                responses::CustomerResponse::ScheduledNotificationCancelled {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                    success: message.success,
+                }
+            }
+            proto::customer_response::Kind::QuotaExceeded(message) => {
+                responses::CustomerResponse::QuotaExceeded {
+                    user_id: UserId(message.user_id),
+                    quota: proto::Quota::from_i32(message.quota)
+                        .ok_or(ProtoError::InvalidEnum("quota", message.quota))?
+                        .into(),
+                    limit: message.limit,
+                    retry_after_secs: message.retry_after_secs,
+                }
+            }
+            proto::customer_response::Kind::RecurringNotificationCreated(message) => {
+                responses::CustomerResponse::RecurringNotificationCreated {
+                    user_id: UserId(message.user_id),
+                    notification: required(message.notification, "notification")?.try_into()?,
+                }
+            }
+            proto::customer_response::Kind::RecurringNotifications(message) => {
+                responses::CustomerResponse::RecurringNotifications {
+                    user_id: UserId(message.user_id),
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response::Kind::RecurringNotificationPaused(message) => {
+                responses::CustomerResponse::RecurringNotificationPaused {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                    paused: message.paused,
+                    success: message.success,
+                }
+            }
+            proto::customer_response::Kind::RecurringNotificationDeleted(message) => {
+                responses::CustomerResponse::RecurringNotificationDeleted {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                    success: message.success,
+                }
+            }
+            proto::customer_response::Kind::InvalidSchedule(message) => {
+                responses::CustomerResponse::InvalidSchedule {
+                    user_id: UserId(message.user_id),
+                    schedule: message.schedule,
+                    timezone: message.timezone,
+                }
+            }
+            proto::customer_response::Kind::DeliveryProgress(message) => {
+                responses::CustomerResponse::DeliveryProgress {
+                    user_id: UserId(message.user_id),
+                    published: message.published,
+                    failed: message.failed,
+                    total: message.total,
+                }
+            }
+            proto::customer_response::Kind::NotificationRejected(message) => {
+                responses::CustomerResponse::NotificationRejected {
+                    user_id: UserId(message.user_id),
+                    reason: required(message.reason, "reason")?.try_into()?,
+                }
+            }
+            proto::customer_response::Kind::NotificationHeld(message) => {
+                responses::CustomerResponse::NotificationHeld {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                }
+            }
+            proto::customer_response::Kind::NotificationDeferred(message) => {
+                responses::CustomerResponse::NotificationDeferred {
+                    user_id: UserId(message.user_id),
+                    id: message.id,
+                    quota: proto::Quota::from_i32(message.quota)
+                        .ok_or(ProtoError::InvalidEnum("quota", message.quota))?
+                        .into(),
+                    limit: message.limit,
+                    send_at: timestamp(message.send_at)?,
+                }
+            }
+            proto::customer_response::Kind::ServiceUnavailable(message) => {
+                responses::CustomerResponse::ServiceUnavailable {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::WebhookRegistered(message) => {
+                responses::CustomerResponse::WebhookRegistered {
+                    user_id: UserId(message.user_id),
+                    url: message.url,
+                    secret: message.secret,
+                }
+            }
+            proto::customer_response::Kind::WebhookRemoved(message) => {
+                responses::CustomerResponse::WebhookRemoved {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::InvalidWebhookUrl(message) => {
+                responses::CustomerResponse::InvalidWebhookUrl {
+                    user_id: UserId(message.user_id),
+                    url: message.url,
+                }
+            }
+            proto::customer_response::Kind::AuthorizationLocked(message) => {
+                responses::CustomerResponse::AuthorizationLocked {
+                    user_id: UserId(message.user_id),
+                    until: timestamp(message.until)?,
+                }
+            }
+            proto::customer_response::Kind::Operators(message) => {
+                responses::CustomerResponse::Operators {
+                    user_id: UserId(message.user_id),
+                    operators: message
+                        .operators
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response::Kind::OperatorInvited(message) => {
+                responses::CustomerResponse::OperatorInvited {
+                    user_id: UserId(message.user_id),
+                    role: proto::OperatorRole::from_i32(message.role)
+                        .ok_or(ProtoError::InvalidEnum("role", message.role))?
+                        .into(),
+                    key: message.key,
+                    expires_at: timestamp(message.expires_at)?,
+                }
+            }
+            proto::customer_response::Kind::OperatorRevoked(message) => {
+                responses::CustomerResponse::OperatorRevoked {
+                    user_id: UserId(message.user_id),
+                    operator_id: message.operator_id,
+                    success: message.success,
+                }
+            }
+            proto::customer_response::Kind::OperatorAccessRevoked(message) => {
+                responses::CustomerResponse::OperatorAccessRevoked {
+                    user_id: UserId(message.user_id),
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_response::Kind::PermissionDenied(message) => {
+                responses::CustomerResponse::PermissionDenied {
+                    user_id: UserId(message.user_id),
+                }
+            }
+            proto::customer_response::Kind::NotificationHistory(message) => {
+                responses::CustomerResponse::NotificationHistory {
+                    user_id: UserId(message.user_id),
+                    product: message
+                        .product
+                        .map(|name| ProductName::parse(&name))
+                        .transpose()?,
+                    page: message.page,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                    has_more: message.has_more,
+                }
+            }
+        })
+    }
+}
+
+impl From<&responses::ClientResponseFromRepository> for proto::ClientResponseFromRepository {
+    fn from(value: &responses::ClientResponseFromRepository) -> Self {
+        let kind = match value {
+            responses::ClientResponseFromRepository::Customers { user_id, customers } => {
+                proto::client_response_from_repository::Kind::Customers(
+                    proto::client_response_from_repository::Customers {
+                        user_id: *user_id,
+                        customers: customers.iter().map(Into::into).collect(),
+                    },
+                )
+            }
+            responses::ClientResponseFromRepository::Products { user_id, products } => {
+                proto::client_response_from_repository::Kind::Products(
+                    proto::client_response_from_repository::Products {
+                        user_id: *user_id,
+                        products: products.iter().map(Into::into).collect(),
+                    },
+                )
+            }
+            responses::ClientResponseFromRepository::NewSubscription { user_id, success } => {
+                proto::client_response_from_repository::Kind::NewSubscription(
+                    proto::client_response_from_repository::NewSubscription {
+                        user_id: *user_id,
+                        success: *success,
+                    },
+                )
+            }
+            responses::ClientResponseFromRepository::UnknownCustomer { user_id, customer } => {
+                proto::client_response_from_repository::Kind::UnknownCustomer(
+                    proto::client_response_from_repository::UnknownCustomer {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            responses::ClientResponseFromRepository::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => proto::client_response_from_repository::Kind::UnknownProduct(
+                proto::client_response_from_repository::UnknownProduct {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            responses::ClientResponseFromRepository::Subscriptions {
+                user_id,
+                subscriptions,
+            } => proto::client_response_from_repository::Kind::Subscriptions(
+                proto::client_response_from_repository::Subscriptions {
+                    user_id: *user_id,
+                    subscriptions: subscriptions.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::ClientResponseFromRepository::SubscriptionCancelled {
+                user_id,
+                id,
+                success,
+            } => proto::client_response_from_repository::Kind::SubscriptionCancelled(
+                proto::client_response_from_repository::SubscriptionCancelled {
+                    user_id: *user_id,
+                    id: *id,
+                    success: *success,
+                },
+            ),
+            responses::ClientResponseFromRepository::SubscriptionsCancelled { user_id, count } => {
+                proto::client_response_from_repository::Kind::SubscriptionsCancelled(
+                    proto::client_response_from_repository::SubscriptionsCancelled {
+                        user_id: *user_id,
+                        count: *count,
+                    },
+                )
+            }
+            responses::ClientResponseFromRepository::NotificationHistory {
+                user_id,
+                page,
+                notifications,
+                has_more,
+            } => proto::client_response_from_repository::Kind::NotificationHistory(
+                proto::client_response_from_repository::NotificationHistory {
+                    user_id: *user_id,
+                    page: *page,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                    has_more: *has_more,
+                },
+            ),
+        };
+        proto::ClientResponseFromRepository {
+            request_id: 0,
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::ClientResponseFromRepository> for responses::ClientResponseFromRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ClientResponseFromRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::client_response_from_repository::Kind::Customers(message) => {
+                responses::ClientResponseFromRepository::Customers {
+                    user_id: message.user_id,
+                    customers: message
+                        .customers
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::client_response_from_repository::Kind::Products(message) => {
+                responses::ClientResponseFromRepository::Products {
+                    user_id: message.user_id,
+                    products: message
+                        .products
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::client_response_from_repository::Kind::NewSubscription(message) => {
+                responses::ClientResponseFromRepository::NewSubscription {
+                    user_id: message.user_id,
+                    success: message.success,
+                }
+            }
+            proto::client_response_from_repository::Kind::UnknownCustomer(message) => {
+                responses::ClientResponseFromRepository::UnknownCustomer {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::client_response_from_repository::Kind::UnknownProduct(message) => {
+                responses::ClientResponseFromRepository::UnknownProduct {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::client_response_from_repository::Kind::Subscriptions(message) => {
+                responses::ClientResponseFromRepository::Subscriptions {
+                    user_id: message.user_id,
+                    subscriptions: message
+                        .subscriptions
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::client_response_from_repository::Kind::SubscriptionCancelled(message) => {
+                responses::ClientResponseFromRepository::SubscriptionCancelled {
+                    user_id: message.user_id,
+                    id: message.id,
+                    success: message.success,
+                }
+            }
+            proto::client_response_from_repository::Kind::SubscriptionsCancelled(message) => {
+                responses::ClientResponseFromRepository::SubscriptionsCancelled {
+                    user_id: message.user_id,
+                    count: message.count,
+                }
+            }
+            proto::client_response_from_repository::Kind::NotificationHistory(message) => {
+                responses::ClientResponseFromRepository::NotificationHistory {
+                    user_id: message.user_id,
+                    page: message.page,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                    has_more: message.has_more,
+                }
+            }
+        })
+    }
+}
+
+impl From<&responses::CustomerResponseFromRepository> for proto::CustomerResponseFromRepository {
+    fn from(value: &responses::CustomerResponseFromRepository) -> Self {
+        let kind = match value {
+            responses::CustomerResponseFromRepository::Authorization {
+                user_id,
+                customer,
+                role,
+            } => proto::customer_response_from_repository::Kind::Authorization(
+                proto::customer_response_from_repository::Authorization {
+                    user_id: *user_id,
+                    customer: customer.as_ref().map(Into::into),
+                    role: proto::OperatorRole::from(*role) as i32,
+                },
+            ),
+            responses::CustomerResponseFromRepository::ProductsForNotification {
+                user_id,
+                customer,
+                products,
+            } => proto::customer_response_from_repository::Kind::ProductsForNotification(
+                proto::customer_response_from_repository::ProductsForNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    products: products.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::NewNotification {
+                user_id,
+                customer,
+                success,
+            } => proto::customer_response_from_repository::Kind::NewNotification(
+                proto::customer_response_from_repository::NewNotification {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponseFromRepository::UnknownCustomer { user_id, customer } => {
+                proto::customer_response_from_repository::Kind::UnknownCustomer(
+                    proto::customer_response_from_repository::UnknownCustomer {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            responses::CustomerResponseFromRepository::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => proto::customer_response_from_repository::Kind::UnknownProduct(
+                proto::customer_response_from_repository::UnknownProduct {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::NotificationScheduled {
+                user_id,
+                customer,
+                notification,
+            } => proto::customer_response_from_repository::Kind::NotificationScheduled(
+                proto::customer_response_from_repository::NotificationScheduled {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    notification: Some(notification.into()),
+                },
+            ),
+            responses::CustomerResponseFromRepository::ScheduledNotifications {
+                user_id,
+                customer,
+                notifications,
+            } => proto::customer_response_from_repository::Kind::ScheduledNotifications(
+                proto::customer_response_from_repository::ScheduledNotifications {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    notifications: notifications.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::ScheduledNotificationCancelled {
+                user_id,
+                customer,
+                id,
+                success,
+            } => proto::customer_response_from_repository::Kind::ScheduledNotificationCancelled(
+                proto::customer_response_from_repository::ScheduledNotificationCancelled {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponseFromRepository::RecurringNotificationCreated {
+                user_id,
+                customer,
+                notification,
+            } => proto::customer_response_from_repository::Kind::RecurringNotificationCreated(
+                proto::customer_response_from_repository::RecurringNotificationCreated {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    notification: Some(notification.into()),
+                },
+            ),
+            responses::CustomerResponseFromRepository::RecurringNotifications {
+                user_id,
+                customer,
+                notifications,
+            } => proto::customer_response_from_repository::Kind::RecurringNotifications(
+                proto::customer_response_from_repository::RecurringNotifications {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    notifications: notifications.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::RecurringNotificationPaused {
+                user_id,
+                customer,
+                id,
+                paused,
+                success,
+            } => proto::customer_response_from_repository::Kind::RecurringNotificationPaused(
+                proto::customer_response_from_repository::RecurringNotificationPaused {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                    paused: *paused,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponseFromRepository::RecurringNotificationDeleted {
+                user_id,
+                customer,
+                id,
+                success,
+            } => proto::customer_response_from_repository::Kind::RecurringNotificationDeleted(
+                proto::customer_response_from_repository::RecurringNotificationDeleted {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    id: *id,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponseFromRepository::InvalidSchedule {
+                user_id,
+                customer,
+                schedule,
+                timezone,
+            } => proto::customer_response_from_repository::Kind::InvalidSchedule(
+                proto::customer_response_from_repository::InvalidSchedule {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    schedule: schedule.clone(),
+                    timezone: timezone.clone(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::AuthorizationLocked { user_id, until } => {
+                proto::customer_response_from_repository::Kind::AuthorizationLocked(
+                    proto::customer_response_from_repository::AuthorizationLocked {
+                        user_id: *user_id,
+                        until: until.as_millis(),
+                    },
+                )
+            }
+            responses::CustomerResponseFromRepository::Operators {
+                user_id,
+                customer,
+                operators,
+            } => proto::customer_response_from_repository::Kind::Operators(
+                proto::customer_response_from_repository::Operators {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    operators: operators.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::OperatorInvited {
+                user_id,
+                customer,
+                role,
+                key,
+                expires_at,
+            } => proto::customer_response_from_repository::Kind::OperatorInvited(
+                proto::customer_response_from_repository::OperatorInvited {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    role: proto::OperatorRole::from(*role) as i32,
+                    key: key.clone(),
+                    expires_at: expires_at.as_millis(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::OperatorRevoked {
+                user_id,
+                customer,
+                operator_id,
+                success,
+            } => proto::customer_response_from_repository::Kind::OperatorRevoked(
+                proto::customer_response_from_repository::OperatorRevoked {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    operator_id: *operator_id,
+                    success: *success,
+                },
+            ),
+            responses::CustomerResponseFromRepository::PermissionDenied { user_id, customer } => {
+                proto::customer_response_from_repository::Kind::PermissionDenied(
+                    proto::customer_response_from_repository::PermissionDenied {
+                        user_id: *user_id,
+                        customer: customer.to_string(),
+                    },
+                )
+            }
+            responses::CustomerResponseFromRepository::WebhookAllowed {
+                user_id,
+                customer,
+                url,
+            } => proto::customer_response_from_repository::Kind::WebhookAllowed(
+                proto::customer_response_from_repository::WebhookAllowed {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    url: url.clone(),
+                },
+            ),
+            responses::CustomerResponseFromRepository::NotificationHistory {
+                user_id,
+                customer,
+                product,
+                page,
+                notifications,
+                has_more,
+            } => proto::customer_response_from_repository::Kind::NotificationHistory(
+                proto::customer_response_from_repository::NotificationHistory {
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.as_ref().map(ToString::to_string),
+                    page: *page,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                    has_more: *has_more,
+                },
+            ),
+        };
+        proto::CustomerResponseFromRepository {
+            request_id: 0,
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::CustomerResponseFromRepository> for responses::CustomerResponseFromRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::CustomerResponseFromRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::customer_response_from_repository::Kind::Authorization(message) => {
+                responses::CustomerResponseFromRepository::Authorization {
+                    user_id: message.user_id,
+                    customer: message.customer.map(TryInto::try_into).transpose()?,
+                    role: proto::OperatorRole::from_i32(message.role)
+                        .ok_or(ProtoError::InvalidEnum("role", message.role))?
+                        .into(),
+                }
+            }
+            proto::customer_response_from_repository::Kind::ProductsForNotification(message) => {
+                responses::CustomerResponseFromRepository::ProductsForNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    products: message
+                        .products
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::NewNotification(message) => {
+                responses::CustomerResponseFromRepository::NewNotification {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    success: message.success,
+                }
+            }
+            proto::customer_response_from_repository::Kind::UnknownCustomer(message) => {
+                responses::CustomerResponseFromRepository::UnknownCustomer {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::UnknownProduct(message) => {
+                responses::CustomerResponseFromRepository::UnknownProduct {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::NotificationScheduled(message) => {
+                responses::CustomerResponseFromRepository::NotificationScheduled {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::ScheduledNotifications(message) => {
+                responses::CustomerResponseFromRepository::ScheduledNotifications {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::ScheduledNotificationCancelled(
+                message,
+            ) => responses::CustomerResponseFromRepository::ScheduledNotificationCancelled {
+                user_id: message.user_id,
+                customer: CustomerName::parse(&message.customer)?,
+                id: message.id,
+                success: message.success,
+            },
+            proto::customer_response_from_repository::Kind::RecurringNotificationCreated(
+                message,
+            ) => responses::CustomerResponseFromRepository::RecurringNotificationCreated {
+                user_id: message.user_id,
+                customer: CustomerName::parse(&message.customer)?,
+                notification: required(message.notification, "notification")?.try_into()?,
+            },
+            proto::customer_response_from_repository::Kind::RecurringNotifications(message) => {
+                responses::CustomerResponseFromRepository::RecurringNotifications {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::RecurringNotificationPaused(
+                message,
+            ) => responses::CustomerResponseFromRepository::RecurringNotificationPaused {
+                user_id: message.user_id,
+                customer: CustomerName::parse(&message.customer)?,
+                id: message.id,
+                paused: message.paused,
+                success: message.success,
+            },
+            proto::customer_response_from_repository::Kind::RecurringNotificationDeleted(
+                message,
+            ) => responses::CustomerResponseFromRepository::RecurringNotificationDeleted {
+                user_id: message.user_id,
+                customer: CustomerName::parse(&message.customer)?,
+                id: message.id,
+                success: message.success,
+            },
+            proto::customer_response_from_repository::Kind::InvalidSchedule(message) => {
+                responses::CustomerResponseFromRepository::InvalidSchedule {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    schedule: message.schedule,
+                    timezone: message.timezone,
+                }
+            }
+            proto::customer_response_from_repository::Kind::AuthorizationLocked(message) => {
+                responses::CustomerResponseFromRepository::AuthorizationLocked {
+                    user_id: message.user_id,
+                    until: timestamp(message.until)?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::Operators(message) => {
+                responses::CustomerResponseFromRepository::Operators {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    operators: message
+                        .operators
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::OperatorInvited(message) => {
+                responses::CustomerResponseFromRepository::OperatorInvited {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    role: proto::OperatorRole::from_i32(message.role)
+                        .ok_or(ProtoError::InvalidEnum("role", message.role))?
+                        .into(),
+                    key: message.key,
+                    expires_at: timestamp(message.expires_at)?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::OperatorRevoked(message) => {
+                responses::CustomerResponseFromRepository::OperatorRevoked {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    operator_id: message.operator_id,
+                    success: message.success,
+                }
+            }
+            proto::customer_response_from_repository::Kind::PermissionDenied(message) => {
+                responses::CustomerResponseFromRepository::PermissionDenied {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                }
+            }
+            proto::customer_response_from_repository::Kind::WebhookAllowed(message) => {
+                responses::CustomerResponseFromRepository::WebhookAllowed {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    url: message.url,
+                }
+            }
+            proto::customer_response_from_repository::Kind::NotificationHistory(message) => {
+                responses::CustomerResponseFromRepository::NotificationHistory {
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: message
+                        .product
+                        .map(|name| ProductName::parse(&name))
+                        .transpose()?,
+                    page: message.page,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                    has_more: message.has_more,
+                }
+            }
+        })
+    }
+}
+
+impl From<&responses::ResponseFromRepository> for proto::ResponseFromRepository {
+    fn from(value: &responses::ResponseFromRepository) -> Self {
+        let kind = match value {
+            responses::ResponseFromRepository::Notifications {
+                user_id,
+                notifications,
+            } => proto::response_from_repository::Kind::Notifications(
+                proto::response_from_repository::Notifications {
+                    user_id: *user_id,
+                    notifications: notifications.iter().map(Into::into).collect(),
+                },
+            ),
+            responses::ResponseFromRepository::Subscription {
+                operators,
+                customer,
+                product,
+            } => proto::response_from_repository::Kind::Subscription(
+                proto::response_from_repository::Subscription {
+                    operators: operators.clone(),
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                },
+            ),
+            responses::ResponseFromRepository::DueNotification {
+                id,
+                user_id,
+                customer,
+                product,
+                notification,
+                send_at,
+            } => proto::response_from_repository::Kind::DueNotification(
+                proto::response_from_repository::DueNotification {
+                    id: *id,
+                    user_id: *user_id,
+                    customer: customer.to_string(),
+                    product: product.to_string(),
+                    notification: Some(notification.into()),
+                    send_at: send_at.as_millis(),
+                },
+            ),
+        };
+        proto::ResponseFromRepository { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::ResponseFromRepository> for responses::ResponseFromRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::ResponseFromRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::response_from_repository::Kind::Notifications(message) => {
+                responses::ResponseFromRepository::Notifications {
+                    user_id: message.user_id,
+                    notifications: message
+                        .notifications
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            proto::response_from_repository::Kind::Subscription(message) => {
+                responses::ResponseFromRepository::Subscription {
+                    operators: message.operators,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                }
+            }
+            proto::response_from_repository::Kind::DueNotification(message) => {
+                responses::ResponseFromRepository::DueNotification {
+                    id: message.id,
+                    user_id: message.user_id,
+                    customer: CustomerName::parse(&message.customer)?,
+                    product: ProductName::parse(&message.product)?,
+                    notification: required(message.notification, "notification")?.try_into()?,
+                    send_at: timestamp(message.send_at)?,
+                }
+            }
+        })
+    }
+}
+
+impl From<&records::Record> for proto::Record {
+    fn from(value: &records::Record) -> Self {
+        let kind = match value {
+            records::Record::UserEvent(record) => proto::record::Kind::UserEvent(record.into()),
+            records::Record::CustomerEvent(record) => {
+                proto::record::Kind::CustomerEvent(record.into())
+            }
+        };
+        proto::Record { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::Record> for records::Record {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::Record) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::record::Kind::UserEvent(record) => {
+                records::Record::UserEvent(record.try_into()?)
+            }
+            proto::record::Kind::CustomerEvent(record) => {
+                records::Record::CustomerEvent(record.try_into()?)
+            }
+        })
+    }
+}
+
+impl From<&requests::AdminRequestToRepository> for proto::AdminRequestToRepository {
+    fn from(value: &requests::AdminRequestToRepository) -> Self {
+        let kind = match value {
+            requests::AdminRequestToRepository::ReloadCache => {
+                proto::admin_request_to_repository::Kind::ReloadCache(
+                    proto::admin_request_to_repository::ReloadCache {},
+                )
+            }
+            requests::AdminRequestToRepository::CacheStats => {
+                proto::admin_request_to_repository::Kind::CacheStats(
+                    proto::admin_request_to_repository::CacheStats {},
+                )
+            }
+        };
+        proto::AdminRequestToRepository { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::AdminRequestToRepository> for requests::AdminRequestToRepository {
+    type Error = ProtoError;
+
+    fn try_from(value: proto::AdminRequestToRepository) -> Result<Self, Self::Error> {
+        Ok(match required(value.kind, "kind")? {
+            proto::admin_request_to_repository::Kind::ReloadCache(_) => {
+                requests::AdminRequestToRepository::ReloadCache
+            }
+            proto::admin_request_to_repository::Kind::CacheStats(_) => {
+                requests::AdminRequestToRepository::CacheStats
+            }
+        })
+    }
+}
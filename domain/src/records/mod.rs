@@ -1,3 +1,6 @@
+use std::fmt::{self, Display};
+
+use chrono::Duration;
 use serde::{Deserialize, Serialize};
 
 use crate::models::{CustomerEventRecord, UserEventRecord};
@@ -8,8 +11,17 @@ pub enum Record {
     CustomerEvent(CustomerEventRecord),
 }
 
-impl ToString for Record {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Record {
+    pub fn latency(&self) -> Option<Duration> {
+        match self {
+            Record::UserEvent(record) => record.latency(),
+            Record::CustomerEvent(record) => record.latency(),
+        }
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
@@ -1,23 +1,51 @@
+use std::fmt::{self, Display};
+
 use serde::{Deserialize, Serialize};
 
-use crate::models::UserId;
+use crate::models::{
+    CustomerName, DeliveryChannel, NotificationContent, OperatorRole, ProductName,
+    SubscriptionMode, Timestamp, UserId, WebhookEvent,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientRequest {
     Customers {
         user_id: UserId,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
     Products {
         user_id: UserId,
-        customer: String,
-        timestamp: i64,
+        customer: CustomerName,
+        timestamp: Timestamp,
     },
     NewSubscription {
         user_id: UserId,
-        customer: String,
-        product: String,
-        timestamp: i64,
+        customer: CustomerName,
+        product: ProductName,
+        channel: DeliveryChannel,
+        email: Option<String>,
+        timestamp: Timestamp,
+        #[serde(default)]
+        mode: SubscriptionMode,
+    },
+    Subscriptions {
+        user_id: UserId,
+        timestamp: Timestamp,
+    },
+    CancelSubscription {
+        user_id: UserId,
+        id: i64,
+        timestamp: Timestamp,
+    },
+    CancelAllSubscriptions {
+        user_id: UserId,
+        timestamp: Timestamp,
+    },
+    /// Notifications received by the user, newest first, `page` counts from 0.
+    NotificationHistory {
+        user_id: UserId,
+        page: u32,
+        timestamp: Timestamp,
     },
 }
 
@@ -26,20 +54,105 @@ pub enum CustomerRequest {
     Authorization {
         user_id: UserId,
         key: String,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
     ProductsForNotification {
         user_id: UserId,
-        customer: String,
-        timestamp: i64,
+        customer: CustomerName,
+        timestamp: Timestamp,
     },
+    /// Sent right away, or at `send_at` if it is set.
     NewNotification {
         user_id: UserId,
-        customer: String,
-        product: String,
-        notification: String,
-        timestamp: i64,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
+        #[serde(default)]
+        send_at: Option<Timestamp>,
+        timestamp: Timestamp,
+    },
+    ScheduledNotifications {
+        user_id: UserId,
+        customer: CustomerName,
+        timestamp: Timestamp,
+    },
+    CancelScheduledNotification {
+        user_id: UserId,
+        customer: CustomerName,
+        id: i64,
+        timestamp: Timestamp,
+    },
+    /// Sent on every occurrence of the cron `schedule` in `timezone`.
+    NewRecurringNotification {
+        user_id: UserId,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
+        schedule: String,
+        timezone: String,
+        timestamp: Timestamp,
+    },
+    RecurringNotifications {
+        user_id: UserId,
+        customer: CustomerName,
+        timestamp: Timestamp,
+    },
+    /// Pauses the recurring notification, or resumes it if `paused` is false.
+    PauseRecurringNotification {
+        user_id: UserId,
+        customer: CustomerName,
+        id: i64,
+        paused: bool,
+        timestamp: Timestamp,
+    },
+    DeleteRecurringNotification {
+        user_id: UserId,
+        customer: CustomerName,
+        id: i64,
+        timestamp: Timestamp,
+    },
+    /// Registers the webhook of the customer, `None` removes it.
+    SetWebhook {
+        user_id: UserId,
+        customer: CustomerName,
+        url: Option<String>,
+        timestamp: Timestamp,
     },
+    Operators {
+        user_id: UserId,
+        customer: CustomerName,
+        timestamp: Timestamp,
+    },
+    /// Creates a one-time key granting `role`, for owners only.
+    InviteOperator {
+        user_id: UserId,
+        customer: CustomerName,
+        role: OperatorRole,
+        timestamp: Timestamp,
+    },
+    /// Removes the access of another operator, for owners only.
+    RevokeOperator {
+        user_id: UserId,
+        customer: CustomerName,
+        operator_id: u32,
+        timestamp: Timestamp,
+    },
+    /// Notifications sent by the customer, newest first, `page` counts from 0.
+    /// Only those of `product` if it is set.
+    NotificationHistory {
+        user_id: UserId,
+        customer: CustomerName,
+        product: Option<ProductName>,
+        page: u32,
+        timestamp: Timestamp,
+    },
+}
+
+/// Decision of a moderator on a notification held for manual review.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ModerationDecision {
+    Approve { id: u64 },
+    Reject { id: u64, comment: String },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,12 +162,33 @@ pub enum ClientRequestToRepository {
     },
     Products {
         user_id: u32,
-        customer: String,
+        customer: CustomerName,
     },
     NewSubscription {
         user_id: u32,
-        customer: String,
-        product: String,
+        customer: CustomerName,
+        product: ProductName,
+        channel: DeliveryChannel,
+        email: Option<String>,
+        #[serde(default)]
+        mode: SubscriptionMode,
+    },
+    /// Every subscription of the user, delivered ones included.
+    Subscriptions {
+        user_id: u32,
+    },
+    /// Cancels an active subscription of the user.
+    CancelSubscription {
+        user_id: u32,
+        id: i64,
+    },
+    /// Cancels every active subscription of the user.
+    CancelAllSubscriptions {
+        user_id: u32,
+    },
+    NotificationHistory {
+        user_id: u32,
+        page: u32,
     },
 }
 
@@ -66,13 +200,75 @@ pub enum CustomerRequestToRepository {
     },
     ProductsForNotification {
         user_id: u32,
-        customer: String,
+        customer: CustomerName,
     },
+    /// Stored as pending if `send_at` is set, the repository sends it when due.
     NewNotification {
         user_id: u32,
-        customer: String,
-        product: String,
-        notification: String,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
+        #[serde(default)]
+        send_at: Option<Timestamp>,
+    },
+    ScheduledNotifications {
+        user_id: u32,
+        customer: CustomerName,
+    },
+    CancelScheduledNotification {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+    },
+    NewRecurringNotification {
+        user_id: u32,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
+        schedule: String,
+        timezone: String,
+    },
+    RecurringNotifications {
+        user_id: u32,
+        customer: CustomerName,
+    },
+    PauseRecurringNotification {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+        paused: bool,
+    },
+    DeleteRecurringNotification {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+    },
+    Operators {
+        user_id: u32,
+        customer: CustomerName,
+    },
+    InviteOperator {
+        user_id: u32,
+        customer: CustomerName,
+        role: OperatorRole,
+    },
+    RevokeOperator {
+        user_id: u32,
+        customer: CustomerName,
+        operator_id: u32,
+    },
+    /// Checks that the user may set the webhook, the controller registers it
+    /// with the webhooks service once allowed.
+    SetWebhook {
+        user_id: u32,
+        customer: CustomerName,
+        url: Option<String>,
+    },
+    NotificationHistory {
+        user_id: u32,
+        customer: CustomerName,
+        product: Option<ProductName>,
+        page: u32,
     },
 }
 
@@ -80,43 +276,128 @@ pub enum CustomerRequestToRepository {
 pub enum RequestToRepository {
     NotificationForClients {
         user_id: u32,
-        customer: String,
-        product: String,
-        notification: String,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
     },
     SubscriptionForCustomer {
         user_id: u32,
-        customer: String,
-        product: String,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    /// Puts due notification `id` of the customer user `user_id` back in the
+    /// schedule, to be sent at `send_at` once the customer has quota again.
+    DeferNotification {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+        send_at: Timestamp,
     },
 }
 
-impl ToString for ClientRequest {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+/// Maintenance request to the repository service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AdminRequestToRepository {
+    /// Reload the cached customers, products and authorized users.
+    ReloadCache,
+    /// Log the cache hit and miss counters.
+    CacheStats,
+}
+
+/// Request to the webhook dispatcher.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum WebhookRequest {
+    Register {
+        user_id: u32,
+        customer: CustomerName,
+        url: Option<String>,
+    },
+    Event {
+        customer: CustomerName,
+        event: WebhookEvent,
+        timestamp: Timestamp,
+    },
+}
+
+impl ClientRequest {
+    pub fn user_id(&self) -> &UserId {
+        match self {
+            ClientRequest::Customers { user_id, .. }
+            | ClientRequest::Products { user_id, .. }
+            | ClientRequest::NewSubscription { user_id, .. }
+            | ClientRequest::Subscriptions { user_id, .. }
+            | ClientRequest::CancelSubscription { user_id, .. }
+            | ClientRequest::CancelAllSubscriptions { user_id, .. }
+            | ClientRequest::NotificationHistory { user_id, .. } => user_id,
+        }
+    }
+}
+
+impl CustomerRequest {
+    pub fn user_id(&self) -> &UserId {
+        match self {
+            CustomerRequest::Authorization { user_id, .. }
+            | CustomerRequest::ProductsForNotification { user_id, .. }
+            | CustomerRequest::NewNotification { user_id, .. }
+            | CustomerRequest::ScheduledNotifications { user_id, .. }
+            | CustomerRequest::CancelScheduledNotification { user_id, .. }
+            | CustomerRequest::NewRecurringNotification { user_id, .. }
+            | CustomerRequest::RecurringNotifications { user_id, .. }
+            | CustomerRequest::PauseRecurringNotification { user_id, .. }
+            | CustomerRequest::DeleteRecurringNotification { user_id, .. }
+            | CustomerRequest::SetWebhook { user_id, .. }
+            | CustomerRequest::Operators { user_id, .. }
+            | CustomerRequest::InviteOperator { user_id, .. }
+            | CustomerRequest::RevokeOperator { user_id, .. }
+            | CustomerRequest::NotificationHistory { user_id, .. } => user_id,
+        }
+    }
+}
+
+impl Display for ClientRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
+    }
+}
+
+impl Display for ModerationDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
+    }
+}
+
+impl Display for CustomerRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
+    }
+}
+
+impl Display for ClientRequestToRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for CustomerRequest {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for CustomerRequestToRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for ClientRequestToRepository {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for WebhookRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for CustomerRequestToRepository {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for RequestToRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for RequestToRepository {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for AdminRequestToRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
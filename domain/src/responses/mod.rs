@@ -1,6 +1,12 @@
+use std::fmt::{self, Display};
+
 use serde::{Deserialize, Serialize};
 
-use crate::models::{Customer, Notification, Product, UserId};
+use crate::models::{
+    Customer, CustomerName, ModerationReason, Notification, NotificationContent, Operator,
+    OperatorRole, PastNotification, Product, ProductName, Quota, RecurringNotification,
+    ScheduledNotification, Subscription, Timestamp, UserId,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientResponse {
@@ -20,9 +26,46 @@ pub enum ClientResponse {
     },
     CustomerNotification {
         user_id: UserId,
-        customer: String,
-        product: String,
-        notification: String,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
+    },
+    UnknownCustomer {
+        user_id: UserId,
+        customer: CustomerName,
+    },
+    UnknownProduct {
+        user_id: UserId,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    /// The request could not be processed, the user should retry later.
+    RequestFailure {
+        user_id: UserId,
+    },
+    /// The repository did not answer the request in time.
+    ServiceUnavailable {
+        user_id: UserId,
+    },
+    Subscriptions {
+        user_id: UserId,
+        subscriptions: Vec<Subscription>,
+    },
+    SubscriptionCancelled {
+        user_id: UserId,
+        id: i64,
+        success: bool,
+    },
+    SubscriptionsCancelled {
+        user_id: UserId,
+        count: u32,
+    },
+    /// One page of the received notifications, `has_more` if older ones follow.
+    NotificationHistory {
+        user_id: UserId,
+        page: u32,
+        notifications: Vec<PastNotification>,
+        has_more: bool,
     },
 }
 
@@ -31,13 +74,15 @@ pub enum CustomerResponse {
     AuthorizationSuccess {
         user_id: UserId,
         customer: Customer,
+        #[serde(default)]
+        role: OperatorRole,
     },
     AuthorizationFailure {
         user_id: UserId,
     },
     ProductsForNotification {
         user_id: UserId,
-        customer: String,
+        customer: CustomerName,
         products: Vec<Product>,
     },
     NotificationSuccess {
@@ -48,8 +93,149 @@ pub enum CustomerResponse {
     },
     ClientSubscription {
         user_id: UserId,
-        customer: String,
-        product: String,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    UnknownCustomer {
+        user_id: UserId,
+        customer: CustomerName,
+    },
+    UnknownProduct {
+        user_id: UserId,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    /// The request could not be processed, the user should retry later.
+    RequestFailure {
+        user_id: UserId,
+    },
+    NotificationScheduled {
+        user_id: UserId,
+        notification: ScheduledNotification,
+    },
+    ScheduledNotifications {
+        user_id: UserId,
+        notifications: Vec<ScheduledNotification>,
+    },
+    ScheduledNotificationCancelled {
+        user_id: UserId,
+        id: i64,
+        success: bool,
+    },
+    /// The notification was rejected because the customer used up a quota.
+    QuotaExceeded {
+        user_id: UserId,
+        quota: Quota,
+        limit: u32,
+        retry_after_secs: u64,
+    },
+    RecurringNotificationCreated {
+        user_id: UserId,
+        notification: RecurringNotification,
+    },
+    RecurringNotifications {
+        user_id: UserId,
+        notifications: Vec<RecurringNotification>,
+    },
+    RecurringNotificationPaused {
+        user_id: UserId,
+        id: i64,
+        paused: bool,
+        success: bool,
+    },
+    RecurringNotificationDeleted {
+        user_id: UserId,
+        id: i64,
+        success: bool,
+    },
+    /// The cron expression or the timezone of a recurring notification is invalid.
+    InvalidSchedule {
+        user_id: UserId,
+        schedule: String,
+        timezone: String,
+    },
+    /// The notification did not pass moderation.
+    NotificationRejected {
+        user_id: UserId,
+        reason: ModerationReason,
+    },
+    /// The notification waits for a moderator, `id` identifies it in the hold queue.
+    NotificationHeld {
+        user_id: UserId,
+        id: u64,
+    },
+    /// The scheduled notification `id` was due, but the customer used up a
+    /// quota, so it is sent at `send_at` instead.
+    NotificationDeferred {
+        user_id: UserId,
+        id: i64,
+        quota: Quota,
+        limit: u32,
+        send_at: Timestamp,
+    },
+    /// Progress of delivering a notification to the subscribers. `published`
+    /// counts the notifications handed over to the client bot or the SMTP
+    /// server, not the ones the subscribers received.
+    DeliveryProgress {
+        user_id: UserId,
+        published: u32,
+        failed: u32,
+        total: u32,
+    },
+    /// The repository did not answer the request in time.
+    ServiceUnavailable {
+        user_id: UserId,
+    },
+    /// Payloads posted to `url` are signed with `secret`.
+    WebhookRegistered {
+        user_id: UserId,
+        url: String,
+        secret: String,
+    },
+    WebhookRemoved {
+        user_id: UserId,
+    },
+    InvalidWebhookUrl {
+        user_id: UserId,
+        url: String,
+    },
+    /// Too many failed authorization attempts, the next one is accepted after `until`.
+    AuthorizationLocked {
+        user_id: UserId,
+        until: Timestamp,
+    },
+    Operators {
+        user_id: UserId,
+        operators: Vec<Operator>,
+    },
+    /// One-time `key` granting `role` to whoever authorizes with it before `expires_at`.
+    OperatorInvited {
+        user_id: UserId,
+        role: OperatorRole,
+        key: String,
+        expires_at: Timestamp,
+    },
+    OperatorRevoked {
+        user_id: UserId,
+        operator_id: u32,
+        success: bool,
+    },
+    /// Sent to an operator whose access to the customer was revoked.
+    OperatorAccessRevoked {
+        user_id: UserId,
+        customer: CustomerName,
+    },
+    /// The role of the operator does not allow the request.
+    PermissionDenied {
+        user_id: UserId,
+    },
+    /// One page of the sent notifications, `has_more` if older ones follow.
+    NotificationHistory {
+        user_id: UserId,
+        product: Option<ProductName>,
+        page: u32,
+        notifications: Vec<PastNotification>,
+        has_more: bool,
     },
 }
 
@@ -67,6 +253,35 @@ pub enum ClientResponseFromRepository {
         user_id: u32,
         success: bool,
     },
+    UnknownCustomer {
+        user_id: u32,
+        customer: CustomerName,
+    },
+    UnknownProduct {
+        user_id: u32,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    Subscriptions {
+        user_id: u32,
+        subscriptions: Vec<Subscription>,
+    },
+    /// `success` is false when the user has no such active subscription.
+    SubscriptionCancelled {
+        user_id: u32,
+        id: i64,
+        success: bool,
+    },
+    SubscriptionsCancelled {
+        user_id: u32,
+        count: u32,
+    },
+    NotificationHistory {
+        user_id: u32,
+        page: u32,
+        notifications: Vec<PastNotification>,
+        has_more: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -74,55 +289,193 @@ pub enum CustomerResponseFromRepository {
     Authorization {
         user_id: u32,
         customer: Option<Customer>,
+        #[serde(default)]
+        role: OperatorRole,
     },
     ProductsForNotification {
         user_id: u32,
-        customer: String,
+        customer: CustomerName,
         products: Vec<Product>,
     },
     NewNotification {
         user_id: u32,
-        customer: String,
+        customer: CustomerName,
+        success: bool,
+    },
+    UnknownCustomer {
+        user_id: u32,
+        customer: CustomerName,
+    },
+    UnknownProduct {
+        user_id: u32,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    NotificationScheduled {
+        user_id: u32,
+        customer: CustomerName,
+        notification: ScheduledNotification,
+    },
+    ScheduledNotifications {
+        user_id: u32,
+        customer: CustomerName,
+        notifications: Vec<ScheduledNotification>,
+    },
+    ScheduledNotificationCancelled {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+        success: bool,
+    },
+    RecurringNotificationCreated {
+        user_id: u32,
+        customer: CustomerName,
+        notification: RecurringNotification,
+    },
+    RecurringNotifications {
+        user_id: u32,
+        customer: CustomerName,
+        notifications: Vec<RecurringNotification>,
+    },
+    RecurringNotificationPaused {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+        paused: bool,
+        success: bool,
+    },
+    RecurringNotificationDeleted {
+        user_id: u32,
+        customer: CustomerName,
+        id: i64,
+        success: bool,
+    },
+    InvalidSchedule {
+        user_id: u32,
+        customer: CustomerName,
+        schedule: String,
+        timezone: String,
+    },
+    AuthorizationLocked {
+        user_id: u32,
+        until: Timestamp,
+    },
+    Operators {
+        user_id: u32,
+        customer: CustomerName,
+        operators: Vec<Operator>,
+    },
+    OperatorInvited {
+        user_id: u32,
+        customer: CustomerName,
+        role: OperatorRole,
+        key: String,
+        expires_at: Timestamp,
+    },
+    OperatorRevoked {
+        user_id: u32,
+        customer: CustomerName,
+        operator_id: u32,
         success: bool,
     },
+    PermissionDenied {
+        user_id: u32,
+        customer: CustomerName,
+    },
+    /// The user may set the webhook of the customer to `url`.
+    WebhookAllowed {
+        user_id: u32,
+        customer: CustomerName,
+        url: Option<String>,
+    },
+    NotificationHistory {
+        user_id: u32,
+        customer: CustomerName,
+        product: Option<ProductName>,
+        page: u32,
+        notifications: Vec<PastNotification>,
+        has_more: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ResponseFromRepository {
-    Notifications(Vec<Notification>),
+    /// Notifications for the subscribers, `user_id` is the customer user who sent them.
+    Notifications {
+        user_id: u32,
+        notifications: Vec<Notification>,
+    },
+    /// New subscriber of the product, for every operator of the customer.
     Subscription {
+        operators: Vec<u32>,
+        customer: CustomerName,
+        product: ProductName,
+    },
+    /// Scheduled or recurring notification `id` of the customer user `user_id`
+    /// that is due at `send_at`. The controller sends it on like a new
+    /// notification, every recurring occurrence is charged to the quota.
+    DueNotification {
+        id: i64,
+        user_id: u32,
+        customer: CustomerName,
+        product: ProductName,
+        notification: NotificationContent,
+        send_at: Timestamp,
+    },
+}
+
+/// Response of the webhook dispatcher.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum WebhookResponse {
+    Registered {
         user_id: u32,
-        customer: String,
-        product: String,
+        url: String,
+        secret: String,
     },
+    Removed {
+        user_id: u32,
+    },
+    InvalidUrl {
+        user_id: u32,
+        url: String,
+    },
+    Failure {
+        user_id: u32,
+    },
+}
+
+impl Display for ClientResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
+    }
 }
 
-impl ToString for ClientResponse {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for CustomerResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for CustomerResponse {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for ClientResponseFromRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for ClientResponseFromRepository {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for CustomerResponseFromRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for CustomerResponseFromRepository {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for WebhookResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
 
-impl ToString for ResponseFromRepository {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl Display for ResponseFromRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(&self).unwrap())
     }
 }
@@ -1,9 +1,6 @@
 -- Add migration script here
 
-DROP TABLE IF EXISTS users;
-DROP TABLE IF EXISTS customers;
-
-CREATE TABLE "users" (
+CREATE TABLE IF NOT EXISTS "users" (
 	"id"	INTEGER NOT NULL,
 	"timestamp"	INTEGER NOT NULL,
 	"user_id"	INTEGER NOT NULL,
@@ -12,7 +9,7 @@ CREATE TABLE "users" (
 	PRIMARY KEY("id" AUTOINCREMENT)
 );
 
-CREATE TABLE "customers" (
+CREATE TABLE IF NOT EXISTS "customers" (
 	"id"	INTEGER NOT NULL UNIQUE,
 	"timestamp"	INTEGER NOT NULL,
 	"user_id"	INTEGER NOT NULL,
@@ -0,0 +1,20 @@
+-- Add migration script here
+
+ALTER TABLE "users" RENAME COLUMN "timestamp" TO "received_at";
+UPDATE "users" SET "received_at" = "received_at" * 1000;
+ALTER TABLE "users" ADD COLUMN "sent_at" INTEGER;
+
+ALTER TABLE "customers" RENAME COLUMN "timestamp" TO "received_at";
+UPDATE "customers" SET "received_at" = "received_at" * 1000;
+ALTER TABLE "customers" ADD COLUMN "sent_at" INTEGER;
+
+CREATE VIEW "request_latency" AS
+	SELECT 'user' AS "source", "id", "user_id", "event", "sent_at", "received_at",
+		"received_at" - "sent_at" AS "latency_ms"
+	FROM "users"
+	WHERE "sent_at" IS NOT NULL
+	UNION ALL
+	SELECT 'customer' AS "source", "id", "user_id", "event", "sent_at", "received_at",
+		"received_at" - "sent_at" AS "latency_ms"
+	FROM "customers"
+	WHERE "sent_at" IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Add migration script here
+
+CREATE INDEX IF NOT EXISTS "users_user_id" ON "users" ("user_id");
+CREATE INDEX IF NOT EXISTS "users_received_at" ON "users" ("received_at");
+
+CREATE INDEX IF NOT EXISTS "customers_user_id" ON "customers" ("user_id");
+CREATE INDEX IF NOT EXISTS "customers_received_at" ON "customers" ("received_at");
@@ -0,0 +1,31 @@
+-- Add migration script here
+
+CREATE TABLE IF NOT EXISTS "users" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"sent_at"	BIGINT,
+	"received_at"	BIGINT NOT NULL,
+	"user_id"	BIGINT NOT NULL,
+	"event"	TEXT NOT NULL,
+	"data"	TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS "customers" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"sent_at"	BIGINT,
+	"received_at"	BIGINT NOT NULL,
+	"user_id"	BIGINT NOT NULL,
+	"customer"	TEXT,
+	"event"	TEXT NOT NULL,
+	"data"	TEXT NOT NULL
+);
+
+CREATE OR REPLACE VIEW "request_latency" AS
+	SELECT 'user' AS "source", "id", "user_id", "event", "sent_at", "received_at",
+		"received_at" - "sent_at" AS "latency_ms"
+	FROM "users"
+	WHERE "sent_at" IS NOT NULL
+	UNION ALL
+	SELECT 'customer' AS "source", "id", "user_id", "event", "sent_at", "received_at",
+		"received_at" - "sent_at" AS "latency_ms"
+	FROM "customers"
+	WHERE "sent_at" IS NOT NULL;
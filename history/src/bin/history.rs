@@ -1,9 +1,14 @@
-use dotenv::dotenv;
-use history::{repository::SqliteRepository, Config, HistoryService, MessageHandler};
 use amqp::RabbitMqManager;
+use dotenv::dotenv;
+use history::{
+    repository::{connect, migrate},
+    Config, HistoryService, MessageHandler,
+};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+const MIGRATE_ONLY_FLAG: &str = "--migrate-only";
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -11,9 +16,13 @@ async fn main() {
 
     let config = envy::from_env::<Config>().unwrap();
 
-    let repository = SqliteRepository::new(&config.history_database_url)
-        .await
-        .unwrap();
+    migrate(&config.history_database_url).await.unwrap();
+    if std::env::args().any(|arg| arg == MIGRATE_ONLY_FLAG) {
+        log::info!("Migrations applied");
+        return;
+    }
+
+    let repository = connect(&config.history_database_url).await.unwrap();
     let service = Arc::new(Mutex::new(HistoryService::new(repository)));
 
     let mut client = RabbitMqManager::builder()
@@ -22,7 +31,10 @@ async fn main() {
         .unwrap();
 
     client
-        .add_consumer(&config.history_queue, MessageHandler::record(service))
+        .add_consumer(
+            &config.history_queue,
+            MessageHandler::record(service, config.wire_format),
+        )
         .await
         .unwrap();
 
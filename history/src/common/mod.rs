@@ -1,8 +1,13 @@
+use domain::codec::WireFormat;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Config {
     pub amqp_address: String,
+    /// `sqlite:` or `postgres://` url, the scheme selects the storage backend.
+    /// Pending migrations are applied at startup.
     pub history_database_url: String,
     pub history_queue: String,
+    #[serde(default)]
+    pub wire_format: WireFormat,
 }
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use domain::records::Record;
+use domain::{
+    codec::{WireFormat, WireMessage},
+    records::Record,
+};
 use amqp::IncomingMessageHandler;
 use tokio::sync::Mutex;
 
@@ -8,11 +11,20 @@ use crate::HistoryService;
 
 pub struct MessageHandler {}
 impl MessageHandler {
-    pub fn record(service: Arc<Mutex<HistoryService>>) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+    pub fn record(
+        service: Arc<Mutex<HistoryService>>,
+        wire_format: WireFormat,
+    ) -> impl IncomingMessageHandler + 'static {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let record: Record = serde_json::from_str(&message).unwrap();
+                let record = match Record::decode(&message, wire_format) {
+                    Ok(record) => record,
+                    Err(error) => {
+                        log::error!("Message handler record decode error: {}", error);
+                        return;
+                    }
+                };
                 service.lock().await.add_record(record).await;
             }
         }
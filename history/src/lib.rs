@@ -1,3 +1,6 @@
+#[cfg(not(any(feature = "sqlite", feature = "postgres")))]
+compile_error!("enable the `sqlite` or `postgres` feature of the history crate");
+
 mod common;
 mod handler;
 pub mod repository;
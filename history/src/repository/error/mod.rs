@@ -4,6 +4,10 @@ use thiserror::Error;
 pub enum DatabaseErrors {
     #[error("Connection failure: [{0}]")]
     ConnectionFailure(String),
+    #[error("Unsupported database: [{0}]")]
+    UnsupportedDatabase(String),
+    #[error("Migration error: [{0}]")]
+    MigrationError(String),
     #[error("Request error: [{0}]")]
     RequestError(String),
     #[error("Transaction error: [{0}]")]
@@ -1,4 +1,51 @@
 mod error;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
 mod sqlite;
 
+use async_trait::async_trait;
+use domain::models::{CustomerEventRecord, UserEventRecord};
+
+pub use error::DatabaseErrors;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresRepository;
+#[cfg(feature = "sqlite")]
 pub use sqlite::SqliteRepository;
+
+/// Storage of the user and customer event history.
+#[async_trait]
+pub trait Repository: Send + Sync {
+    async fn add_user_event(&mut self, record: UserEventRecord) -> Result<(), DatabaseErrors>;
+
+    async fn add_customer_event(
+        &mut self,
+        record: CustomerEventRecord,
+    ) -> Result<(), DatabaseErrors>;
+}
+
+/// Opens the storage backend matching the scheme of the database url,
+/// `sqlite:` or `postgres://`. Each backend is behind a cargo feature of the same name.
+pub async fn connect(url: &str) -> Result<Box<dyn Repository>, DatabaseErrors> {
+    let scheme = url.split(':').next().unwrap_or_default();
+    match scheme {
+        #[cfg(feature = "sqlite")]
+        "sqlite" => Ok(Box::new(SqliteRepository::new(url).await?)),
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => Ok(Box::new(PostgresRepository::new(url).await?)),
+        _ => Err(DatabaseErrors::UnsupportedDatabase(scheme.to_string())),
+    }
+}
+
+/// Applies the embedded migrations of the backend matching the scheme of the
+/// database url. Migrations only add to the schema, existing data is kept.
+pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+    let scheme = url.split(':').next().unwrap_or_default();
+    match scheme {
+        #[cfg(feature = "sqlite")]
+        "sqlite" => SqliteRepository::migrate(url).await,
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => PostgresRepository::migrate(url).await,
+        _ => Err(DatabaseErrors::UnsupportedDatabase(scheme.to_string())),
+    }
+}
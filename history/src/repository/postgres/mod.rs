@@ -0,0 +1,80 @@
+use crate::repository::{error::DatabaseErrors, Repository};
+use async_trait::async_trait;
+use domain::models::{CustomerEventRecord, UserEventRecord};
+use sqlx::{migrate::Migrator, PgPool};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// PostgreSQL history, the schema is in `migrations/postgres`.
+pub struct PostgresRepository {
+    pool: PgPool,
+}
+
+impl PostgresRepository {
+    pub async fn new(url: &str) -> Result<Self, DatabaseErrors> {
+        match PgPool::connect(url).await {
+            Ok(pool) => Ok(PostgresRepository { pool }),
+            Err(error) => Err(DatabaseErrors::ConnectionFailure(error.to_string())),
+        }
+    }
+
+    /// Applies pending migrations. The database itself must exist.
+    pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+        let pool = PgPool::connect(url)
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionFailure(error.to_string()))?;
+        MIGRATOR
+            .run(&pool)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))
+    }
+}
+
+#[async_trait]
+impl Repository for PostgresRepository {
+    async fn add_user_event(&mut self, record: UserEventRecord) -> Result<(), DatabaseErrors> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO users ( sent_at, received_at, user_id, data, event )
+            VALUES ( $1, $2, $3, $4, $5 )
+            "#,
+        )
+        .bind(record.sent_at.map(|sent_at| sent_at.as_millis()))
+        .bind(record.received_at.as_millis())
+        .bind(i64::from(record.user_id))
+        .bind(record.data)
+        .bind(record.event)
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn add_customer_event(
+        &mut self,
+        record: CustomerEventRecord,
+    ) -> Result<(), DatabaseErrors> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO customers ( sent_at, received_at, user_id, customer, data, event )
+            VALUES ( $1, $2, $3, $4, $5, $6 )
+            "#,
+        )
+        .bind(record.sent_at.map(|sent_at| sent_at.as_millis()))
+        .bind(record.received_at.as_millis())
+        .bind(i64::from(record.user_id))
+        .bind(record.customer)
+        .bind(record.data)
+        .bind(record.event)
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+            Ok(_) => Ok(()),
+        }
+    }
+}
@@ -1,6 +1,18 @@
-use crate::repository::error::DatabaseErrors;
+use crate::repository::{error::DatabaseErrors, Repository};
+use async_trait::async_trait;
 use domain::models::{CustomerEventRecord, UserEventRecord};
-use sqlx::SqlitePool;
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    sqlite::SqliteConnectOptions,
+    SqlitePool,
+};
+use std::str::FromStr;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Initial migrations rewritten to be non-destructive. Databases created by
+/// the old versions get the stored checksum replaced instead of failing.
+const REWRITTEN_MIGRATIONS: [i64; 1] = [20220914111131];
 
 pub struct SqliteRepository {
     pool: SqlitePool,
@@ -13,18 +25,61 @@ impl SqliteRepository {
             Err(error) => Err(DatabaseErrors::ConnectionFailure(error.to_string())),
         }
     }
-    pub async fn add_user_event(&mut self, record: UserEventRecord) -> Result<(), DatabaseErrors> {
+
+    /// Creates the database file if needed and applies pending migrations.
+    pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+        let options = SqliteConnectOptions::from_str(url)
+            .map_err(|error| DatabaseErrors::ConnectionFailure(error.to_string()))?
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options)
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionFailure(error.to_string()))?;
+
+        let mut connection = pool
+            .acquire()
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionFailure(error.to_string()))?;
+        connection
+            .ensure_migrations_table()
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+        let rewritten = MIGRATOR
+            .iter()
+            .filter(|migration| REWRITTEN_MIGRATIONS.contains(&migration.version));
+        for migration in rewritten {
+            sqlx::query("UPDATE _sqlx_migrations SET checksum = ?1 WHERE version = ?2")
+                .bind(migration.checksum.as_ref())
+                .bind(migration.version)
+                .execute(&mut connection)
+                .await
+                .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+        }
+        drop(connection);
+
+        MIGRATOR
+            .run(&pool)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))
+    }
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn add_user_event(&mut self, record: UserEventRecord) -> Result<(), DatabaseErrors> {
         let mut transaction = match self.pool.begin().await {
             Ok(transaction) => transaction,
             Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
         };
+        let sent_at = record.sent_at.map(|sent_at| sent_at.as_millis());
+        let received_at = record.received_at.as_millis();
 
         let result = sqlx::query!(
             r#"
-            INSERT INTO users ( timestamp, user_id, data, event )
-            VALUES ( ?1, ?2, ?3, ?4 )
+            INSERT INTO users ( sent_at, received_at, user_id, data, event )
+            VALUES ( ?1, ?2, ?3, ?4, ?5 )
             "#,
-            record.timestamp,
+            sent_at,
+            received_at,
             record.user_id,
             record.data,
             record.event
@@ -42,7 +97,7 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn add_customer_event(
+    async fn add_customer_event(
         &mut self,
         record: CustomerEventRecord,
     ) -> Result<(), DatabaseErrors> {
@@ -50,12 +105,15 @@ impl SqliteRepository {
             Ok(transaction) => transaction,
             Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
         };
+        let sent_at = record.sent_at.map(|sent_at| sent_at.as_millis());
+        let received_at = record.received_at.as_millis();
         let result = sqlx::query!(
             r#"
-            INSERT INTO customers ( timestamp, user_id, customer, data, event )
-            VALUES ( ?1, ?2, ?3, ?4, ?5 )
+            INSERT INTO customers ( sent_at, received_at, user_id, customer, data, event )
+            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6 )
             "#,
-            record.timestamp,
+            sent_at,
+            received_at,
             record.user_id,
             record.customer,
             record.data,
@@ -1,13 +1,13 @@
 use domain::records::Record;
 
-use crate::repository::SqliteRepository;
+use crate::repository::Repository;
 
 pub struct HistoryService {
-    repository: SqliteRepository,
+    repository: Box<dyn Repository>,
 }
 
 impl HistoryService {
-    pub fn new(repository: SqliteRepository) -> Self {
+    pub fn new(repository: Box<dyn Repository>) -> Self {
         HistoryService { repository }
     }
     pub async fn add_record(&mut self, record: Record) {
@@ -16,10 +16,7 @@ impl HistoryService {
             Record::CustomerEvent(record) => self.repository.add_customer_event(record).await,
         };
         if let Err(err) = result {
-            log::error!(
-                "Error history service on adding record: [{}]",
-                err.to_string()
-            );
+            log::error!("Error history service on adding record: [{}]", err);
         }
     }
 }
@@ -1,36 +1,31 @@
 -- Add migration script here
 
-DROP TABLE IF EXISTS products;
-DROP TABLE IF EXISTS customers;
-DROP TABLE IF EXISTS customers_products;
-DROP TABLE IF EXISTS subscriptions;
-
-CREATE TABLE "customers" (
+CREATE TABLE IF NOT EXISTS "customers" (
 	"id"	INTEGER NOT NULL,
 	"name"	TEXT NOT NULL,
 	"key"	TEXT NOT NULL,
 	PRIMARY KEY("id" AUTOINCREMENT)
 );
 
-CREATE TABLE "products" (
+CREATE TABLE IF NOT EXISTS "products" (
 	"id"	INTEGER NOT NULL,
 	"name"	TEXT NOT NULL,
 	PRIMARY KEY("id" AUTOINCREMENT)
 );
 
-CREATE TABLE "customers_products" (
+CREATE TABLE IF NOT EXISTS "customers_products" (
 	"customer_id"	INTEGER NOT NULL,
 	"product_id"	INTEGER NOT NULL,
 	FOREIGN KEY("customer_id") REFERENCES "customers"("id") on delete cascade
 );
 
-CREATE TABLE "users_customers" (
+CREATE TABLE IF NOT EXISTS "users_customers" (
 	"user_id"	INTEGER NOT NULL,
 	"customer_id"	INTEGER NOT NULL,
 	FOREIGN KEY("customer_id") REFERENCES "customers"("id") on delete cascade
 );
 
-CREATE TABLE "subscriptions" (
+CREATE TABLE IF NOT EXISTS "subscriptions" (
 	"id"	INTEGER NOT NULL,
 	"user_id"	INTEGER NOT NULL,
 	"customer_id"	INTEGER NOT NULL,
@@ -40,7 +35,7 @@ CREATE TABLE "subscriptions" (
 	PRIMARY KEY("id" AUTOINCREMENT)
 );
 
-CREATE TABLE "notifications" (
+CREATE TABLE IF NOT EXISTS "notifications" (
 	"id"	INTEGER NOT NULL,
 	"customer_id"	INTEGER NOT NULL,
 	"product_id"	INTEGER NOT NULL,
@@ -50,7 +45,7 @@ CREATE TABLE "notifications" (
 	PRIMARY KEY("id" AUTOINCREMENT)
 );
 
-CREATE TABLE "active_subscriptions" (
+CREATE TABLE IF NOT EXISTS "active_subscriptions" (
 	"subscription_id"	INTEGER NOT NULL,
 	FOREIGN KEY("subscription_id") REFERENCES "subscriptions"("id") ON DELETE CASCADE
 );
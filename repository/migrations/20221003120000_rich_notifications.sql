@@ -0,0 +1,6 @@
+-- Add migration script here
+
+ALTER TABLE "notifications" ADD COLUMN "parse_mode" TEXT NOT NULL DEFAULT 'plain';
+ALTER TABLE "notifications" ADD COLUMN "attachment_kind" TEXT;
+ALTER TABLE "notifications" ADD COLUMN "attachment_file_id" TEXT;
+ALTER TABLE "notifications" ADD COLUMN "buttons" TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,7 @@
+-- Add migration script here
+
+ALTER TABLE "notifications" ADD COLUMN "user_id" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "notifications" ADD COLUMN "send_at" INTEGER;
+ALTER TABLE "notifications" ADD COLUMN "status" TEXT NOT NULL DEFAULT 'sent';
+
+CREATE INDEX "notifications_status_send_at" ON "notifications" ("status", "send_at");
@@ -0,0 +1,22 @@
+-- Add migration script here
+
+CREATE TABLE IF NOT EXISTS "recurring_notifications" (
+	"id"	INTEGER NOT NULL,
+	"user_id"	INTEGER NOT NULL,
+	"customer_id"	INTEGER NOT NULL,
+	"product_id"	INTEGER NOT NULL,
+	"schedule"	TEXT NOT NULL,
+	"timezone"	TEXT NOT NULL,
+	"paused"	BOOLEAN NOT NULL DEFAULT FALSE,
+	"next_send_at"	INTEGER,
+	"text"	TEXT NOT NULL,
+	"parse_mode"	TEXT NOT NULL DEFAULT 'plain',
+	"attachment_kind"	TEXT,
+	"attachment_file_id"	TEXT,
+	"buttons"	TEXT NOT NULL DEFAULT '[]',
+	FOREIGN KEY("product_id") REFERENCES "products"("id") ON DELETE CASCADE,
+	FOREIGN KEY("customer_id") REFERENCES "customers"("id") ON DELETE CASCADE,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "recurring_notifications_next_send_at" ON "recurring_notifications" ("paused", "next_send_at");
@@ -0,0 +1,4 @@
+-- Add migration script here
+
+ALTER TABLE "subscriptions" ADD COLUMN "channel" TEXT NOT NULL DEFAULT 'telegram';
+ALTER TABLE "subscriptions" ADD COLUMN "email" TEXT;
@@ -0,0 +1,20 @@
+-- Add migration script here
+
+DELETE FROM "customers_products" WHERE "rowid" NOT IN (
+	SELECT MIN("rowid") FROM "customers_products" GROUP BY "customer_id", "product_id"
+);
+CREATE UNIQUE INDEX IF NOT EXISTS "customers_products_customer_product" ON "customers_products" ("customer_id", "product_id");
+
+DELETE FROM "users_customers" WHERE "rowid" NOT IN (
+	SELECT MIN("rowid") FROM "users_customers" GROUP BY "user_id", "customer_id"
+);
+CREATE UNIQUE INDEX IF NOT EXISTS "users_customers_user_customer" ON "users_customers" ("user_id", "customer_id");
+
+DELETE FROM "active_subscriptions" WHERE "rowid" NOT IN (
+	SELECT MIN("rowid") FROM "active_subscriptions" GROUP BY "subscription_id"
+);
+CREATE UNIQUE INDEX IF NOT EXISTS "active_subscriptions_subscription" ON "active_subscriptions" ("subscription_id");
+
+CREATE UNIQUE INDEX IF NOT EXISTS "customers_name" ON "customers" ("name");
+
+CREATE INDEX IF NOT EXISTS "subscriptions_customer_product" ON "subscriptions" ("customer_id", "product_id");
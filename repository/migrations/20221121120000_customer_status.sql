@@ -0,0 +1,3 @@
+-- Add migration script here
+
+ALTER TABLE "customers" ADD COLUMN "disabled" BOOLEAN NOT NULL DEFAULT FALSE;
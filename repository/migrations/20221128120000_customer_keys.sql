@@ -0,0 +1,24 @@
+-- Add migration script here
+
+CREATE TABLE IF NOT EXISTS "customer_keys" (
+	"id"	INTEGER NOT NULL,
+	"customer_id"	INTEGER NOT NULL,
+	"key_id"	TEXT,
+	"hash"	TEXT NOT NULL,
+	"label"	TEXT NOT NULL DEFAULT '',
+	"one_time"	BOOLEAN NOT NULL DEFAULT FALSE,
+	"expires_at"	INTEGER,
+	"created_at"	INTEGER NOT NULL,
+	PRIMARY KEY("id" AUTOINCREMENT),
+	FOREIGN KEY("customer_id") REFERENCES "customers"("id") on delete cascade
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS "customer_keys_key_id" ON "customer_keys" ("key_id");
+CREATE INDEX IF NOT EXISTS "customer_keys_customer_id" ON "customer_keys" ("customer_id");
+
+CREATE TABLE IF NOT EXISTS "authorization_failures" (
+	"user_id"	INTEGER NOT NULL,
+	"failures"	INTEGER NOT NULL DEFAULT 0,
+	"locked_until"	INTEGER,
+	PRIMARY KEY("user_id")
+);
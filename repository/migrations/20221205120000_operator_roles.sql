@@ -0,0 +1,4 @@
+-- Add migration script here
+
+ALTER TABLE "users_customers" ADD COLUMN "role" TEXT NOT NULL DEFAULT 'owner';
+ALTER TABLE "customer_keys" ADD COLUMN "role" TEXT NOT NULL DEFAULT 'owner';
@@ -0,0 +1,5 @@
+-- Add migration script here
+
+ALTER TABLE "subscriptions" ADD COLUMN "mode" TEXT NOT NULL DEFAULT 'once';
+ALTER TABLE "subscriptions" ADD COLUMN "remaining" INTEGER;
+ALTER TABLE "subscriptions" ADD COLUMN "expires_at" INTEGER;
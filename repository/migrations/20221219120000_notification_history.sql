@@ -0,0 +1,23 @@
+-- Add migration script here
+
+ALTER TABLE "notifications" ADD COLUMN "sent_at" INTEGER;
+
+CREATE INDEX "notifications_customer_status" ON "notifications" ("customer_id", "status");
+
+CREATE TABLE IF NOT EXISTS "received_notifications" (
+	"id"	INTEGER NOT NULL,
+	"user_id"	INTEGER NOT NULL,
+	"customer_id"	INTEGER NOT NULL,
+	"product_id"	INTEGER NOT NULL,
+	"text"	TEXT NOT NULL,
+	"parse_mode"	TEXT NOT NULL DEFAULT 'plain',
+	"attachment_kind"	TEXT,
+	"attachment_file_id"	TEXT,
+	"buttons"	TEXT NOT NULL DEFAULT '[]',
+	"received_at"	INTEGER NOT NULL,
+	FOREIGN KEY("product_id") REFERENCES "products"("id") ON DELETE CASCADE,
+	FOREIGN KEY("customer_id") REFERENCES "customers"("id") ON DELETE CASCADE,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "received_notifications_user_id" ON "received_notifications" ("user_id", "id");
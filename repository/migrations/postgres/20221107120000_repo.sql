@@ -0,0 +1,69 @@
+-- Add migration script here
+
+CREATE TABLE IF NOT EXISTS "customers" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"name"	TEXT NOT NULL,
+	"key"	TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS "products" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"name"	TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS "customers_products" (
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE,
+	"product_id"	BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS "users_customers" (
+	"user_id"	BIGINT NOT NULL,
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS "subscriptions" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"user_id"	BIGINT NOT NULL,
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE,
+	"product_id"	BIGINT NOT NULL REFERENCES "products"("id") ON DELETE CASCADE,
+	"channel"	TEXT NOT NULL DEFAULT 'telegram',
+	"email"	TEXT
+);
+
+CREATE TABLE IF NOT EXISTS "active_subscriptions" (
+	"subscription_id"	BIGINT NOT NULL REFERENCES "subscriptions"("id") ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS "notifications" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE,
+	"product_id"	BIGINT NOT NULL REFERENCES "products"("id") ON DELETE CASCADE,
+	"text"	TEXT NOT NULL,
+	"parse_mode"	TEXT NOT NULL DEFAULT 'plain',
+	"attachment_kind"	TEXT,
+	"attachment_file_id"	TEXT,
+	"buttons"	TEXT NOT NULL DEFAULT '[]',
+	"user_id"	BIGINT NOT NULL DEFAULT 0,
+	"send_at"	BIGINT,
+	"status"	TEXT NOT NULL DEFAULT 'sent'
+);
+
+CREATE INDEX IF NOT EXISTS "notifications_status_send_at" ON "notifications" ("status", "send_at");
+
+CREATE TABLE IF NOT EXISTS "recurring_notifications" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"user_id"	BIGINT NOT NULL,
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE,
+	"product_id"	BIGINT NOT NULL REFERENCES "products"("id") ON DELETE CASCADE,
+	"schedule"	TEXT NOT NULL,
+	"timezone"	TEXT NOT NULL,
+	"paused"	BOOLEAN NOT NULL DEFAULT FALSE,
+	"next_send_at"	BIGINT,
+	"text"	TEXT NOT NULL,
+	"parse_mode"	TEXT NOT NULL DEFAULT 'plain',
+	"attachment_kind"	TEXT,
+	"attachment_file_id"	TEXT,
+	"buttons"	TEXT NOT NULL DEFAULT '[]'
+);
+
+CREATE INDEX IF NOT EXISTS "recurring_notifications_next_send_at" ON "recurring_notifications" ("paused", "next_send_at");
@@ -0,0 +1,17 @@
+-- Add migration script here
+
+DELETE FROM "customers_products" AS a USING "customers_products" AS b
+	WHERE a."ctid" > b."ctid" AND a."customer_id" = b."customer_id" AND a."product_id" = b."product_id";
+CREATE UNIQUE INDEX IF NOT EXISTS "customers_products_customer_product" ON "customers_products" ("customer_id", "product_id");
+
+DELETE FROM "users_customers" AS a USING "users_customers" AS b
+	WHERE a."ctid" > b."ctid" AND a."user_id" = b."user_id" AND a."customer_id" = b."customer_id";
+CREATE UNIQUE INDEX IF NOT EXISTS "users_customers_user_customer" ON "users_customers" ("user_id", "customer_id");
+
+DELETE FROM "active_subscriptions" AS a USING "active_subscriptions" AS b
+	WHERE a."ctid" > b."ctid" AND a."subscription_id" = b."subscription_id";
+CREATE UNIQUE INDEX IF NOT EXISTS "active_subscriptions_subscription" ON "active_subscriptions" ("subscription_id");
+
+CREATE UNIQUE INDEX IF NOT EXISTS "customers_name" ON "customers" ("name");
+
+CREATE INDEX IF NOT EXISTS "subscriptions_customer_product" ON "subscriptions" ("customer_id", "product_id");
@@ -0,0 +1,21 @@
+-- Add migration script here
+
+CREATE TABLE IF NOT EXISTS "customer_keys" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE,
+	"key_id"	TEXT,
+	"hash"	TEXT NOT NULL,
+	"label"	TEXT NOT NULL DEFAULT '',
+	"one_time"	BOOLEAN NOT NULL DEFAULT FALSE,
+	"expires_at"	BIGINT,
+	"created_at"	BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS "customer_keys_key_id" ON "customer_keys" ("key_id");
+CREATE INDEX IF NOT EXISTS "customer_keys_customer_id" ON "customer_keys" ("customer_id");
+
+CREATE TABLE IF NOT EXISTS "authorization_failures" (
+	"user_id"	BIGINT PRIMARY KEY,
+	"failures"	INTEGER NOT NULL DEFAULT 0,
+	"locked_until"	BIGINT
+);
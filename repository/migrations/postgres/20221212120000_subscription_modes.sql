@@ -0,0 +1,5 @@
+-- Add migration script here
+
+ALTER TABLE "subscriptions" ADD COLUMN IF NOT EXISTS "mode" TEXT NOT NULL DEFAULT 'once';
+ALTER TABLE "subscriptions" ADD COLUMN IF NOT EXISTS "remaining" BIGINT;
+ALTER TABLE "subscriptions" ADD COLUMN IF NOT EXISTS "expires_at" BIGINT;
@@ -0,0 +1,20 @@
+-- Add migration script here
+
+ALTER TABLE "notifications" ADD COLUMN IF NOT EXISTS "sent_at" BIGINT;
+
+CREATE INDEX IF NOT EXISTS "notifications_customer_status" ON "notifications" ("customer_id", "status");
+
+CREATE TABLE IF NOT EXISTS "received_notifications" (
+	"id"	BIGSERIAL PRIMARY KEY,
+	"user_id"	BIGINT NOT NULL,
+	"customer_id"	BIGINT NOT NULL REFERENCES "customers"("id") ON DELETE CASCADE,
+	"product_id"	BIGINT NOT NULL REFERENCES "products"("id") ON DELETE CASCADE,
+	"text"	TEXT NOT NULL,
+	"parse_mode"	TEXT NOT NULL DEFAULT 'plain',
+	"attachment_kind"	TEXT,
+	"attachment_file_id"	TEXT,
+	"buttons"	TEXT NOT NULL DEFAULT '[]',
+	"received_at"	BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS "received_notifications_user_id" ON "received_notifications" ("user_id", "id");
@@ -0,0 +1,336 @@
+use std::process::ExitCode;
+
+use amqp::RabbitMqManager;
+use clap::{Parser, Subcommand};
+use domain::{
+    codec::WireMessage,
+    models::{CustomerName, OperatorRole, ProductName, Timestamp},
+    requests::AdminRequestToRepository,
+};
+use dotenv::dotenv;
+use repository::{
+    connect_admin, generate_key, migrate, AdminConfig, AdminRepository, DatabaseErrors,
+};
+
+/// Manages customers, keys and products of the repository database.
+#[derive(Parser)]
+#[command(name = "repository-admin")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(subcommand)]
+    Customer(CustomerCommand),
+    #[command(subcommand)]
+    Key(KeyCommand),
+    #[command(subcommand)]
+    Product(ProductCommand),
+    /// Lists the subscriptions to the products of a customer
+    Subscriptions {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+    /// Lists the notifications of a customer
+    Notifications {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+}
+
+#[derive(Subcommand)]
+enum CustomerCommand {
+    /// Creates a customer and prints its authorization key
+    Create {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+    /// Lists customers with their products
+    List,
+    /// Hides a customer from users and rejects its authorization key
+    Disable {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+    Enable {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+    /// Revokes all authorization keys of a customer and prints a new one
+    RotateKey {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyCommand {
+    /// Adds an authorization key of a customer and prints it
+    Add {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+        #[arg(long, default_value = "")]
+        label: String,
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+        /// The key stops working after the first successful authorization
+        #[arg(long)]
+        one_time: bool,
+        /// Role of the operators authorized with the key: owner, editor or viewer
+        #[arg(long, default_value = "owner", value_parser = operator_role)]
+        role: OperatorRole,
+    },
+    /// Lists the authorization keys of a customer, the keys themselves are not stored
+    List {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+    },
+    Revoke {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProductCommand {
+    Add {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+        #[arg(value_parser = product_name)]
+        product: ProductName,
+    },
+    /// Detaches a product, deactivating its subscriptions and pending notifications
+    Remove {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+        #[arg(value_parser = product_name)]
+        product: ProductName,
+    },
+    Rename {
+        #[arg(value_parser = customer_name)]
+        customer: CustomerName,
+        #[arg(value_parser = product_name)]
+        product: ProductName,
+        #[arg(value_parser = product_name)]
+        new_name: ProductName,
+    },
+}
+
+fn customer_name(name: &str) -> Result<CustomerName, String> {
+    CustomerName::parse(name).map_err(|error| error.to_string())
+}
+
+fn product_name(name: &str) -> Result<ProductName, String> {
+    ProductName::parse(name).map_err(|error| error.to_string())
+}
+
+fn operator_role(role: &str) -> Result<OperatorRole, String> {
+    match role {
+        "owner" | "editor" | "viewer" => Ok(OperatorRole::from(role)),
+        _ => Err(String::from("expected owner, editor or viewer")),
+    }
+}
+
+fn format_timestamp(timestamp: Option<Timestamp>) -> String {
+    timestamp
+        .map(|timestamp| timestamp.as_datetime().to_rfc3339())
+        .unwrap_or_default()
+}
+
+fn changed(changed: bool, unknown: DatabaseErrors) -> Result<bool, DatabaseErrors> {
+    match changed {
+        true => Ok(true),
+        false => Err(unknown),
+    }
+}
+
+/// Runs the command and returns whether the database was changed.
+async fn run(repository: &dyn AdminRepository, command: Command) -> Result<bool, DatabaseErrors> {
+    match command {
+        Command::Customer(CustomerCommand::Create { customer }) => {
+            let key = generate_key();
+            repository.create_customer(&customer, &key).await?;
+            println!("{}", key);
+            Ok(true)
+        }
+        Command::Customer(CustomerCommand::List) => {
+            for customer in repository.list_customers().await? {
+                let status = if customer.disabled {
+                    "disabled"
+                } else {
+                    "active"
+                };
+                let products: Vec<&str> = customer
+                    .products
+                    .iter()
+                    .map(|product| product.as_str())
+                    .collect();
+                println!("{}\t{}\t{}", customer.name, status, products.join(", "));
+            }
+            Ok(false)
+        }
+        Command::Customer(CustomerCommand::Disable { customer }) => changed(
+            repository.set_customer_disabled(&customer, true).await?,
+            DatabaseErrors::UnknownCustomer(customer),
+        ),
+        Command::Customer(CustomerCommand::Enable { customer }) => changed(
+            repository.set_customer_disabled(&customer, false).await?,
+            DatabaseErrors::UnknownCustomer(customer),
+        ),
+        Command::Customer(CustomerCommand::RotateKey { customer }) => {
+            let key = generate_key();
+            changed(
+                repository.replace_customer_keys(&customer, &key).await?,
+                DatabaseErrors::UnknownCustomer(customer),
+            )?;
+            println!("{}", key);
+            Ok(true)
+        }
+        Command::Key(KeyCommand::Add {
+            customer,
+            label,
+            expires_in_days,
+            one_time,
+            role,
+        }) => {
+            let key = generate_key();
+            let expires_at = expires_in_days.map(|days| {
+                Timestamp::from(chrono::Utc::now() + chrono::Duration::days(days.into()))
+            });
+            repository
+                .add_customer_key(&customer, &key, &label, expires_at, one_time, role)
+                .await?;
+            println!("{}", key);
+            Ok(false)
+        }
+        Command::Key(KeyCommand::List { customer }) => {
+            for key in repository.list_customer_keys(&customer).await? {
+                let kind = if key.one_time { "one-time" } else { "reusable" };
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    key.id,
+                    key.label,
+                    kind,
+                    key.role.as_str(),
+                    format_timestamp(Some(key.created_at)),
+                    format_timestamp(key.expires_at)
+                );
+            }
+            Ok(false)
+        }
+        Command::Key(KeyCommand::Revoke { customer, id }) => {
+            changed(
+                repository.revoke_customer_key(&customer, id).await?,
+                DatabaseErrors::UnknownKey(customer, id),
+            )?;
+            Ok(false)
+        }
+        Command::Product(ProductCommand::Add { customer, product }) => {
+            repository.add_product(&customer, &product).await?;
+            Ok(true)
+        }
+        Command::Product(ProductCommand::Remove { customer, product }) => changed(
+            repository.remove_product(&customer, &product).await?,
+            DatabaseErrors::UnknownProduct(customer, product),
+        ),
+        Command::Product(ProductCommand::Rename {
+            customer,
+            product,
+            new_name,
+        }) => changed(
+            repository
+                .rename_product(&customer, &product, &new_name)
+                .await?,
+            DatabaseErrors::UnknownProduct(customer, product),
+        ),
+        Command::Subscriptions { customer } => {
+            for subscription in repository.list_subscriptions(&customer).await? {
+                let status = if subscription.active {
+                    "active"
+                } else {
+                    "inactive"
+                };
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    subscription.id,
+                    subscription.user_id,
+                    subscription.product,
+                    subscription.channel.as_str(),
+                    subscription.email.unwrap_or_default(),
+                    status
+                );
+            }
+            Ok(false)
+        }
+        Command::Notifications { customer } => {
+            for notification in repository.list_notifications(&customer).await? {
+                let send_at = format_timestamp(notification.send_at);
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    notification.id,
+                    notification.product,
+                    notification.status,
+                    send_at,
+                    notification.text.replace('\n', " ")
+                );
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Asks the running repository service to reload its cache, if configured.
+async fn request_cache_reload(config: &AdminConfig) {
+    let (amqp_address, exchange, queue) = match (
+        &config.amqp_address,
+        &config.exchange,
+        &config.admin_request_queue,
+    ) {
+        (Some(amqp_address), Some(exchange), Some(queue)) => (amqp_address, exchange, queue),
+        _ => {
+            log::warn!("Admin request queue is not configured, reload the service cache manually");
+            return;
+        }
+    };
+    let result = async {
+        let manager = RabbitMqManager::builder().build(amqp_address).await?;
+        let publisher = manager.get_publisher().await?;
+        let request = AdminRequestToRepository::ReloadCache;
+        publisher
+            .publish_message(exchange, queue, request.encode(config.wire_format))
+            .await
+    }
+    .await;
+    if let Err(error) = result {
+        log::error!("Failed to request cache reload: {}", error);
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenv().ok();
+    pretty_env_logger::init();
+
+    let cli = Cli::parse();
+    let config = envy::from_env::<AdminConfig>().unwrap();
+
+    migrate(&config.repository_database_url).await.unwrap();
+    let repository = connect_admin(&config.repository_database_url)
+        .await
+        .unwrap();
+
+    match run(repository.as_ref(), cli.command).await {
+        Ok(true) => {
+            request_cache_reload(&config).await;
+            ExitCode::SUCCESS
+        }
+        Ok(false) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use dotenv::dotenv;
 use amqp::RabbitMqManager;
-use repository::{Config, MessageHandler, RepositoryService, SqliteRepository};
+use repository::{connect, migrate, Config, MessageHandler, RepositoryService};
 use tokio::sync::Mutex;
 
+const DEFAULT_SCHEDULER_INTERVAL_SECS: u64 = 30;
+const MIGRATE_ONLY_FLAG: &str = "--migrate-only";
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -12,15 +15,19 @@ async fn main() {
 
     let config = envy::from_env::<Config>().unwrap();
 
+    migrate(&config.repository_database_url).await.unwrap();
+    if std::env::args().any(|arg| arg == MIGRATE_ONLY_FLAG) {
+        log::info!("Migrations applied");
+        return;
+    }
+
     let mut manager = RabbitMqManager::builder()
         .build(&config.amqp_address)
         .await
         .unwrap();
     let publisher = manager.get_publisher().await.unwrap();
 
-    let repository = SqliteRepository::new(&config.repository_database_url)
-        .await
-        .unwrap();
+    let repository = connect(&config.repository_database_url).await.unwrap();
     let service = Arc::new(Mutex::new(RepositoryService::new(
         config.clone(),
         repository,
@@ -30,24 +37,47 @@ async fn main() {
     manager
         .add_consumer(
             &config.client_repository_request_queue,
-            MessageHandler::client_request(service.clone()),
+            MessageHandler::client_request(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
     manager
         .add_consumer(
             &config.customer_repository_request_queue,
-            MessageHandler::customer_request(service.clone()),
+            MessageHandler::customer_request(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
     manager
         .add_consumer(
             &config.repository_request_queue,
-            MessageHandler::request_to_repository(service.clone()),
+            MessageHandler::request_to_repository(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
+    if let Some(admin_request_queue) = &config.admin_request_queue {
+        manager
+            .add_consumer(
+                admin_request_queue,
+                MessageHandler::admin_request(service.clone(), config.wire_format),
+            )
+            .await
+            .unwrap();
+    }
+
+    let scheduler_interval = Duration::from_secs(
+        config
+            .scheduler_interval_secs
+            .unwrap_or(DEFAULT_SCHEDULER_INTERVAL_SECS),
+    );
+    let scheduler = service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(scheduler_interval);
+        loop {
+            interval.tick().await;
+            scheduler.lock().await.send_due_notifications().await;
+        }
+    });
 
     manager.run();
 }
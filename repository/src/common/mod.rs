@@ -1,7 +1,10 @@
+use domain::codec::WireFormat;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Config {
+    /// `sqlite:` or `postgres://` url, the scheme selects the storage backend.
+    /// Pending migrations are applied at startup.
     pub repository_database_url: String,
     pub amqp_address: String,
     pub exchange: String,
@@ -11,4 +14,40 @@ pub struct Config {
     pub client_repository_response_queue: String,
     pub customer_repository_response_queue: String,
     pub repository_response_queue: String,
+    /// Queue of maintenance requests such as cache reloads, not consumed if not set.
+    #[serde(default)]
+    pub admin_request_queue: Option<String>,
+    #[serde(default)]
+    pub wire_format: WireFormat,
+    /// How often scheduled notifications are checked, in seconds.
+    #[serde(default)]
+    pub scheduler_interval_secs: Option<u64>,
+    /// Failed authorizations in a row after which a user is locked out.
+    #[serde(default)]
+    pub authorization_max_failures: Option<u32>,
+    /// How long a locked out user has to wait, in seconds.
+    #[serde(default)]
+    pub authorization_lockout_secs: Option<u64>,
+    /// How long an operator invite key stays valid, in seconds.
+    #[serde(default)]
+    pub operator_invite_ttl_secs: Option<u64>,
+    /// Notifications per page of the client and customer history.
+    #[serde(default)]
+    pub history_page_size: Option<u32>,
+}
+
+/// Configuration of `repository-admin`. With `amqp_address`, `exchange` and
+/// `admin_request_queue` set, changes are followed by a cache reload request
+/// to the running service.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AdminConfig {
+    pub repository_database_url: String,
+    #[serde(default)]
+    pub amqp_address: Option<String>,
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default)]
+    pub admin_request_queue: Option<String>,
+    #[serde(default)]
+    pub wire_format: WireFormat,
 }
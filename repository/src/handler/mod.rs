@@ -1,6 +1,10 @@
 use crate::RepositoryService;
-use domain::requests::{
-    ClientRequestToRepository, CustomerRequestToRepository, RequestToRepository,
+use domain::{
+    codec::{Correlated, WireFormat, WireMessage},
+    requests::{
+        AdminRequestToRepository, ClientRequestToRepository, CustomerRequestToRepository,
+        RequestToRepository,
+    },
 };
 use amqp::IncomingMessageHandler;
 use std::sync::Arc;
@@ -10,46 +14,94 @@ pub struct MessageHandler {}
 impl MessageHandler {
     pub fn client_request(
         service: Arc<Mutex<RepositoryService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let request: ClientRequestToRepository = serde_json::from_str(&message).unwrap();
-                service
+                let request = match Correlated::<ClientRequestToRepository>::decode(&message, wire_format) {
+                    Ok(request) => request,
+                    Err(error) => {
+                        log::error!("Message handler client request decode error: {}", error);
+                        return;
+                    }
+                };
+                if let Err(error) = service
                     .lock()
                     .await
                     .handle_client_request_to_repository(request)
-                    .await;
+                    .await
+                {
+                    log::error!("Message handler client request error: {}", error);
+                }
             }
         }
     }
     pub fn customer_request(
         service: Arc<Mutex<RepositoryService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let request: CustomerRequestToRepository = serde_json::from_str(&message).unwrap();
-                service
+                let request = match Correlated::<CustomerRequestToRepository>::decode(&message, wire_format) {
+                    Ok(request) => request,
+                    Err(error) => {
+                        log::error!("Message handler customer request decode error: {}", error);
+                        return;
+                    }
+                };
+                if let Err(error) = service
                     .lock()
                     .await
                     .handle_customer_request_to_repository(request)
-                    .await;
+                    .await
+                {
+                    log::error!("Message handler customer request error: {}", error);
+                }
             }
         }
     }
     pub fn request_to_repository(
         service: Arc<Mutex<RepositoryService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let request: RequestToRepository = serde_json::from_str(&message).unwrap();
-                service
+                let request = match RequestToRepository::decode(&message, wire_format) {
+                    Ok(request) => request,
+                    Err(error) => {
+                        log::error!("Message handler request to repository decode error: {}", error);
+                        return;
+                    }
+                };
+                if let Err(error) = service
                     .lock()
                     .await
                     .handle_request_to_repository(request)
-                    .await;
+                    .await
+                {
+                    log::error!("Message handler request to repository error: {}", error);
+                }
+            }
+        }
+    }
+    pub fn admin_request(
+        service: Arc<Mutex<RepositoryService>>,
+        wire_format: WireFormat,
+    ) -> impl IncomingMessageHandler + 'static {
+        move |message: Vec<u8>| {
+            let service = service.clone();
+            async move {
+                let request = match AdminRequestToRepository::decode(&message, wire_format) {
+                    Ok(request) => request,
+                    Err(error) => {
+                        log::error!("Message handler admin request decode error: {}", error);
+                        return;
+                    }
+                };
+                service.lock().await.handle_admin_request(request).await;
             }
         }
     }
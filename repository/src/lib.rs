@@ -1,9 +1,20 @@
+#[cfg(not(any(feature = "sqlite", feature = "postgres")))]
+compile_error!("enable the `sqlite` or `postgres` feature of the repository crate");
+
 mod common;
 mod handler;
 mod repository;
+mod schedule;
 mod service;
 
-pub use common::Config;
+pub use common::{AdminConfig, Config};
 pub use handler::MessageHandler;
+#[cfg(feature = "postgres")]
+pub use repository::PostgresRepository;
+#[cfg(feature = "sqlite")]
 pub use repository::SqliteRepository;
+pub use repository::{
+    connect, connect_admin, generate_key, migrate, AdminRepository, CustomerSummary,
+    DatabaseErrors, KeySummary, NotificationSummary, Repository, SubscriptionSummary,
+};
 pub use service::RepositoryService;
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use domain::models::{CustomerName, DeliveryChannel, OperatorRole, ProductName, Timestamp};
+
+use super::DatabaseErrors;
+
+pub struct CustomerSummary {
+    pub name: CustomerName,
+    pub disabled: bool,
+    pub products: Vec<ProductName>,
+}
+
+pub struct SubscriptionSummary {
+    pub id: i64,
+    pub user_id: u32,
+    pub product: ProductName,
+    pub channel: DeliveryChannel,
+    pub email: Option<String>,
+    pub active: bool,
+}
+
+/// Authorization key of a customer, the key itself is only stored hashed.
+pub struct KeySummary {
+    pub id: i64,
+    pub label: String,
+    pub one_time: bool,
+    pub role: OperatorRole,
+    pub expires_at: Option<Timestamp>,
+    pub created_at: Timestamp,
+}
+
+pub struct NotificationSummary {
+    pub id: i64,
+    pub product: ProductName,
+    pub status: String,
+    pub send_at: Option<Timestamp>,
+    pub text: String,
+}
+
+/// Management of customers, keys and products for `repository-admin`.
+/// Methods returning `bool` report `false` when the customer or product
+/// does not exist.
+#[async_trait]
+pub trait AdminRepository: Send + Sync {
+    /// Creates the customer with `key` as its only authorization key.
+    async fn create_customer(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+    ) -> Result<(), DatabaseErrors>;
+
+    /// All customers, disabled ones included.
+    async fn list_customers(&self) -> Result<Vec<CustomerSummary>, DatabaseErrors>;
+
+    /// Disabled customers are hidden from users and cannot be authorized.
+    async fn set_customer_disabled(
+        &self,
+        customer: &CustomerName,
+        disabled: bool,
+    ) -> Result<bool, DatabaseErrors>;
+
+    /// Revokes all authorization keys of the customer and adds `key`.
+    async fn replace_customer_keys(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+    ) -> Result<bool, DatabaseErrors>;
+
+    /// Adds an authorization key next to the existing ones and returns its id.
+    /// Keys stop working after `expires_at`, one-time keys after their first use.
+    /// Users authorized with the key become operators with `role`.
+    async fn add_customer_key(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+        label: &str,
+        expires_at: Option<Timestamp>,
+        one_time: bool,
+        role: OperatorRole,
+    ) -> Result<i64, DatabaseErrors>;
+
+    async fn list_customer_keys(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<KeySummary>, DatabaseErrors>;
+
+    async fn revoke_customer_key(
+        &self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors>;
+
+    async fn add_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+    ) -> Result<(), DatabaseErrors>;
+
+    /// Detaches the product from the customer, deactivates its subscriptions
+    /// and stops its pending and recurring notifications. Past notifications are kept.
+    async fn remove_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+    ) -> Result<bool, DatabaseErrors>;
+
+    async fn rename_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+        new_name: &ProductName,
+    ) -> Result<bool, DatabaseErrors>;
+
+    async fn list_subscriptions(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<SubscriptionSummary>, DatabaseErrors>;
+
+    async fn list_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<NotificationSummary>, DatabaseErrors>;
+}
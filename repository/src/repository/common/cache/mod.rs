@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+use domain::models::{Customer, CustomerName, Operator, OperatorRole, Product, ProductName};
+
+use super::{parse_customer, parse_product};
+
+/// Lookups served by the cache and lookups which went to the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    customers: HashMap<CustomerName, u32>,
+    products: HashMap<CustomerName, HashMap<ProductName, u32>>,
+    operators: HashMap<CustomerName, HashMap<u32, OperatorRole>>,
+}
+
+/// Customer and product ids of the repository. The backends reload it on
+/// request and fill in entries found in the database after a miss.
+#[derive(Default)]
+pub struct CacheData {
+    entries: RwLock<CacheEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+impl CacheData {
+    /// Builds the cache from the rows of a storage backend: `(id, name)` of
+    /// the customers, `(customer, id, name)` of their products and
+    /// `(customer, user_id, role)` of their operators. Invalid names are skipped.
+    pub fn from_rows(
+        customers: Vec<(i64, String)>,
+        products: Vec<(String, i64, String)>,
+        operators: Vec<(String, i64, String)>,
+    ) -> Self {
+        let customers = customers
+            .iter()
+            .filter_map(|(id, name)| parse_customer(name).map(|name| (name, *id as u32)))
+            .collect();
+
+        let mut products_by_customer = HashMap::<CustomerName, HashMap<ProductName, u32>>::new();
+        for (customer, id, name) in products {
+            let (customer, product) = match (parse_customer(&customer), parse_product(&name)) {
+                (Some(customer), Some(product)) => (customer, product),
+                _ => continue,
+            };
+            products_by_customer
+                .entry(customer)
+                .or_default()
+                .insert(product, id as u32);
+        }
+
+        let mut operators_by_customer = HashMap::<CustomerName, HashMap<u32, OperatorRole>>::new();
+        for (customer, user_id, role) in operators {
+            let customer = match parse_customer(&customer) {
+                Some(customer) => customer,
+                None => continue,
+            };
+            operators_by_customer
+                .entry(customer)
+                .or_default()
+                .insert(user_id as u32, OperatorRole::from(role.as_str()));
+        }
+
+        let entries = CacheEntries {
+            customers,
+            products: products_by_customer,
+            operators: operators_by_customer,
+        };
+        CacheData {
+            entries: RwLock::new(entries),
+            ..Default::default()
+        }
+    }
+    /// Takes the entries of a freshly loaded cache, the hit and miss
+    /// counters are kept.
+    pub fn replace(&self, cache: CacheData) {
+        *self.entries.write().unwrap() = cache.entries.into_inner().unwrap();
+    }
+    pub fn get_customer_id(&self, customer: &CustomerName) -> Option<u32> {
+        let id = self
+            .entries
+            .read()
+            .unwrap()
+            .customers
+            .get(customer)
+            .cloned();
+        self.count(id.is_some());
+        id
+    }
+    pub fn get_product_id(&self, customer: &CustomerName, product: &ProductName) -> Option<u32> {
+        let id = self
+            .entries
+            .read()
+            .unwrap()
+            .products
+            .get(customer)
+            .and_then(|products| products.get(product))
+            .cloned();
+        self.count(id.is_some());
+        id
+    }
+    pub fn get_operators(&self, customer: &CustomerName) -> Option<Vec<Operator>> {
+        let operators = self
+            .entries
+            .read()
+            .unwrap()
+            .operators
+            .get(customer)
+            .map(|operators| {
+                let mut operators: Vec<Operator> = operators
+                    .iter()
+                    .map(|(user_id, role)| Operator {
+                        user_id: *user_id,
+                        role: *role,
+                    })
+                    .collect();
+                operators.sort_by_key(|operator| operator.user_id);
+                operators
+            });
+        self.count(operators.is_some());
+        operators
+    }
+    pub fn get_operator_role(&self, customer: &CustomerName, user_id: u32) -> Option<OperatorRole> {
+        let role = self
+            .entries
+            .read()
+            .unwrap()
+            .operators
+            .get(customer)
+            .and_then(|operators| operators.get(&user_id))
+            .cloned();
+        self.count(role.is_some());
+        role
+    }
+    /// Adds a customer found in the database with its products.
+    pub fn insert_customer(&self, customer: &CustomerName, id: u32, products: Vec<(i64, String)>) {
+        let products = products
+            .iter()
+            .filter_map(|(id, name)| parse_product(name).map(|name| (name, *id as u32)))
+            .collect();
+        let mut entries = self.entries.write().unwrap();
+        entries.customers.insert(customer.clone(), id);
+        entries.products.insert(customer.clone(), products);
+    }
+    pub fn insert_product(&self, customer: &CustomerName, product: &ProductName, id: u32) {
+        self.entries
+            .write()
+            .unwrap()
+            .products
+            .entry(customer.clone())
+            .or_default()
+            .insert(product.clone(), id);
+    }
+    /// Replaces the operators of a customer with the ones found in the database.
+    pub fn insert_operators(&self, customer: &CustomerName, operators: &[Operator]) {
+        let operators = operators
+            .iter()
+            .map(|operator| (operator.user_id, operator.role))
+            .collect();
+        self.entries
+            .write()
+            .unwrap()
+            .operators
+            .insert(customer.clone(), operators);
+    }
+    pub fn insert_operator(&self, customer: &CustomerName, user_id: u32, role: OperatorRole) {
+        self.entries
+            .write()
+            .unwrap()
+            .operators
+            .entry(customer.clone())
+            .or_default()
+            .insert(user_id, role);
+    }
+    pub fn remove_operator(&self, customer: &CustomerName, user_id: u32) {
+        if let Some(operators) = self.entries.write().unwrap().operators.get_mut(customer) {
+            operators.remove(&user_id);
+        }
+    }
+    pub fn get_customers(&self) -> Vec<Customer> {
+        self.entries
+            .read()
+            .unwrap()
+            .customers
+            .keys()
+            .map(|name| Customer { name: name.clone() })
+            .collect()
+    }
+    /// Products of a cached customer. Call after `get_customer_id` succeeded
+    /// or the customer was inserted.
+    pub fn get_customer_products(&self, customer: &CustomerName) -> Vec<Product> {
+        self.entries
+            .read()
+            .unwrap()
+            .products
+            .get(customer)
+            .map(|products| {
+                products
+                    .keys()
+                    .map(|name| Product { name: name.clone() })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -1,11 +1,28 @@
+use domain::models::{CustomerName, ProductName};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum DatabaseErrors {
     #[error("Connection error: [{0}]")]
     ConnectionProblem(String),
+    #[error("Unsupported database: [{0}]")]
+    UnsupportedDatabase(String),
+    #[error("Migration error: [{0}]")]
+    MigrationError(String),
     #[error("Transaction error: [{0}]")]
     TransactionError(String),
     #[error("Request error: [{0}]")]
     RequestError(String),
+    #[error("Key error: [{0}]")]
+    KeyError(String),
+    #[error("Unknown customer: [{0}]")]
+    UnknownCustomer(CustomerName),
+    #[error("Unknown product [{1}] of customer [{0}]")]
+    UnknownProduct(CustomerName, ProductName),
+    #[error("Unknown key [{1}] of customer [{0}]")]
+    UnknownKey(CustomerName, i64),
+    #[error("Customer already exists: [{0}]")]
+    CustomerExists(CustomerName),
+    #[error("Product [{1}] of customer [{0}] already exists")]
+    ProductExists(CustomerName, ProductName),
 }
@@ -0,0 +1,156 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+use super::errors::DatabaseErrors;
+
+const KEY_ID_LENGTH: usize = 4;
+const SECRET_LENGTH: usize = 16;
+
+/// Generates an authorization key `<key id>.<secret>`. The key id is stored
+/// in plain text next to the hash and selects the hash to check.
+pub fn generate_key() -> String {
+    format!(
+        "{}.{}",
+        hex::encode(rand::random::<[u8; KEY_ID_LENGTH]>()),
+        hex::encode(rand::random::<[u8; SECRET_LENGTH]>())
+    )
+}
+
+/// Key id of a generated key, so a key is only checked against the hashes
+/// stored under its key id.
+pub fn key_id(key: &str) -> Option<&str> {
+    let (key_id, _) = key.split_once('.')?;
+    let valid = key_id.len() == KEY_ID_LENGTH * 2 && key_id.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then_some(key_id)
+}
+
+/// Key id of a key issued before key ids existed. It is derived from the key,
+/// since the key itself cannot change, and set on the first authorization.
+pub fn legacy_key_id(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    hex::encode(&digest[..KEY_ID_LENGTH])
+}
+
+pub fn hash_key(key: &str) -> Result<String, DatabaseErrors> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(key.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|error| DatabaseErrors::KeyError(error.to_string()))
+}
+
+pub fn verify_key(key: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(key.as_bytes(), &hash)
+            .is_ok(),
+        Err(error) => {
+            log::error!("Invalid key hash in database: {}", error);
+            false
+        }
+    }
+}
+
+/// Key hash of a customer which may match a key being authorized.
+pub struct StoredKey {
+    pub id: i64,
+    pub hash: String,
+    pub one_time: bool,
+    pub role: String,
+    pub customer: String,
+}
+
+/// Finds the stored key matching `key` among the keys with its key id. Hashes
+/// are checked on the blocking pool since each check takes a noticeable amount
+/// of CPU time.
+pub async fn find_key(key: String, stored_keys: Vec<StoredKey>) -> Option<StoredKey> {
+    let result = tokio::task::spawn_blocking(move || {
+        stored_keys
+            .into_iter()
+            .find(|stored_key| verify_key(&key, &stored_key.hash))
+    })
+    .await;
+    match result {
+        Ok(stored_key) => stored_key,
+        Err(error) => {
+            log::error!("Key check failed: {}", error);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_key(id: i64, key: &str, customer: &str) -> StoredKey {
+        StoredKey {
+            id,
+            hash: hash_key(key).unwrap(),
+            one_time: false,
+            role: "owner".to_string(),
+            customer: customer.to_string(),
+        }
+    }
+
+    #[test]
+    fn generated_keys_have_a_key_id() {
+        let key = generate_key();
+        let key_id = key_id(&key).unwrap();
+        assert_eq!(key_id.len(), KEY_ID_LENGTH * 2);
+        assert!(key.starts_with(key_id));
+        assert_ne!(key, generate_key());
+    }
+
+    #[test]
+    fn keys_without_a_valid_key_id_have_none() {
+        assert_eq!(key_id("plaintext-legacy-key"), None);
+        assert_eq!(key_id(".secret"), None);
+        assert_eq!(key_id("abc.secret"), None);
+        assert_eq!(key_id("abcdef012.secret"), None);
+        assert_eq!(key_id("ghijklmn.secret"), None);
+        assert_eq!(key_id("abcdef01.secret"), Some("abcdef01"));
+    }
+
+    #[test]
+    fn legacy_key_ids_are_stable() {
+        let id = legacy_key_id("plaintext-legacy-key");
+        assert_eq!(id.len(), KEY_ID_LENGTH * 2);
+        assert_eq!(id, legacy_key_id("plaintext-legacy-key"));
+        assert_ne!(id, legacy_key_id("another-legacy-key"));
+    }
+
+    #[test]
+    fn hashes_are_salted_and_verify_the_key_only() {
+        let key = generate_key();
+        let hash = hash_key(&key).unwrap();
+        assert_ne!(hash, hash_key(&key).unwrap());
+        assert!(verify_key(&key, &hash));
+        assert!(!verify_key(&generate_key(), &hash));
+        assert!(!verify_key(&key, "not a hash"));
+    }
+
+    #[tokio::test]
+    async fn find_key_returns_the_matching_key() {
+        let (first, second) = (generate_key(), generate_key());
+        let stored_keys = vec![
+            stored_key(1, &first, "Shop"),
+            stored_key(2, &second, "Cafe"),
+        ];
+
+        let found = find_key(second, stored_keys).await.unwrap();
+        assert_eq!(found.id, 2);
+        assert_eq!(found.customer, "Cafe");
+    }
+
+    #[tokio::test]
+    async fn find_key_rejects_unknown_keys() {
+        let stored_keys = vec![stored_key(1, &generate_key(), "Shop")];
+        assert!(find_key(generate_key(), stored_keys).await.is_none());
+        assert!(find_key(generate_key(), vec![]).await.is_none());
+    }
+}
@@ -1 +1,156 @@
+pub mod cache;
 pub mod errors;
+pub mod keys;
+
+use domain::models::{
+    Attachment, CustomerName, LinkButton, NotificationContent, ParseMode, ProductName,
+    SubscriptionMode, Timestamp,
+};
+
+pub const STATUS_SENT: &str = "sent";
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+pub fn notification_content(
+    text: String,
+    parse_mode: &str,
+    attachment_kind: Option<String>,
+    attachment_file_id: Option<String>,
+    buttons: &str,
+) -> NotificationContent {
+    let attachment = match (attachment_kind, attachment_file_id) {
+        (Some(kind), Some(file_id)) => Attachment::from_parts(&kind, file_id),
+        _ => None,
+    };
+    let buttons: Vec<LinkButton> = serde_json::from_str(buttons).unwrap_or_else(|error| {
+        log::error!(
+            "Invalid notification buttons [{}] in database: {}",
+            buttons,
+            error
+        );
+        vec![]
+    });
+    NotificationContent {
+        text,
+        parse_mode: ParseMode::from(parse_mode),
+        attachment,
+        buttons,
+    }
+}
+
+pub fn subscription_mode(
+    mode: &str,
+    remaining: Option<i64>,
+    expires_at: Option<i64>,
+) -> SubscriptionMode {
+    SubscriptionMode::from_parts(
+        mode,
+        remaining.map(|remaining| remaining as u32),
+        expires_at.and_then(Timestamp::from_millis),
+    )
+}
+
+/// What a new notification does to an active subscription.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// The subscription is notified and stays active.
+    Keep,
+    /// The subscription is notified and has one notification less left.
+    Countdown,
+    /// The subscription is notified for the last time.
+    Last,
+    /// The subscription ran out of time and is not notified.
+    Expired,
+}
+
+pub fn delivery(mode: SubscriptionMode, now: Timestamp) -> Delivery {
+    match mode {
+        SubscriptionMode::Once => Delivery::Last,
+        SubscriptionMode::Always => Delivery::Keep,
+        SubscriptionMode::Times { count } if count > 1 => Delivery::Countdown,
+        SubscriptionMode::Times { .. } => Delivery::Last,
+        SubscriptionMode::Until { until } if until > now => Delivery::Keep,
+        SubscriptionMode::Until { .. } => Delivery::Expired,
+    }
+}
+
+pub fn parse_customer(name: &str) -> Option<CustomerName> {
+    match CustomerName::parse(name) {
+        Ok(name) => Some(name),
+        Err(error) => {
+            log::error!("Invalid customer name [{}] in database: {}", name, error);
+            None
+        }
+    }
+}
+
+pub fn parse_product(name: &str) -> Option<ProductName> {
+    match ProductName::parse(name) {
+        Ok(name) => Some(name),
+        Err(error) => {
+            log::error!("Invalid product name [{}] in database: {}", name, error);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn at(millis: i64) -> Timestamp {
+        Timestamp::from_millis(millis).unwrap()
+    }
+
+    #[test]
+    fn once_is_notified_for_the_last_time() {
+        assert_eq!(delivery(SubscriptionMode::Once, at(NOW)), Delivery::Last);
+    }
+
+    #[test]
+    fn always_stays_active() {
+        assert_eq!(delivery(SubscriptionMode::Always, at(NOW)), Delivery::Keep);
+    }
+
+    #[test]
+    fn times_counts_down_to_the_last_notification() {
+        let steps = [
+            (3, Delivery::Countdown),
+            (2, Delivery::Countdown),
+            (1, Delivery::Last),
+        ];
+        for (remaining, expected) in steps {
+            let mode = subscription_mode("times", Some(remaining), None);
+            assert_eq!(delivery(mode, at(NOW)), expected);
+        }
+    }
+
+    #[test]
+    fn times_without_notifications_left_is_notified_once_more() {
+        let mode = SubscriptionMode::Times { count: 0 };
+        assert_eq!(delivery(mode, at(NOW)), Delivery::Last);
+    }
+
+    #[test]
+    fn until_expires_at_the_date() {
+        let mode = subscription_mode("until", None, Some(NOW + 1));
+        assert_eq!(delivery(mode, at(NOW)), Delivery::Keep);
+        assert_eq!(delivery(mode, at(NOW + 1)), Delivery::Expired);
+        assert_eq!(delivery(mode, at(NOW + 2)), Delivery::Expired);
+    }
+
+    #[test]
+    fn incomplete_stored_modes_fall_back_to_once() {
+        let modes = [
+            subscription_mode("times", None, None),
+            subscription_mode("until", None, None),
+            subscription_mode("unknown", Some(3), Some(NOW)),
+        ];
+        for mode in modes {
+            assert_eq!(mode, SubscriptionMode::Once);
+            assert_eq!(delivery(mode, at(NOW)), Delivery::Last);
+        }
+    }
+}
@@ -1,4 +1,277 @@
+mod admin;
 mod common;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
 mod sqlite;
+#[cfg(test)]
+mod tests;
 
+use async_trait::async_trait;
+use domain::models::{
+    Customer, CustomerName, DeliveryChannel, Notification, NotificationContent, Operator,
+    OperatorRole, PastNotification, Product, ProductName, RecurringNotification,
+    ScheduledNotification, Subscription, SubscriptionMode, Timestamp,
+};
+
+pub use admin::{
+    AdminRepository, CustomerSummary, KeySummary, NotificationSummary, SubscriptionSummary,
+};
+pub use common::{cache::CacheStats, errors::DatabaseErrors, keys::generate_key};
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresRepository;
+#[cfg(feature = "sqlite")]
 pub use sqlite::SqliteRepository;
+
+/// Storage of customers, subscriptions and notifications.
+#[async_trait]
+pub trait Repository: Send + Sync {
+    async fn get_customers(&self) -> Result<Vec<Customer>, DatabaseErrors>;
+
+    async fn get_products(&self, customer: &CustomerName) -> Result<Vec<Product>, DatabaseErrors>;
+
+    async fn add_subscription(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        channel: DeliveryChannel,
+        email: Option<&str>,
+        mode: SubscriptionMode,
+    ) -> Result<(), DatabaseErrors>;
+
+    /// Subscriptions of the user, the delivered ones included as inactive.
+    async fn get_user_subscriptions(
+        &self,
+        user_id: u32,
+    ) -> Result<Vec<Subscription>, DatabaseErrors>;
+
+    /// Deletes an active subscription of the user. Returns `false` if the
+    /// user has no such subscription or it was already delivered.
+    async fn cancel_subscription(&mut self, user_id: u32, id: i64) -> Result<bool, DatabaseErrors>;
+
+    /// Deletes every active subscription of the user and returns how many there were.
+    async fn cancel_all_subscriptions(&mut self, user_id: u32) -> Result<u64, DatabaseErrors>;
+
+    /// Checks the key against the unexpired key hashes of enabled customers
+    /// and makes the user an operator of the matching customer, with the
+    /// role granted by the key. One-time keys are deleted on success.
+    async fn try_authorize(
+        &mut self,
+        user_id: u32,
+        key: String,
+    ) -> Result<Option<(Customer, OperatorRole)>, DatabaseErrors>;
+
+    /// Stores a one-time key making its user an operator with `role`.
+    async fn add_invite_key(
+        &mut self,
+        customer: &CustomerName,
+        key: &str,
+        role: OperatorRole,
+        expires_at: Timestamp,
+    ) -> Result<(), DatabaseErrors>;
+
+    /// Returns until when the user is locked out of authorization, if at `now`.
+    async fn get_authorization_lock(
+        &self,
+        user_id: u32,
+        now: Timestamp,
+    ) -> Result<Option<Timestamp>, DatabaseErrors>;
+
+    /// Counts a failed authorization of the user. The `max_failures`th failure
+    /// in a row locks the user out until `lock_until` and returns it.
+    async fn add_authorization_failure(
+        &mut self,
+        user_id: u32,
+        max_failures: u32,
+        lock_until: Timestamp,
+    ) -> Result<Option<Timestamp>, DatabaseErrors>;
+
+    async fn clear_authorization_failures(&mut self, user_id: u32) -> Result<(), DatabaseErrors>;
+
+    async fn get_products_for_notification(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<Product>, DatabaseErrors>;
+
+    /// Stores a notification and returns its id. With `send_at` the
+    /// notification is pending until `take_due_notifications` picks it up.
+    async fn add_notification(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        content: NotificationContent,
+        send_at: Option<Timestamp>,
+    ) -> Result<i64, DatabaseErrors>;
+
+    async fn get_scheduled_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<ScheduledNotification>, DatabaseErrors>;
+
+    /// Cancels a pending notification of the customer. Returns `false` if
+    /// there is no such notification or it was already sent.
+    async fn cancel_scheduled_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn add_recurring_notification(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        schedule: &str,
+        timezone: &str,
+        content: NotificationContent,
+        next_send_at: Timestamp,
+    ) -> Result<i64, DatabaseErrors>;
+
+    async fn get_recurring_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<RecurringNotification>, DatabaseErrors>;
+
+    /// Returns the cron schedule and timezone of a recurring notification of the customer.
+    async fn get_recurring_schedule(
+        &self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<Option<(String, String)>, DatabaseErrors>;
+
+    /// Pauses or resumes a recurring notification of the customer. Paused
+    /// notifications have no `next_send_at`.
+    async fn set_recurring_notification_paused(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+        paused: bool,
+        next_send_at: Option<Timestamp>,
+    ) -> Result<bool, DatabaseErrors>;
+
+    async fn delete_recurring_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors>;
+
+    /// Returns active recurring notifications due at `now` with the user who
+    /// created them. The caller moves them on with `set_recurring_next_send_at`.
+    async fn get_due_recurring_notifications(
+        &self,
+        now: Timestamp,
+    ) -> Result<Vec<(u32, CustomerName, RecurringNotification)>, DatabaseErrors>;
+
+    async fn set_recurring_next_send_at(
+        &mut self,
+        id: i64,
+        next_send_at: Option<Timestamp>,
+    ) -> Result<(), DatabaseErrors>;
+
+    /// Marks pending notifications due at `now` as sent and returns them with
+    /// the user who scheduled them.
+    async fn take_due_notifications(
+        &mut self,
+        now: Timestamp,
+    ) -> Result<Vec<(u32, CustomerName, ScheduledNotification)>, DatabaseErrors>;
+
+    /// Puts a notification taken by `take_due_notifications` back in the
+    /// schedule at `send_at`. Returns false if it was not sent.
+    async fn defer_notification(
+        &mut self,
+        id: i64,
+        send_at: Timestamp,
+    ) -> Result<bool, DatabaseErrors>;
+
+    /// Returns a notification for every active subscription to the product
+    /// and deactivates the subscriptions that got their last one, as set by
+    /// their mode. Subscriptions past their `Until` date are not notified.
+    /// Every notified user gets the notification in their history.
+    async fn get_notifications(
+        &mut self,
+        customer: &CustomerName,
+        product: &ProductName,
+        content: NotificationContent,
+    ) -> Result<Vec<Notification>, DatabaseErrors>;
+
+    /// Sent notifications of the customer, of `product` only if it is set,
+    /// newest first.
+    async fn get_sent_notifications(
+        &self,
+        customer: &CustomerName,
+        product: Option<&ProductName>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<PastNotification>, DatabaseErrors>;
+
+    /// Notifications received by the user, newest first.
+    async fn get_received_notifications(
+        &self,
+        user_id: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<PastNotification>, DatabaseErrors>;
+
+    async fn get_operators(&self, customer: &CustomerName)
+        -> Result<Vec<Operator>, DatabaseErrors>;
+
+    /// Role of the user for the customer, `None` if the user is not its operator.
+    async fn get_operator_role(
+        &self,
+        user_id: u32,
+        customer: &CustomerName,
+    ) -> Result<Option<OperatorRole>, DatabaseErrors>;
+
+    async fn remove_operator(
+        &mut self,
+        customer: &CustomerName,
+        user_id: u32,
+    ) -> Result<bool, DatabaseErrors>;
+
+    /// Reloads the cached customers, products and authorized users, picking
+    /// up rows changed directly in the database.
+    async fn reload_cache(&self) -> Result<(), DatabaseErrors>;
+
+    fn cache_stats(&self) -> CacheStats;
+}
+
+/// Opens the storage backend matching the scheme of the database url,
+/// `sqlite:` or `postgres://`. Each backend is behind a cargo feature of the same name.
+pub async fn connect(url: &str) -> Result<Box<dyn Repository>, DatabaseErrors> {
+    let scheme = url.split(':').next().unwrap_or_default();
+    match scheme {
+        #[cfg(feature = "sqlite")]
+        "sqlite" => Ok(Box::new(SqliteRepository::new(url).await?)),
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => Ok(Box::new(PostgresRepository::new(url).await?)),
+        _ => Err(DatabaseErrors::UnsupportedDatabase(scheme.to_string())),
+    }
+}
+
+/// Applies the embedded migrations of the backend matching the scheme of the
+/// database url. Migrations only add to the schema, existing data is kept.
+pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+    let scheme = url.split(':').next().unwrap_or_default();
+    match scheme {
+        #[cfg(feature = "sqlite")]
+        "sqlite" => SqliteRepository::migrate(url).await,
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => PostgresRepository::migrate(url).await,
+        _ => Err(DatabaseErrors::UnsupportedDatabase(scheme.to_string())),
+    }
+}
+
+/// Opens the storage backend of the database url for `repository-admin`, see `connect`.
+pub async fn connect_admin(url: &str) -> Result<Box<dyn AdminRepository>, DatabaseErrors> {
+    let scheme = url.split(':').next().unwrap_or_default();
+    match scheme {
+        #[cfg(feature = "sqlite")]
+        "sqlite" => Ok(Box::new(SqliteRepository::new(url).await?)),
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => Ok(Box::new(PostgresRepository::new(url).await?)),
+        _ => Err(DatabaseErrors::UnsupportedDatabase(scheme.to_string())),
+    }
+}
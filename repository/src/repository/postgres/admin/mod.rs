@@ -0,0 +1,645 @@
+use async_trait::async_trait;
+use domain::models::{CustomerName, DeliveryChannel, OperatorRole, ProductName, Timestamp};
+use sqlx::{FromRow, Postgres, Transaction};
+
+use super::{request_error, PostgresRepository};
+use crate::repository::{
+    admin::{
+        AdminRepository, CustomerSummary, KeySummary, NotificationSummary, SubscriptionSummary,
+    },
+    common::{
+        errors::DatabaseErrors, keys, parse_customer, parse_product, STATUS_CANCELLED,
+        STATUS_PENDING,
+    },
+};
+
+impl PostgresRepository {
+    /// Id of the customer, disabled customers included.
+    async fn find_customer_id(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Option<i64>, DatabaseErrors> {
+        sqlx::query_scalar(
+            r#"
+            SELECT
+                id
+            FROM
+                customers
+            WHERE
+                name = $1
+            "#,
+        )
+        .bind(customer.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)
+    }
+
+    async fn find_product_id(
+        &self,
+        customer_id: i64,
+        product: &ProductName,
+    ) -> Result<Option<i64>, DatabaseErrors> {
+        sqlx::query_scalar(
+            r#"
+            SELECT
+                products.id
+            FROM
+                products
+                    INNER JOIN customers_products
+                    ON products.id = customers_products.product_id
+            WHERE
+                customers_products.customer_id = $1 AND products.name = $2
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)
+    }
+}
+
+#[async_trait]
+impl AdminRepository for PostgresRepository {
+    async fn create_customer(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+    ) -> Result<(), DatabaseErrors> {
+        if self.find_customer_id(customer).await?.is_some() {
+            return Err(DatabaseErrors::CustomerExists(customer.clone()));
+        }
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let customer_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO
+                customers ( name, key )
+            VALUES
+                ( $1, '' )
+            RETURNING
+                id
+            "#,
+        )
+        .bind(customer.as_str())
+        .fetch_one(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        insert_key(
+            &mut transaction,
+            customer_id,
+            key,
+            "",
+            None,
+            false,
+            OperatorRole::Owner,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))
+    }
+
+    async fn list_customers(&self) -> Result<Vec<CustomerSummary>, DatabaseErrors> {
+        let rows: Vec<(String, bool, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT
+                customers.name,
+                customers.disabled,
+                products.name as product
+            FROM
+                customers
+                    LEFT JOIN customers_products
+                    ON customers.id = customers_products.customer_id
+                    LEFT JOIN products
+                    ON customers_products.product_id = products.id
+            ORDER BY
+                customers.name, products.name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        let mut customers: Vec<CustomerSummary> = vec![];
+        for (name, disabled, product) in rows {
+            let name = match parse_customer(&name) {
+                Some(name) => name,
+                None => continue,
+            };
+            if customers.last().map(|customer| &customer.name) != Some(&name) {
+                customers.push(CustomerSummary {
+                    name,
+                    disabled,
+                    products: vec![],
+                });
+            }
+            if let Some(product) = product.as_deref().and_then(parse_product) {
+                customers.last_mut().unwrap().products.push(product);
+            }
+        }
+        Ok(customers)
+    }
+
+    async fn set_customer_disabled(
+        &self,
+        customer: &CustomerName,
+        disabled: bool,
+    ) -> Result<bool, DatabaseErrors> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                customers
+            SET
+                disabled = $1
+            WHERE
+                name = $2
+            "#,
+        )
+        .bind(disabled)
+        .bind(customer.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn replace_customer_keys(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        sqlx::query(
+            r#"
+            DELETE FROM
+                customer_keys
+            WHERE
+                customer_id = $1
+            "#,
+        )
+        .bind(customer_id)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        insert_key(
+            &mut transaction,
+            customer_id,
+            key,
+            "",
+            None,
+            false,
+            OperatorRole::Owner,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(true)
+    }
+
+    async fn add_customer_key(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+        label: &str,
+        expires_at: Option<Timestamp>,
+        one_time: bool,
+        role: OperatorRole,
+    ) -> Result<i64, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let id = insert_key(
+            &mut transaction,
+            customer_id,
+            key,
+            label,
+            expires_at,
+            one_time,
+            role,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(id)
+    }
+
+    async fn list_customer_keys(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<KeySummary>, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        let rows: Vec<(i64, String, bool, String, Option<i64>, i64)> = sqlx::query_as(
+            r#"
+            SELECT
+                id, label, one_time, role, expires_at, created_at
+            FROM
+                customer_keys
+            WHERE
+                customer_id = $1
+            ORDER BY
+                id
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, label, one_time, role, expires_at, created_at)| {
+                Some(KeySummary {
+                    id,
+                    label,
+                    one_time,
+                    role: OperatorRole::from(role.as_str()),
+                    expires_at: expires_at.and_then(Timestamp::from_millis),
+                    created_at: Timestamp::from_millis(created_at)?,
+                })
+            })
+            .collect())
+    }
+
+    async fn revoke_customer_key(
+        &self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                customer_keys
+            WHERE
+                id = $1 AND customer_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+    ) -> Result<(), DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        if self.find_product_id(customer_id, product).await?.is_some() {
+            return Err(DatabaseErrors::ProductExists(
+                customer.clone(),
+                product.clone(),
+            ));
+        }
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let product_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO
+                products ( name )
+            VALUES
+                ( $1 )
+            RETURNING
+                id
+            "#,
+        )
+        .bind(product.as_str())
+        .fetch_one(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO
+                customers_products ( customer_id, product_id )
+            VALUES
+                ( $1, $2 )
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product_id)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))
+    }
+
+    async fn remove_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+        let product_id = match self.find_product_id(customer_id, product).await? {
+            Some(product_id) => product_id,
+            None => return Ok(false),
+        };
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        sqlx::query(
+            r#"
+            DELETE FROM
+                active_subscriptions
+            WHERE
+                subscription_id IN (
+                    SELECT id FROM subscriptions WHERE customer_id = $1 AND product_id = $2
+                )
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product_id)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = $1
+            WHERE
+                customer_id = $2 AND product_id = $3 AND status = $4
+            "#,
+        )
+        .bind(STATUS_CANCELLED)
+        .bind(customer_id)
+        .bind(product_id)
+        .bind(STATUS_PENDING)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE
+                recurring_notifications
+            SET
+                paused = TRUE, next_send_at = NULL
+            WHERE
+                customer_id = $1 AND product_id = $2
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product_id)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM
+                customers_products
+            WHERE
+                customer_id = $1 AND product_id = $2
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product_id)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(true)
+    }
+
+    async fn rename_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+        new_name: &ProductName,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+        let product_id = match self.find_product_id(customer_id, product).await? {
+            Some(product_id) => product_id,
+            None => return Ok(false),
+        };
+        if self.find_product_id(customer_id, new_name).await?.is_some() {
+            return Err(DatabaseErrors::ProductExists(
+                customer.clone(),
+                new_name.clone(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE
+                products
+            SET
+                name = $1
+            WHERE
+                id = $2
+            "#,
+        )
+        .bind(new_name.as_str())
+        .bind(product_id)
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(true)
+    }
+
+    async fn list_subscriptions(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<SubscriptionSummary>, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        let rows: Vec<SubscriptionRow> = sqlx::query_as(
+            r#"
+            SELECT
+                subscriptions.id,
+                subscriptions.user_id,
+                products.name as product,
+                subscriptions.channel,
+                subscriptions.email,
+                EXISTS (
+                    SELECT 1 FROM active_subscriptions
+                    WHERE active_subscriptions.subscription_id = subscriptions.id
+                ) as active
+            FROM
+                subscriptions
+                    INNER JOIN products
+                    ON subscriptions.product_id = products.id
+            WHERE
+                subscriptions.customer_id = $1
+            ORDER BY
+                subscriptions.id
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(SubscriptionSummary {
+                    id: row.id,
+                    user_id: row.user_id as u32,
+                    product: parse_product(&row.product)?,
+                    channel: DeliveryChannel::from(row.channel.as_str()),
+                    email: row.email,
+                    active: row.active,
+                })
+            })
+            .collect())
+    }
+
+    async fn list_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<NotificationSummary>, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        let rows: Vec<(i64, String, String, Option<i64>, String)> = sqlx::query_as(
+            r#"
+            SELECT
+                notifications.id,
+                products.name as product,
+                notifications.status,
+                notifications.send_at,
+                notifications.text
+            FROM
+                notifications
+                    INNER JOIN products
+                    ON notifications.product_id = products.id
+            WHERE
+                notifications.customer_id = $1
+            ORDER BY
+                notifications.id
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, product, status, send_at, text)| {
+                Some(NotificationSummary {
+                    id,
+                    product: parse_product(&product)?,
+                    status,
+                    send_at: send_at.and_then(Timestamp::from_millis),
+                    text,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Stores the hash of a new key of the customer and returns its id.
+pub(super) async fn insert_key(
+    transaction: &mut Transaction<'_, Postgres>,
+    customer_id: i64,
+    key: &str,
+    label: &str,
+    expires_at: Option<Timestamp>,
+    one_time: bool,
+    role: OperatorRole,
+) -> Result<i64, DatabaseErrors> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO
+            customer_keys ( customer_id, key_id, hash, label, one_time, expires_at, created_at, role )
+        VALUES
+            ( $1, $2, $3, $4, $5, $6, $7, $8 )
+        RETURNING
+            id
+        "#,
+    )
+    .bind(customer_id)
+    .bind(keys::key_id(key))
+    .bind(keys::hash_key(key)?)
+    .bind(label)
+    .bind(one_time)
+    .bind(expires_at.map(|expires_at| expires_at.as_millis()))
+    .bind(Timestamp::now().as_millis())
+    .bind(role.as_str())
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(request_error)
+}
+
+#[derive(FromRow)]
+struct SubscriptionRow {
+    id: i64,
+    user_id: i64,
+    product: String,
+    channel: String,
+    email: Option<String>,
+    active: bool,
+}
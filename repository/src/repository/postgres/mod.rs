@@ -0,0 +1,1714 @@
+mod admin;
+
+use async_trait::async_trait;
+use domain::models::{
+    Customer, CustomerName, DeliveryChannel, Notification, NotificationContent, Operator,
+    OperatorRole, PastNotification, Product, ProductName, RecurringNotification,
+    ScheduledNotification, Subscription, SubscriptionMode, Timestamp,
+};
+use sqlx::{migrate::Migrator, FromRow, PgPool};
+
+use crate::repository::{
+    common::{
+        cache::{CacheData, CacheStats},
+        delivery,
+        errors::DatabaseErrors,
+        keys::{find_key, hash_key, key_id, legacy_key_id, StoredKey},
+        notification_content, parse_customer, parse_product, subscription_mode, Delivery,
+        STATUS_CANCELLED, STATUS_PENDING, STATUS_SENT,
+    },
+    Repository,
+};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// PostgreSQL storage, the schema is in `migrations/postgres`. Queries are
+/// checked at runtime since the `query!` macros of the crate target SQLite.
+pub struct PostgresRepository {
+    pool: PgPool,
+    hash_data: CacheData,
+}
+
+impl PostgresRepository {
+    pub async fn new(url: &str) -> Result<Self, DatabaseErrors> {
+        match PgPool::connect(url).await {
+            Ok(pool) => {
+                let hash_data = Self::load_cache(&pool).await?;
+                Ok(PostgresRepository { pool, hash_data })
+            }
+            Err(error) => Err(DatabaseErrors::ConnectionProblem(error.to_string())),
+        }
+    }
+
+    /// Applies pending migrations. The database itself must exist.
+    pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+        let pool = PgPool::connect(url)
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionProblem(error.to_string()))?;
+        MIGRATOR
+            .run(&pool)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+        Self::hash_plaintext_keys(&pool).await
+    }
+
+    /// Moves the keys stored in plain text by older versions into `customer_keys`.
+    /// They have no key id until their first authorization, see `find_stored_key`.
+    async fn hash_plaintext_keys(pool: &PgPool) -> Result<(), DatabaseErrors> {
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT
+                id, key
+            FROM
+                customers
+            WHERE
+                key != ''
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+
+        if !rows.is_empty() {
+            log::warn!("Hashed {} plain text keys", rows.len());
+        }
+        let created_at = Timestamp::now().as_millis();
+        for (customer_id, key) in rows {
+            let hash = hash_key(&key)?;
+            let mut transaction = pool
+                .begin()
+                .await
+                .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+            sqlx::query(
+                r#"
+                INSERT INTO
+                    customer_keys ( customer_id, hash, created_at )
+                VALUES
+                    ( $1, $2, $3 )
+                "#,
+            )
+            .bind(customer_id)
+            .bind(hash)
+            .bind(created_at)
+            .execute(&mut transaction)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+
+            sqlx::query(
+                r#"
+                UPDATE
+                    customers
+                SET
+                    key = ''
+                WHERE
+                    id = $1
+                "#,
+            )
+            .bind(customer_id)
+            .execute(&mut transaction)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+
+            transaction
+                .commit()
+                .await
+                .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Stored key matching `key`. Keys issued before key ids are checked
+    /// against the keys without one and rehashed under their legacy key id,
+    /// so they are found by it from then on.
+    async fn find_stored_key(
+        &self,
+        key: String,
+        now: Timestamp,
+    ) -> Result<Option<StoredKey>, DatabaseErrors> {
+        if let Some(key_id) = key_id(&key) {
+            let stored_keys = self.get_stored_keys(Some(key_id), now).await?;
+            return Ok(find_key(key, stored_keys).await);
+        }
+        let legacy_key_id = legacy_key_id(&key);
+        let stored_keys = self.get_stored_keys(Some(&legacy_key_id), now).await?;
+        if let Some(stored_key) = find_key(key.clone(), stored_keys).await {
+            return Ok(Some(stored_key));
+        }
+        let stored_keys = self.get_stored_keys(None, now).await?;
+        let stored_key = match find_key(key.clone(), stored_keys).await {
+            Some(stored_key) => stored_key,
+            None => return Ok(None),
+        };
+        if let Err(error) = self
+            .rehash_legacy_key(stored_key.id, &legacy_key_id, &key)
+            .await
+        {
+            log::error!("Failed to rehash legacy key [{}]: {}", stored_key.id, error);
+        }
+        Ok(Some(stored_key))
+    }
+
+    async fn rehash_legacy_key(
+        &self,
+        id: i64,
+        key_id: &str,
+        key: &str,
+    ) -> Result<(), DatabaseErrors> {
+        sqlx::query(
+            r#"
+            UPDATE
+                customer_keys
+            SET
+                key_id = $1,
+                hash = $2
+            WHERE
+                id = $3
+                AND key_id IS NULL
+            "#,
+        )
+        .bind(key_id)
+        .bind(hash_key(key)?)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+        Ok(())
+    }
+
+    /// Unexpired keys of enabled customers with the key id, or without one.
+    async fn get_stored_keys(
+        &self,
+        key_id: Option<&str>,
+        now: Timestamp,
+    ) -> Result<Vec<StoredKey>, DatabaseErrors> {
+        let rows: Vec<(i64, String, bool, String, String)> = sqlx::query_as(
+            r#"
+            SELECT
+                customer_keys.id,
+                customer_keys.hash,
+                customer_keys.one_time,
+                customer_keys.role,
+                customers.name as customer
+            FROM
+                customer_keys
+                    INNER JOIN customers
+                    ON customer_keys.customer_id = customers.id
+            WHERE
+                customer_keys.key_id IS NOT DISTINCT FROM $1
+                AND (customer_keys.expires_at IS NULL OR customer_keys.expires_at > $2)
+                AND NOT customers.disabled
+            "#,
+        )
+        .bind(key_id)
+        .bind(now.as_millis())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, hash, one_time, role, customer)| StoredKey {
+                id,
+                hash,
+                one_time,
+                role,
+                customer,
+            })
+            .collect())
+    }
+
+    async fn load_cache(pool: &PgPool) -> Result<CacheData, DatabaseErrors> {
+        let customers = sqlx::query_as(
+            r#"
+            SELECT
+                id, name
+            FROM
+                customers
+            WHERE
+                NOT disabled
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(request_error)?;
+
+        let products = sqlx::query_as(
+            r#"
+            SELECT
+                customers.name as customer,
+                products.id,
+                products.name
+            FROM
+                products
+                    INNER JOIN customers_products
+                    ON products.id = customers_products.product_id
+                    INNER JOIN customers
+                    ON customers_products.customer_id = customers.id
+            WHERE
+                NOT customers.disabled
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(request_error)?;
+
+        let users_customers = sqlx::query_as(
+            r#"
+            SELECT
+                customers.name,
+                users_customers.user_id,
+                users_customers.role
+            FROM
+                users_customers
+                INNER JOIN customers
+                ON users_customers.customer_id = customers.id
+            WHERE
+                NOT customers.disabled
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(CacheData::from_rows(customers, products, users_customers))
+    }
+
+    /// Id of the customer. On a cache miss the customer is looked up in the
+    /// database and cached with its products.
+    async fn customer_id(&self, customer: &CustomerName) -> Result<u32, DatabaseErrors> {
+        if let Some(id) = self.hash_data.get_customer_id(customer) {
+            return Ok(id);
+        }
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            SELECT
+                id
+            FROM
+                customers
+            WHERE
+                name = $1 AND NOT disabled
+            "#,
+        )
+        .bind(customer.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?
+        .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+
+        let products = sqlx::query_as(
+            r#"
+            SELECT
+                products.id,
+                products.name
+            FROM
+                products
+                    INNER JOIN customers_products
+                    ON products.id = customers_products.product_id
+            WHERE
+                customers_products.customer_id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        self.hash_data
+            .insert_customer(customer, id as u32, products);
+        Ok(id as u32)
+    }
+
+    /// Id of the product of the customer, looked up in the database on a cache miss.
+    async fn product_id(
+        &self,
+        customer: &CustomerName,
+        customer_id: u32,
+        product: &ProductName,
+    ) -> Result<u32, DatabaseErrors> {
+        if let Some(id) = self.hash_data.get_product_id(customer, product) {
+            return Ok(id);
+        }
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            SELECT
+                products.id
+            FROM
+                products
+                    INNER JOIN customers_products
+                    ON products.id = customers_products.product_id
+            WHERE
+                customers_products.customer_id = $1 AND products.name = $2
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .bind(product.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?
+        .ok_or_else(|| DatabaseErrors::UnknownProduct(customer.clone(), product.clone()))?;
+
+        self.hash_data.insert_product(customer, product, id as u32);
+        Ok(id as u32)
+    }
+}
+
+#[async_trait]
+impl Repository for PostgresRepository {
+    async fn get_customers(&self) -> Result<Vec<Customer>, DatabaseErrors> {
+        Ok(self.hash_data.get_customers())
+    }
+
+    async fn get_products(&self, customer: &CustomerName) -> Result<Vec<Product>, DatabaseErrors> {
+        self.customer_id(customer).await?;
+        Ok(self.hash_data.get_customer_products(customer))
+    }
+
+    async fn add_subscription(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        channel: DeliveryChannel,
+        email: Option<&str>,
+        mode: SubscriptionMode,
+    ) -> Result<(), DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+        let mut transaction = self.pool.begin().await.map_err(transaction_error)?;
+
+        let subscription_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO
+                subscriptions ( user_id, customer_id, product_id, channel, email, mode, remaining, expires_at )
+            VALUES
+                ( $1, $2, $3, $4, $5, $6, $7, $8 )
+            RETURNING
+                id
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(i64::from(customer_id))
+        .bind(i64::from(product_id))
+        .bind(channel.as_str())
+        .bind(email)
+        .bind(mode.as_str())
+        .bind(mode.count().map(i64::from))
+        .bind(mode.until().map(|until| until.as_millis()))
+        .fetch_one(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO
+                active_subscriptions ( subscription_id )
+            VALUES
+                ( $1 )
+            "#,
+        )
+        .bind(subscription_id)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction.commit().await.map_err(transaction_error)
+    }
+
+    async fn get_user_subscriptions(
+        &self,
+        user_id: u32,
+    ) -> Result<Vec<Subscription>, DatabaseErrors> {
+        let rows: Vec<SubscriptionRow> = sqlx::query_as(
+            r#"
+            SELECT
+                subscriptions.id,
+                customers.name as customer,
+                products.name as product,
+                subscriptions.channel,
+                subscriptions.mode,
+                subscriptions.remaining,
+                subscriptions.expires_at,
+                EXISTS (
+                    SELECT 1 FROM active_subscriptions
+                    WHERE active_subscriptions.subscription_id = subscriptions.id
+                ) as active
+            FROM
+                subscriptions
+                    INNER JOIN customers
+                    ON customers.id = subscriptions.customer_id
+                    INNER JOIN products
+                    ON products.id = subscriptions.product_id
+            WHERE
+                subscriptions.user_id = $1
+            ORDER BY
+                subscriptions.id
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        let now = Timestamp::now();
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.into_subscription(now))
+            .collect())
+    }
+
+    async fn cancel_subscription(&mut self, user_id: u32, id: i64) -> Result<bool, DatabaseErrors> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                subscriptions
+            WHERE
+                id = $1
+                AND user_id = $2
+                AND id IN ( SELECT subscription_id FROM active_subscriptions )
+            "#,
+        )
+        .bind(id)
+        .bind(i64::from(user_id))
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn cancel_all_subscriptions(&mut self, user_id: u32) -> Result<u64, DatabaseErrors> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                subscriptions
+            WHERE
+                user_id = $1
+                AND id IN ( SELECT subscription_id FROM active_subscriptions )
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn try_authorize(
+        &mut self,
+        user_id: u32,
+        key: String,
+    ) -> Result<Option<(Customer, OperatorRole)>, DatabaseErrors> {
+        let stored_key = match self.find_stored_key(key, Timestamp::now()).await? {
+            Some(stored_key) => stored_key,
+            None => return Ok(None),
+        };
+        let customer = match parse_customer(&stored_key.customer) {
+            Some(customer) => customer,
+            None => return Ok(None),
+        };
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        if stored_key.one_time {
+            let result = sqlx::query(
+                r#"
+                DELETE FROM
+                    customer_keys
+                WHERE
+                    id = $1
+                "#,
+            )
+            .bind(stored_key.id)
+            .execute(&mut transaction)
+            .await
+            .map_err(request_error)?;
+
+            // Used up by a concurrent authorization.
+            if result.rows_affected() == 0 {
+                return Ok(None);
+            }
+        }
+
+        let role = OperatorRole::from(stored_key.role.as_str());
+        let customer_id = self.customer_id(&customer).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO
+                users_customers ( user_id, customer_id, role )
+            VALUES
+                ( $1, $2, $3 )
+            ON CONFLICT ( user_id, customer_id ) DO UPDATE SET
+                role = excluded.role
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(i64::from(customer_id))
+        .bind(role.as_str())
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+
+        self.hash_data.insert_operator(&customer, user_id, role);
+        Ok(Some((Customer { name: customer }, role)))
+    }
+
+    async fn add_invite_key(
+        &mut self,
+        customer: &CustomerName,
+        key: &str,
+        role: OperatorRole,
+        expires_at: Timestamp,
+    ) -> Result<(), DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        admin::insert_key(
+            &mut transaction,
+            i64::from(customer_id),
+            key,
+            "invite",
+            Some(expires_at),
+            true,
+            role,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))
+    }
+
+    async fn get_authorization_lock(
+        &self,
+        user_id: u32,
+        now: Timestamp,
+    ) -> Result<Option<Timestamp>, DatabaseErrors> {
+        let locked_until: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT
+                locked_until
+            FROM
+                authorization_failures
+            WHERE
+                user_id = $1 AND locked_until > $2
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(now.as_millis())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(locked_until.and_then(Timestamp::from_millis))
+    }
+
+    async fn add_authorization_failure(
+        &mut self,
+        user_id: u32,
+        max_failures: u32,
+        lock_until: Timestamp,
+    ) -> Result<Option<Timestamp>, DatabaseErrors> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let failures: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO
+                authorization_failures ( user_id, failures )
+            VALUES
+                ( $1, 1 )
+            ON CONFLICT ( user_id ) DO UPDATE SET
+                failures = authorization_failures.failures + 1
+            RETURNING
+                failures
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .fetch_one(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        let locked = failures as i64 >= i64::from(max_failures);
+        if locked {
+            sqlx::query(
+                r#"
+                UPDATE
+                    authorization_failures
+                SET
+                    failures = 0, locked_until = $1
+                WHERE
+                    user_id = $2
+                "#,
+            )
+            .bind(lock_until.as_millis())
+            .bind(i64::from(user_id))
+            .execute(&mut transaction)
+            .await
+            .map_err(request_error)?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(locked.then_some(lock_until))
+    }
+
+    async fn clear_authorization_failures(&mut self, user_id: u32) -> Result<(), DatabaseErrors> {
+        sqlx::query(
+            r#"
+            DELETE FROM
+                authorization_failures
+            WHERE
+                user_id = $1
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(())
+    }
+
+    async fn get_products_for_notification(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<Product>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let names: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT
+                products.name
+            FROM
+                subscriptions
+                    INNER JOIN active_subscriptions
+                    ON subscriptions.id = active_subscriptions.subscription_id
+                    INNER JOIN products
+                    ON subscriptions.product_id = products.id
+            WHERE
+                subscriptions.customer_id = $1
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(names
+            .iter()
+            .filter_map(|name| parse_product(name))
+            .map(|name| Product { name })
+            .collect())
+    }
+
+    async fn add_notification(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        content: NotificationContent,
+        send_at: Option<Timestamp>,
+    ) -> Result<i64, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+
+        let buttons = serde_json::to_string(&content.buttons).unwrap();
+        let send_at = send_at.map(|send_at| send_at.as_millis());
+        let (status, sent_at) = match send_at {
+            Some(_) => (STATUS_PENDING, None),
+            None => (STATUS_SENT, Some(Timestamp::now().as_millis())),
+        };
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO
+                notifications ( customer_id, product_id, text, parse_mode, attachment_kind, attachment_file_id, buttons, user_id, send_at, status, sent_at )
+            VALUES
+                ( $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11 )
+            RETURNING
+                id
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .bind(i64::from(product_id))
+        .bind(&content.text)
+        .bind(content.parse_mode.as_str())
+        .bind(content.attachment.as_ref().map(|attachment| attachment.kind()))
+        .bind(content.attachment.as_ref().map(|attachment| attachment.file_id()))
+        .bind(buttons)
+        .bind(i64::from(user_id))
+        .bind(send_at)
+        .bind(status)
+        .bind(sent_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(request_error)
+    }
+
+    async fn get_scheduled_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<ScheduledNotification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let rows: Vec<ScheduledRow> = sqlx::query_as(
+            r#"
+            SELECT
+                notifications.id,
+                notifications.user_id,
+                customers.name as customer,
+                products.name as product,
+                notifications.send_at,
+                notifications.text,
+                notifications.parse_mode,
+                notifications.attachment_kind,
+                notifications.attachment_file_id,
+                notifications.buttons
+            FROM
+                notifications
+                    INNER JOIN customers
+                    ON customers.id = notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = notifications.product_id
+            WHERE
+                notifications.customer_id = $1
+                AND notifications.status = $2
+            ORDER BY
+                notifications.send_at
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .bind(STATUS_PENDING)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(ScheduledRow::into_notification)
+            .map(|(_, _, notification)| notification)
+            .collect())
+    }
+
+    async fn cancel_scheduled_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = $1
+            WHERE
+                id = $2
+                AND customer_id = $3
+                AND status = $4
+            "#,
+        )
+        .bind(STATUS_CANCELLED)
+        .bind(id)
+        .bind(i64::from(customer_id))
+        .bind(STATUS_PENDING)
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_recurring_notification(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        schedule: &str,
+        timezone: &str,
+        content: NotificationContent,
+        next_send_at: Timestamp,
+    ) -> Result<i64, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+
+        let buttons = serde_json::to_string(&content.buttons).unwrap();
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO
+                recurring_notifications ( user_id, customer_id, product_id, schedule, timezone, next_send_at, text, parse_mode, attachment_kind, attachment_file_id, buttons )
+            VALUES
+                ( $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11 )
+            RETURNING
+                id
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(i64::from(customer_id))
+        .bind(i64::from(product_id))
+        .bind(schedule)
+        .bind(timezone)
+        .bind(next_send_at.as_millis())
+        .bind(&content.text)
+        .bind(content.parse_mode.as_str())
+        .bind(content.attachment.as_ref().map(|attachment| attachment.kind()))
+        .bind(content.attachment.as_ref().map(|attachment| attachment.file_id()))
+        .bind(buttons)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(request_error)
+    }
+
+    async fn get_recurring_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<RecurringNotification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let rows: Vec<RecurringRow> = sqlx::query_as(
+            r#"
+            SELECT
+                recurring_notifications.id,
+                recurring_notifications.user_id,
+                customers.name as customer,
+                products.name as product,
+                recurring_notifications.schedule,
+                recurring_notifications.timezone,
+                recurring_notifications.paused,
+                recurring_notifications.next_send_at,
+                recurring_notifications.text,
+                recurring_notifications.parse_mode,
+                recurring_notifications.attachment_kind,
+                recurring_notifications.attachment_file_id,
+                recurring_notifications.buttons
+            FROM
+                recurring_notifications
+                    INNER JOIN customers
+                    ON customers.id = recurring_notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = recurring_notifications.product_id
+            WHERE
+                recurring_notifications.customer_id = $1
+            ORDER BY
+                recurring_notifications.id
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(RecurringRow::into_notification)
+            .map(|(_, _, notification)| notification)
+            .collect())
+    }
+
+    async fn get_recurring_schedule(
+        &self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<Option<(String, String)>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        sqlx::query_as(
+            r#"
+            SELECT
+                schedule,
+                timezone
+            FROM
+                recurring_notifications
+            WHERE
+                id = $1
+                AND customer_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(i64::from(customer_id))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)
+    }
+
+    async fn set_recurring_notification_paused(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+        paused: bool,
+        next_send_at: Option<Timestamp>,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                recurring_notifications
+            SET
+                paused = $1,
+                next_send_at = $2
+            WHERE
+                id = $3
+                AND customer_id = $4
+            "#,
+        )
+        .bind(paused)
+        .bind(next_send_at.map(|next_send_at| next_send_at.as_millis()))
+        .bind(id)
+        .bind(i64::from(customer_id))
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_recurring_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                recurring_notifications
+            WHERE
+                id = $1
+                AND customer_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(i64::from(customer_id))
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_due_recurring_notifications(
+        &self,
+        now: Timestamp,
+    ) -> Result<Vec<(u32, CustomerName, RecurringNotification)>, DatabaseErrors> {
+        let rows: Vec<RecurringRow> = sqlx::query_as(
+            r#"
+            SELECT
+                recurring_notifications.id,
+                recurring_notifications.user_id,
+                customers.name as customer,
+                products.name as product,
+                recurring_notifications.schedule,
+                recurring_notifications.timezone,
+                recurring_notifications.paused,
+                recurring_notifications.next_send_at,
+                recurring_notifications.text,
+                recurring_notifications.parse_mode,
+                recurring_notifications.attachment_kind,
+                recurring_notifications.attachment_file_id,
+                recurring_notifications.buttons
+            FROM
+                recurring_notifications
+                    INNER JOIN customers
+                    ON customers.id = recurring_notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = recurring_notifications.product_id
+            WHERE
+                recurring_notifications.paused = FALSE
+                AND recurring_notifications.next_send_at <= $1
+            ORDER BY
+                recurring_notifications.next_send_at
+            "#,
+        )
+        .bind(now.as_millis())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(RecurringRow::into_notification)
+            .collect())
+    }
+
+    async fn set_recurring_next_send_at(
+        &mut self,
+        id: i64,
+        next_send_at: Option<Timestamp>,
+    ) -> Result<(), DatabaseErrors> {
+        sqlx::query(
+            r#"
+            UPDATE
+                recurring_notifications
+            SET
+                next_send_at = $1
+            WHERE
+                id = $2
+            "#,
+        )
+        .bind(next_send_at.map(|next_send_at| next_send_at.as_millis()))
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(())
+    }
+
+    /// Takes the notifications with a single `UPDATE ... RETURNING`, so two
+    /// repositories sharing the database never send the same notification.
+    async fn take_due_notifications(
+        &mut self,
+        now: Timestamp,
+    ) -> Result<Vec<(u32, CustomerName, ScheduledNotification)>, DatabaseErrors> {
+        let mut rows: Vec<ScheduledRow> = sqlx::query_as(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = $1,
+                sent_at = $3
+            FROM
+                customers,
+                products
+            WHERE
+                customers.id = notifications.customer_id
+                AND products.id = notifications.product_id
+                AND notifications.status = $2
+                AND notifications.send_at <= $3
+            RETURNING
+                notifications.id,
+                notifications.user_id,
+                customers.name as customer,
+                products.name as product,
+                notifications.send_at,
+                notifications.text,
+                notifications.parse_mode,
+                notifications.attachment_kind,
+                notifications.attachment_file_id,
+                notifications.buttons
+            "#,
+        )
+        .bind(STATUS_SENT)
+        .bind(STATUS_PENDING)
+        .bind(now.as_millis())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        rows.sort_by_key(|row| row.send_at);
+        Ok(rows
+            .into_iter()
+            .filter_map(ScheduledRow::into_notification)
+            .collect())
+    }
+
+    async fn defer_notification(
+        &mut self,
+        id: i64,
+        send_at: Timestamp,
+    ) -> Result<bool, DatabaseErrors> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = $1,
+                send_at = $2,
+                sent_at = NULL
+            WHERE
+                id = $3
+                AND status = $4
+            "#,
+        )
+        .bind(STATUS_PENDING)
+        .bind(send_at.as_millis())
+        .bind(id)
+        .bind(STATUS_SENT)
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_notifications(
+        &mut self,
+        customer: &CustomerName,
+        product: &ProductName,
+        content: NotificationContent,
+    ) -> Result<Vec<Notification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+        let mut transaction = self.pool.begin().await.map_err(transaction_error)?;
+
+        let rows: Vec<ActiveSubscriptionRow> = sqlx::query_as(
+            r#"
+            SELECT
+                subscriptions.id,
+                subscriptions.user_id,
+                subscriptions.channel,
+                subscriptions.email,
+                subscriptions.mode,
+                subscriptions.remaining,
+                subscriptions.expires_at
+            FROM
+                subscriptions
+                    INNER JOIN active_subscriptions
+                    ON subscriptions.id = active_subscriptions.subscription_id
+            WHERE
+                subscriptions.customer_id = $1
+                AND subscriptions.product_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .bind(i64::from(product_id))
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        let now = Timestamp::now();
+        let mut notifications = vec![];
+        let mut receivers: Vec<i64> = vec![];
+        let mut subscriptions_to_delete: Vec<i64> = vec![];
+        let mut subscriptions_to_count_down: Vec<i64> = vec![];
+        for row in rows {
+            let mode = subscription_mode(&row.mode, row.remaining, row.expires_at);
+            match delivery(mode, now) {
+                Delivery::Keep => {}
+                Delivery::Countdown => subscriptions_to_count_down.push(row.id),
+                Delivery::Last => subscriptions_to_delete.push(row.id),
+                Delivery::Expired => {
+                    subscriptions_to_delete.push(row.id);
+                    continue;
+                }
+            }
+            receivers.push(row.user_id);
+            notifications.push(Notification {
+                user_id: row.user_id as u32,
+                customer: customer.clone(),
+                product: product.clone(),
+                content: content.clone(),
+                channel: DeliveryChannel::from(row.channel.as_str()),
+                email: row.email,
+            });
+        }
+
+        sqlx::query(
+            r#"
+            DELETE FROM
+                active_subscriptions
+            WHERE
+                subscription_id = ANY($1)
+            "#,
+        )
+        .bind(&subscriptions_to_delete)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE
+                subscriptions
+            SET
+                remaining = remaining - 1
+            WHERE
+                id = ANY($1)
+            "#,
+        )
+        .bind(&subscriptions_to_count_down)
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        let buttons = serde_json::to_string(&content.buttons).unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO
+                received_notifications ( user_id, customer_id, product_id, text, parse_mode, attachment_kind, attachment_file_id, buttons, received_at )
+            SELECT
+                user_id, $2, $3, $4, $5, $6, $7, $8, $9
+            FROM
+                UNNEST($1::BIGINT[]) AS user_id
+            "#,
+        )
+        .bind(&receivers)
+        .bind(i64::from(customer_id))
+        .bind(i64::from(product_id))
+        .bind(&content.text)
+        .bind(content.parse_mode.as_str())
+        .bind(content.attachment.as_ref().map(|attachment| attachment.kind()))
+        .bind(content.attachment.as_ref().map(|attachment| attachment.file_id()))
+        .bind(buttons)
+        .bind(now.as_millis())
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction.commit().await.map_err(transaction_error)?;
+        Ok(notifications)
+    }
+
+    async fn get_sent_notifications(
+        &self,
+        customer: &CustomerName,
+        product: Option<&ProductName>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<PastNotification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = match product {
+            Some(product) => Some(self.product_id(customer, customer_id, product).await?),
+            None => None,
+        };
+        let rows: Vec<PastNotificationRow> = sqlx::query_as(
+            r#"
+            SELECT
+                notifications.id,
+                customers.name as customer,
+                products.name as product,
+                COALESCE(notifications.sent_at, notifications.send_at) as sent_at,
+                notifications.text,
+                notifications.parse_mode,
+                notifications.attachment_kind,
+                notifications.attachment_file_id,
+                notifications.buttons
+            FROM
+                notifications
+                    INNER JOIN customers
+                    ON customers.id = notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = notifications.product_id
+            WHERE
+                notifications.customer_id = $1
+                AND notifications.status = $2
+                AND ( $3::BIGINT IS NULL OR notifications.product_id = $3 )
+            ORDER BY
+                COALESCE(notifications.sent_at, notifications.send_at, 0) DESC,
+                notifications.id DESC
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .bind(STATUS_SENT)
+        .bind(product_id.map(i64::from))
+        .bind(i64::from(limit))
+        .bind(i64::from(offset))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(PastNotificationRow::into_notification)
+            .collect())
+    }
+
+    async fn get_received_notifications(
+        &self,
+        user_id: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<PastNotification>, DatabaseErrors> {
+        let rows: Vec<PastNotificationRow> = sqlx::query_as(
+            r#"
+            SELECT
+                received_notifications.id,
+                customers.name as customer,
+                products.name as product,
+                received_notifications.received_at as sent_at,
+                received_notifications.text,
+                received_notifications.parse_mode,
+                received_notifications.attachment_kind,
+                received_notifications.attachment_file_id,
+                received_notifications.buttons
+            FROM
+                received_notifications
+                    INNER JOIN customers
+                    ON customers.id = received_notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = received_notifications.product_id
+            WHERE
+                received_notifications.user_id = $1
+            ORDER BY
+                received_notifications.id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(i64::from(limit))
+        .bind(i64::from(offset))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(PastNotificationRow::into_notification)
+            .collect())
+    }
+
+    async fn get_operators(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<Operator>, DatabaseErrors> {
+        if let Some(operators) = self.hash_data.get_operators(customer) {
+            return Ok(operators);
+        }
+        let customer_id = self.customer_id(customer).await?;
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT
+                user_id, role
+            FROM
+                users_customers
+            WHERE
+                customer_id = $1
+            ORDER BY
+                user_id
+            "#,
+        )
+        .bind(i64::from(customer_id))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        let operators: Vec<Operator> = rows
+            .into_iter()
+            .map(|(user_id, role)| Operator {
+                user_id: user_id as u32,
+                role: OperatorRole::from(role.as_str()),
+            })
+            .collect();
+        self.hash_data.insert_operators(customer, &operators);
+        Ok(operators)
+    }
+
+    async fn get_operator_role(
+        &self,
+        user_id: u32,
+        customer: &CustomerName,
+    ) -> Result<Option<OperatorRole>, DatabaseErrors> {
+        if let Some(role) = self.hash_data.get_operator_role(customer, user_id) {
+            return Ok(Some(role));
+        }
+        let customer_id = self.customer_id(customer).await?;
+        let role: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT
+                role
+            FROM
+                users_customers
+            WHERE
+                user_id = $1 AND customer_id = $2
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(i64::from(customer_id))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        let role = role.map(|role| OperatorRole::from(role.as_str()));
+        if let Some(role) = role {
+            self.hash_data.insert_operator(customer, user_id, role);
+        }
+        Ok(role)
+    }
+
+    async fn remove_operator(
+        &mut self,
+        customer: &CustomerName,
+        user_id: u32,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                users_customers
+            WHERE
+                user_id = $1 AND customer_id = $2
+            "#,
+        )
+        .bind(i64::from(user_id))
+        .bind(i64::from(customer_id))
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        self.hash_data.remove_operator(customer, user_id);
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn reload_cache(&self) -> Result<(), DatabaseErrors> {
+        let cache = Self::load_cache(&self.pool).await?;
+        self.hash_data.replace(cache);
+        Ok(())
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        self.hash_data.stats()
+    }
+}
+
+#[derive(FromRow)]
+struct SubscriptionRow {
+    id: i64,
+    customer: String,
+    product: String,
+    channel: String,
+    mode: String,
+    remaining: Option<i64>,
+    expires_at: Option<i64>,
+    active: bool,
+}
+
+impl SubscriptionRow {
+    fn into_subscription(self, now: Timestamp) -> Option<Subscription> {
+        let mode = subscription_mode(&self.mode, self.remaining, self.expires_at);
+        Some(Subscription {
+            id: self.id,
+            customer: parse_customer(&self.customer)?,
+            product: parse_product(&self.product)?,
+            channel: DeliveryChannel::from(self.channel.as_str()),
+            active: self.active && mode.is_valid(now),
+            mode,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct ActiveSubscriptionRow {
+    id: i64,
+    user_id: i64,
+    channel: String,
+    email: Option<String>,
+    mode: String,
+    remaining: Option<i64>,
+    expires_at: Option<i64>,
+}
+
+#[derive(FromRow)]
+struct ScheduledRow {
+    id: i64,
+    user_id: i64,
+    customer: String,
+    product: String,
+    send_at: i64,
+    text: String,
+    parse_mode: String,
+    attachment_kind: Option<String>,
+    attachment_file_id: Option<String>,
+    buttons: String,
+}
+
+impl ScheduledRow {
+    fn into_notification(self) -> Option<(u32, CustomerName, ScheduledNotification)> {
+        let customer = parse_customer(&self.customer)?;
+        let notification = ScheduledNotification {
+            id: self.id,
+            product: parse_product(&self.product)?,
+            send_at: Timestamp::from_millis(self.send_at)?,
+            content: notification_content(
+                self.text,
+                &self.parse_mode,
+                self.attachment_kind,
+                self.attachment_file_id,
+                &self.buttons,
+            ),
+        };
+        Some((self.user_id as u32, customer, notification))
+    }
+}
+
+#[derive(FromRow)]
+struct PastNotificationRow {
+    id: i64,
+    customer: String,
+    product: String,
+    sent_at: Option<i64>,
+    text: String,
+    parse_mode: String,
+    attachment_kind: Option<String>,
+    attachment_file_id: Option<String>,
+    buttons: String,
+}
+
+impl PastNotificationRow {
+    fn into_notification(self) -> Option<PastNotification> {
+        Some(PastNotification {
+            id: self.id,
+            customer: parse_customer(&self.customer)?,
+            product: parse_product(&self.product)?,
+            sent_at: self.sent_at.and_then(Timestamp::from_millis),
+            content: notification_content(
+                self.text,
+                &self.parse_mode,
+                self.attachment_kind,
+                self.attachment_file_id,
+                &self.buttons,
+            ),
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct RecurringRow {
+    id: i64,
+    user_id: i64,
+    customer: String,
+    product: String,
+    schedule: String,
+    timezone: String,
+    paused: bool,
+    next_send_at: Option<i64>,
+    text: String,
+    parse_mode: String,
+    attachment_kind: Option<String>,
+    attachment_file_id: Option<String>,
+    buttons: String,
+}
+
+impl RecurringRow {
+    fn into_notification(self) -> Option<(u32, CustomerName, RecurringNotification)> {
+        let customer = parse_customer(&self.customer)?;
+        let notification = RecurringNotification {
+            id: self.id,
+            product: parse_product(&self.product)?,
+            schedule: self.schedule,
+            timezone: self.timezone,
+            paused: self.paused,
+            next_send_at: self.next_send_at.and_then(Timestamp::from_millis),
+            content: notification_content(
+                self.text,
+                &self.parse_mode,
+                self.attachment_kind,
+                self.attachment_file_id,
+                &self.buttons,
+            ),
+        };
+        Some((self.user_id as u32, customer, notification))
+    }
+}
+
+fn request_error(error: sqlx::Error) -> DatabaseErrors {
+    DatabaseErrors::RequestError(error.to_string())
+}
+
+fn transaction_error(error: sqlx::Error) -> DatabaseErrors {
+    DatabaseErrors::TransactionError(error.to_string())
+}
+
+/// Run against a migrated Postgres database when `DATABASE_URL` points to one,
+/// skipped otherwise. Build the tests before setting it: the `query!` macros of
+/// the SQLite backend check the queries against the same variable.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{common::keys::legacy_key_id, tests as scenarios};
+
+    async fn repository() -> Option<PostgresRepository> {
+        let url = std::env::var("DATABASE_URL").ok()?;
+        if !url.starts_with("postgres") {
+            return None;
+        }
+        PostgresRepository::migrate(&url).await.unwrap();
+        Some(PostgresRepository::new(&url).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn pre_upgrade_keys_authorize_after_the_migration() {
+        let mut repository = match repository().await {
+            Some(repository) => repository,
+            None => return,
+        };
+        let name = format!("shop-{}", rand::random::<u32>());
+        let key = format!("plaintext-legacy-key-{}", rand::random::<u32>());
+        sqlx::query("INSERT INTO customers ( name, key ) VALUES ( $1, $2 )")
+            .bind(&name)
+            .bind(&key)
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        PostgresRepository::hash_plaintext_keys(&repository.pool)
+            .await
+            .unwrap();
+        repository.reload_cache().await.unwrap();
+
+        let (customer, role) = repository
+            .try_authorize(7, key.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(customer.name.as_str(), name);
+        assert_eq!(role, OperatorRole::Owner);
+
+        let key_id: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT
+                customer_keys.key_id
+            FROM
+                customer_keys
+                    INNER JOIN customers
+                    ON customers.id = customer_keys.customer_id
+            WHERE
+                customers.name = $1
+            "#,
+        )
+        .bind(&name)
+        .fetch_one(&repository.pool)
+        .await
+        .unwrap();
+        assert_eq!(key_id, Some(legacy_key_id(&key)));
+        assert!(repository
+            .try_authorize(8, key.clone())
+            .await
+            .unwrap()
+            .is_some());
+        assert!(repository
+            .try_authorize(9, format!("{}-another", key))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn notifies_subscribers_of_the_product() {
+        if let Some(repository) = repository().await {
+            scenarios::notifies_subscribers_of_the_product(repository).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn once_subscriptions_end_after_the_first_notification() {
+        if let Some(repository) = repository().await {
+            scenarios::once_subscriptions_end_after_the_first_notification(repository).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn times_subscriptions_count_down_to_the_last_notification() {
+        if let Some(repository) = repository().await {
+            scenarios::times_subscriptions_count_down_to_the_last_notification(repository).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_until_subscriptions_are_skipped() {
+        if let Some(repository) = repository().await {
+            scenarios::expired_until_subscriptions_are_skipped(repository).await;
+        }
+    }
+}
@@ -0,0 +1,654 @@
+use async_trait::async_trait;
+use domain::models::{CustomerName, DeliveryChannel, OperatorRole, ProductName, Timestamp};
+
+use sqlx::{Sqlite, Transaction};
+
+use super::SqliteRepository;
+use crate::repository::{
+    admin::{
+        AdminRepository, CustomerSummary, KeySummary, NotificationSummary, SubscriptionSummary,
+    },
+    common::{
+        errors::DatabaseErrors, keys, parse_customer, parse_product, STATUS_CANCELLED,
+        STATUS_PENDING,
+    },
+};
+
+impl SqliteRepository {
+    /// Id of the customer, disabled customers included.
+    async fn find_customer_id(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Option<i64>, DatabaseErrors> {
+        let name = customer.as_str();
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                id
+            FROM
+                customers
+            WHERE
+                name = ?1
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.map(|record| record.id))
+    }
+
+    async fn find_product_id(
+        &self,
+        customer_id: i64,
+        product: &ProductName,
+    ) -> Result<Option<i64>, DatabaseErrors> {
+        let name = product.as_str();
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                products.id
+            FROM
+                products
+                    INNER JOIN customers_products
+                    ON products.id = customers_products.product_id
+            WHERE
+                customers_products.customer_id = ?1 AND products.name = ?2
+            "#,
+            customer_id,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.map(|record| record.id))
+    }
+}
+
+#[async_trait]
+impl AdminRepository for SqliteRepository {
+    async fn create_customer(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+    ) -> Result<(), DatabaseErrors> {
+        if self.find_customer_id(customer).await?.is_some() {
+            return Err(DatabaseErrors::CustomerExists(customer.clone()));
+        }
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let name = customer.as_str();
+        let customer_id = sqlx::query!(
+            r#"
+            INSERT INTO
+                customers ( name, key )
+            VALUES
+                ( ?1, '' )
+            "#,
+            name
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?
+        .last_insert_rowid();
+
+        insert_key(
+            &mut transaction,
+            customer_id,
+            key,
+            "",
+            None,
+            false,
+            OperatorRole::Owner,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))
+    }
+
+    async fn list_customers(&self) -> Result<Vec<CustomerSummary>, DatabaseErrors> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                customers.name,
+                customers.disabled,
+                products.name as "product?"
+            FROM
+                customers
+                    LEFT JOIN customers_products
+                    ON customers.id = customers_products.customer_id
+                    LEFT JOIN products
+                    ON customers_products.product_id = products.id
+            ORDER BY
+                customers.name, products.name
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        let mut customers: Vec<CustomerSummary> = vec![];
+        for record in records {
+            let name = match parse_customer(&record.name) {
+                Some(name) => name,
+                None => continue,
+            };
+            if customers.last().map(|customer| &customer.name) != Some(&name) {
+                customers.push(CustomerSummary {
+                    name,
+                    disabled: record.disabled,
+                    products: vec![],
+                });
+            }
+            if let Some(product) = record.product.as_deref().and_then(parse_product) {
+                customers.last_mut().unwrap().products.push(product);
+            }
+        }
+        Ok(customers)
+    }
+
+    async fn set_customer_disabled(
+        &self,
+        customer: &CustomerName,
+        disabled: bool,
+    ) -> Result<bool, DatabaseErrors> {
+        let name = customer.as_str();
+        let result = sqlx::query!(
+            r#"
+            UPDATE
+                customers
+            SET
+                disabled = ?1
+            WHERE
+                name = ?2
+            "#,
+            disabled,
+            name
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn replace_customer_keys(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        sqlx::query!(
+            r#"
+            DELETE FROM
+                customer_keys
+            WHERE
+                customer_id = ?1
+            "#,
+            customer_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        insert_key(
+            &mut transaction,
+            customer_id,
+            key,
+            "",
+            None,
+            false,
+            OperatorRole::Owner,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(true)
+    }
+
+    async fn add_customer_key(
+        &self,
+        customer: &CustomerName,
+        key: &str,
+        label: &str,
+        expires_at: Option<Timestamp>,
+        one_time: bool,
+        role: OperatorRole,
+    ) -> Result<i64, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let id = insert_key(
+            &mut transaction,
+            customer_id,
+            key,
+            label,
+            expires_at,
+            one_time,
+            role,
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(id)
+    }
+
+    async fn list_customer_keys(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<KeySummary>, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                id, label, one_time, role, expires_at, created_at
+            FROM
+                customer_keys
+            WHERE
+                customer_id = ?1
+            ORDER BY
+                id
+            "#,
+            customer_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                Some(KeySummary {
+                    id: record.id,
+                    label: record.label,
+                    one_time: record.one_time,
+                    role: OperatorRole::from(record.role.as_str()),
+                    expires_at: record.expires_at.and_then(Timestamp::from_millis),
+                    created_at: Timestamp::from_millis(record.created_at)?,
+                })
+            })
+            .collect())
+    }
+
+    async fn revoke_customer_key(
+        &self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM
+                customer_keys
+            WHERE
+                id = ?1 AND customer_id = ?2
+            "#,
+            id,
+            customer_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+    ) -> Result<(), DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        if self.find_product_id(customer_id, product).await?.is_some() {
+            return Err(DatabaseErrors::ProductExists(
+                customer.clone(),
+                product.clone(),
+            ));
+        }
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        let name = product.as_str();
+        let product_id = sqlx::query!(
+            r#"
+            INSERT INTO
+                products ( name )
+            VALUES
+                ( ?1 )
+            "#,
+            name
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?
+        .last_insert_rowid();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                customers_products ( customer_id, product_id )
+            VALUES
+                ( ?1, ?2 )
+            "#,
+            customer_id,
+            product_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))
+    }
+
+    async fn remove_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+        let product_id = match self.find_product_id(customer_id, product).await? {
+            Some(product_id) => product_id,
+            None => return Ok(false),
+        };
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        sqlx::query!(
+            r#"
+            DELETE FROM
+                active_subscriptions
+            WHERE
+                subscription_id IN (
+                    SELECT id FROM subscriptions WHERE customer_id = ?1 AND product_id = ?2
+                )
+            "#,
+            customer_id,
+            product_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query!(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = ?1
+            WHERE
+                customer_id = ?2 AND product_id = ?3 AND status = ?4
+            "#,
+            STATUS_CANCELLED,
+            customer_id,
+            product_id,
+            STATUS_PENDING
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query!(
+            r#"
+            UPDATE
+                recurring_notifications
+            SET
+                paused = TRUE, next_send_at = NULL
+            WHERE
+                customer_id = ?1 AND product_id = ?2
+            "#,
+            customer_id,
+            product_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM
+                customers_products
+            WHERE
+                customer_id = ?1 AND product_id = ?2
+            "#,
+            customer_id,
+            product_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(request_error)?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(true)
+    }
+
+    async fn rename_product(
+        &self,
+        customer: &CustomerName,
+        product: &ProductName,
+        new_name: &ProductName,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = match self.find_customer_id(customer).await? {
+            Some(customer_id) => customer_id,
+            None => return Ok(false),
+        };
+        let product_id = match self.find_product_id(customer_id, product).await? {
+            Some(product_id) => product_id,
+            None => return Ok(false),
+        };
+        if self.find_product_id(customer_id, new_name).await?.is_some() {
+            return Err(DatabaseErrors::ProductExists(
+                customer.clone(),
+                new_name.clone(),
+            ));
+        }
+
+        let name = new_name.as_str();
+        sqlx::query!(
+            r#"
+            UPDATE
+                products
+            SET
+                name = ?1
+            WHERE
+                id = ?2
+            "#,
+            name,
+            product_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(true)
+    }
+
+    async fn list_subscriptions(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<SubscriptionSummary>, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                subscriptions.id,
+                subscriptions.user_id,
+                products.name as product,
+                subscriptions.channel,
+                subscriptions.email,
+                EXISTS (
+                    SELECT 1 FROM active_subscriptions
+                    WHERE active_subscriptions.subscription_id = subscriptions.id
+                ) as "active!: bool"
+            FROM
+                subscriptions
+                    INNER JOIN products
+                    ON subscriptions.product_id = products.id
+            WHERE
+                subscriptions.customer_id = ?1
+            ORDER BY
+                subscriptions.id
+            "#,
+            customer_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                Some(SubscriptionSummary {
+                    id: record.id,
+                    user_id: record.user_id as u32,
+                    product: parse_product(&record.product)?,
+                    channel: DeliveryChannel::from(record.channel.as_str()),
+                    email: record.email,
+                    active: record.active,
+                })
+            })
+            .collect())
+    }
+
+    async fn list_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<NotificationSummary>, DatabaseErrors> {
+        let customer_id = self
+            .find_customer_id(customer)
+            .await?
+            .ok_or_else(|| DatabaseErrors::UnknownCustomer(customer.clone()))?;
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                notifications.id,
+                products.name as product,
+                notifications.status,
+                notifications.send_at,
+                notifications.text
+            FROM
+                notifications
+                    INNER JOIN products
+                    ON notifications.product_id = products.id
+            WHERE
+                notifications.customer_id = ?1
+            ORDER BY
+                notifications.id
+            "#,
+            customer_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                Some(NotificationSummary {
+                    id: record.id,
+                    product: parse_product(&record.product)?,
+                    status: record.status,
+                    send_at: record.send_at.and_then(Timestamp::from_millis),
+                    text: record.text,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Stores the hash of a new key of the customer and returns its id.
+pub(super) async fn insert_key(
+    transaction: &mut Transaction<'_, Sqlite>,
+    customer_id: i64,
+    key: &str,
+    label: &str,
+    expires_at: Option<Timestamp>,
+    one_time: bool,
+    role: OperatorRole,
+) -> Result<i64, DatabaseErrors> {
+    let key_id = keys::key_id(key);
+    let hash = keys::hash_key(key)?;
+    let expires_at = expires_at.map(|expires_at| expires_at.as_millis());
+    let created_at = Timestamp::now().as_millis();
+    let role = role.as_str();
+    let id = sqlx::query!(
+        r#"
+        INSERT INTO
+            customer_keys ( customer_id, key_id, hash, label, one_time, expires_at, created_at, role )
+        VALUES
+            ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8 )
+        "#,
+        customer_id,
+        key_id,
+        hash,
+        label,
+        one_time,
+        expires_at,
+        created_at,
+        role
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(request_error)?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+fn request_error(error: sqlx::Error) -> DatabaseErrors {
+    DatabaseErrors::RequestError(error.to_string())
+}
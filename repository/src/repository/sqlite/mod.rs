@@ -1,9 +1,36 @@
-use std::collections::HashMap;
+mod admin;
 
-use domain::models::{Customer, Notification, Product};
-use sqlx::SqlitePool;
+use async_trait::async_trait;
+use domain::models::{
+    Customer, CustomerName, DeliveryChannel, Notification, NotificationContent, Operator,
+    OperatorRole, PastNotification, Product, ProductName, RecurringNotification,
+    ScheduledNotification, Subscription, SubscriptionMode, Timestamp,
+};
+use std::str::FromStr;
 
-use crate::repository::common::errors::DatabaseErrors;
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    sqlite::SqliteConnectOptions,
+    SqlitePool,
+};
+
+use crate::repository::{
+    common::{
+        cache::{CacheData, CacheStats},
+        delivery,
+        errors::DatabaseErrors,
+        keys::{find_key, hash_key, key_id, legacy_key_id, StoredKey},
+        notification_content, parse_customer, parse_product, subscription_mode, Delivery,
+        STATUS_CANCELLED, STATUS_PENDING, STATUS_SENT,
+    },
+    Repository,
+};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Initial migrations rewritten to be non-destructive. Databases created by
+/// the old versions get the stored checksum replaced instead of failing.
+const REWRITTEN_MIGRATIONS: [i64; 1] = [20220916095818];
 
 pub struct SqliteRepository {
     pool: SqlitePool,
@@ -14,44 +41,455 @@ impl SqliteRepository {
     pub async fn new(url: &str) -> Result<Self, DatabaseErrors> {
         match SqlitePool::connect(url).await {
             Ok(pool) => {
-                let hash_data = CacheData::new(&pool).await;
+                let hash_data = Self::load_cache(&pool).await?;
                 Ok(SqliteRepository { pool, hash_data })
             }
             Err(error) => Err(DatabaseErrors::ConnectionProblem(error.to_string())),
         }
     }
 
-    pub async fn get_customers(&self) -> Result<Vec<Customer>, DatabaseErrors> {
+    /// Creates the database file if needed and applies pending migrations.
+    pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+        let options = SqliteConnectOptions::from_str(url)
+            .map_err(|error| DatabaseErrors::ConnectionProblem(error.to_string()))?
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options)
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionProblem(error.to_string()))?;
+        Self::apply_migrations(&pool).await
+    }
+
+    async fn apply_migrations(pool: &SqlitePool) -> Result<(), DatabaseErrors> {
+        let mut connection = pool
+            .acquire()
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionProblem(error.to_string()))?;
+        connection
+            .ensure_migrations_table()
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+        let rewritten = MIGRATOR
+            .iter()
+            .filter(|migration| REWRITTEN_MIGRATIONS.contains(&migration.version));
+        for migration in rewritten {
+            sqlx::query("UPDATE _sqlx_migrations SET checksum = ?1 WHERE version = ?2")
+                .bind(migration.checksum.as_ref())
+                .bind(migration.version)
+                .execute(&mut connection)
+                .await
+                .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+        }
+        drop(connection);
+
+        MIGRATOR
+            .run(pool)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+        Self::hash_plaintext_keys(pool).await
+    }
+
+    /// Moves the keys stored in plain text by older versions into `customer_keys`.
+    /// They have no key id until their first authorization, see `find_stored_key`.
+    async fn hash_plaintext_keys(pool: &SqlitePool) -> Result<(), DatabaseErrors> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                id, key
+            FROM
+                customers
+            WHERE
+                key != ''
+            "#
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+
+        if !records.is_empty() {
+            log::warn!("Hashed {} plain text keys", records.len());
+        }
+        let created_at = Timestamp::now().as_millis();
+        for record in records {
+            let hash = hash_key(&record.key)?;
+            let mut transaction = pool
+                .begin()
+                .await
+                .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+            sqlx::query!(
+                r#"
+                INSERT INTO
+                    customer_keys ( customer_id, hash, created_at )
+                VALUES
+                    ( ?1, ?2, ?3 )
+                "#,
+                record.id,
+                hash,
+                created_at
+            )
+            .execute(&mut transaction)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+
+            sqlx::query!(
+                r#"
+                UPDATE
+                    customers
+                SET
+                    key = ''
+                WHERE
+                    id = ?1
+                "#,
+                record.id
+            )
+            .execute(&mut transaction)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))?;
+
+            transaction
+                .commit()
+                .await
+                .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Stored key matching `key`. Keys issued before key ids are checked
+    /// against the keys without one and rehashed under their legacy key id,
+    /// so they are found by it from then on.
+    async fn find_stored_key(
+        &self,
+        key: String,
+        now: Timestamp,
+    ) -> Result<Option<StoredKey>, DatabaseErrors> {
+        if let Some(key_id) = key_id(&key) {
+            let stored_keys = self.get_stored_keys(Some(key_id), now).await?;
+            return Ok(find_key(key, stored_keys).await);
+        }
+        let legacy_key_id = legacy_key_id(&key);
+        let stored_keys = self.get_stored_keys(Some(&legacy_key_id), now).await?;
+        if let Some(stored_key) = find_key(key.clone(), stored_keys).await {
+            return Ok(Some(stored_key));
+        }
+        let stored_keys = self.get_stored_keys(None, now).await?;
+        let stored_key = match find_key(key.clone(), stored_keys).await {
+            Some(stored_key) => stored_key,
+            None => return Ok(None),
+        };
+        if let Err(error) = self
+            .rehash_legacy_key(stored_key.id, &legacy_key_id, &key)
+            .await
+        {
+            log::error!("Failed to rehash legacy key [{}]: {}", stored_key.id, error);
+        }
+        Ok(Some(stored_key))
+    }
+
+    async fn rehash_legacy_key(
+        &self,
+        id: i64,
+        key_id: &str,
+        key: &str,
+    ) -> Result<(), DatabaseErrors> {
+        let hash = hash_key(key)?;
+        sqlx::query!(
+            r#"
+            UPDATE
+                customer_keys
+            SET
+                key_id = ?1,
+                hash = ?2
+            WHERE
+                id = ?3
+                AND key_id IS NULL
+            "#,
+            key_id,
+            hash,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|error| DatabaseErrors::RequestError(error.to_string()))?;
+        Ok(())
+    }
+
+    /// Unexpired keys of enabled customers with the key id, or without one.
+    async fn get_stored_keys(
+        &self,
+        key_id: Option<&str>,
+        now: Timestamp,
+    ) -> Result<Vec<StoredKey>, DatabaseErrors> {
+        let now = now.as_millis();
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                customer_keys.id,
+                customer_keys.hash,
+                customer_keys.one_time,
+                customer_keys.role,
+                customers.name as customer
+            FROM
+                customer_keys
+                    INNER JOIN customers
+                    ON customer_keys.customer_id = customers.id
+            WHERE
+                customer_keys.key_id IS ?1
+                AND (customer_keys.expires_at IS NULL OR customer_keys.expires_at > ?2)
+                AND NOT customers.disabled
+            "#,
+            key_id,
+            now
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|error| DatabaseErrors::RequestError(error.to_string()))?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| StoredKey {
+                id: record.id,
+                hash: record.hash,
+                one_time: record.one_time,
+                role: record.role,
+                customer: record.customer,
+            })
+            .collect())
+    }
+
+    async fn load_cache(pool: &SqlitePool) -> Result<CacheData, DatabaseErrors> {
+        let customers = Self::get_all_customers(pool).await?;
+        let products = Self::get_all_products(pool).await?;
+        let users_customers = Self::get_users_customers(pool).await?;
+        Ok(CacheData::from_rows(customers, products, users_customers))
+    }
+
+    async fn get_all_customers(pool: &SqlitePool) -> Result<Vec<(i64, String)>, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                id, name 
+            FROM 
+                customers 
+            WHERE 
+                NOT disabled
+            "#
+        )
+        .fetch_all(pool)
+        .await;
+
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .map(|record| (record.id, record.name))
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn get_all_products(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(String, i64, String)>, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                customers.name as customer,
+                products.id,
+                products.name 
+            FROM 
+                products 
+                    INNER JOIN customers_products 
+                    ON products.id = customers_products.product_id
+                    INNER JOIN customers
+                    ON customers_products.customer_id = customers.id
+            WHERE 
+                NOT customers.disabled
+            "#
+        )
+        .fetch_all(pool)
+        .await;
+
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .map(|record| (record.customer, record.id, record.name))
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn get_users_customers(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(String, i64, String)>, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                customers.name,
+                users_customers.user_id,
+                users_customers.role
+            FROM 
+                users_customers
+                LEFT JOIN customers
+                ON users_customers.customer_id = customers.id
+            WHERE 
+                NOT customers.disabled
+            "#
+        )
+        .fetch_all(pool)
+        .await;
+
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .map(|record| (record.name, record.user_id, record.role))
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    /// Id of the customer. On a cache miss the customer is looked up in the
+    /// database and cached with its products.
+    async fn customer_id(&self, customer: &CustomerName) -> Result<u32, DatabaseErrors> {
+        if let Some(id) = self.hash_data.get_customer_id(customer) {
+            return Ok(id);
+        }
+        let name = customer.as_str();
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                id 
+            FROM 
+                customers 
+            WHERE 
+                name = ?1 AND NOT disabled
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        let id = match result {
+            Ok(Some(record)) => record.id,
+            Ok(None) => return Err(DatabaseErrors::UnknownCustomer(customer.clone())),
+            Err(error) => return Err(DatabaseErrors::RequestError(error.to_string())),
+        };
+
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                products.id,
+                products.name 
+            FROM 
+                products 
+                    INNER JOIN customers_products 
+                    ON products.id = customers_products.product_id
+            WHERE 
+                customers_products.customer_id = ?1
+            "#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        let products = match result {
+            Ok(records) => records
+                .into_iter()
+                .map(|record| (record.id, record.name))
+                .collect(),
+            Err(error) => return Err(DatabaseErrors::RequestError(error.to_string())),
+        };
+        self.hash_data
+            .insert_customer(customer, id as u32, products);
+        Ok(id as u32)
+    }
+
+    /// Id of the product of the customer, looked up in the database on a cache miss.
+    async fn product_id(
+        &self,
+        customer: &CustomerName,
+        customer_id: u32,
+        product: &ProductName,
+    ) -> Result<u32, DatabaseErrors> {
+        if let Some(id) = self.hash_data.get_product_id(customer, product) {
+            return Ok(id);
+        }
+        let name = product.as_str();
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                products.id 
+            FROM 
+                products 
+                    INNER JOIN customers_products 
+                    ON products.id = customers_products.product_id
+            WHERE 
+                customers_products.customer_id = ?1 AND products.name = ?2
+            "#,
+            customer_id,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Ok(Some(record)) => {
+                self.hash_data
+                    .insert_product(customer, product, record.id as u32);
+                Ok(record.id as u32)
+            }
+            Ok(None) => Err(DatabaseErrors::UnknownProduct(
+                customer.clone(),
+                product.clone(),
+            )),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn get_customers(&self) -> Result<Vec<Customer>, DatabaseErrors> {
         Ok(self.hash_data.get_customers())
     }
 
-    pub async fn get_products(&self, customer: &str) -> Result<Vec<Product>, DatabaseErrors> {
+    async fn get_products(&self, customer: &CustomerName) -> Result<Vec<Product>, DatabaseErrors> {
+        self.customer_id(customer).await?;
         Ok(self.hash_data.get_customer_products(customer))
     }
 
-    pub async fn add_subscription(
+    async fn add_subscription(
         &mut self,
         user_id: u32,
-        customer: &str,
-        product: &str,
+        customer: &CustomerName,
+        product: &ProductName,
+        channel: DeliveryChannel,
+        email: Option<&str>,
+        mode: SubscriptionMode,
     ) -> Result<(), DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+        let channel = channel.as_str();
+        let remaining = mode.count();
+        let expires_at = mode.until().map(|until| until.as_millis());
+        let mode = mode.as_str();
         let mut transaction = match self.pool.begin().await {
             Ok(transaction) => transaction,
             Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
         };
 
-        let customer_id = self.hash_data.get_customer_id(customer);
-        let product_id = self.hash_data.get_product_id(customer, product);
         let result = sqlx::query!(
             r#"
             INSERT INTO 
-                subscriptions ( user_id, customer_id, product_id )
+                subscriptions ( user_id, customer_id, product_id, channel, email, mode, remaining, expires_at )
             VALUES 
-                ( ?1, ?2, ?3 )
+                ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8 )
             "#,
             user_id,
             customer_id,
-            product_id
+            product_id,
+            channel,
+            email,
+            mode,
+            remaining,
+            expires_at
         )
         .execute(&mut transaction)
         .await;
@@ -80,136 +518,893 @@ impl SqliteRepository {
             return Err(DatabaseErrors::RequestError(error.to_string()));
         }
 
-        if let Err(error) = transaction.commit().await {
-            return Err(DatabaseErrors::TransactionError(error.to_string()));
+        if let Err(error) = transaction.commit().await {
+            return Err(DatabaseErrors::TransactionError(error.to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn get_user_subscriptions(
+        &self,
+        user_id: u32,
+    ) -> Result<Vec<Subscription>, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                subscriptions.id,
+                customers.name as customer,
+                products.name as product,
+                subscriptions.channel,
+                subscriptions.mode,
+                subscriptions.remaining,
+                subscriptions.expires_at,
+                EXISTS (
+                    SELECT 1 FROM active_subscriptions
+                    WHERE active_subscriptions.subscription_id = subscriptions.id
+                ) as "active!: bool"
+            FROM
+                subscriptions
+                    INNER JOIN customers
+                    ON customers.id = subscriptions.customer_id
+                    INNER JOIN products
+                    ON products.id = subscriptions.product_id
+            WHERE
+                subscriptions.user_id = ?1
+            ORDER BY
+                subscriptions.id
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        let now = Timestamp::now();
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    let mode = subscription_mode(&record.mode, record.remaining, record.expires_at);
+                    Some(Subscription {
+                        id: record.id,
+                        customer: parse_customer(&record.customer)?,
+                        product: parse_product(&record.product)?,
+                        channel: DeliveryChannel::from(record.channel.as_str()),
+                        active: record.active && mode.is_valid(now),
+                        mode,
+                    })
+                })
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn cancel_subscription(&mut self, user_id: u32, id: i64) -> Result<bool, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM
+                subscriptions
+            WHERE
+                id = ?1
+                AND user_id = ?2
+                AND id IN ( SELECT subscription_id FROM active_subscriptions )
+            "#,
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn cancel_all_subscriptions(&mut self, user_id: u32) -> Result<u64, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM
+                subscriptions
+            WHERE
+                user_id = ?1
+                AND id IN ( SELECT subscription_id FROM active_subscriptions )
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn try_authorize(
+        &mut self,
+        user_id: u32,
+        key: String,
+    ) -> Result<Option<(Customer, OperatorRole)>, DatabaseErrors> {
+        let stored_key = match self.find_stored_key(key, Timestamp::now()).await? {
+            Some(stored_key) => stored_key,
+            None => return Ok(None),
+        };
+        let customer = match parse_customer(&stored_key.customer) {
+            Some(customer) => customer,
+            None => return Ok(None),
+        };
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
+        };
+
+        if stored_key.one_time {
+            let result = sqlx::query!(
+                r#"
+                DELETE FROM
+                    customer_keys
+                WHERE
+                    id = ?1
+                "#,
+                stored_key.id
+            )
+            .execute(&mut transaction)
+            .await;
+
+            match result {
+                // Used up by a concurrent authorization.
+                Ok(result) if result.rows_affected() == 0 => return Ok(None),
+                Ok(_) => {}
+                Err(error) => return Err(DatabaseErrors::RequestError(error.to_string())),
+            }
+        }
+
+        let role = OperatorRole::from(stored_key.role.as_str());
+        let role_name = role.as_str();
+        let customer_id = self.customer_id(&customer).await?;
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO 
+                    users_customers ( user_id, customer_id, role )
+                VALUES 
+                    ( ?1, ?2, ?3 )
+                ON CONFLICT ( user_id, customer_id ) DO UPDATE SET
+                    role = excluded.role
+                "#,
+            user_id,
+            customer_id,
+            role_name
+        )
+        .execute(&mut transaction)
+        .await;
+
+        if let Err(error) = result {
+            return Err(DatabaseErrors::RequestError(error.to_string()));
+        }
+        if let Err(error) = transaction.commit().await {
+            return Err(DatabaseErrors::TransactionError(error.to_string()));
+        }
+
+        self.hash_data.insert_operator(&customer, user_id, role);
+        Ok(Some((Customer { name: customer }, role)))
+    }
+
+    async fn add_invite_key(
+        &mut self,
+        customer: &CustomerName,
+        key: &str,
+        role: OperatorRole,
+        expires_at: Timestamp,
+    ) -> Result<(), DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
+        };
+        admin::insert_key(
+            &mut transaction,
+            customer_id as i64,
+            key,
+            "invite",
+            Some(expires_at),
+            true,
+            role,
+        )
+        .await?;
+        if let Err(error) = transaction.commit().await {
+            return Err(DatabaseErrors::TransactionError(error.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn get_authorization_lock(
+        &self,
+        user_id: u32,
+        now: Timestamp,
+    ) -> Result<Option<Timestamp>, DatabaseErrors> {
+        let now = now.as_millis();
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                locked_until as "locked_until!"
+            FROM
+                authorization_failures
+            WHERE
+                user_id = ?1 AND locked_until > ?2
+            "#,
+            user_id,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Ok(record) => Ok(record.and_then(|record| Timestamp::from_millis(record.locked_until))),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn add_authorization_failure(
+        &mut self,
+        user_id: u32,
+        max_failures: u32,
+        lock_until: Timestamp,
+    ) -> Result<Option<Timestamp>, DatabaseErrors> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                authorization_failures ( user_id, failures )
+            VALUES
+                ( ?1, 1 )
+            ON CONFLICT ( user_id ) DO UPDATE SET
+                failures = failures + 1
+            "#,
+            user_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(|error| DatabaseErrors::RequestError(error.to_string()))?;
+
+        let failures = sqlx::query!(
+            r#"
+            SELECT
+                failures
+            FROM
+                authorization_failures
+            WHERE
+                user_id = ?1
+            "#,
+            user_id
+        )
+        .fetch_one(&mut transaction)
+        .await
+        .map_err(|error| DatabaseErrors::RequestError(error.to_string()))?
+        .failures;
+
+        let locked = failures >= i64::from(max_failures);
+        if locked {
+            let lock_until = lock_until.as_millis();
+            sqlx::query!(
+                r#"
+                UPDATE
+                    authorization_failures
+                SET
+                    failures = 0, locked_until = ?1
+                WHERE
+                    user_id = ?2
+                "#,
+                lock_until,
+                user_id
+            )
+            .execute(&mut transaction)
+            .await
+            .map_err(|error| DatabaseErrors::RequestError(error.to_string()))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|error| DatabaseErrors::TransactionError(error.to_string()))?;
+        Ok(locked.then_some(lock_until))
+    }
+
+    async fn clear_authorization_failures(&mut self, user_id: u32) -> Result<(), DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM
+                authorization_failures
+            WHERE
+                user_id = ?1
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn get_products_for_notification(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<Product>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                products.name
+            FROM 
+                subscriptions
+                    INNER JOIN active_subscriptions 
+                    ON subscriptions.id = active_subscriptions.subscription_id 
+                    LEFT JOIN products
+                    ON subscriptions.product_id = products.id
+            WHERE
+                subscriptions.customer_id = ?1               
+            "#,
+            customer_id
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        if let Err(error) = result {
+            return Err(DatabaseErrors::RequestError(error.to_string()));
+        }
+
+        Ok(result
+            .unwrap()
+            .iter()
+            .filter_map(|record| parse_product(&record.name))
+            .map(|name| Product { name })
+            .collect())
+    }
+
+    async fn add_notification(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        content: NotificationContent,
+        send_at: Option<Timestamp>,
+    ) -> Result<i64, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
+        };
+
+        let parse_mode = content.parse_mode.as_str();
+        let attachment_kind = content
+            .attachment
+            .as_ref()
+            .map(|attachment| attachment.kind());
+        let attachment_file_id = content
+            .attachment
+            .as_ref()
+            .map(|attachment| attachment.file_id());
+        let buttons = serde_json::to_string(&content.buttons).unwrap();
+        let send_at = send_at.map(|send_at| send_at.as_millis());
+        let (status, sent_at) = match send_at {
+            Some(_) => (STATUS_PENDING, None),
+            None => (STATUS_SENT, Some(Timestamp::now().as_millis())),
+        };
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO 
+                notifications ( customer_id, product_id, text, parse_mode, attachment_kind, attachment_file_id, buttons, user_id, send_at, status, sent_at )
+            VALUES 
+                ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11 )
+            "#,
+            customer_id,
+            product_id,
+            content.text,
+            parse_mode,
+            attachment_kind,
+            attachment_file_id,
+            buttons,
+            user_id,
+            send_at,
+            status,
+            sent_at
+        )
+        .execute(&mut transaction)
+        .await;
+
+        let id = match result {
+            Ok(result) => result.last_insert_rowid(),
+            Err(error) => {
+                if let Err(error) = transaction.rollback().await {
+                    return Err(DatabaseErrors::TransactionError(error.to_string()));
+                }
+                return Err(DatabaseErrors::RequestError(error.to_string()));
+            }
+        };
+        if let Err(error) = transaction.commit().await {
+            return Err(DatabaseErrors::TransactionError(error.to_string()));
+        }
+
+        Ok(id)
+    }
+
+    async fn get_scheduled_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<ScheduledNotification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                notifications.id,
+                products.name as product,
+                notifications.send_at as "send_at!",
+                notifications.text,
+                notifications.parse_mode,
+                notifications.attachment_kind,
+                notifications.attachment_file_id,
+                notifications.buttons
+            FROM
+                notifications
+                    INNER JOIN products
+                    ON products.id = notifications.product_id
+            WHERE
+                notifications.customer_id = ?1
+                AND notifications.status = ?2
+            ORDER BY
+                notifications.send_at
+            "#,
+            customer_id,
+            STATUS_PENDING
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    Some(ScheduledNotification {
+                        id: record.id,
+                        product: parse_product(&record.product)?,
+                        send_at: Timestamp::from_millis(record.send_at)?,
+                        content: notification_content(
+                            record.text,
+                            &record.parse_mode,
+                            record.attachment_kind,
+                            record.attachment_file_id,
+                            &record.buttons,
+                        ),
+                    })
+                })
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn cancel_scheduled_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = ?1
+            WHERE
+                id = ?2
+                AND customer_id = ?3
+                AND status = ?4
+            "#,
+            STATUS_CANCELLED,
+            id,
+            customer_id,
+            STATUS_PENDING
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn add_recurring_notification(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        product: &ProductName,
+        schedule: &str,
+        timezone: &str,
+        content: NotificationContent,
+        next_send_at: Timestamp,
+    ) -> Result<i64, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+
+        let parse_mode = content.parse_mode.as_str();
+        let attachment_kind = content
+            .attachment
+            .as_ref()
+            .map(|attachment| attachment.kind());
+        let attachment_file_id = content
+            .attachment
+            .as_ref()
+            .map(|attachment| attachment.file_id());
+        let buttons = serde_json::to_string(&content.buttons).unwrap();
+        let next_send_at = next_send_at.as_millis();
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO 
+                recurring_notifications ( user_id, customer_id, product_id, schedule, timezone, next_send_at, text, parse_mode, attachment_kind, attachment_file_id, buttons )
+            VALUES 
+                ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11 )
+            "#,
+            user_id,
+            customer_id,
+            product_id,
+            schedule,
+            timezone,
+            next_send_at,
+            content.text,
+            parse_mode,
+            attachment_kind,
+            attachment_file_id,
+            buttons
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result.last_insert_rowid()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn get_recurring_notifications(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<RecurringNotification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                recurring_notifications.id,
+                products.name as product,
+                recurring_notifications.schedule,
+                recurring_notifications.timezone,
+                recurring_notifications.paused,
+                recurring_notifications.next_send_at,
+                recurring_notifications.text,
+                recurring_notifications.parse_mode,
+                recurring_notifications.attachment_kind,
+                recurring_notifications.attachment_file_id,
+                recurring_notifications.buttons
+            FROM
+                recurring_notifications
+                    INNER JOIN products
+                    ON products.id = recurring_notifications.product_id
+            WHERE
+                recurring_notifications.customer_id = ?1
+            ORDER BY
+                recurring_notifications.id
+            "#,
+            customer_id
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    Some(RecurringNotification {
+                        id: record.id,
+                        product: parse_product(&record.product)?,
+                        schedule: record.schedule,
+                        timezone: record.timezone,
+                        paused: record.paused,
+                        next_send_at: record.next_send_at.and_then(Timestamp::from_millis),
+                        content: notification_content(
+                            record.text,
+                            &record.parse_mode,
+                            record.attachment_kind,
+                            record.attachment_file_id,
+                            &record.buttons,
+                        ),
+                    })
+                })
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn get_recurring_schedule(
+        &self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<Option<(String, String)>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                schedule,
+                timezone
+            FROM
+                recurring_notifications
+            WHERE
+                id = ?1
+                AND customer_id = ?2
+            "#,
+            id,
+            customer_id
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Ok(record) => Ok(record.map(|record| (record.schedule, record.timezone))),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn set_recurring_notification_paused(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+        paused: bool,
+        next_send_at: Option<Timestamp>,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let next_send_at = next_send_at.map(|next_send_at| next_send_at.as_millis());
+        let result = sqlx::query!(
+            r#"
+            UPDATE
+                recurring_notifications
+            SET
+                paused = ?1,
+                next_send_at = ?2
+            WHERE
+                id = ?3
+                AND customer_id = ?4
+            "#,
+            paused,
+            next_send_at,
+            id,
+            customer_id
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn delete_recurring_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM
+                recurring_notifications
+            WHERE
+                id = ?1
+                AND customer_id = ?2
+            "#,
+            id,
+            customer_id
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
         }
-
-        Ok(())
     }
 
-    pub async fn try_authorize(
-        &mut self,
-        user_id: u32,
-        key: String,
-    ) -> Result<Option<Customer>, DatabaseErrors> {
+    async fn get_due_recurring_notifications(
+        &self,
+        now: Timestamp,
+    ) -> Result<Vec<(u32, CustomerName, RecurringNotification)>, DatabaseErrors> {
+        let now = now.as_millis();
         let result = sqlx::query!(
             r#"
-            SELECT 
-                name 
-            FROM 
-                customers 
+            SELECT
+                recurring_notifications.id,
+                recurring_notifications.user_id,
+                customers.name as customer,
+                products.name as product,
+                recurring_notifications.schedule,
+                recurring_notifications.timezone,
+                recurring_notifications.paused,
+                recurring_notifications.next_send_at,
+                recurring_notifications.text,
+                recurring_notifications.parse_mode,
+                recurring_notifications.attachment_kind,
+                recurring_notifications.attachment_file_id,
+                recurring_notifications.buttons
+            FROM
+                recurring_notifications
+                    INNER JOIN customers
+                    ON customers.id = recurring_notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = recurring_notifications.product_id
             WHERE
-                key = ?1   
+                recurring_notifications.paused = FALSE
+                AND recurring_notifications.next_send_at <= ?1
+            ORDER BY
+                recurring_notifications.next_send_at
             "#,
-            key
+            now
         )
         .fetch_all(&self.pool)
         .await;
 
-        if let Err(error) = result {
-            return Err(DatabaseErrors::RequestError(error.to_string()));
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    let customer = parse_customer(&record.customer)?;
+                    let notification = RecurringNotification {
+                        id: record.id,
+                        product: parse_product(&record.product)?,
+                        schedule: record.schedule,
+                        timezone: record.timezone,
+                        paused: record.paused,
+                        next_send_at: record.next_send_at.and_then(Timestamp::from_millis),
+                        content: notification_content(
+                            record.text,
+                            &record.parse_mode,
+                            record.attachment_kind,
+                            record.attachment_file_id,
+                            &record.buttons,
+                        ),
+                    };
+                    Some((record.user_id as u32, customer, notification))
+                })
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
         }
+    }
 
-        let mut customers: Vec<String> = result
-            .unwrap()
-            .iter()
-            .map(|record| record.name.to_string())
-            .collect();
-
-        let customer = match customers.pop() {
-            Some(customer) => customer,
-            None => return Ok(None),
-        };
-
-        let mut transaction = match self.pool.begin().await {
-            Ok(transaction) => transaction,
-            Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
-        };
-
-        let customer_id = self.hash_data.get_customer_id(&customer);
+    async fn set_recurring_next_send_at(
+        &mut self,
+        id: i64,
+        next_send_at: Option<Timestamp>,
+    ) -> Result<(), DatabaseErrors> {
+        let next_send_at = next_send_at.map(|next_send_at| next_send_at.as_millis());
         let result = sqlx::query!(
             r#"
-                INSERT INTO 
-                    users_customers ( user_id, customer_id )
-                VALUES 
-                    ( ?1, ?2 )
-                "#,
-            user_id,
-            customer_id
+            UPDATE
+                recurring_notifications
+            SET
+                next_send_at = ?1
+            WHERE
+                id = ?2
+            "#,
+            next_send_at,
+            id
         )
-        .execute(&mut transaction)
+        .execute(&self.pool)
         .await;
 
-        if let Err(error) = result {
-            return Err(DatabaseErrors::RequestError(error.to_string()));
-        }
-        if let Err(error) = transaction.commit().await {
-            return Err(DatabaseErrors::TransactionError(error.to_string()));
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
         }
-
-        self.hash_data.insert_user_for_customer(&customer, user_id);
-        Ok(Some(Customer { name: customer }))
     }
 
-    pub async fn get_products_for_notification(
-        &self,
-        customer: &str,
-    ) -> Result<Vec<Product>, DatabaseErrors> {
-        let customer_id = self.hash_data.get_customer_id(customer);
+    async fn defer_notification(
+        &mut self,
+        id: i64,
+        send_at: Timestamp,
+    ) -> Result<bool, DatabaseErrors> {
+        let send_at = send_at.as_millis();
         let result = sqlx::query!(
             r#"
-            SELECT DISTINCT
-                products.name
-            FROM 
-                subscriptions
-                    INNER JOIN active_subscriptions 
-                    ON subscriptions.id = active_subscriptions.subscription_id 
-                    LEFT JOIN products
-                    ON subscriptions.product_id = products.id
+            UPDATE
+                notifications
+            SET
+                status = ?1,
+                send_at = ?2,
+                sent_at = NULL
             WHERE
-                subscriptions.customer_id = ?1               
+                id = ?3
+                AND status = ?4
             "#,
-            customer_id
+            STATUS_PENDING,
+            send_at,
+            id,
+            STATUS_SENT
         )
-        .fetch_all(&self.pool)
+        .execute(&self.pool)
         .await;
 
-        if let Err(error) = result {
-            return Err(DatabaseErrors::RequestError(error.to_string()));
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
         }
-
-        Ok(result
-            .unwrap()
-            .iter()
-            .map(|record| Product {
-                name: record.name.to_string(),
-            })
-            .collect())
     }
 
-    pub async fn add_notification(
+    async fn take_due_notifications(
         &mut self,
-        customer: &str,
-        product: &str,
-        text: String,
-    ) -> Result<(), DatabaseErrors> {
+        now: Timestamp,
+    ) -> Result<Vec<(u32, CustomerName, ScheduledNotification)>, DatabaseErrors> {
+        let now = now.as_millis();
         let mut transaction = match self.pool.begin().await {
             Ok(transaction) => transaction,
             Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
         };
 
-        let customer_id = self.hash_data.get_customer_id(customer);
-        let product_id = self.hash_data.get_product_id(customer, product);
         let result = sqlx::query!(
             r#"
-            INSERT INTO 
-                notifications ( customer_id, product_id, text )
-            VALUES 
-                ( ?1, ?2, ?3 )
+            SELECT
+                notifications.id,
+                notifications.user_id,
+                customers.name as customer,
+                products.name as product,
+                notifications.send_at as "send_at!",
+                notifications.text,
+                notifications.parse_mode,
+                notifications.attachment_kind,
+                notifications.attachment_file_id,
+                notifications.buttons
+            FROM
+                notifications
+                    INNER JOIN customers
+                    ON customers.id = notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = notifications.product_id
+            WHERE
+                notifications.status = ?1
+                AND notifications.send_at <= ?2
+            ORDER BY
+                notifications.send_at
             "#,
-            customer_id,
-            product_id,
-            text
+            STATUS_PENDING,
+            now
+        )
+        .fetch_all(&mut transaction)
+        .await;
+
+        let records = match result {
+            Ok(records) => records,
+            Err(error) => return Err(DatabaseErrors::RequestError(error.to_string())),
+        };
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE
+                notifications
+            SET
+                status = ?1,
+                sent_at = ?3
+            WHERE
+                status = ?2
+                AND send_at <= ?3
+            "#,
+            STATUS_SENT,
+            STATUS_PENDING,
+            now
         )
         .execute(&mut transaction)
         .await;
@@ -224,21 +1419,51 @@ impl SqliteRepository {
             return Err(DatabaseErrors::TransactionError(error.to_string()));
         }
 
-        Ok(())
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                let customer = parse_customer(&record.customer)?;
+                let notification = ScheduledNotification {
+                    id: record.id,
+                    product: parse_product(&record.product)?,
+                    send_at: Timestamp::from_millis(record.send_at)?,
+                    content: notification_content(
+                        record.text,
+                        &record.parse_mode,
+                        record.attachment_kind,
+                        record.attachment_file_id,
+                        &record.buttons,
+                    ),
+                };
+                Some((record.user_id as u32, customer, notification))
+            })
+            .collect())
     }
-    pub async fn get_notifications(
+    async fn get_notifications(
         &mut self,
-        customer: &str,
-        product: &str,
-        notification: String,
+        customer: &CustomerName,
+        product: &ProductName,
+        content: NotificationContent,
     ) -> Result<Vec<Notification>, DatabaseErrors> {
-        let customer_id = self.hash_data.get_customer_id(customer);
-        let product_id = self.hash_data.get_product_id(customer, product);
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = self.product_id(customer, customer_id, product).await?;
+        // The subscriptions are read in the transaction that updates them, so
+        // a concurrent notification does not count down the same subscription.
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
+        };
+
         let result = sqlx::query!(
             r#"
             SELECT
                 subscriptions.id,
                 subscriptions.user_id as user_id,
+                subscriptions.channel as channel,
+                subscriptions.email as email,
+                subscriptions.mode,
+                subscriptions.remaining,
+                subscriptions.expires_at,
                 customers.name as customer,
                 products.name as product
             FROM 
@@ -255,45 +1480,121 @@ impl SqliteRepository {
             customer_id,
             product_id
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut transaction)
         .await;
 
         if let Err(error) = result {
+            if let Err(error) = transaction.rollback().await {
+                return Err(DatabaseErrors::TransactionError(error.to_string()));
+            }
             return Err(DatabaseErrors::RequestError(error.to_string()));
         }
 
-        let result = result.unwrap();
-        let notifications = result
-            .iter()
-            .map(|record| Notification {
+        let now = Timestamp::now();
+        let mut notifications = vec![];
+        let mut receivers: Vec<i64> = vec![];
+        let mut subscriptions_to_delete: Vec<i64> = vec![];
+        let mut subscriptions_to_count_down: Vec<i64> = vec![];
+        for record in result.unwrap() {
+            let mode = subscription_mode(&record.mode, record.remaining, record.expires_at);
+            match delivery(mode, now) {
+                Delivery::Keep => {}
+                Delivery::Countdown => subscriptions_to_count_down.push(record.id),
+                Delivery::Last => subscriptions_to_delete.push(record.id),
+                Delivery::Expired => {
+                    subscriptions_to_delete.push(record.id);
+                    continue;
+                }
+            }
+            receivers.push(record.user_id);
+            notifications.push(Notification {
                 user_id: record.user_id as u32,
-                customer: record.customer.to_string(),
-                product: record.product.to_string(),
-                text: notification.clone(),
-            })
-            .collect();
-
-        let subscriptions_to_delete: Vec<String> =
-            result.iter().map(|record| record.id.to_string()).collect();
-        let subscriptions_to_delete = subscriptions_to_delete.join(",");
+                customer: customer.clone(),
+                product: product.clone(),
+                content: content.clone(),
+                channel: DeliveryChannel::from(record.channel.as_str()),
+                email: record.email,
+            });
+        }
 
-        let mut transaction = match self.pool.begin().await {
-            Ok(transaction) => transaction,
-            Err(error) => return Err(DatabaseErrors::TransactionError(error.to_string())),
-        };
+        let subscriptions_to_delete = serde_json::to_string(&subscriptions_to_delete).unwrap();
+        let subscriptions_to_count_down =
+            serde_json::to_string(&subscriptions_to_count_down).unwrap();
+        let receivers = serde_json::to_string(&receivers).unwrap();
+        let parse_mode = content.parse_mode.as_str();
+        let attachment_kind = content
+            .attachment
+            .as_ref()
+            .map(|attachment| attachment.kind());
+        let attachment_file_id = content
+            .attachment
+            .as_ref()
+            .map(|attachment| attachment.file_id());
+        let buttons = serde_json::to_string(&content.buttons).unwrap();
+        let received_at = now.as_millis();
 
         let result = sqlx::query!(
             r#"
                 DELETE FROM 
                     active_subscriptions
                 WHERE 
-                    active_subscriptions.subscription_id IN ( ?1 )
+                    active_subscriptions.subscription_id IN ( SELECT value FROM json_each(?1) )
                 "#,
             subscriptions_to_delete
         )
         .execute(&mut transaction)
         .await;
 
+        if let Err(error) = result {
+            if let Err(error) = transaction.rollback().await {
+                return Err(DatabaseErrors::TransactionError(error.to_string()));
+            }
+            return Err(DatabaseErrors::RequestError(error.to_string()));
+        }
+
+        let result = sqlx::query!(
+            r#"
+                UPDATE
+                    subscriptions
+                SET
+                    remaining = remaining - 1
+                WHERE
+                    subscriptions.id IN ( SELECT value FROM json_each(?1) )
+                "#,
+            subscriptions_to_count_down
+        )
+        .execute(&mut transaction)
+        .await;
+
+        if let Err(error) = result {
+            if let Err(error) = transaction.rollback().await {
+                return Err(DatabaseErrors::TransactionError(error.to_string()));
+            }
+            return Err(DatabaseErrors::RequestError(error.to_string()));
+        }
+
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO
+                    received_notifications ( user_id, customer_id, product_id, text, parse_mode, attachment_kind, attachment_file_id, buttons, received_at )
+                SELECT
+                    value, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
+                FROM
+                    json_each(?1)
+                "#,
+            receivers,
+            customer_id,
+            product_id,
+            content.text,
+            parse_mode,
+            attachment_kind,
+            attachment_file_id,
+            buttons,
+            received_at
+        )
+        .execute(&mut transaction)
+        .await;
+
         if let Err(error) = result {
             if let Err(error) = transaction.rollback().await {
                 return Err(DatabaseErrors::TransactionError(error.to_string()));
@@ -307,131 +1608,328 @@ impl SqliteRepository {
         Ok(notifications)
     }
 
-    pub async fn get_customers_user_id(&mut self, customer: &str) -> Result<u32, DatabaseErrors> {
-        Ok(self.hash_data.get_user_for_customer(customer).unwrap())
-    }
-}
+    async fn get_sent_notifications(
+        &self,
+        customer: &CustomerName,
+        product: Option<&ProductName>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<PastNotification>, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
+        let product_id = match product {
+            Some(product) => Some(self.product_id(customer, customer_id, product).await?),
+            None => None,
+        };
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                notifications.id as "id!",
+                products.name as "product!",
+                COALESCE(notifications.sent_at, notifications.send_at) as "sent_at: i64",
+                notifications.text as "text!",
+                notifications.parse_mode as "parse_mode!",
+                notifications.attachment_kind,
+                notifications.attachment_file_id,
+                notifications.buttons as "buttons!"
+            FROM
+                notifications
+                    INNER JOIN products
+                    ON products.id = notifications.product_id
+            WHERE
+                notifications.customer_id = ?1
+                AND notifications.status = ?2
+                AND ( ?3 IS NULL OR notifications.product_id = ?3 )
+            ORDER BY
+                COALESCE(notifications.sent_at, notifications.send_at, 0) DESC,
+                notifications.id DESC
+            LIMIT ?4 OFFSET ?5
+            "#,
+            customer_id,
+            STATUS_SENT,
+            product_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await;
 
-struct CacheData {
-    customers: HashMap<String, u32>,
-    products: HashMap<String, HashMap<String, u32>>,
-    users_customers: HashMap<String, u32>,
-}
-impl CacheData {
-    async fn new(pool: &SqlitePool) -> Self {
-        let customers = Self::get_all_customers(pool).await;
-        let products = Self::get_all_products(pool).await;
-        let users_customers = Self::get_users_customers(pool).await;
-        CacheData {
-            customers,
-            products,
-            users_customers,
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    Some(PastNotification {
+                        id: record.id,
+                        customer: customer.clone(),
+                        product: parse_product(&record.product)?,
+                        sent_at: record.sent_at.and_then(Timestamp::from_millis),
+                        content: notification_content(
+                            record.text,
+                            &record.parse_mode,
+                            record.attachment_kind,
+                            record.attachment_file_id,
+                            &record.buttons,
+                        ),
+                    })
+                })
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
         }
     }
-    fn get_customer_id(&self, customer: &str) -> Option<u32> {
-        self.customers.get(customer).cloned()
-    }
-    fn get_product_id(&self, customer: &str, product: &str) -> Option<u32> {
-        self.products.get(customer).unwrap().get(product).cloned()
-    }
-    fn get_user_for_customer(&self, customer: &str) -> Option<u32> {
-        self.users_customers.get(customer).cloned()
-    }
-    fn insert_user_for_customer(&mut self, customer: &str, user_id: u32) {
-        self.users_customers.insert(customer.to_string(), user_id);
-    }
-    fn get_customers(&self) -> Vec<Customer> {
-        self.customers
-            .keys()
-            .into_iter()
-            .map(|key| Customer {
-                name: key.to_string(),
-            })
-            .collect()
-    }
-    fn get_customer_products(&self, customer: &str) -> Vec<Product> {
-        self.products
-            .get(customer)
-            .unwrap()
-            .keys()
-            .into_iter()
-            .map(|key| Product {
-                name: key.to_string(),
-            })
-            .collect()
+
+    async fn get_received_notifications(
+        &self,
+        user_id: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<PastNotification>, DatabaseErrors> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                received_notifications.id,
+                customers.name as customer,
+                products.name as product,
+                received_notifications.received_at,
+                received_notifications.text,
+                received_notifications.parse_mode,
+                received_notifications.attachment_kind,
+                received_notifications.attachment_file_id,
+                received_notifications.buttons
+            FROM
+                received_notifications
+                    INNER JOIN customers
+                    ON customers.id = received_notifications.customer_id
+                    INNER JOIN products
+                    ON products.id = received_notifications.product_id
+            WHERE
+                received_notifications.user_id = ?1
+            ORDER BY
+                received_notifications.id DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        match result {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    Some(PastNotification {
+                        id: record.id,
+                        customer: parse_customer(&record.customer)?,
+                        product: parse_product(&record.product)?,
+                        sent_at: Timestamp::from_millis(record.received_at),
+                        content: notification_content(
+                            record.text,
+                            &record.parse_mode,
+                            record.attachment_kind,
+                            record.attachment_file_id,
+                            &record.buttons,
+                        ),
+                    })
+                })
+                .collect()),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
     }
-    async fn get_all_customers(pool: &SqlitePool) -> HashMap<String, u32> {
+
+    async fn get_operators(
+        &self,
+        customer: &CustomerName,
+    ) -> Result<Vec<Operator>, DatabaseErrors> {
+        if let Some(operators) = self.hash_data.get_operators(customer) {
+            return Ok(operators);
+        }
+        let customer_id = self.customer_id(customer).await?;
         let result = sqlx::query!(
             r#"
             SELECT 
-                id, name 
+                user_id,
+                role
             FROM 
-                customers 
-            "#
+                users_customers 
+            WHERE 
+                customer_id = ?1
+            ORDER BY 
+                user_id
+            "#,
+            customer_id
         )
-        .fetch_all(pool)
+        .fetch_all(&self.pool)
         .await;
 
-        result
-            .unwrap()
-            .iter()
-            .map(|record| (record.name.to_string(), record.id as u32))
-            .collect()
+        match result {
+            Ok(records) => {
+                let operators: Vec<Operator> = records
+                    .into_iter()
+                    .map(|record| Operator {
+                        user_id: record.user_id as u32,
+                        role: OperatorRole::from(record.role.as_str()),
+                    })
+                    .collect();
+                self.hash_data.insert_operators(customer, &operators);
+                Ok(operators)
+            }
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
     }
 
-    async fn get_all_products(pool: &SqlitePool) -> HashMap<String, HashMap<String, u32>> {
+    async fn get_operator_role(
+        &self,
+        user_id: u32,
+        customer: &CustomerName,
+    ) -> Result<Option<OperatorRole>, DatabaseErrors> {
+        if let Some(role) = self.hash_data.get_operator_role(customer, user_id) {
+            return Ok(Some(role));
+        }
+        let customer_id = self.customer_id(customer).await?;
         let result = sqlx::query!(
             r#"
             SELECT 
-                customers.name as customer,
-                products.id,
-                products.name 
+                role
             FROM 
-                products 
-                    INNER JOIN customers_products 
-                    ON products.id = customers_products.product_id
-                    INNER JOIN customers
-                    ON customers_products.customer_id = customers.id
-            "#
+                users_customers 
+            WHERE 
+                user_id = ?1
+                AND customer_id = ?2
+            "#,
+            user_id,
+            customer_id
         )
-        .fetch_all(pool)
+        .fetch_optional(&self.pool)
         .await;
 
-        let mut products = HashMap::<String, HashMap<String, u32>>::new();
-        for rec in result.unwrap() {
-            let customer_products = match products.get_mut(&rec.customer) {
-                Some(customer_products) => customer_products,
-                None => {
-                    let customer_products = HashMap::<String, u32>::new();
-                    products.insert(rec.customer.clone(), customer_products);
-                    products.get_mut(&rec.customer).unwrap()
-                }
-            };
-            customer_products.insert(rec.name, rec.id as u32);
+        match result {
+            Ok(Some(record)) => {
+                let role = OperatorRole::from(record.role.as_str());
+                self.hash_data.insert_operator(customer, user_id, role);
+                Ok(Some(role))
+            }
+            Ok(None) => Ok(None),
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
         }
-
-        products
     }
 
-    async fn get_users_customers(pool: &SqlitePool) -> HashMap<String, u32> {
+    async fn remove_operator(
+        &mut self,
+        customer: &CustomerName,
+        user_id: u32,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer_id = self.customer_id(customer).await?;
         let result = sqlx::query!(
             r#"
-            SELECT 
-                customers.name,
-                users_customers.user_id 
-            FROM 
-                users_customers
-                LEFT JOIN customers
-                ON users_customers.customer_id = customers.id
-            "#
+            DELETE FROM 
+                users_customers 
+            WHERE 
+                user_id = ?1
+                AND customer_id = ?2
+            "#,
+            user_id,
+            customer_id
         )
-        .fetch_all(pool)
+        .execute(&self.pool)
         .await;
 
-        result
+        match result {
+            Ok(result) => {
+                self.hash_data.remove_operator(customer, user_id);
+                Ok(result.rows_affected() > 0)
+            }
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+        }
+    }
+
+    async fn reload_cache(&self) -> Result<(), DatabaseErrors> {
+        let cache = Self::load_cache(&self.pool).await?;
+        self.hash_data.replace(cache);
+        Ok(())
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        self.hash_data.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{common::keys::legacy_key_id, tests as scenarios};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// Migrated in-memory database. It lives as long as its only connection.
+    async fn repository() -> SqliteRepository {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SqliteRepository::apply_migrations(&pool).await.unwrap();
+        let hash_data = SqliteRepository::load_cache(&pool).await.unwrap();
+        SqliteRepository { pool, hash_data }
+    }
+
+    #[tokio::test]
+    async fn pre_upgrade_keys_authorize_after_the_migration() {
+        let mut repository = repository().await;
+        let key = "plaintext-legacy-key";
+        sqlx::query("INSERT INTO customers ( name, key ) VALUES ( 'shop', ?1 )")
+            .bind(key)
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        SqliteRepository::hash_plaintext_keys(&repository.pool)
+            .await
+            .unwrap();
+        repository.reload_cache().await.unwrap();
+
+        let (customer, role) = repository
+            .try_authorize(7, key.to_string())
+            .await
             .unwrap()
-            .iter()
-            .map(|record| (record.name.to_string(), record.user_id as u32))
-            .collect()
+            .unwrap();
+        assert_eq!(customer.name.as_str(), "shop");
+        assert_eq!(role, OperatorRole::Owner);
+
+        let key_id: Option<String> = sqlx::query_scalar("SELECT key_id FROM customer_keys")
+            .fetch_one(&repository.pool)
+            .await
+            .unwrap();
+        assert_eq!(key_id, Some(legacy_key_id(key)));
+        assert!(repository
+            .try_authorize(8, key.to_string())
+            .await
+            .unwrap()
+            .is_some());
+        assert!(repository
+            .try_authorize(9, "another-legacy-key".to_string())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn notifies_subscribers_of_the_product() {
+        scenarios::notifies_subscribers_of_the_product(repository().await).await;
+    }
+
+    #[tokio::test]
+    async fn once_subscriptions_end_after_the_first_notification() {
+        scenarios::once_subscriptions_end_after_the_first_notification(repository().await).await;
+    }
+
+    #[tokio::test]
+    async fn times_subscriptions_count_down_to_the_last_notification() {
+        scenarios::times_subscriptions_count_down_to_the_last_notification(repository().await)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn expired_until_subscriptions_are_skipped() {
+        scenarios::expired_until_subscriptions_are_skipped(repository().await).await;
     }
 }
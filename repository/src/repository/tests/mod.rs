@@ -0,0 +1,189 @@
+//! Scenarios shared by the tests of the storage backends. Names and user ids
+//! are random, so the scenarios can run against a database that is not empty.
+
+use domain::models::{
+    CustomerName, DeliveryChannel, NotificationContent, ProductName, Subscription,
+    SubscriptionMode, Timestamp,
+};
+
+use super::{AdminRepository, Repository};
+
+pub(crate) struct Shop {
+    pub customer: CustomerName,
+    pub product: ProductName,
+}
+
+pub(crate) fn user_id() -> u32 {
+    rand::random::<u32>() >> 1
+}
+
+pub(crate) async fn shop<R: Repository + AdminRepository>(repository: &R) -> Shop {
+    let customer = CustomerName::parse(&format!("shop-{}", rand::random::<u32>())).unwrap();
+    let product = ProductName::parse("coffee").unwrap();
+    repository
+        .create_customer(&customer, &super::generate_key())
+        .await
+        .unwrap();
+    repository.add_product(&customer, &product).await.unwrap();
+    repository.reload_cache().await.unwrap();
+    Shop { customer, product }
+}
+
+fn content(text: &str) -> NotificationContent {
+    NotificationContent::from(text.to_string())
+}
+
+async fn subscribe<R: Repository>(repository: &mut R, shop: &Shop, mode: SubscriptionMode) -> u32 {
+    let user_id = user_id();
+    repository
+        .add_subscription(
+            user_id,
+            &shop.customer,
+            &shop.product,
+            DeliveryChannel::Telegram,
+            None,
+            mode,
+        )
+        .await
+        .unwrap();
+    user_id
+}
+
+async fn subscription<R: Repository>(repository: &R, user_id: u32) -> Subscription {
+    let mut subscriptions = repository.get_user_subscriptions(user_id).await.unwrap();
+    assert_eq!(subscriptions.len(), 1);
+    subscriptions.remove(0)
+}
+
+/// Sends a notification of the shop and returns the users it is delivered to.
+async fn notify<R: Repository>(repository: &mut R, shop: &Shop) -> Vec<u32> {
+    let mut receivers: Vec<u32> = repository
+        .get_notifications(&shop.customer, &shop.product, content("in stock"))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|notification| notification.user_id)
+        .collect();
+    receivers.sort_unstable();
+    receivers
+}
+
+pub(crate) async fn notifies_subscribers_of_the_product<R: Repository + AdminRepository>(
+    mut repository: R,
+) {
+    let shop = shop(&repository).await;
+    let other = ProductName::parse("tea").unwrap();
+    repository
+        .add_product(&shop.customer, &other)
+        .await
+        .unwrap();
+    repository.reload_cache().await.unwrap();
+    let (subscriber, other_subscriber) = (user_id(), user_id());
+    repository
+        .add_subscription(
+            subscriber,
+            &shop.customer,
+            &shop.product,
+            DeliveryChannel::Telegram,
+            None,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    repository
+        .add_subscription(
+            other_subscriber,
+            &shop.customer,
+            &other,
+            DeliveryChannel::Telegram,
+            None,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+
+    let notifications = repository
+        .get_notifications(&shop.customer, &shop.product, content("in stock"))
+        .await
+        .unwrap();
+
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].user_id, subscriber);
+    assert_eq!(notifications[0].product, shop.product);
+    let received = repository
+        .get_received_notifications(subscriber, 0, 10)
+        .await
+        .unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].content.text, "in stock");
+    assert!(repository
+        .get_received_notifications(other_subscriber, 0, 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+pub(crate) async fn once_subscriptions_end_after_the_first_notification<
+    R: Repository + AdminRepository,
+>(
+    mut repository: R,
+) {
+    let shop = shop(&repository).await;
+    let once = subscribe(&mut repository, &shop, SubscriptionMode::Once).await;
+    let always = subscribe(&mut repository, &shop, SubscriptionMode::Always).await;
+
+    let mut both = vec![once, always];
+    both.sort_unstable();
+    assert_eq!(notify(&mut repository, &shop).await, both);
+    assert!(!subscription(&repository, once).await.active);
+    assert!(subscription(&repository, always).await.active);
+
+    assert_eq!(notify(&mut repository, &shop).await, vec![always]);
+}
+
+pub(crate) async fn times_subscriptions_count_down_to_the_last_notification<
+    R: Repository + AdminRepository,
+>(
+    mut repository: R,
+) {
+    let shop = shop(&repository).await;
+    let user_id = subscribe(&mut repository, &shop, SubscriptionMode::Times { count: 3 }).await;
+
+    for remaining in [2, 1] {
+        assert_eq!(notify(&mut repository, &shop).await, vec![user_id]);
+        let subscription = subscription(&repository, user_id).await;
+        assert!(subscription.active);
+        assert_eq!(
+            subscription.mode,
+            SubscriptionMode::Times { count: remaining }
+        );
+    }
+
+    assert_eq!(notify(&mut repository, &shop).await, vec![user_id]);
+    assert!(!subscription(&repository, user_id).await.active);
+    assert!(notify(&mut repository, &shop).await.is_empty());
+}
+
+pub(crate) async fn expired_until_subscriptions_are_skipped<R: Repository + AdminRepository>(
+    mut repository: R,
+) {
+    let shop = shop(&repository).await;
+    let now = Timestamp::now().as_millis();
+    let expired = SubscriptionMode::Until {
+        until: Timestamp::from_millis(now - 60_000).unwrap(),
+    };
+    let running = SubscriptionMode::Until {
+        until: Timestamp::from_millis(now + 3_600_000).unwrap(),
+    };
+    let expired = subscribe(&mut repository, &shop, expired).await;
+    let running = subscribe(&mut repository, &shop, running).await;
+
+    assert_eq!(notify(&mut repository, &shop).await, vec![running]);
+    assert!(!subscription(&repository, expired).await.active);
+    assert!(subscription(&repository, running).await.active);
+    assert!(repository
+        .get_received_notifications(expired, 0, 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,140 @@
+use chrono::Utc;
+use chrono_tz::Tz;
+use croner::Cron;
+use domain::models::Timestamp;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("Invalid cron expression [{0}]: {1}")]
+    InvalidExpression(String, String),
+    #[error("Unknown timezone [{0}]")]
+    UnknownTimezone(String),
+    #[error("No next occurrence of [{0}]: {1}")]
+    NoOccurrence(String, String),
+}
+
+/// Cron schedule of a recurring notification, evaluated in an IANA timezone.
+/// Expressions have the usual five fields: minute, hour, day of month, month
+/// and day of week.
+pub struct Schedule {
+    expression: String,
+    cron: Cron,
+    timezone: Tz,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str, timezone: &str) -> Result<Self, ScheduleError> {
+        let expression = expression.trim().to_string();
+        let cron = Cron::new(&expression).parse().map_err(|error| {
+            ScheduleError::InvalidExpression(expression.clone(), error.to_string())
+        })?;
+        let timezone: Tz = timezone
+            .trim()
+            .parse()
+            .map_err(|_| ScheduleError::UnknownTimezone(timezone.to_string()))?;
+        Ok(Schedule {
+            expression,
+            cron,
+            timezone,
+        })
+    }
+
+    /// First occurrence strictly after `after`.
+    pub fn next_after(&self, after: Timestamp) -> Result<Timestamp, ScheduleError> {
+        let after = after.as_datetime().with_timezone(&self.timezone);
+        match self.cron.find_next_occurrence(&after, false) {
+            Ok(next) => Ok(Timestamp::from(next.with_timezone(&Utc))),
+            Err(error) => Err(ScheduleError::NoOccurrence(
+                self.expression.clone(),
+                error.to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> Timestamp {
+        Timestamp::from(
+            Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+                .unwrap(),
+        )
+    }
+
+    fn next(expression: &str, timezone: &str, after: Timestamp) -> Timestamp {
+        Schedule::parse(expression, timezone)
+            .unwrap()
+            .next_after(after)
+            .unwrap()
+    }
+
+    #[test]
+    fn next_occurrence_in_utc() {
+        let after = utc(2022, 12, 19, 8, 30);
+        assert_eq!(next("0 9 * * *", "UTC", after), utc(2022, 12, 19, 9, 0));
+        assert_eq!(next("*/15 * * * *", "UTC", after), utc(2022, 12, 19, 8, 45));
+    }
+
+    #[test]
+    fn next_occurrence_is_strictly_after() {
+        let at_nine = utc(2022, 12, 19, 9, 0);
+        assert_eq!(next("0 9 * * *", "UTC", at_nine), utc(2022, 12, 20, 9, 0));
+    }
+
+    #[test]
+    fn next_occurrence_in_timezone() {
+        // 09:00 in Moscow (UTC+3) is 06:00 UTC.
+        let after = utc(2022, 12, 19, 5, 0);
+        assert_eq!(
+            next("0 9 * * *", "Europe/Moscow", after),
+            utc(2022, 12, 19, 6, 0)
+        );
+        // Already past 09:00 in Moscow, so the next day.
+        let after = utc(2022, 12, 19, 7, 0);
+        assert_eq!(
+            next("0 9 * * *", "Europe/Moscow", after),
+            utc(2022, 12, 20, 6, 0)
+        );
+    }
+
+    #[test]
+    fn day_of_week_follows_local_date() {
+        // Monday 2022-12-19 23:30 UTC is already Tuesday in Tokyo.
+        let after = utc(2022, 12, 19, 23, 30);
+        assert_eq!(
+            next("0 9 * * 2", "Asia/Tokyo", after),
+            utc(2022, 12, 20, 0, 0)
+        );
+    }
+
+    #[test]
+    fn next_occurrence_across_daylight_saving_change() {
+        // New York switches from EST (UTC-5) to EDT (UTC-4) on 2023-03-12.
+        let before = utc(2023, 3, 11, 15, 0);
+        assert_eq!(
+            next("0 9 * * *", "America/New_York", before),
+            utc(2023, 3, 12, 13, 0)
+        );
+        assert_eq!(
+            next("0 9 * * *", "America/New_York", utc(2023, 3, 10, 15, 0)),
+            utc(2023, 3, 11, 14, 0)
+        );
+    }
+
+    #[test]
+    fn invalid_schedules_are_rejected() {
+        assert!(matches!(
+            Schedule::parse("not a cron", "UTC"),
+            Err(ScheduleError::InvalidExpression(..))
+        ));
+        assert!(matches!(
+            Schedule::parse("0 9 * * *", "Mars/Olympus"),
+            Err(ScheduleError::UnknownTimezone(..))
+        ));
+    }
+}
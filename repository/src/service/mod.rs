@@ -1,21 +1,35 @@
+use amqp::{MessageBrokerError, Publisher};
 use domain::{
-    requests::{ClientRequestToRepository, CustomerRequestToRepository, RequestToRepository},
+    codec::{Correlated, WireMessage},
+    models::{CustomerName, OperatorRole, RecurringNotification, ScheduledNotification, Timestamp},
+    requests::{
+        AdminRequestToRepository, ClientRequestToRepository, CustomerRequestToRepository,
+        RequestToRepository,
+    },
     responses::{
         ClientResponseFromRepository, CustomerResponseFromRepository, ResponseFromRepository,
     },
 };
-use amqp::Publisher;
 
-use crate::{Config, SqliteRepository};
+use crate::{
+    repository::{generate_key, DatabaseErrors},
+    schedule::Schedule,
+    Config, Repository,
+};
+
+const DEFAULT_AUTHORIZATION_MAX_FAILURES: u32 = 5;
+const DEFAULT_AUTHORIZATION_LOCKOUT_SECS: u64 = 15 * 60;
+const DEFAULT_OPERATOR_INVITE_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+const DEFAULT_HISTORY_PAGE_SIZE: u32 = 10;
 
 pub struct RepositoryService {
     config: Config,
-    repository: SqliteRepository,
+    repository: Box<dyn Repository>,
     publisher: Publisher,
 }
 
 impl RepositoryService {
-    pub fn new(config: Config, repository: SqliteRepository, publisher: Publisher) -> Self {
+    pub fn new(config: Config, repository: Box<dyn Repository>, publisher: Publisher) -> Self {
         Self {
             config,
             repository,
@@ -24,28 +38,148 @@ impl RepositoryService {
     }
     pub async fn handle_client_request_to_repository(
         &mut self,
-        request: ClientRequestToRepository,
-    ) {
+        request: Correlated<ClientRequestToRepository>,
+    ) -> Result<(), MessageBrokerError> {
+        let Correlated {
+            request_id,
+            message: request,
+        } = request;
         let response = match request {
             ClientRequestToRepository::Customers { user_id } => {
-                let customers = self.repository.get_customers().await.unwrap();
+                let customers = match self.repository.get_customers().await {
+                    Ok(customers) => customers,
+                    Err(error) => {
+                        log::error!("Failed to get customers: {}", error);
+                        vec![]
+                    }
+                };
                 ClientResponseFromRepository::Customers { user_id, customers }
             }
             ClientRequestToRepository::Products { user_id, customer } => {
-                let products = self.repository.get_products(&customer).await.unwrap();
-                ClientResponseFromRepository::Products { user_id, products }
+                match self.repository.get_products(&customer).await {
+                    Ok(products) => ClientResponseFromRepository::Products { user_id, products },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        ClientResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get products: {}", error);
+                        ClientResponseFromRepository::Products {
+                            user_id,
+                            products: vec![],
+                        }
+                    }
+                }
+            }
+            ClientRequestToRepository::NewSubscription { user_id, mode, .. }
+                if !mode.is_valid(Timestamp::now()) =>
+            {
+                log::info!("Invalid subscription mode from user [{}]", user_id);
+                ClientResponseFromRepository::NewSubscription {
+                    user_id,
+                    success: false,
+                }
             }
             ClientRequestToRepository::NewSubscription {
                 user_id,
                 customer,
                 product,
-            } => {
-                let success = self
+                channel,
+                email,
+                mode,
+            } => match self
+                .repository
+                .add_subscription(
+                    user_id,
+                    &customer,
+                    &product,
+                    channel,
+                    email.as_deref(),
+                    mode,
+                )
+                .await
+            {
+                Ok(_) => ClientResponseFromRepository::NewSubscription {
+                    user_id,
+                    success: true,
+                },
+                Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                    ClientResponseFromRepository::UnknownCustomer { user_id, customer }
+                }
+                Err(DatabaseErrors::UnknownProduct(customer, product)) => {
+                    ClientResponseFromRepository::UnknownProduct {
+                        user_id,
+                        customer,
+                        product,
+                    }
+                }
+                Err(error) => {
+                    log::error!("Failed to add subscription: {}", error);
+                    ClientResponseFromRepository::NewSubscription {
+                        user_id,
+                        success: false,
+                    }
+                }
+            },
+            ClientRequestToRepository::Subscriptions { user_id } => {
+                match self.repository.get_user_subscriptions(user_id).await {
+                    Ok(subscriptions) => ClientResponseFromRepository::Subscriptions {
+                        user_id,
+                        subscriptions,
+                    },
+                    Err(error) => {
+                        log::error!("Failed to get subscriptions: {}", error);
+                        ClientResponseFromRepository::Subscriptions {
+                            user_id,
+                            subscriptions: vec![],
+                        }
+                    }
+                }
+            }
+            ClientRequestToRepository::CancelSubscription { user_id, id } => {
+                let success = match self.repository.cancel_subscription(user_id, id).await {
+                    Ok(success) => success,
+                    Err(error) => {
+                        log::error!("Failed to cancel subscription: {}", error);
+                        false
+                    }
+                };
+                ClientResponseFromRepository::SubscriptionCancelled {
+                    user_id,
+                    id,
+                    success,
+                }
+            }
+            ClientRequestToRepository::CancelAllSubscriptions { user_id } => {
+                let count = match self.repository.cancel_all_subscriptions(user_id).await {
+                    Ok(count) => count as u32,
+                    Err(error) => {
+                        log::error!("Failed to cancel subscriptions: {}", error);
+                        0
+                    }
+                };
+                ClientResponseFromRepository::SubscriptionsCancelled { user_id, count }
+            }
+            ClientRequestToRepository::NotificationHistory { user_id, page } => {
+                let page_size = self.history_page_size();
+                let result = self
                     .repository
-                    .add_subscription(user_id, &customer, &product)
-                    .await
-                    .is_ok();
-                ClientResponseFromRepository::NewSubscription { user_id, success }
+                    .get_received_notifications(user_id, page * page_size, page_size + 1)
+                    .await;
+                let mut notifications = match result {
+                    Ok(notifications) => notifications,
+                    Err(error) => {
+                        log::error!("Failed to get notification history: {}", error);
+                        vec![]
+                    }
+                };
+                let has_more = notifications.len() > page_size as usize;
+                notifications.truncate(page_size as usize);
+                ClientResponseFromRepository::NotificationHistory {
+                    user_id,
+                    page,
+                    notifications,
+                    has_more,
+                }
             }
         };
 
@@ -53,30 +187,150 @@ impl RepositoryService {
             .publish_message(
                 &self.config.exchange,
                 &self.config.client_repository_response_queue,
-                response.to_string(),
+                Correlated::new(request_id, response).encode(self.config.wire_format),
             )
             .await
-            .unwrap();
+    }
+    /// Authorizes the user unless locked out after too many failed attempts.
+    /// Database errors fail the authorization without counting as a failed attempt.
+    async fn authorize(&mut self, user_id: u32, key: String) -> CustomerResponseFromRepository {
+        let now = Timestamp::now();
+        match self.repository.get_authorization_lock(user_id, now).await {
+            Ok(Some(until)) => {
+                return CustomerResponseFromRepository::AuthorizationLocked { user_id, until }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                log::error!(
+                    "Failed to get authorization lock of [{}]: {}",
+                    user_id,
+                    error
+                );
+                return unauthorized(user_id);
+            }
+        }
+
+        let authorized = match self.repository.try_authorize(user_id, key).await {
+            Ok(authorized) => authorized,
+            Err(error) => {
+                log::error!("Failed to authorize [{}]: {}", user_id, error);
+                return unauthorized(user_id);
+            }
+        };
+        if let Some((customer, role)) = authorized {
+            if let Err(error) = self.repository.clear_authorization_failures(user_id).await {
+                log::error!(
+                    "Failed to clear authorization failures of [{}]: {}",
+                    user_id,
+                    error
+                );
+            }
+            return CustomerResponseFromRepository::Authorization {
+                user_id,
+                customer: Some(customer),
+                role,
+            };
+        }
+
+        let max_failures = self
+            .config
+            .authorization_max_failures
+            .unwrap_or(DEFAULT_AUTHORIZATION_MAX_FAILURES);
+        let lockout_secs = self
+            .config
+            .authorization_lockout_secs
+            .unwrap_or(DEFAULT_AUTHORIZATION_LOCKOUT_SECS);
+        let lock_until =
+            Timestamp::from(now.as_datetime() + chrono::Duration::seconds(lockout_secs as i64));
+        let lock = self
+            .repository
+            .add_authorization_failure(user_id, max_failures, lock_until)
+            .await;
+        match lock {
+            Ok(Some(until)) => {
+                log::warn!("User [{}] locked out of authorization", user_id);
+                CustomerResponseFromRepository::AuthorizationLocked { user_id, until }
+            }
+            Ok(None) => unauthorized(user_id),
+            Err(error) => {
+                log::error!(
+                    "Failed to add authorization failure of [{}]: {}",
+                    user_id,
+                    error
+                );
+                unauthorized(user_id)
+            }
+        }
+    }
+    /// Denial response if the user is not an operator of the customer with
+    /// at least the `required` role.
+    async fn check_operator_role(
+        &self,
+        user_id: u32,
+        customer: &CustomerName,
+        required: OperatorRole,
+    ) -> Option<CustomerResponseFromRepository> {
+        let customer = customer.clone();
+        match self.repository.get_operator_role(user_id, &customer).await {
+            Ok(Some(role)) if role.allows(required) => None,
+            Ok(_) => {
+                log::info!(
+                    "User [{}] is not allowed to act as {} of customer [{}]",
+                    user_id,
+                    required.as_str(),
+                    customer
+                );
+                Some(CustomerResponseFromRepository::PermissionDenied { user_id, customer })
+            }
+            Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                Some(CustomerResponseFromRepository::UnknownCustomer { user_id, customer })
+            }
+            Err(error) => {
+                log::error!("Failed to get operator role: {}", error);
+                Some(CustomerResponseFromRepository::PermissionDenied { user_id, customer })
+            }
+        }
     }
     pub async fn handle_customer_request_to_repository(
         &mut self,
-        request: CustomerRequestToRepository,
-    ) {
+        request: Correlated<CustomerRequestToRepository>,
+    ) -> Result<(), MessageBrokerError> {
+        let Correlated {
+            request_id,
+            message: request,
+        } = request;
+        if let Some((user_id, customer, required)) = required_operator_role(&request) {
+            if let Some(response) = self.check_operator_role(user_id, customer, required).await {
+                return self.publish_customer_response(request_id, response).await;
+            }
+        }
+
         let response = match request {
             CustomerRequestToRepository::Authorization { user_id, key } => {
-                let customer = self.repository.try_authorize(user_id, key).await.unwrap();
-                CustomerResponseFromRepository::Authorization { user_id, customer }
+                self.authorize(user_id, key).await
             }
             CustomerRequestToRepository::ProductsForNotification { user_id, customer } => {
-                let products = self
+                match self
                     .repository
                     .get_products_for_notification(&customer)
                     .await
-                    .unwrap();
-                CustomerResponseFromRepository::ProductsForNotification {
-                    user_id,
-                    customer,
-                    products,
+                {
+                    Ok(products) => CustomerResponseFromRepository::ProductsForNotification {
+                        user_id,
+                        customer,
+                        products,
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get products for notification: {}", error);
+                        CustomerResponseFromRepository::ProductsForNotification {
+                            user_id,
+                            customer,
+                            products: vec![],
+                        }
+                    }
                 }
             }
             CustomerRequestToRepository::NewNotification {
@@ -84,67 +338,723 @@ impl RepositoryService {
                 customer,
                 product,
                 notification,
+                send_at,
+            } => match self
+                .repository
+                .add_notification(user_id, &customer, &product, notification.clone(), send_at)
+                .await
+            {
+                Ok(id) => match send_at {
+                    Some(send_at) => CustomerResponseFromRepository::NotificationScheduled {
+                        user_id,
+                        customer,
+                        notification: ScheduledNotification {
+                            id,
+                            product,
+                            send_at,
+                            content: notification,
+                        },
+                    },
+                    None => CustomerResponseFromRepository::NewNotification {
+                        user_id,
+                        customer,
+                        success: true,
+                    },
+                },
+                Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                    CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                }
+                Err(DatabaseErrors::UnknownProduct(customer, product)) => {
+                    CustomerResponseFromRepository::UnknownProduct {
+                        user_id,
+                        customer,
+                        product,
+                    }
+                }
+                Err(error) => {
+                    log::error!("Failed to add notification: {}", error);
+                    CustomerResponseFromRepository::NewNotification {
+                        user_id,
+                        customer,
+                        success: false,
+                    }
+                }
+            },
+            CustomerRequestToRepository::ScheduledNotifications { user_id, customer } => {
+                match self.repository.get_scheduled_notifications(&customer).await {
+                    Ok(notifications) => CustomerResponseFromRepository::ScheduledNotifications {
+                        user_id,
+                        customer,
+                        notifications,
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get scheduled notifications: {}", error);
+                        CustomerResponseFromRepository::ScheduledNotifications {
+                            user_id,
+                            customer,
+                            notifications: vec![],
+                        }
+                    }
+                }
+            }
+            CustomerRequestToRepository::CancelScheduledNotification {
+                user_id,
+                customer,
+                id,
+            } => match self
+                .repository
+                .cancel_scheduled_notification(&customer, id)
+                .await
+            {
+                Ok(success) => CustomerResponseFromRepository::ScheduledNotificationCancelled {
+                    user_id,
+                    customer,
+                    id,
+                    success,
+                },
+                Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                    CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                }
+                Err(error) => {
+                    log::error!("Failed to cancel scheduled notification: {}", error);
+                    CustomerResponseFromRepository::ScheduledNotificationCancelled {
+                        user_id,
+                        customer,
+                        id,
+                        success: false,
+                    }
+                }
+            },
+            CustomerRequestToRepository::NewRecurringNotification {
+                user_id,
+                customer,
+                product,
+                notification,
+                schedule,
+                timezone,
             } => {
-                let success = self
+                let next_send_at = match Schedule::parse(&schedule, &timezone)
+                    .and_then(|parsed| parsed.next_after(Timestamp::now()))
+                {
+                    Ok(next_send_at) => next_send_at,
+                    Err(error) => {
+                        log::info!("Invalid schedule from customer [{}]: {}", customer, error);
+                        return self
+                            .publish_customer_response(
+                                request_id,
+                                CustomerResponseFromRepository::InvalidSchedule {
+                                    user_id,
+                                    customer,
+                                    schedule,
+                                    timezone,
+                                },
+                            )
+                            .await;
+                    }
+                };
+                match self
                     .repository
-                    .add_notification(&customer, &product, notification)
+                    .add_recurring_notification(
+                        user_id,
+                        &customer,
+                        &product,
+                        &schedule,
+                        &timezone,
+                        notification.clone(),
+                        next_send_at,
+                    )
                     .await
-                    .is_ok();
-                CustomerResponseFromRepository::NewNotification {
+                {
+                    Ok(id) => CustomerResponseFromRepository::RecurringNotificationCreated {
+                        user_id,
+                        customer,
+                        notification: RecurringNotification {
+                            id,
+                            product,
+                            schedule,
+                            timezone,
+                            paused: false,
+                            next_send_at: Some(next_send_at),
+                            content: notification,
+                        },
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(DatabaseErrors::UnknownProduct(customer, product)) => {
+                        CustomerResponseFromRepository::UnknownProduct {
+                            user_id,
+                            customer,
+                            product,
+                        }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to add recurring notification: {}", error);
+                        CustomerResponseFromRepository::NewNotification {
+                            user_id,
+                            customer,
+                            success: false,
+                        }
+                    }
+                }
+            }
+            CustomerRequestToRepository::RecurringNotifications { user_id, customer } => {
+                match self.repository.get_recurring_notifications(&customer).await {
+                    Ok(notifications) => CustomerResponseFromRepository::RecurringNotifications {
+                        user_id,
+                        customer,
+                        notifications,
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get recurring notifications: {}", error);
+                        CustomerResponseFromRepository::RecurringNotifications {
+                            user_id,
+                            customer,
+                            notifications: vec![],
+                        }
+                    }
+                }
+            }
+            CustomerRequestToRepository::PauseRecurringNotification {
+                user_id,
+                customer,
+                id,
+                paused,
+            } => match self
+                .pause_recurring_notification(&customer, id, paused)
+                .await
+            {
+                Ok(success) => CustomerResponseFromRepository::RecurringNotificationPaused {
+                    user_id,
+                    customer,
+                    id,
+                    paused,
+                    success,
+                },
+                Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                    CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                }
+                Err(error) => {
+                    log::error!("Failed to pause recurring notification: {}", error);
+                    CustomerResponseFromRepository::RecurringNotificationPaused {
+                        user_id,
+                        customer,
+                        id,
+                        paused,
+                        success: false,
+                    }
+                }
+            },
+            CustomerRequestToRepository::DeleteRecurringNotification {
+                user_id,
+                customer,
+                id,
+            } => match self
+                .repository
+                .delete_recurring_notification(&customer, id)
+                .await
+            {
+                Ok(success) => CustomerResponseFromRepository::RecurringNotificationDeleted {
                     user_id,
                     customer,
+                    id,
                     success,
+                },
+                Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                    CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                }
+                Err(error) => {
+                    log::error!("Failed to delete recurring notification: {}", error);
+                    CustomerResponseFromRepository::RecurringNotificationDeleted {
+                        user_id,
+                        customer,
+                        id,
+                        success: false,
+                    }
+                }
+            },
+            CustomerRequestToRepository::Operators { user_id, customer } => {
+                match self.repository.get_operators(&customer).await {
+                    Ok(operators) => CustomerResponseFromRepository::Operators {
+                        user_id,
+                        customer,
+                        operators,
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get operators: {}", error);
+                        CustomerResponseFromRepository::Operators {
+                            user_id,
+                            customer,
+                            operators: vec![],
+                        }
+                    }
+                }
+            }
+            CustomerRequestToRepository::InviteOperator {
+                user_id,
+                customer,
+                role,
+            } => {
+                let ttl_secs = self
+                    .config
+                    .operator_invite_ttl_secs
+                    .unwrap_or(DEFAULT_OPERATOR_INVITE_TTL_SECS);
+                let expires_at = Timestamp::from(
+                    Timestamp::now().as_datetime() + chrono::Duration::seconds(ttl_secs as i64),
+                );
+                let key = generate_key();
+                match self
+                    .repository
+                    .add_invite_key(&customer, &key, role, expires_at)
+                    .await
+                {
+                    Ok(()) => CustomerResponseFromRepository::OperatorInvited {
+                        user_id,
+                        customer,
+                        role,
+                        key,
+                        expires_at,
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to add invite key: {}", error);
+                        CustomerResponseFromRepository::PermissionDenied { user_id, customer }
+                    }
+                }
+            }
+            CustomerRequestToRepository::RevokeOperator {
+                user_id,
+                customer,
+                operator_id,
+            } => {
+                // Owners can not lock themselves out of the customer.
+                let result = match operator_id == user_id {
+                    true => Ok(false),
+                    false => {
+                        self.repository
+                            .remove_operator(&customer, operator_id)
+                            .await
+                    }
+                };
+                match result {
+                    Ok(success) => CustomerResponseFromRepository::OperatorRevoked {
+                        user_id,
+                        customer,
+                        operator_id,
+                        success,
+                    },
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to revoke operator: {}", error);
+                        CustomerResponseFromRepository::OperatorRevoked {
+                            user_id,
+                            customer,
+                            operator_id,
+                            success: false,
+                        }
+                    }
+                }
+            }
+            CustomerRequestToRepository::SetWebhook {
+                user_id,
+                customer,
+                url,
+            } => CustomerResponseFromRepository::WebhookAllowed {
+                user_id,
+                customer,
+                url,
+            },
+            CustomerRequestToRepository::NotificationHistory {
+                user_id,
+                customer,
+                product,
+                page,
+            } => {
+                let page_size = self.history_page_size();
+                let result = self
+                    .repository
+                    .get_sent_notifications(
+                        &customer,
+                        product.as_ref(),
+                        page * page_size,
+                        page_size + 1,
+                    )
+                    .await;
+                match result {
+                    Ok(mut notifications) => {
+                        let has_more = notifications.len() > page_size as usize;
+                        notifications.truncate(page_size as usize);
+                        CustomerResponseFromRepository::NotificationHistory {
+                            user_id,
+                            customer,
+                            product,
+                            page,
+                            notifications,
+                            has_more,
+                        }
+                    }
+                    Err(DatabaseErrors::UnknownCustomer(customer)) => {
+                        CustomerResponseFromRepository::UnknownCustomer { user_id, customer }
+                    }
+                    // Nothing was sent for a product the customer does not have.
+                    Err(DatabaseErrors::UnknownProduct(..)) => {
+                        CustomerResponseFromRepository::NotificationHistory {
+                            user_id,
+                            customer,
+                            product,
+                            page,
+                            notifications: vec![],
+                            has_more: false,
+                        }
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get notification history: {}", error);
+                        CustomerResponseFromRepository::NotificationHistory {
+                            user_id,
+                            customer,
+                            product,
+                            page,
+                            notifications: vec![],
+                            has_more: false,
+                        }
+                    }
                 }
             }
         };
 
+        self.publish_customer_response(request_id, response).await
+    }
+    fn history_page_size(&self) -> u32 {
+        self.config
+            .history_page_size
+            .unwrap_or(DEFAULT_HISTORY_PAGE_SIZE)
+            .max(1)
+    }
+    async fn publish_customer_response(
+        &mut self,
+        request_id: u64,
+        response: CustomerResponseFromRepository,
+    ) -> Result<(), MessageBrokerError> {
         self.publisher
             .publish_message(
                 &self.config.exchange,
                 &self.config.customer_repository_response_queue,
-                response.to_string(),
+                Correlated::new(request_id, response).encode(self.config.wire_format),
             )
             .await
-            .unwrap();
     }
-    pub async fn handle_request_to_repository(&mut self, request: RequestToRepository) {
+    /// Resuming starts from the next occurrence after now, missed ones are skipped.
+    async fn pause_recurring_notification(
+        &mut self,
+        customer: &CustomerName,
+        id: i64,
+        paused: bool,
+    ) -> Result<bool, DatabaseErrors> {
+        let next_send_at = match paused {
+            true => None,
+            false => {
+                let (schedule, timezone) =
+                    match self.repository.get_recurring_schedule(customer, id).await? {
+                        Some(schedule) => schedule,
+                        None => return Ok(false),
+                    };
+                match Schedule::parse(&schedule, &timezone)
+                    .and_then(|schedule| schedule.next_after(Timestamp::now()))
+                {
+                    Ok(next_send_at) => Some(next_send_at),
+                    Err(error) => {
+                        log::error!(
+                            "Failed to resume recurring notification [{}]: {}",
+                            id,
+                            error
+                        );
+                        return Ok(false);
+                    }
+                }
+            }
+        };
+        self.repository
+            .set_recurring_notification_paused(customer, id, paused, next_send_at)
+            .await
+    }
+    pub async fn handle_admin_request(&mut self, request: AdminRequestToRepository) {
+        match request {
+            AdminRequestToRepository::ReloadCache => {
+                if let Err(error) = self.repository.reload_cache().await {
+                    log::error!("Failed to reload cache: {}", error);
+                    return;
+                }
+                log::info!("Cache reloaded");
+            }
+            AdminRequestToRepository::CacheStats => {
+                let stats = self.repository.cache_stats();
+                log::info!("Cache hits: {}, misses: {}", stats.hits, stats.misses);
+            }
+        }
+    }
+    pub async fn handle_request_to_repository(
+        &mut self,
+        request: RequestToRepository,
+    ) -> Result<(), MessageBrokerError> {
+        if let RequestToRepository::NotificationForClients {
+            user_id, customer, ..
+        } = &request
+        {
+            let role = self.repository.get_operator_role(*user_id, customer).await;
+            match role {
+                Ok(Some(role)) if role.allows(OperatorRole::Editor) => {}
+                Ok(_) => {
+                    log::info!(
+                        "Skipping notification of customer [{}] from user [{}] without editor role",
+                        customer,
+                        user_id
+                    );
+                    return Ok(());
+                }
+                Err(error) => {
+                    log::error!("Skipping notification for clients: {}", error);
+                    return Ok(());
+                }
+            }
+        }
+
         let response = match request {
             RequestToRepository::NotificationForClients {
+                user_id,
                 customer,
                 product,
                 notification,
-                ..
             } => {
-                let notifications = self
+                let notifications = match self
                     .repository
                     .get_notifications(&customer, &product, notification)
                     .await
-                    .unwrap();
-                ResponseFromRepository::Notifications(notifications)
+                {
+                    Ok(notifications) => notifications,
+                    Err(
+                        error @ (DatabaseErrors::UnknownCustomer(_)
+                        | DatabaseErrors::UnknownProduct(_, _)),
+                    ) => {
+                        log::error!("Skipping notification for clients: {}", error);
+                        return Ok(());
+                    }
+                    Err(error) => {
+                        log::error!("Failed to get notifications: {}", error);
+                        return Ok(());
+                    }
+                };
+                ResponseFromRepository::Notifications {
+                    user_id,
+                    notifications,
+                }
             }
             RequestToRepository::SubscriptionForCustomer {
                 customer, product, ..
             } => {
-                let user_id = self
-                    .repository
-                    .get_customers_user_id(&customer)
-                    .await
-                    .unwrap();
+                let operators = match self.repository.get_operators(&customer).await {
+                    Ok(operators) if operators.is_empty() => {
+                        log::info!("No operators for customer [{}]", customer);
+                        return Ok(());
+                    }
+                    Ok(operators) => operators,
+                    Err(error) => {
+                        log::error!("Skipping subscription for customer: {}", error);
+                        return Ok(());
+                    }
+                };
+                let operators = operators
+                    .into_iter()
+                    .map(|operator| operator.user_id)
+                    .collect();
                 ResponseFromRepository::Subscription {
-                    user_id,
+                    operators,
                     customer,
                     product,
                 }
             }
+            RequestToRepository::DeferNotification { id, send_at, .. } => {
+                match self.repository.defer_notification(id, send_at).await {
+                    Ok(true) => log::info!("Deferred notification [{}] to {:?}", id, send_at),
+                    Ok(false) => log::info!("Notification [{}] is not sent, not deferring", id),
+                    Err(error) => log::error!("Failed to defer notification [{}]: {}", id, error),
+                }
+                return Ok(());
+            }
         };
 
+        self.publish_response(response).await
+    }
+    async fn publish_response(
+        &mut self,
+        response: ResponseFromRepository,
+    ) -> Result<(), MessageBrokerError> {
         self.publisher
             .publish_message(
                 &self.config.exchange,
                 &self.config.repository_response_queue,
-                response.to_string(),
+                response.encode(self.config.wire_format),
             )
             .await
-            .unwrap();
+    }
+    /// Hands the scheduled and recurring notifications that are due to the
+    /// controller, which sends them on like new notifications of the customer.
+    pub async fn send_due_notifications(&mut self) {
+        let now = Timestamp::now();
+        self.schedule_due_recurring_notifications(now).await;
+        let due = match self.repository.take_due_notifications(now).await {
+            Ok(due) => due,
+            Err(error) => {
+                log::error!("Failed to take due notifications: {}", error);
+                return;
+            }
+        };
+        for (user_id, customer, notification) in due {
+            log::info!(
+                "Scheduled notification [{}] of customer [{}] is due",
+                notification.id,
+                customer
+            );
+            let response = ResponseFromRepository::DueNotification {
+                id: notification.id,
+                user_id,
+                customer,
+                product: notification.product,
+                notification: notification.content,
+                send_at: notification.send_at,
+            };
+            if let Err(error) = self.publish_response(response).await {
+                log::error!(
+                    "Failed to send notification [{}]: {}",
+                    notification.id,
+                    error
+                );
+            }
+        }
+    }
+    /// Moves every due recurring notification to its next occurrence and
+    /// schedules the due occurrence as a notification of its own, so it is
+    /// sent, deferred and kept in the history like any scheduled notification.
+    /// A failed occurrence is not repeated on the next tick.
+    async fn schedule_due_recurring_notifications(&mut self, now: Timestamp) {
+        let due = match self.repository.get_due_recurring_notifications(now).await {
+            Ok(due) => due,
+            Err(error) => {
+                log::error!("Failed to get due recurring notifications: {}", error);
+                return;
+            }
+        };
+        for (user_id, customer, notification) in due {
+            let next_send_at = match Schedule::parse(&notification.schedule, &notification.timezone)
+                .and_then(|schedule| schedule.next_after(now))
+            {
+                Ok(next_send_at) => Some(next_send_at),
+                Err(error) => {
+                    log::error!(
+                        "Stopping recurring notification [{}]: {}",
+                        notification.id,
+                        error
+                    );
+                    None
+                }
+            };
+            if let Err(error) = self
+                .repository
+                .set_recurring_next_send_at(notification.id, next_send_at)
+                .await
+            {
+                log::error!(
+                    "Skipping recurring notification [{}]: {}",
+                    notification.id,
+                    error
+                );
+                continue;
+            }
+            log::info!(
+                "Recurring notification [{}] of customer [{}] is due",
+                notification.id,
+                customer
+            );
+            let send_at = notification.next_send_at.unwrap_or(now);
+            if let Err(error) = self
+                .repository
+                .add_notification(
+                    user_id,
+                    &customer,
+                    &notification.product,
+                    notification.content,
+                    Some(send_at),
+                )
+                .await
+            {
+                log::error!(
+                    "Failed to schedule recurring notification [{}]: {}",
+                    notification.id,
+                    error
+                );
+            }
+        }
+    }
+}
+
+/// Response to a failed authorization.
+fn unauthorized(user_id: u32) -> CustomerResponseFromRepository {
+    CustomerResponseFromRepository::Authorization {
+        user_id,
+        customer: None,
+        role: OperatorRole::default(),
+    }
+}
+
+/// Operator role the request needs, `None` for requests not made on behalf
+/// of a customer.
+fn required_operator_role(
+    request: &CustomerRequestToRepository,
+) -> Option<(u32, &CustomerName, OperatorRole)> {
+    match request {
+        CustomerRequestToRepository::Authorization { .. } => None,
+        CustomerRequestToRepository::ScheduledNotifications { user_id, customer }
+        | CustomerRequestToRepository::RecurringNotifications { user_id, customer }
+        | CustomerRequestToRepository::NotificationHistory {
+            user_id, customer, ..
+        } => Some((*user_id, customer, OperatorRole::Viewer)),
+        CustomerRequestToRepository::ProductsForNotification { user_id, customer }
+        | CustomerRequestToRepository::NewNotification {
+            user_id, customer, ..
+        }
+        | CustomerRequestToRepository::CancelScheduledNotification {
+            user_id, customer, ..
+        }
+        | CustomerRequestToRepository::NewRecurringNotification {
+            user_id, customer, ..
+        }
+        | CustomerRequestToRepository::PauseRecurringNotification {
+            user_id, customer, ..
+        }
+        | CustomerRequestToRepository::DeleteRecurringNotification {
+            user_id, customer, ..
+        } => Some((*user_id, customer, OperatorRole::Editor)),
+        CustomerRequestToRepository::Operators { user_id, customer }
+        | CustomerRequestToRepository::InviteOperator {
+            user_id, customer, ..
+        }
+        | CustomerRequestToRepository::RevokeOperator {
+            user_id, customer, ..
+        }
+        | CustomerRequestToRepository::SetWebhook {
+            user_id, customer, ..
+        } => Some((*user_id, customer, OperatorRole::Owner)),
     }
 }
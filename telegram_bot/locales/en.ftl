@@ -0,0 +1,132 @@
+## Common
+
+language-usage = Choose a language: /language ru or /language en
+language-changed = Interface language: English.
+invalid-name = Invalid name. Please choose an option from the list.
+unknown-customer = Supplier [{ $customer }] was not found. Choose a supplier from the list:
+unknown-product = Product [{ $product }] of supplier [{ $customer }] was not found. Choose a product from the list:
+request-failure = The service is temporarily unavailable, please try again later.
+service-unavailable = The service did not respond in time, please try again later.
+
+## Client bot
+
+choose-customer = Choose a supplier:
+choose-product = Choose the product you are interested in:
+choose-channel = How do you want to receive notifications?
+channel-telegram = Telegram
+channel-email = Email
+channel-both = Telegram and email
+invalid-channel = Please choose an option from the list.
+enter-email = Enter your email address:
+invalid-email = Invalid email address, please try again:
+subscription-success = Subscription created!
+subscription-failure = Sorry, something went wrong and the subscription was not created!
+new-notification = New notification from supplier [{ $customer }] for product [{ $product }]:
+
+## Customer bot
+
+enter-key = Enter your authorization key:
+authorization-failure = The key is incorrect!
+authorization-locked = Too many failed attempts. Try again after { $until }.
+welcome = Welcome, { $customer }
+choose-command = Supplier [{ $customer }]. What would you like to do?
+command-add-notification = Create notification
+command-scheduled-notifications = Scheduled notifications
+command-recurring-notifications = Recurring notifications
+no-subscriptions = There are no product subscriptions!
+choose-goods = Choose a product:
+enter-notification = Enter the notification text (it will be sent to clients). You can attach a photo or a document. To use formatting, start the text with a /html or /markdown line, and add link buttons at the end as lines like "Text | https://address". To send the notification later, put a line "/at YYYY-MM-DD HH:MM" first, to send it regularly put a line "/every <cron expression> [timezone]" first, e.g. "/every 0 9 * * MON-FRI Europe/Moscow":
+empty-notification = Send a text, a photo or a document:
+notification-success = Notification sent!
+notification-failure = Failed to send the notification!
+invalid-send-at = Invalid send time. Use the format /at YYYY-MM-DD HH:MM (timezone { $timezone }).
+past-send-at = The send time has already passed (timezone { $timezone }). Enter a time in the future.
+notification-scheduled = Notification #{ $id } will be sent at { $send_at }.
+no-scheduled-notifications = There are no scheduled notifications.
+scheduled-notifications = Scheduled notifications:
+scheduled-notification = #{ $id } at { $send_at }, product [{ $product }]: { $text }
+cancel-scheduled-notification = Cancel #{ $id }
+scheduled-notification-cancelled = Notification #{ $id } cancelled.
+scheduled-notification-not-cancelled = Notification #{ $id } has already been sent or cancelled.
+invalid-schedule = Invalid schedule "{ $schedule }" (timezone { $timezone }). Use a cron expression of five fields: minute, hour, day of month, month, day of week, e.g. /every 0 9 * * MON-FRI Europe/Moscow.
+recurring-notification-created = Recurring notification created: { $notification }
+no-recurring-notifications = There are no recurring notifications.
+recurring-notifications = Recurring notifications:
+recurring-notification = #{ $id } "{ $schedule }" ({ $timezone }), next { $next_send_at }, product [{ $product }]: { $text }
+recurring-notification-inactive = paused
+pause-recurring-notification = Pause #{ $id }
+resume-recurring-notification = Resume #{ $id }
+delete-recurring-notification = Delete #{ $id }
+recurring-notification-paused = Recurring notification #{ $id } paused.
+recurring-notification-resumed = Recurring notification #{ $id } resumed.
+recurring-notification-deleted = Recurring notification #{ $id } deleted.
+recurring-notification-not-found = Recurring notification #{ $id } was not found.
+quota-exceeded = The notification was not sent: the limit of { $quota } is reached. Try again in { $minutes } min.
+quota-notifications-per-hour = { $limit } notifications per hour
+quota-notifications-per-day = { $limit } notifications per day
+quota-recipients-per-day = { $limit } recipients per day
+notification-deferred = The scheduled notification #{ $id } is postponed: the limit of { $quota } is reached. It will be sent at { $send_at }.
+delivery-progress = Notification sent out to { $published } of { $total } subscribers...
+delivery-finished = Notification sending finished: sent out to { $published } of { $total } subscribers, { $failed } failed.
+notification-rejected-too-long = The notification was rejected: it is { $length } characters long, the limit is { $max_length }.
+notification-rejected-banned-word = The notification was rejected: the word "{ $word }" is not allowed.
+notification-rejected-denied-link = The notification was rejected: links to { $domain } are not allowed.
+notification-rejected-by-moderator = The notification was rejected by a moderator: { $comment }
+notification-held = The notification #{ $id } is waiting for moderator review, it will be sent once approved.
+client-subscription = New subscription for product [{ $product }]!
+webhook-usage = Set a webhook for your events: /webhook https://example.com/hook, remove it: /webhook off
+webhook-registered = Events will be posted to { $url }. Verify the X-NotifyMe-Signature header with the secret: { $secret }
+webhook-removed = The webhook was removed.
+invalid-webhook-url = The address { $url } can't be used for a webhook, it must start with https://.
+command-operators = Operators
+operators = Operators:
+operator = { $id }: { $role }
+operator-self = { $id }: { $role } (you)
+role-owner = owner
+role-editor = editor
+role-viewer = viewer
+revoke-operator = Revoke { $id }
+invite-operator = Invite { $role }
+operator-invited = Invite key for a new { $role }, valid once until { $expires_at }: { $key }
+operator-revoked = Operator { $id } no longer has access.
+operator-not-revoked = Operator { $id } was not found.
+operator-access-revoked = Your access to supplier [{ $customer }] was revoked.
+permission-denied = You don't have permission to do that.
+command-switch-customer = Switch supplier
+command-add-customer = Add supplier
+choose-active-customer = Choose the supplier to work with:
+no-subscriptions = You have no subscriptions. Send any message to subscribe.
+subscriptions = Your subscriptions:
+subscription-active = #{ $id } supplier [{ $customer }], product [{ $product }], { $channel }: { $mode }
+subscription-delivered = #{ $id } supplier [{ $customer }], product [{ $product }], { $channel }: delivered
+cancel-subscription = Cancel #{ $id }
+cancel-all-subscriptions = Cancel all
+subscription-cancelled = Subscription #{ $id } cancelled.
+subscription-not-cancelled = Subscription #{ $id } has already been delivered or cancelled.
+subscriptions-cancelled = Subscriptions cancelled: { $count }
+choose-mode = Which notifications do you want to receive?
+mode-once = Only the next one
+mode-always = Every one
+mode-times = A number of them
+mode-until = Until a date
+invalid-mode = Please choose an option from the list.
+enter-count = How many notifications do you want to receive?
+invalid-count = Please enter a positive whole number:
+enter-until = Enter the last day to receive notifications, as YYYY-MM-DD:
+invalid-until = Please enter a future date as YYYY-MM-DD:
+subscription-mode-once = waiting for the next notification
+subscription-mode-always = every notification
+subscription-mode-times = { $count } more notifications
+subscription-mode-until = notifications until { $until }
+command-history = Sent notifications
+no-sent-notifications = No notifications have been sent.
+sent-notifications = Sent notifications:
+sent-notifications-product = Sent notifications for product [{ $product }]:
+sent-notification = #{ $id } at { $sent_at }, product [{ $product }]: { $text }
+sent-at-unknown = unknown time
+history-newer = « Newer
+history-older = Older »
+no-received-notifications = You have not received any notifications yet.
+received-notifications = Your notifications:
+received-notification = { $received_at } supplier [{ $customer }], product [{ $product }]: { $text }
+caption-too-long = The text of a photo or document is { $length } characters long, Telegram allows at most { $max_length }. Shorten the text or send it without the attachment.
@@ -0,0 +1,132 @@
+## Common
+
+language-usage = Выберите язык: /language ru или /language en
+language-changed = Язык интерфейса: русский.
+invalid-name = Некорректное название. Выберите вариант из списка.
+unknown-customer = Поставщик [{ $customer }] не найден. Выберите поставщика из списка:
+unknown-product = Товар [{ $product }] поставщика [{ $customer }] не найден. Выберите товар из списка:
+request-failure = Сервис временно недоступен, попробуйте позже.
+service-unavailable = Сервис не ответил вовремя, попробуйте позже.
+
+## Client bot
+
+choose-customer = Выберите поставщика:
+choose-product = Выберите интересующий вас продукт:
+choose-channel = Как вы хотите получать уведомления?
+channel-telegram = Telegram
+channel-email = Email
+channel-both = Telegram и email
+invalid-channel = Выберите вариант из списка.
+enter-email = Введите ваш адрес электронной почты:
+invalid-email = Некорректный адрес электронной почты, попробуйте ещё раз:
+subscription-success = Подписка успешно оформлена!
+subscription-failure = К сожалению, что-то пошло не так и подписка не оформлена!
+new-notification = Новое уведомление от поставщика [{ $customer }] для товара [{ $product }]:
+
+## Customer bot
+
+enter-key = Введите ключ для авторизации:
+authorization-failure = Указан не верный ключ!
+authorization-locked = Слишком много неудачных попыток. Повторите после { $until }.
+welcome = Добро пожаловать, { $customer }
+choose-command = Поставщик [{ $customer }]. Что хотите сделать?
+command-add-notification = Создать уведомление
+command-scheduled-notifications = Запланированные уведомления
+command-recurring-notifications = Повторяющиеся уведомления
+no-subscriptions = Нет подписок на товары!
+choose-goods = Выберите товар:
+enter-notification = Введите текст уведомления (он будет отправлен клиентам). К сообщению можно приложить фото или документ. Для форматирования начните текст строкой /html или /markdown, а кнопки-ссылки добавьте в конце строками вида «Текст | https://адрес». Чтобы отправить уведомление позже, первой строкой укажите «/at ГГГГ-ММ-ДД ЧЧ:ММ», чтобы отправлять его регулярно — «/every <cron-выражение> [часовой пояс]», например «/every 0 9 * * MON-FRI Europe/Moscow»:
+empty-notification = Отправьте текст, фото или документ:
+notification-success = Уведомление успешно отправлено!
+notification-failure = Не удалось отправить уведомление!
+invalid-send-at = Некорректное время отправки. Используйте формат /at ГГГГ-ММ-ДД ЧЧ:ММ (часовой пояс { $timezone }).
+past-send-at = Время отправки уже прошло (часовой пояс { $timezone }). Укажите время в будущем.
+notification-scheduled = Уведомление №{ $id } будет отправлено { $send_at }.
+no-scheduled-notifications = Нет запланированных уведомлений.
+scheduled-notifications = Запланированные уведомления:
+scheduled-notification = №{ $id } на { $send_at }, товар [{ $product }]: { $text }
+cancel-scheduled-notification = Отменить №{ $id }
+scheduled-notification-cancelled = Уведомление №{ $id } отменено.
+scheduled-notification-not-cancelled = Уведомление №{ $id } уже отправлено или отменено.
+invalid-schedule = Некорректное расписание «{ $schedule }» (часовой пояс { $timezone }). Используйте cron-выражение из пяти полей: минута, час, день месяца, месяц, день недели, например /every 0 9 * * MON-FRI Europe/Moscow.
+recurring-notification-created = Создано повторяющееся уведомление: { $notification }
+no-recurring-notifications = Нет повторяющихся уведомлений.
+recurring-notifications = Повторяющиеся уведомления:
+recurring-notification = №{ $id } «{ $schedule }» ({ $timezone }), следующая отправка { $next_send_at }, товар [{ $product }]: { $text }
+recurring-notification-inactive = приостановлено
+pause-recurring-notification = Приостановить №{ $id }
+resume-recurring-notification = Возобновить №{ $id }
+delete-recurring-notification = Удалить №{ $id }
+recurring-notification-paused = Повторяющееся уведомление №{ $id } приостановлено.
+recurring-notification-resumed = Повторяющееся уведомление №{ $id } возобновлено.
+recurring-notification-deleted = Повторяющееся уведомление №{ $id } удалено.
+recurring-notification-not-found = Повторяющееся уведомление №{ $id } не найдено.
+quota-exceeded = Уведомление не отправлено: достигнут лимит { $quota }. Повторите через { $minutes } мин.
+quota-notifications-per-hour = { $limit } уведомлений в час
+quota-notifications-per-day = { $limit } уведомлений в сутки
+quota-recipients-per-day = { $limit } получателей в сутки
+notification-deferred = Запланированное уведомление №{ $id } отложено: достигнут лимит { $quota }. Оно будет отправлено { $send_at }.
+delivery-progress = Уведомление отправлено { $published } из { $total } подписчиков...
+delivery-finished = Отправка уведомления завершена: отправлено { $published } из { $total } подписчиков, ошибок: { $failed }.
+notification-rejected-too-long = Уведомление отклонено: его длина { $length } символов при лимите { $max_length }.
+notification-rejected-banned-word = Уведомление отклонено: слово «{ $word }» запрещено.
+notification-rejected-denied-link = Уведомление отклонено: ссылки на { $domain } запрещены.
+notification-rejected-by-moderator = Уведомление отклонено модератором: { $comment }
+notification-held = Уведомление №{ $id } ожидает проверки модератором и будет отправлено после одобрения.
+client-subscription = Оформлена подписка на товар [{ $product }]!
+webhook-usage = Подключите вебхук для событий: /webhook https://example.com/hook, отключите: /webhook off
+webhook-registered = События будут отправляться на { $url }. Проверяйте заголовок X-NotifyMe-Signature с секретом: { $secret }
+webhook-removed = Вебхук отключён.
+invalid-webhook-url = Адрес { $url } не подходит для вебхука, он должен начинаться с https://.
+command-operators = Операторы
+operators = Операторы:
+operator = { $id }: { $role }
+operator-self = { $id }: { $role } (вы)
+role-owner = владелец
+role-editor = редактор
+role-viewer = наблюдатель
+revoke-operator = Отозвать { $id }
+invite-operator = Пригласить: { $role }
+operator-invited = Ключ приглашения (роль: { $role }), действует один раз до { $expires_at }: { $key }
+operator-revoked = Оператор { $id } больше не имеет доступа.
+operator-not-revoked = Оператор { $id } не найден.
+operator-access-revoked = Ваш доступ к поставщику [{ $customer }] отозван.
+permission-denied = У вас нет прав на это действие.
+command-switch-customer = Сменить поставщика
+command-add-customer = Добавить поставщика
+choose-active-customer = Выберите поставщика для работы:
+no-subscriptions = У вас нет подписок. Отправьте любое сообщение, чтобы подписаться.
+subscriptions = Ваши подписки:
+subscription-active = #{ $id } поставщик [{ $customer }], товар [{ $product }], { $channel }: { $mode }
+subscription-delivered = #{ $id } поставщик [{ $customer }], товар [{ $product }], { $channel }: доставлена
+cancel-subscription = Отменить #{ $id }
+cancel-all-subscriptions = Отменить все
+subscription-cancelled = Подписка #{ $id } отменена.
+subscription-not-cancelled = Подписка #{ $id } уже доставлена или отменена.
+subscriptions-cancelled = Отменено подписок: { $count }
+choose-mode = Какие уведомления вы хотите получать?
+mode-once = Только следующее
+mode-always = Все
+mode-times = Несколько
+mode-until = До даты
+invalid-mode = Выберите вариант из списка.
+enter-count = Сколько уведомлений вы хотите получить?
+invalid-count = Введите целое положительное число:
+enter-until = Введите последний день получения уведомлений в формате ГГГГ-ММ-ДД:
+invalid-until = Введите будущую дату в формате ГГГГ-ММ-ДД:
+subscription-mode-once = ожидает следующего уведомления
+subscription-mode-always = все уведомления
+subscription-mode-times = ещё уведомлений: { $count }
+subscription-mode-until = уведомления до { $until }
+command-history = Отправленные уведомления
+no-sent-notifications = Нет отправленных уведомлений.
+sent-notifications = Отправленные уведомления:
+sent-notifications-product = Отправленные уведомления по товару [{ $product }]:
+sent-notification = №{ $id } в { $sent_at }, товар [{ $product }]: { $text }
+sent-at-unknown = время неизвестно
+history-newer = « Новее
+history-older = Старее »
+no-received-notifications = Вы ещё не получали уведомлений.
+received-notifications = Ваши уведомления:
+received-notification = { $received_at } поставщик [{ $customer }], товар [{ $product }]: { $text }
+caption-too-long = Текст к фото или документу длиной { $length } символов, Telegram допускает не более { $max_length }. Сократите текст или отправьте его без вложения.
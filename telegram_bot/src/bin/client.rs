@@ -1,10 +1,22 @@
 use std::{net::SocketAddr, sync::Arc};
 
-use domain::{models::UserId, requests::ClientRequest};
-use dotenv::dotenv;
 use amqp::{Publisher, RabbitMqManager};
+use chrono_tz::Tz;
+use domain::{
+    codec::{WireFormat, WireMessage},
+    models::{CustomerName, DeliveryChannel, ProductName, SubscriptionMode, Timestamp, UserId},
+    requests::ClientRequest,
+};
+use dotenv::dotenv;
 use telegram_bot::{
-    client::{state::State, ClientService, MessageHandler},
+    client::{
+        command::{Command, HISTORY_COMMAND, SUBSCRIPTIONS_COMMAND},
+        mode::{subscription_count, subscription_until},
+        state::State,
+        ClientService, Deliverer, DeliveryQueue, MessageHandler,
+    },
+    command_argument,
+    i18n::{I18n, Language, LANGUAGE_COMMAND},
     storage::StateStorage,
     Config,
 };
@@ -12,15 +24,18 @@ use telegram_bot::{
 use teloxide::{
     dispatching::{update_listeners::webhooks, UpdateFilterExt},
     dptree,
+    payloads::SendMessageSetters,
     prelude::{AutoSend, Dispatcher, LoggingErrorHandler},
-    requests::RequesterExt,
-    types::{Message, Update},
+    requests::{Requester, RequesterExt},
+    types::{CallbackQuery, KeyboardButton, KeyboardMarkup, KeyboardRemove, Message, Update},
     Bot,
 };
 
 use tokio::sync::Mutex;
 use url::Url;
 
+const DEFAULT_LANGUAGES_PATH: &str = "client_languages.json";
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -37,7 +52,34 @@ async fn main() {
         .await
         .expect("Couldn't setup webhook");
 
-    let service = Arc::new(Mutex::new(ClientService::new(bot.clone())));
+    let i18n = I18n::new(
+        config
+            .telegram_client_languages_path
+            .as_deref()
+            .unwrap_or(DEFAULT_LANGUAGES_PATH),
+    );
+    let timezone: Tz = match &config.telegram_client_timezone {
+        Some(timezone) => timezone.parse().unwrap(),
+        None => Tz::UTC,
+    };
+    let state_storage = StateStorage::<State>::new();
+    let (delivery, jobs) = DeliveryQueue::new();
+    tokio::spawn(
+        Deliverer::new(
+            bot.clone(),
+            i18n.clone(),
+            jobs,
+            config.telegram_client_delivery_rate,
+        )
+        .run(),
+    );
+    let service = Arc::new(Mutex::new(ClientService::new(
+        bot.clone(),
+        state_storage.clone(),
+        i18n.clone(),
+        delivery,
+        timezone,
+    )));
 
     let mut manager = RabbitMqManager::builder()
         .build(&config.amqp_address)
@@ -46,20 +88,22 @@ async fn main() {
     manager
         .add_consumer(
             &config.client_response_queue,
-            MessageHandler::client_response(service.clone()),
+            MessageHandler::client_response(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
 
     let publisher = Arc::new(Mutex::new(manager.get_publisher().await.unwrap()));
-    let params = ConfigParams::new(config, publisher);
-    let state_storage = StateStorage::<State>::new();
+    let params = ConfigParams::new(config, publisher, timezone);
 
     let telegram_message_handler = Update::filter_message().endpoint(message_handler);
-    let handler = dptree::entry().branch(telegram_message_handler);
+    let callback_query_handler = Update::filter_callback_query().endpoint(callback_handler);
+    let handler = dptree::entry()
+        .branch(telegram_message_handler)
+        .branch(callback_query_handler);
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![state_storage, params])
+        .dependencies(dptree::deps![state_storage, params, i18n])
         .enable_ctrlc_handler()
         .build()
         .dispatch_with_listener(
@@ -74,15 +118,20 @@ struct ConfigParams {
     publisher: Arc<Mutex<Publisher>>,
     exchange: String,
     request_queue: String,
+    wire_format: WireFormat,
+    timezone: Tz,
 }
 impl ConfigParams {
-    fn new(config: Config, publisher: Arc<Mutex<Publisher>>) -> Self {
+    fn new(config: Config, publisher: Arc<Mutex<Publisher>>, timezone: Tz) -> Self {
         let exchange = config.exchange;
         let request_queue = config.client_request_queue;
+        let wire_format = config.wire_format;
         ConfigParams {
             publisher,
             exchange,
             request_queue,
+            wire_format,
+            timezone,
         }
     }
 }
@@ -90,12 +139,27 @@ impl ConfigParams {
 type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
 async fn message_handler(
-    _bot: AutoSend<Bot>,
+    bot: AutoSend<Bot>,
     msg: Message,
     storage: Arc<StateStorage<State>>,
     params: ConfigParams,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
     log::info!("Message from user [{}]", msg.chat.id.0);
+    let language_code = msg.from().and_then(|user| user.language_code.as_deref());
+    i18n.detect(msg.chat.id, language_code).await;
+    if let Some(text) = msg.text() {
+        if let Some(argument) = command_argument(text, LANGUAGE_COMMAND).map(str::to_owned) {
+            return change_language(bot, msg, i18n, &argument).await;
+        }
+        if command_argument(text, SUBSCRIPTIONS_COMMAND).is_some() {
+            return subscriptions(msg, params).await;
+        }
+        if command_argument(text, HISTORY_COMMAND).is_some() {
+            return history(msg, params).await;
+        }
+    }
+
     let state = match storage.get_state(&msg.chat.id).await {
         Some(state) => state,
         None => State::Start,
@@ -103,14 +167,150 @@ async fn message_handler(
 
     match state {
         State::Start => choose_customer(msg, storage, params).await?,
-        State::Customer => choose_product(msg, storage, params).await?,
-        State::Product { customer } => add_subscription(msg, storage, params, customer).await?,
+        State::Customer => choose_product(bot, msg, storage, params, i18n).await?,
+        State::Product { customer } => choose_channel(bot, msg, storage, customer, i18n).await?,
+        State::Channel { customer, product } => {
+            choose_email(bot, msg, storage, customer, product, i18n).await?
+        }
+        State::Email {
+            customer,
+            product,
+            channel,
+        } => set_email(bot, msg, storage, customer, product, channel, i18n).await?,
+        State::Mode {
+            customer,
+            product,
+            channel,
+            email,
+        } => {
+            set_mode(
+                bot, msg, storage, params, customer, product, channel, email, i18n,
+            )
+            .await?
+        }
+        State::Count {
+            customer,
+            product,
+            channel,
+            email,
+        } => {
+            set_count(
+                bot, msg, storage, params, customer, product, channel, email, i18n,
+            )
+            .await?
+        }
+        State::Until {
+            customer,
+            product,
+            channel,
+            email,
+        } => {
+            set_until(
+                bot, msg, storage, params, customer, product, channel, email, i18n,
+            )
+            .await?
+        }
         State::End => choose_customer(msg, storage, params).await?, //Костыль
     }
 
     Ok(())
 }
 
+async fn change_language(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    i18n: Arc<I18n>,
+    argument: &str,
+) -> HandlerResult {
+    let text = match Language::from_code(argument) {
+        Some(language) => {
+            i18n.set_language(msg.chat.id, language).await?;
+            i18n.text(msg.chat.id, "language-changed").await
+        }
+        None => i18n.text(msg.chat.id, "language-usage").await,
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+async fn subscriptions(msg: Message, params: ConfigParams) -> HandlerResult {
+    log::info!("Subscriptions of user [{}]", msg.chat.id.0);
+    let message = ClientRequest::Subscriptions {
+        user_id: UserId::from(msg.chat.id.0),
+        timestamp: Timestamp::now(),
+    }
+    .encode(params.wire_format);
+    params
+        .publisher
+        .lock()
+        .await
+        .publish_message(&params.exchange, &params.request_queue, message)
+        .await
+        .unwrap();
+    Ok(())
+}
+
+async fn history(msg: Message, params: ConfigParams) -> HandlerResult {
+    log::info!("Notification history of user [{}]", msg.chat.id.0);
+    let message = ClientRequest::NotificationHistory {
+        user_id: UserId::from(msg.chat.id.0),
+        page: 0,
+        timestamp: Timestamp::now(),
+    }
+    .encode(params.wire_format);
+    params
+        .publisher
+        .lock()
+        .await
+        .publish_message(&params.exchange, &params.request_queue, message)
+        .await
+        .unwrap();
+    Ok(())
+}
+
+async fn callback_handler(
+    q: CallbackQuery,
+    bot: AutoSend<Bot>,
+    params: ConfigParams,
+) -> HandlerResult {
+    if let Some(data) = q.data {
+        log::info!("Callback [{}]", data);
+
+        if let Some(message) = q.message {
+            bot.delete_message(message.chat.id, message.id).await?;
+            let user_id = UserId::from(message.chat.id.0);
+            let timestamp = Timestamp::now();
+            let request = match Command::from(data) {
+                Command::CancelSubscription(id) => ClientRequest::CancelSubscription {
+                    user_id,
+                    id,
+                    timestamp,
+                },
+                Command::CancelAllSubscriptions => {
+                    ClientRequest::CancelAllSubscriptions { user_id, timestamp }
+                }
+                Command::History(page) => ClientRequest::NotificationHistory {
+                    user_id,
+                    page,
+                    timestamp,
+                },
+            };
+            let message = request.encode(params.wire_format);
+            params
+                .publisher
+                .lock()
+                .await
+                .publish_message(&params.exchange, &params.request_queue, message)
+                .await
+                .unwrap();
+        }
+    } else {
+        log::info!("None of callback");
+    }
+
+    Ok(())
+}
+
 async fn choose_customer(
     msg: Message,
     storage: Arc<StateStorage<State>>,
@@ -119,9 +319,9 @@ async fn choose_customer(
     log::info!("Choose customer for user [{}]", msg.chat.id.0);
     let message = ClientRequest::Customers {
         user_id: UserId::from(msg.chat.id.0),
-        timestamp: msg.date.timestamp(),
+        timestamp: Timestamp::now(),
     }
-    .to_string();
+    .encode(params.wire_format);
 
     params
         .publisher
@@ -135,18 +335,32 @@ async fn choose_customer(
 }
 
 async fn choose_product(
+    bot: AutoSend<Bot>,
     msg: Message,
     storage: Arc<StateStorage<State>>,
     params: ConfigParams,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
     log::info!("choose product for user [{}]", msg.chat.id.0);
-    let customer = String::from(msg.text().unwrap());
+    let customer = match CustomerName::parse(msg.text().unwrap_or_default()) {
+        Ok(customer) => customer,
+        Err(error) => {
+            log::info!(
+                "Invalid customer name from user [{}]: {}",
+                msg.chat.id.0,
+                error
+            );
+            let text = i18n.text(msg.chat.id, "invalid-name").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
     let message = ClientRequest::Products {
         user_id: UserId::from(msg.chat.id.0),
         customer: customer.clone(),
-        timestamp: msg.date.timestamp(),
+        timestamp: Timestamp::now(),
     }
-    .to_string();
+    .encode(params.wire_format);
     params
         .publisher
         .lock()
@@ -160,21 +374,290 @@ async fn choose_product(
     Ok(())
 }
 
+async fn choose_channel(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    customer: CustomerName,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    log::info!("choose channel for user [{}]", msg.chat.id.0);
+    let product = match ProductName::parse(msg.text().unwrap_or_default()) {
+        Ok(product) => product,
+        Err(error) => {
+            log::info!(
+                "Invalid product name from user [{}]: {}",
+                msg.chat.id.0,
+                error
+            );
+            let text = i18n.text(msg.chat.id, "invalid-name").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
+    let mut keyboard: Vec<Vec<KeyboardButton>> = vec![];
+    for key in ["channel-telegram", "channel-email", "channel-both"] {
+        keyboard.push(vec![KeyboardButton::new(i18n.text(msg.chat.id, key).await)]);
+    }
+    let text = i18n.text(msg.chat.id, "choose-channel").await;
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(KeyboardMarkup::new(keyboard))
+        .await?;
+    storage
+        .set_state(msg.chat.id, State::Channel { customer, product })
+        .await;
+    Ok(())
+}
+
+async fn choose_email(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    customer: CustomerName,
+    product: ProductName,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let text = msg.text().unwrap_or_default();
+    let channel = if text == i18n.text(msg.chat.id, "channel-telegram").await {
+        DeliveryChannel::Telegram
+    } else if text == i18n.text(msg.chat.id, "channel-email").await {
+        DeliveryChannel::Email
+    } else if text == i18n.text(msg.chat.id, "channel-both").await {
+        DeliveryChannel::Both
+    } else {
+        let text = i18n.text(msg.chat.id, "invalid-channel").await;
+        bot.send_message(msg.chat.id, text).await?;
+        return Ok(());
+    };
+    if !channel.email() {
+        return choose_mode(bot, msg, storage, customer, product, channel, None, i18n).await;
+    }
+
+    let text = i18n.text(msg.chat.id, "enter-email").await;
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(KeyboardRemove::new())
+        .await?;
+    storage
+        .set_state(
+            msg.chat.id,
+            State::Email {
+                customer,
+                product,
+                channel,
+            },
+        )
+        .await;
+    Ok(())
+}
+
+async fn set_email(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    customer: CustomerName,
+    product: ProductName,
+    channel: DeliveryChannel,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let email = msg.text().unwrap_or_default().trim().to_string();
+    if !is_email(&email) {
+        log::info!("Invalid email from user [{}]", msg.chat.id.0);
+        let text = i18n.text(msg.chat.id, "invalid-email").await;
+        bot.send_message(msg.chat.id, text).await?;
+        return Ok(());
+    }
+    choose_mode(
+        bot,
+        msg,
+        storage,
+        customer,
+        product,
+        channel,
+        Some(email),
+        i18n,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn choose_mode(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    customer: CustomerName,
+    product: ProductName,
+    channel: DeliveryChannel,
+    email: Option<String>,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let mut keyboard: Vec<Vec<KeyboardButton>> = vec![];
+    for key in ["mode-once", "mode-always", "mode-times", "mode-until"] {
+        keyboard.push(vec![KeyboardButton::new(i18n.text(msg.chat.id, key).await)]);
+    }
+    let text = i18n.text(msg.chat.id, "choose-mode").await;
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(KeyboardMarkup::new(keyboard))
+        .await?;
+    storage
+        .set_state(
+            msg.chat.id,
+            State::Mode {
+                customer,
+                product,
+                channel,
+                email,
+            },
+        )
+        .await;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn set_mode(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    params: ConfigParams,
+    customer: CustomerName,
+    product: ProductName,
+    channel: DeliveryChannel,
+    email: Option<String>,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let text = msg.text().unwrap_or_default();
+    let mode = if text == i18n.text(msg.chat.id, "mode-once").await {
+        SubscriptionMode::Once
+    } else if text == i18n.text(msg.chat.id, "mode-always").await {
+        SubscriptionMode::Always
+    } else if text == i18n.text(msg.chat.id, "mode-times").await {
+        let text = i18n.text(msg.chat.id, "enter-count").await;
+        bot.send_message(msg.chat.id, text)
+            .reply_markup(KeyboardRemove::new())
+            .await?;
+        let state = State::Count {
+            customer,
+            product,
+            channel,
+            email,
+        };
+        storage.set_state(msg.chat.id, state).await;
+        return Ok(());
+    } else if text == i18n.text(msg.chat.id, "mode-until").await {
+        let text = i18n.text(msg.chat.id, "enter-until").await;
+        bot.send_message(msg.chat.id, text)
+            .reply_markup(KeyboardRemove::new())
+            .await?;
+        let state = State::Until {
+            customer,
+            product,
+            channel,
+            email,
+        };
+        storage.set_state(msg.chat.id, state).await;
+        return Ok(());
+    } else {
+        let text = i18n.text(msg.chat.id, "invalid-mode").await;
+        bot.send_message(msg.chat.id, text).await?;
+        return Ok(());
+    };
+    add_subscription(
+        msg, storage, params, customer, product, channel, email, mode,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn set_count(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    params: ConfigParams,
+    customer: CustomerName,
+    product: ProductName,
+    channel: DeliveryChannel,
+    email: Option<String>,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let count = match subscription_count(msg.text().unwrap_or_default()) {
+        Some(count) => count,
+        None => {
+            let text = i18n.text(msg.chat.id, "invalid-count").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
+    let mode = SubscriptionMode::Times { count };
+    add_subscription(
+        msg, storage, params, customer, product, channel, email, mode,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn set_until(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    params: ConfigParams,
+    customer: CustomerName,
+    product: ProductName,
+    channel: DeliveryChannel,
+    email: Option<String>,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let text = msg.text().unwrap_or_default();
+    let until = match subscription_until(text, params.timezone, Timestamp::from(msg.date)) {
+        Some(until) => until,
+        None => {
+            let text = i18n.text(msg.chat.id, "invalid-until").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
+    let mode = SubscriptionMode::Until { until };
+    add_subscription(
+        msg, storage, params, customer, product, channel, email, mode,
+    )
+    .await
+}
+
+/// Rough check only, the mail server has the final say on the address.
+fn is_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+                && !domain.contains('@')
+        }
+        None => false,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn add_subscription(
     msg: Message,
     storage: Arc<StateStorage<State>>,
     params: ConfigParams,
-    customer: String,
+    customer: CustomerName,
+    product: ProductName,
+    channel: DeliveryChannel,
+    email: Option<String>,
+    mode: SubscriptionMode,
 ) -> HandlerResult {
     log::info!("add subscription for user [{}]", msg.chat.id.0);
-    let product = String::from(msg.text().unwrap());
     let message = ClientRequest::NewSubscription {
         user_id: UserId::from(msg.chat.id.0),
         customer,
         product,
-        timestamp: msg.date.timestamp(),
+        channel,
+        email,
+        timestamp: Timestamp::now(),
+        mode,
     }
-    .to_string();
+    .encode(params.wire_format);
     params
         .publisher
         .lock()
@@ -1,14 +1,22 @@
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
+use amqp::{Publisher, RabbitMqManager};
+use chrono_tz::Tz;
 use domain::{
-    models::{Customer, UserId},
+    codec::{WireFormat, WireMessage},
+    models::{CustomerName, OperatorRole, ProductName, Timestamp, UserId},
     requests::CustomerRequest,
 };
 use dotenv::dotenv;
-use amqp::{Publisher, RabbitMqManager};
-use serde::{Deserialize, Serialize};
 use telegram_bot::{
-    customer::{state::State, CustomerService, MessageHandler},
+    command_argument,
+    customer::{
+        command::{history_product, webhook_url, Command, HISTORY_COMMAND, WEBHOOK_COMMAND},
+        notification_content, recurring_schedule, send_at,
+        state::{OperatorSession, State},
+        ContentError, CustomerService, MessageHandler, SendAtError,
+    },
+    i18n::{I18n, Language, LANGUAGE_COMMAND},
     storage::StateStorage,
     Config, HandlerResult,
 };
@@ -28,6 +36,8 @@ use teloxide::{
 use tokio::sync::Mutex;
 use url::Url;
 
+const DEFAULT_LANGUAGES_PATH: &str = "customer_languages.json";
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -45,11 +55,23 @@ async fn main() {
         .expect("Couldn't setup webhook");
 
     let state_storage = StateStorage::<State>::new();
-    let authorized_customers = Arc::new(Mutex::new(HashMap::<ChatId, Customer>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::<ChatId, OperatorSession>::new()));
+    let i18n = I18n::new(
+        config
+            .telegram_customer_languages_path
+            .as_deref()
+            .unwrap_or(DEFAULT_LANGUAGES_PATH),
+    );
+    let timezone: Tz = match &config.telegram_customer_timezone {
+        Some(timezone) => timezone.parse().unwrap(),
+        None => Tz::UTC,
+    };
     let service = Arc::new(Mutex::new(CustomerService::new(
         bot.clone(),
         state_storage.clone(),
-        authorized_customers.clone(),
+        sessions.clone(),
+        i18n.clone(),
+        timezone,
     )));
 
     let mut manager = RabbitMqManager::builder()
@@ -59,13 +81,13 @@ async fn main() {
     manager
         .add_consumer(
             &config.customer_response_queue,
-            MessageHandler::customer_response(service.clone()),
+            MessageHandler::customer_response(service.clone(), config.wire_format),
         )
         .await
         .unwrap();
 
     let publisher = Arc::new(Mutex::new(manager.get_publisher().await.unwrap()));
-    let params = ConfigParams::new(config, authorized_customers, publisher);
+    let params = ConfigParams::new(config, sessions, publisher, timezone);
 
     let message_handler = Update::filter_message().endpoint(message_handler);
     let callback_query_handler = Update::filter_callback_query().endpoint(callback_handler);
@@ -75,7 +97,7 @@ async fn main() {
         .branch(callback_query_handler);
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![state_storage, params])
+        .dependencies(dptree::deps![state_storage, params, i18n])
         .enable_ctrlc_handler()
         .build()
         .dispatch_with_listener(
@@ -87,24 +109,30 @@ async fn main() {
 
 #[derive(Clone)]
 struct ConfigParams {
-    authorized_customers: Arc<Mutex<HashMap<ChatId, Customer>>>,
+    sessions: Arc<Mutex<HashMap<ChatId, OperatorSession>>>,
     publisher: Arc<Mutex<Publisher>>,
     exchange: String,
     request_queue: String,
+    wire_format: WireFormat,
+    timezone: Tz,
 }
 impl ConfigParams {
     fn new(
         config: Config,
-        authorized_customers: Arc<Mutex<HashMap<ChatId, Customer>>>,
+        sessions: Arc<Mutex<HashMap<ChatId, OperatorSession>>>,
         publisher: Arc<Mutex<Publisher>>,
+        timezone: Tz,
     ) -> Self {
         let exchange = config.exchange;
         let request_queue = config.customer_request_queue;
+        let wire_format = config.wire_format;
         ConfigParams {
-            authorized_customers,
+            sessions,
             publisher,
             exchange,
             request_queue,
+            wire_format,
+            timezone,
         }
     }
 }
@@ -114,46 +142,183 @@ async fn message_handler(
     msg: Message,
     storage: Arc<StateStorage<State>>,
     params: ConfigParams,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
     let state = match storage.get_state(&msg.chat.id).await {
         Some(state) => state,
         None => State::Start,
     };
     log::info!("Message from user [{}], state: {:?}", msg.chat.id, state);
+    let language_code = msg.from().and_then(|user| user.language_code.as_deref());
+    i18n.detect(msg.chat.id, language_code).await;
+    if let Some(text) = msg.text() {
+        if let Some(argument) = command_argument(text, LANGUAGE_COMMAND).map(str::to_owned) {
+            return change_language(bot, msg, i18n, &argument).await;
+        }
+        if command_argument(text, WEBHOOK_COMMAND).is_some() {
+            return set_webhook(bot, msg, storage, params, i18n).await;
+        }
+        if command_argument(text, HISTORY_COMMAND).is_some() {
+            return history(bot, msg, storage, params, i18n).await;
+        }
+    }
+
     match state {
-        State::Start => start(bot, msg, storage, params).await?,
+        State::Start => start(bot, msg, storage, params, i18n).await?,
         State::Authorization => authorization(msg, params).await?,
-        State::Command => choose_command(bot, msg).await?,
+        State::Command => start(bot, msg, storage, params, i18n).await?,
         State::AddNotification { customer } => {
-            add_notification(bot, msg, storage, customer).await?
+            add_notification(bot, msg, storage, customer, i18n).await?
         }
         State::SendNotification { customer, product } => {
-            send_notification(msg, params, customer, product).await?
+            send_notification(bot, msg, params, customer, product, i18n).await?
         }
+        State::History { .. } => start(bot, msg, storage, params, i18n).await?,
     }
 
     Ok(())
 }
 
-async fn start(
+async fn change_language(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    i18n: Arc<I18n>,
+    argument: &str,
+) -> HandlerResult {
+    let text = match Language::from_code(argument) {
+        Some(language) => {
+            i18n.set_language(msg.chat.id, language).await?;
+            i18n.text(msg.chat.id, "language-changed").await
+        }
+        None => i18n.text(msg.chat.id, "language-usage").await,
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+async fn set_webhook(
     bot: AutoSend<Bot>,
     msg: Message,
     storage: Arc<StateStorage<State>>,
     params: ConfigParams,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
-    log::info!("Start for user [{}]", msg.chat.id.0);
-    if params
-        .authorized_customers
+    let authorized = params
+        .sessions
         .lock()
         .await
         .get(&msg.chat.id)
-        .is_some()
-    {
+        .and_then(|session| session.active().cloned());
+    let customer = match authorized {
+        Some(authorized) if authorized.role == OperatorRole::Owner => authorized.customer.name,
+        Some(_) => {
+            let text = i18n.text(msg.chat.id, "permission-denied").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+        None => return start(bot, msg, storage, params, i18n).await,
+    };
+    let url = match webhook_url(
+        command_argument(msg.text().unwrap_or_default(), WEBHOOK_COMMAND).unwrap_or_default(),
+    ) {
+        Some(url) => url,
+        None => {
+            let text = i18n.text(msg.chat.id, "webhook-usage").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
+    log::info!("Set webhook for user [{}]", msg.chat.id.0);
+    let message = CustomerRequest::SetWebhook {
+        user_id: UserId::from(msg.chat.id.0),
+        customer,
+        url,
+        timestamp: Timestamp::now(),
+    }
+    .encode(params.wire_format);
+    params
+        .publisher
+        .lock()
+        .await
+        .publish_message(&params.exchange, &params.request_queue, message)
+        .await
+        .unwrap();
+    Ok(())
+}
+
+async fn history(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    params: ConfigParams,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    let customer = params
+        .sessions
+        .lock()
+        .await
+        .get(&msg.chat.id)
+        .and_then(|session| session.active())
+        .map(|authorized| authorized.customer.name.clone());
+    let customer = match customer {
+        Some(customer) => customer,
+        None => return start(bot, msg, storage, params, i18n).await,
+    };
+    let product = match history_product(
+        command_argument(msg.text().unwrap_or_default(), HISTORY_COMMAND).unwrap_or_default(),
+    ) {
+        Some(product) => match ProductName::parse(product) {
+            Ok(product) => Some(product),
+            Err(_) => {
+                let text = i18n.text(msg.chat.id, "invalid-name").await;
+                bot.send_message(msg.chat.id, text).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    log::info!("Notification history for user [{}]", msg.chat.id.0);
+    storage
+        .set_state(
+            msg.chat.id,
+            State::History {
+                product: product.clone(),
+            },
+        )
+        .await;
+    let message = CustomerRequest::NotificationHistory {
+        user_id: UserId::from(msg.chat.id.0),
+        customer,
+        product,
+        page: 0,
+        timestamp: Timestamp::now(),
+    }
+    .encode(params.wire_format);
+    params
+        .publisher
+        .lock()
+        .await
+        .publish_message(&params.exchange, &params.request_queue, message)
+        .await
+        .unwrap();
+    Ok(())
+}
+
+async fn start(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    params: ConfigParams,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    log::info!("Start for user [{}]", msg.chat.id.0);
+    let session = params.sessions.lock().await.get(&msg.chat.id).cloned();
+    if let Some(session) = session {
         storage.set_state(msg.chat.id, State::Command).await;
-        choose_command(bot, msg).await?;
+        choose_command(bot, msg, session, i18n).await?;
     } else {
-        bot.send_message(msg.chat.id, "Введите ключ для авторизации:")
-            .await?;
+        let text = i18n.text(msg.chat.id, "enter-key").await;
+        bot.send_message(msg.chat.id, text).await?;
         storage.set_state(msg.chat.id, State::Authorization).await;
     }
     Ok(())
@@ -165,9 +330,9 @@ async fn authorization(msg: Message, params: ConfigParams) -> HandlerResult {
     let message = CustomerRequest::Authorization {
         user_id: UserId::from(msg.chat.id.0),
         key,
-        timestamp: msg.date.timestamp(),
+        timestamp: Timestamp::now(),
     }
-    .to_string();
+    .encode(params.wire_format);
     params
         .publisher
         .lock()
@@ -177,13 +342,63 @@ async fn authorization(msg: Message, params: ConfigParams) -> HandlerResult {
         .unwrap();
     Ok(())
 }
-async fn choose_command(bot: AutoSend<Bot>, msg: Message) -> HandlerResult {
+async fn choose_command(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    session: OperatorSession,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
     log::info!("Choose command for user [{}]", msg.chat.id.0);
-    let keyboard: Vec<Vec<InlineKeyboardButton>> = vec![vec![InlineKeyboardButton::callback(
-        "Создать уведомление".to_owned(),
-        Command::AddNotification,
-    )]];
-    bot.send_message(msg.chat.id, "Что хотите сделать?")
+    let active = match session.active() {
+        Some(active) => active,
+        None => return Ok(()),
+    };
+    let role = active.role;
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+    if role.allows(OperatorRole::Editor) {
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            i18n.text(msg.chat.id, "command-add-notification").await,
+            Command::AddNotification,
+        )]);
+    }
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        i18n.text(msg.chat.id, "command-scheduled-notifications")
+            .await,
+        Command::ScheduledNotifications,
+    )]);
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        i18n.text(msg.chat.id, "command-recurring-notifications")
+            .await,
+        Command::RecurringNotifications,
+    )]);
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        i18n.text(msg.chat.id, "command-history").await,
+        Command::History,
+    )]);
+    if role.allows(OperatorRole::Owner) {
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            i18n.text(msg.chat.id, "command-operators").await,
+            Command::Operators,
+        )]);
+    }
+    if session.customers().len() > 1 {
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            i18n.text(msg.chat.id, "command-switch-customer").await,
+            Command::SwitchCustomer,
+        )]);
+    }
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        i18n.text(msg.chat.id, "command-add-customer").await,
+        Command::AddCustomer,
+    )]);
+    let text = i18n
+        .text_with(
+            msg.chat.id,
+            "choose-command",
+            &[("customer", active.customer.name.as_str())],
+        )
+        .await;
+    bot.send_message(msg.chat.id, text)
         .reply_markup(InlineKeyboardMarkup::new(keyboard))
         .await?;
 
@@ -194,16 +409,27 @@ async fn add_notification(
     bot: AutoSend<Bot>,
     msg: Message,
     storage: Arc<StateStorage<State>>,
-    customer: String,
+    customer: CustomerName,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
     log::info!("add notification for user [{}]", msg.chat.id.0);
-    let product = msg.text().unwrap().to_string();
-    bot.send_message(
-        msg.chat.id,
-        "Введите текст уведомления (он будет отправлен клиентам):",
-    )
-    .reply_markup(KeyboardRemove::new())
-    .await?;
+    let product = match ProductName::parse(msg.text().unwrap_or_default()) {
+        Ok(product) => product,
+        Err(error) => {
+            log::info!(
+                "Invalid product name from user [{}]: {}",
+                msg.chat.id.0,
+                error
+            );
+            let text = i18n.text(msg.chat.id, "invalid-name").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
+    let text = i18n.text(msg.chat.id, "enter-notification").await;
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(KeyboardRemove::new())
+        .await?;
 
     storage
         .set_state(msg.chat.id, State::SendNotification { customer, product })
@@ -213,23 +439,71 @@ async fn add_notification(
 }
 
 async fn send_notification(
+    bot: AutoSend<Bot>,
     msg: Message,
     params: ConfigParams,
-    customer: String,
-    product: String,
+    customer: CustomerName,
+    product: ProductName,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
     log::info!("add notification for user [{}]", msg.chat.id.0);
+    let send_at = match send_at(&msg, params.timezone) {
+        Ok(send_at) => send_at,
+        Err(error) => {
+            let key = match error {
+                SendAtError::Invalid => "invalid-send-at",
+                SendAtError::InPast => "past-send-at",
+            };
+            let text = i18n
+                .text_with(msg.chat.id, key, &[("timezone", params.timezone.name())])
+                .await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
+    let notification = match notification_content(&msg) {
+        Ok(notification) => notification,
+        Err(ContentError::Empty) => {
+            let text = i18n.text(msg.chat.id, "empty-notification").await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+        Err(ContentError::CaptionTooLong { length, max_length }) => {
+            let length = length.to_string();
+            let max_length = max_length.to_string();
+            let text = i18n
+                .text_with(
+                    msg.chat.id,
+                    "caption-too-long",
+                    &[("length", &length), ("max_length", &max_length)],
+                )
+                .await;
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+    };
     let user_id = UserId::from(msg.chat.id.0);
-    let timestamp = msg.date.timestamp();
-    let notification = msg.text().unwrap().to_string();
-    let message = CustomerRequest::NewNotification {
-        user_id,
-        customer,
-        product,
-        notification,
-        timestamp,
-    }
-    .to_string();
+    let timestamp = Timestamp::now();
+    let request = match recurring_schedule(&msg, params.timezone) {
+        Some((schedule, timezone)) => CustomerRequest::NewRecurringNotification {
+            user_id,
+            customer,
+            product,
+            notification,
+            schedule,
+            timezone,
+            timestamp,
+        },
+        None => CustomerRequest::NewNotification {
+            user_id,
+            customer,
+            product,
+            notification,
+            send_at,
+            timestamp,
+        },
+    };
+    let message = request.encode(params.wire_format);
     params
         .publisher
         .lock()
@@ -241,10 +515,69 @@ async fn send_notification(
     Ok(())
 }
 
+async fn switch_customer(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    params: ConfigParams,
+    i18n: Arc<I18n>,
+) -> HandlerResult {
+    log::info!("Switch customer for user [{}]", msg.chat.id.0);
+    let session = params.sessions.lock().await.get(&msg.chat.id).cloned();
+    let session = match session {
+        Some(session) => session,
+        None => return Ok(()),
+    };
+    let keyboard: Vec<Vec<InlineKeyboardButton>> = session
+        .customers()
+        .iter()
+        .enumerate()
+        .map(|(index, authorized)| {
+            vec![InlineKeyboardButton::callback(
+                authorized.customer.name.to_string(),
+                Command::SelectCustomer(index),
+            )]
+        })
+        .collect();
+    let text = i18n.text(msg.chat.id, "choose-active-customer").await;
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(InlineKeyboardMarkup::new(keyboard))
+        .await?;
+    Ok(())
+}
+
+async fn select_customer(
+    bot: AutoSend<Bot>,
+    msg: Message,
+    storage: Arc<StateStorage<State>>,
+    params: ConfigParams,
+    i18n: Arc<I18n>,
+    index: usize,
+) -> HandlerResult {
+    log::info!("Select customer for user [{}]", msg.chat.id.0);
+    let session = params
+        .sessions
+        .lock()
+        .await
+        .get_mut(&msg.chat.id)
+        .map(|session| {
+            session.select(index);
+            session.clone()
+        });
+    match session {
+        Some(session) => {
+            storage.set_state(msg.chat.id, State::Command).await;
+            choose_command(bot, msg, session, i18n).await
+        }
+        None => start(bot, msg, storage, params, i18n).await,
+    }
+}
+
 async fn callback_handler(
     q: CallbackQuery,
     bot: AutoSend<Bot>,
+    storage: Arc<StateStorage<State>>,
     params: ConfigParams,
+    i18n: Arc<I18n>,
 ) -> HandlerResult {
     if let Some(data) = q.data {
         log::info!("Callback [{}]", data);
@@ -253,32 +586,142 @@ async fn callback_handler(
             bot.delete_message(message.chat.id, message.id).await?;
             let command: Command = data.into();
             match command {
-                Command::AddNotification => {
-                    let user_id = UserId::from(message.chat.id.0);
-                    let timestamp = message.date.timestamp();
-                    let customer = params
-                        .authorized_customers
-                        .lock()
-                        .await
-                        .get(&message.chat.id)
-                        .unwrap()
-                        .name
-                        .to_string();
-                    let message = CustomerRequest::ProductsForNotification {
+                Command::SwitchCustomer => {
+                    return switch_customer(bot, message, params, i18n).await;
+                }
+                Command::SelectCustomer(index) => {
+                    return select_customer(bot, message, storage, params, i18n, index).await;
+                }
+                Command::AddCustomer => {
+                    let text = i18n.text(message.chat.id, "enter-key").await;
+                    bot.send_message(message.chat.id, text).await?;
+                    storage
+                        .set_state(message.chat.id, State::Authorization)
+                        .await;
+                    return Ok(());
+                }
+                _ => {}
+            }
+            let user_id = UserId::from(message.chat.id.0);
+            let timestamp = Timestamp::now();
+            let customer = params
+                .sessions
+                .lock()
+                .await
+                .get(&message.chat.id)
+                .and_then(|session| session.active())
+                .map(|authorized| authorized.customer.name.clone());
+            let customer = match customer {
+                Some(customer) => customer,
+                None => {
+                    log::info!("Callback from unauthorized user [{}]", message.chat.id);
+                    return Ok(());
+                }
+            };
+            let request = match command {
+                Command::AddNotification => CustomerRequest::ProductsForNotification {
+                    user_id,
+                    customer,
+                    timestamp,
+                },
+                Command::ScheduledNotifications => CustomerRequest::ScheduledNotifications {
+                    user_id,
+                    customer,
+                    timestamp,
+                },
+                Command::CancelScheduledNotification(id) => {
+                    CustomerRequest::CancelScheduledNotification {
+                        user_id,
+                        customer,
+                        id,
+                        timestamp,
+                    }
+                }
+                Command::RecurringNotifications => CustomerRequest::RecurringNotifications {
+                    user_id,
+                    customer,
+                    timestamp,
+                },
+                Command::PauseRecurringNotification(id) => {
+                    CustomerRequest::PauseRecurringNotification {
+                        user_id,
+                        customer,
+                        id,
+                        paused: true,
+                        timestamp,
+                    }
+                }
+                Command::ResumeRecurringNotification(id) => {
+                    CustomerRequest::PauseRecurringNotification {
+                        user_id,
+                        customer,
+                        id,
+                        paused: false,
+                        timestamp,
+                    }
+                }
+                Command::DeleteRecurringNotification(id) => {
+                    CustomerRequest::DeleteRecurringNotification {
+                        user_id,
+                        customer,
+                        id,
+                        timestamp,
+                    }
+                }
+                Command::Operators => CustomerRequest::Operators {
+                    user_id,
+                    customer,
+                    timestamp,
+                },
+                Command::InviteOperator(role) => CustomerRequest::InviteOperator {
+                    user_id,
+                    customer,
+                    role,
+                    timestamp,
+                },
+                Command::RevokeOperator(operator_id) => CustomerRequest::RevokeOperator {
+                    user_id,
+                    customer,
+                    operator_id,
+                    timestamp,
+                },
+                Command::History => {
+                    storage
+                        .set_state(message.chat.id, State::History { product: None })
+                        .await;
+                    CustomerRequest::NotificationHistory {
                         user_id,
                         customer,
+                        product: None,
+                        page: 0,
                         timestamp,
                     }
-                    .to_string();
-                    params
-                        .publisher
-                        .lock()
-                        .await
-                        .publish_message(&params.exchange, &params.request_queue, message)
-                        .await
-                        .unwrap();
+                }
+                Command::HistoryPage(page) => {
+                    let product = match storage.get_state(&message.chat.id).await {
+                        Some(State::History { product }) => product,
+                        _ => None,
+                    };
+                    CustomerRequest::NotificationHistory {
+                        user_id,
+                        customer,
+                        product,
+                        page,
+                        timestamp,
+                    }
+                }
+                Command::SwitchCustomer | Command::SelectCustomer(_) | Command::AddCustomer => {
+                    return Ok(());
                 }
             };
+            let message = request.encode(params.wire_format);
+            params
+                .publisher
+                .lock()
+                .await
+                .publish_message(&params.exchange, &params.request_queue, message)
+                .await
+                .unwrap();
         }
     } else {
         log::info!("None of callback");
@@ -286,19 +729,3 @@ async fn callback_handler(
 
     Ok(())
 }
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-enum Command {
-    AddNotification,
-}
-
-impl From<Command> for String {
-    fn from(command: Command) -> Self {
-        serde_json::to_string(&command).unwrap()
-    }
-}
-impl From<String> for Command {
-    fn from(s: String) -> Self {
-        serde_json::from_str(&s).unwrap()
-    }
-}
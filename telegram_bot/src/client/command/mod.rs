@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Text command listing the subscriptions of the user.
+pub const SUBSCRIPTIONS_COMMAND: &str = "/subscriptions";
+/// Text command listing the notifications received by the user.
+pub const HISTORY_COMMAND: &str = "/history";
+
+/// Callback data of the client bot inline buttons.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Command {
+    CancelSubscription(i64),
+    CancelAllSubscriptions,
+    /// Page of the notification history, 0 is the newest.
+    History(u32),
+}
+
+impl From<Command> for String {
+    fn from(command: Command) -> Self {
+        serde_json::to_string(&command).unwrap()
+    }
+}
+impl From<String> for Command {
+    fn from(s: String) -> Self {
+        serde_json::from_str(&s).unwrap()
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use domain::models::{Attachment, CustomerName, NotificationContent, ParseMode, ProductName};
+use teloxide::{
+    payloads::{SendDocumentSetters, SendMessageSetters, SendPhotoSetters},
+    prelude::AutoSend,
+    requests::Requester,
+    types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile},
+    utils::{html, markdown},
+    Bot, RequestError,
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    time::{self, Instant, MissedTickBehavior},
+};
+use url::Url;
+
+use crate::common::{i18n::I18n, MAX_CAPTION_LENGTH};
+
+/// Telegram allows about 30 messages per second to different chats.
+const DEFAULT_DELIVERY_RATE: u32 = 30;
+/// Telegram allows about one message per second to the same chat.
+const CHAT_PERIOD: Duration = Duration::from_secs(1);
+const MAX_ATTEMPTS: u32 = 5;
+const LAST_SENT_CAPACITY: usize = 1024;
+
+/// Customer notification waiting to be sent to a client chat.
+#[derive(Debug, Clone)]
+pub struct DeliveryJob {
+    chat_id: ChatId,
+    customer: CustomerName,
+    product: ProductName,
+    content: NotificationContent,
+    attempts: u32,
+}
+
+/// Queue of notifications waiting for the `Deliverer`.
+#[derive(Clone)]
+pub struct DeliveryQueue {
+    sender: UnboundedSender<DeliveryJob>,
+}
+
+impl DeliveryQueue {
+    pub fn new() -> (Self, UnboundedReceiver<DeliveryJob>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (DeliveryQueue { sender }, receiver)
+    }
+    pub fn push(
+        &self,
+        chat_id: ChatId,
+        customer: CustomerName,
+        product: ProductName,
+        content: NotificationContent,
+    ) {
+        let job = DeliveryJob {
+            chat_id,
+            customer,
+            product,
+            content,
+            attempts: 0,
+        };
+        if let Err(error) = self.sender.send(job) {
+            log::error!(
+                "Deliverer is stopped, dropping notification for chat [{}]",
+                error.0.chat_id
+            );
+        }
+    }
+}
+
+/// Sends queued notifications within the Telegram limits: at most
+/// `delivery_rate` messages per second overall and one per second to a chat.
+/// On `RetryAfter` the whole delivery waits as long as Telegram asks and the
+/// message is retried.
+pub struct Deliverer {
+    bot: AutoSend<Bot>,
+    i18n: Arc<I18n>,
+    jobs: UnboundedReceiver<DeliveryJob>,
+    period: Duration,
+    pending: VecDeque<DeliveryJob>,
+    last_sent: HashMap<ChatId, Instant>,
+}
+
+impl Deliverer {
+    pub fn new(
+        bot: AutoSend<Bot>,
+        i18n: Arc<I18n>,
+        jobs: UnboundedReceiver<DeliveryJob>,
+        delivery_rate: Option<u32>,
+    ) -> Self {
+        let rate = delivery_rate.unwrap_or(DEFAULT_DELIVERY_RATE).max(1);
+        Deliverer {
+            bot,
+            i18n,
+            jobs,
+            period: Duration::from_secs(1) / rate,
+            pending: VecDeque::new(),
+            last_sent: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut ticker = time::interval(self.period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            if self.pending.is_empty() {
+                match self.jobs.recv().await {
+                    Some(job) => self.pending.push_back(job),
+                    None => return,
+                }
+            }
+            while let Ok(job) = self.jobs.try_recv() {
+                self.pending.push_back(job);
+            }
+
+            let now = Instant::now();
+            let ready = self
+                .pending
+                .iter()
+                .position(|job| self.ready_at(job.chat_id) <= now);
+            let mut job = match ready.and_then(|index| self.pending.remove(index)) {
+                Some(job) => job,
+                None => {
+                    let ready_at = self
+                        .pending
+                        .iter()
+                        .map(|job| self.ready_at(job.chat_id))
+                        .min()
+                        .unwrap_or(now);
+                    time::sleep_until(ready_at).await;
+                    continue;
+                }
+            };
+
+            ticker.tick().await;
+            match self.send(&job).await {
+                Ok(()) => {
+                    self.last_sent.insert(job.chat_id, Instant::now());
+                }
+                Err(RequestError::RetryAfter(duration)) => {
+                    log::warn!(
+                        "Telegram flood control, pausing delivery for {:?}",
+                        duration
+                    );
+                    time::sleep(duration).await;
+                    job.attempts += 1;
+                    match job.attempts < MAX_ATTEMPTS {
+                        true => self.pending.push_front(job),
+                        false => log::error!(
+                            "Dropping notification for chat [{}] after {} attempts",
+                            job.chat_id,
+                            job.attempts
+                        ),
+                    }
+                }
+                Err(error) => {
+                    self.last_sent.insert(job.chat_id, Instant::now());
+                    log::error!(
+                        "Failed to deliver notification to chat [{}]: {}",
+                        job.chat_id,
+                        error
+                    );
+                }
+            }
+
+            if self.last_sent.len() > LAST_SENT_CAPACITY {
+                let now = Instant::now();
+                self.last_sent
+                    .retain(|_, sent_at| now.duration_since(*sent_at) < CHAT_PERIOD);
+            }
+        }
+    }
+
+    fn ready_at(&self, chat_id: ChatId) -> Instant {
+        match self.last_sent.get(&chat_id) {
+            Some(sent_at) => *sent_at + CHAT_PERIOD,
+            None => Instant::now(),
+        }
+    }
+
+    async fn send(&self, job: &DeliveryJob) -> Result<(), RequestError> {
+        let DeliveryJob {
+            chat_id,
+            customer,
+            product,
+            content,
+            ..
+        } = job;
+        let chat_id = *chat_id;
+        let header = self
+            .i18n
+            .text_with(
+                chat_id,
+                "new-notification",
+                &[
+                    ("customer", customer.as_str()),
+                    ("product", product.as_str()),
+                ],
+            )
+            .await;
+        let (header, parse_mode) = match content.parse_mode {
+            ParseMode::Plain => (header, None),
+            ParseMode::MarkdownV2 => (
+                markdown::escape(&header),
+                Some(teloxide::types::ParseMode::MarkdownV2),
+            ),
+            ParseMode::Html => (
+                html::escape(&header),
+                Some(teloxide::types::ParseMode::Html),
+            ),
+        };
+        let keyboard = link_keyboard(content);
+        if content.attachment.is_none() {
+            let text = format!("{header} \n {}", content.text);
+            let mut request = self.bot.send_message(chat_id, text);
+            if let Some(parse_mode) = parse_mode {
+                request = request.parse_mode(parse_mode);
+            }
+            if let Some(keyboard) = keyboard {
+                request = request.reply_markup(keyboard);
+            }
+            request.await?;
+            return Ok(());
+        }
+
+        let (header, text) = caption(header, &content.text);
+        if let Some(header) = header {
+            let mut request = self.bot.send_message(chat_id, header);
+            if let Some(parse_mode) = parse_mode {
+                request = request.parse_mode(parse_mode);
+            }
+            request.await?;
+        }
+        match &content.attachment {
+            Some(Attachment::Photo { file_id }) => {
+                let mut request = self
+                    .bot
+                    .send_photo(chat_id, InputFile::file_id(file_id.clone()))
+                    .caption(text);
+                if let Some(parse_mode) = parse_mode {
+                    request = request.parse_mode(parse_mode);
+                }
+                if let Some(keyboard) = keyboard {
+                    request = request.reply_markup(keyboard);
+                }
+                request.await?;
+            }
+            Some(Attachment::Document { file_id }) => {
+                let mut request = self
+                    .bot
+                    .send_document(chat_id, InputFile::file_id(file_id.clone()))
+                    .caption(text);
+                if let Some(parse_mode) = parse_mode {
+                    request = request.parse_mode(parse_mode);
+                }
+                if let Some(keyboard) = keyboard {
+                    request = request.reply_markup(keyboard);
+                }
+                request.await?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Caption of an attachment: the header in front of the text, or the text
+/// alone and the header to send as a separate message if both do not fit.
+fn caption(header: String, text: &str) -> (Option<String>, String) {
+    let caption = format!("{header} \n {text}");
+    match caption.chars().count() <= MAX_CAPTION_LENGTH {
+        true => (None, caption),
+        false => (Some(header), text.to_string()),
+    }
+}
+
+fn link_keyboard(content: &NotificationContent) -> Option<InlineKeyboardMarkup> {
+    let buttons: Vec<Vec<InlineKeyboardButton>> = content
+        .buttons
+        .iter()
+        .filter_map(|button| match Url::parse(&button.url) {
+            Ok(url) => Some(vec![InlineKeyboardButton::url(button.text.clone(), url)]),
+            Err(error) => {
+                log::error!(
+                    "Invalid notification button url [{}]: {}",
+                    button.url,
+                    error
+                );
+                None
+            }
+        })
+        .collect();
+    match buttons.is_empty() {
+        true => None,
+        false => Some(InlineKeyboardMarkup::new(buttons)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "New notification from supplier [Shop] for product [Bread]:";
+
+    #[test]
+    fn header_and_text_share_a_caption_that_fits() {
+        let text = "я".repeat(MAX_CAPTION_LENGTH - HEADER.chars().count() - 3);
+        let (header, sent_caption) = caption(HEADER.to_string(), &text);
+        assert_eq!(header, None);
+        assert_eq!(sent_caption.chars().count(), MAX_CAPTION_LENGTH);
+        assert!(sent_caption.starts_with(HEADER));
+    }
+
+    #[test]
+    fn header_is_sent_separately_if_the_caption_does_not_fit() {
+        let text = "я".repeat(MAX_CAPTION_LENGTH - HEADER.chars().count() - 2);
+        let (header, sent_caption) = caption(HEADER.to_string(), &text);
+        assert_eq!(header.as_deref(), Some(HEADER));
+        assert_eq!(sent_caption, text);
+
+        let text = "я".repeat(MAX_CAPTION_LENGTH);
+        let (header, sent_caption) = caption(HEADER.to_string(), &text);
+        assert_eq!(header.as_deref(), Some(HEADER));
+        assert_eq!(sent_caption.chars().count(), MAX_CAPTION_LENGTH);
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use domain::responses::ClientResponse;
+use domain::{
+    codec::{WireFormat, WireMessage},
+    responses::ClientResponse,
+};
 use amqp::IncomingMessageHandler;
 use tokio::sync::Mutex;
 
@@ -9,11 +12,18 @@ pub struct MessageHandler {}
 impl MessageHandler {
     pub fn client_response(
         service: Arc<Mutex<ClientService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let response: ClientResponse = serde_json::from_str(&message).unwrap();
+                let response = match ClientResponse::decode(&message, wire_format) {
+                    Ok(response) => response,
+                    Err(error) => {
+                        log::error!("Message handler client response decode error: {}", error);
+                        return;
+                    }
+                };
                 if let Err(error) = service.lock().await.handle_response(response).await {
                     log::error!("Message handler client response error: {}", error);
                 };
@@ -1,6 +1,10 @@
+pub mod command;
+mod delivery;
 mod handler;
+pub mod mode;
 mod service;
 pub mod state;
 
+pub use delivery::{Deliverer, DeliveryJob, DeliveryQueue};
 pub use handler::MessageHandler;
 pub use service::ClientService;
@@ -0,0 +1,20 @@
+use chrono::{NaiveDate, TimeZone};
+use chrono_tz::Tz;
+use domain::models::Timestamp;
+
+pub const UNTIL_FORMAT: &str = "%Y-%m-%d";
+
+/// Reads the number of notifications of a `Times` subscription.
+pub fn subscription_count(text: &str) -> Option<u32> {
+    text.trim().parse().ok().filter(|count| *count > 0)
+}
+
+/// Reads the last day of an `Until` subscription, `YYYY-MM-DD` in `timezone`.
+/// The subscription lasts until the end of that day, which must be after `now`.
+pub fn subscription_until(text: &str, timezone: Tz, now: Timestamp) -> Option<Timestamp> {
+    let last_day = NaiveDate::parse_from_str(text.trim(), UNTIL_FORMAT).ok()?;
+    let end = last_day.succ_opt()?.and_hms_opt(0, 0, 0)?;
+    let until = timezone.from_local_datetime(&end).earliest()?;
+    let until = Timestamp::from(until.with_timezone(&chrono::Utc));
+    (until > now).then_some(until)
+}
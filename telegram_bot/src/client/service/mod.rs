@@ -1,21 +1,53 @@
-use domain::responses::ClientResponse;
+use std::sync::Arc;
+
+use chrono_tz::Tz;
+use domain::{
+    models::{PastNotification, Subscription, SubscriptionMode},
+    responses::ClientResponse,
+};
 use teloxide::{
     payloads::SendMessageSetters,
     prelude::AutoSend,
     requests::Requester,
-    types::{ChatId, KeyboardButton, KeyboardMarkup, KeyboardRemove},
+    types::{
+        ChatId, InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup,
+        KeyboardRemove,
+    },
     Bot,
 };
 
-use crate::common::HandlerResult;
+use crate::{
+    client::{command::Command, delivery::DeliveryQueue, state::State},
+    common::{i18n::I18n, preview, HandlerResult},
+    storage::StateStorage,
+};
+
+const UNTIL_DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M";
+const RECEIVED_AT_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 pub struct ClientService {
     bot: AutoSend<Bot>,
+    state_storage: Arc<StateStorage<State>>,
+    i18n: Arc<I18n>,
+    delivery: DeliveryQueue,
+    timezone: Tz,
 }
 
 impl ClientService {
-    pub fn new(bot: AutoSend<Bot>) -> Self {
-        ClientService { bot }
+    pub fn new(
+        bot: AutoSend<Bot>,
+        state_storage: Arc<StateStorage<State>>,
+        i18n: Arc<I18n>,
+        delivery: DeliveryQueue,
+        timezone: Tz,
+    ) -> Self {
+        ClientService {
+            bot,
+            state_storage,
+            i18n,
+            delivery,
+            timezone,
+        }
     }
     pub async fn handle_response(&mut self, response: ClientResponse) -> HandlerResult {
         match response {
@@ -28,8 +60,10 @@ impl ClientService {
                         .collect();
                     keyboard.push(row);
                 }
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "choose-customer").await;
                 self.bot
-                    .send_message(ChatId(user_id.0 as i64), "Выберите поставщика:")
+                    .send_message(chat_id, text)
                     .reply_markup(KeyboardMarkup::new(keyboard))
                     .await?;
                 Ok(())
@@ -43,44 +77,273 @@ impl ClientService {
                         .collect();
                     keyboard.push(row);
                 }
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "choose-product").await;
                 self.bot
-                    .send_message(
-                        ChatId(user_id.0 as i64),
-                        "Выберите интересующий вас продукт:",
-                    )
+                    .send_message(chat_id, text)
                     .reply_markup(KeyboardMarkup::new(keyboard))
                     .await?;
                 Ok(())
             }
             ClientResponse::SubscriptionSuccess { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "subscription-success").await;
                 self.bot
-                    .send_message(ChatId(user_id.0 as i64), "Подписка успешно оформлена!")
+                    .send_message(chat_id, text)
                     .reply_markup(KeyboardRemove::new())
                     .await?;
                 Ok(())
             }
             ClientResponse::SubscriptionFailure { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "subscription-failure").await;
                 self.bot
-                    .send_message(
-                        ChatId(user_id.0 as i64),
-                        "К сожалению, что-то пошло не так и подписка не оформлена!",
-                    )
+                    .send_message(chat_id, text)
                     .reply_markup(KeyboardRemove::new())
                     .await?;
                 Ok(())
             }
+            ClientResponse::RequestFailure { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "request-failure").await;
+                self.bot
+                    .send_message(chat_id, text)
+                    .reply_markup(KeyboardRemove::new())
+                    .await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            ClientResponse::ServiceUnavailable { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "service-unavailable").await;
+                self.bot
+                    .send_message(chat_id, text)
+                    .reply_markup(KeyboardRemove::new())
+                    .await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
             ClientResponse::CustomerNotification {
                 user_id,
                 customer,
                 product,
                 notification,
             } => {
-                let text = format!("Новое уведомление от поставщика [{customer}] для товара [{product}]: \n {notification}");
+                let chat_id = ChatId(user_id.0 as i64);
+                self.delivery.push(chat_id, customer, product, notification);
+                Ok(())
+            }
+            ClientResponse::UnknownCustomer { user_id, customer } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "unknown-customer",
+                        &[("customer", customer.as_str())],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Customer).await;
+                Ok(())
+            }
+            ClientResponse::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "unknown-product",
+                        &[
+                            ("customer", customer.as_str()),
+                            ("product", product.as_str()),
+                        ],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage
+                    .set_state(chat_id, State::Product { customer })
+                    .await;
+                Ok(())
+            }
+            ClientResponse::Subscriptions {
+                user_id,
+                subscriptions,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                if subscriptions.is_empty() {
+                    let text = self.i18n.text(chat_id, "no-subscriptions").await;
+                    self.bot.send_message(chat_id, text).await?;
+                    return Ok(());
+                }
+                let mut lines = vec![self.i18n.text(chat_id, "subscriptions").await];
+                let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+                for subscription in &subscriptions {
+                    lines.push(self.subscription_line(chat_id, subscription).await);
+                    if !subscription.active {
+                        continue;
+                    }
+                    let id = subscription.id.to_string();
+                    let cancel = self
+                        .i18n
+                        .text_with(chat_id, "cancel-subscription", &[("id", &id)])
+                        .await;
+                    keyboard.push(vec![InlineKeyboardButton::callback(
+                        cancel,
+                        Command::CancelSubscription(subscription.id),
+                    )]);
+                }
+                if keyboard.len() > 1 {
+                    let cancel_all = self.i18n.text(chat_id, "cancel-all-subscriptions").await;
+                    keyboard.push(vec![InlineKeyboardButton::callback(
+                        cancel_all,
+                        Command::CancelAllSubscriptions,
+                    )]);
+                }
+                self.bot
+                    .send_message(chat_id, lines.join("\n"))
+                    .reply_markup(InlineKeyboardMarkup::new(keyboard))
+                    .await?;
+                Ok(())
+            }
+            ClientResponse::SubscriptionCancelled {
+                user_id,
+                id,
+                success,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let key = match success {
+                    true => "subscription-cancelled",
+                    false => "subscription-not-cancelled",
+                };
+                let id = id.to_string();
+                let text = self.i18n.text_with(chat_id, key, &[("id", &id)]).await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            ClientResponse::SubscriptionsCancelled { user_id, count } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let count = count.to_string();
+                let text = self
+                    .i18n
+                    .text_with(chat_id, "subscriptions-cancelled", &[("count", &count)])
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            ClientResponse::NotificationHistory {
+                user_id,
+                page,
+                notifications,
+                has_more,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                if notifications.is_empty() {
+                    let text = self.i18n.text(chat_id, "no-received-notifications").await;
+                    self.bot.send_message(chat_id, text).await?;
+                    return Ok(());
+                }
+                let mut lines = vec![self.i18n.text(chat_id, "received-notifications").await];
+                for notification in &notifications {
+                    lines.push(self.received_notification_line(chat_id, notification).await);
+                }
+                let mut row: Vec<InlineKeyboardButton> = vec![];
+                if page > 0 {
+                    row.push(InlineKeyboardButton::callback(
+                        self.i18n.text(chat_id, "history-newer").await,
+                        Command::History(page - 1),
+                    ));
+                }
+                if has_more {
+                    row.push(InlineKeyboardButton::callback(
+                        self.i18n.text(chat_id, "history-older").await,
+                        Command::History(page + 1),
+                    ));
+                }
                 self.bot
-                    .send_message(ChatId(user_id.0 as i64), text)
+                    .send_message(chat_id, lines.join("\n"))
+                    .reply_markup(InlineKeyboardMarkup::new(vec![row]))
                     .await?;
                 Ok(())
             }
         }
     }
+    async fn received_notification_line(
+        &self,
+        chat_id: ChatId,
+        notification: &PastNotification,
+    ) -> String {
+        let received_at = match notification.sent_at {
+            Some(sent_at) => sent_at
+                .as_datetime()
+                .with_timezone(&self.timezone)
+                .format(RECEIVED_AT_FORMAT)
+                .to_string(),
+            None => self.i18n.text(chat_id, "sent-at-unknown").await,
+        };
+        let text = preview(&notification.content.text);
+        self.i18n
+            .text_with(
+                chat_id,
+                "received-notification",
+                &[
+                    ("received_at", &received_at),
+                    ("customer", notification.customer.as_str()),
+                    ("product", notification.product.as_str()),
+                    ("text", &text),
+                ],
+            )
+            .await
+    }
+    async fn subscription_line(&self, chat_id: ChatId, subscription: &Subscription) -> String {
+        let id = subscription.id.to_string();
+        let channel_key = format!("channel-{}", subscription.channel.as_str());
+        let channel = self.i18n.text(chat_id, &channel_key).await;
+        let mode = self.mode_text(chat_id, subscription.mode).await;
+        let key = match subscription.active {
+            true => "subscription-active",
+            false => "subscription-delivered",
+        };
+        self.i18n
+            .text_with(
+                chat_id,
+                key,
+                &[
+                    ("id", &id),
+                    ("customer", subscription.customer.as_str()),
+                    ("product", subscription.product.as_str()),
+                    ("channel", &channel),
+                    ("mode", &mode),
+                ],
+            )
+            .await
+    }
+    async fn mode_text(&self, chat_id: ChatId, mode: SubscriptionMode) -> String {
+        let key = format!("subscription-mode-{}", mode.as_str());
+        match mode {
+            SubscriptionMode::Once | SubscriptionMode::Always => {
+                self.i18n.text(chat_id, &key).await
+            }
+            SubscriptionMode::Times { count } => {
+                let count = count.to_string();
+                self.i18n
+                    .text_with(chat_id, &key, &[("count", &count)])
+                    .await
+            }
+            SubscriptionMode::Until { until } => {
+                let until = until
+                    .as_datetime()
+                    .with_timezone(&self.timezone)
+                    .format(UNTIL_DISPLAY_FORMAT)
+                    .to_string();
+                self.i18n
+                    .text_with(chat_id, &key, &[("until", &until)])
+                    .await
+            }
+        }
+    }
 }
@@ -1,7 +1,38 @@
+use domain::models::{CustomerName, DeliveryChannel, ProductName};
+
 #[derive(Clone)]
 pub enum State {
     Start,
     Customer,
-    Product { customer: String },
+    Product {
+        customer: CustomerName,
+    },
+    Channel {
+        customer: CustomerName,
+        product: ProductName,
+    },
+    Email {
+        customer: CustomerName,
+        product: ProductName,
+        channel: DeliveryChannel,
+    },
+    Mode {
+        customer: CustomerName,
+        product: ProductName,
+        channel: DeliveryChannel,
+        email: Option<String>,
+    },
+    Count {
+        customer: CustomerName,
+        product: ProductName,
+        channel: DeliveryChannel,
+        email: Option<String>,
+    },
+    Until {
+        customer: CustomerName,
+        product: ProductName,
+        channel: DeliveryChannel,
+        email: Option<String>,
+    },
     End,
 }
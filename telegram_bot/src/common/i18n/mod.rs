@@ -0,0 +1,226 @@
+use std::{collections::HashMap, fs, io, path::PathBuf, sync::Arc};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use serde::{Deserialize, Serialize};
+use teloxide::types::ChatId;
+use tokio::sync::Mutex;
+use unic_langid::LanguageIdentifier;
+
+pub const LANGUAGE_COMMAND: &str = "/language";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Language {
+    const ALL: [Language; 2] = [Language::Ru, Language::En];
+
+    /// Maps a Telegram `language_code` (IETF tag such as `en-US`) to a supported language.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase();
+        match code.split(['-', '_']).next() {
+            Some("ru") => Some(Language::Ru),
+            Some("en") => Some(Language::En),
+            _ => None,
+        }
+    }
+    fn identifier(&self) -> LanguageIdentifier {
+        match self {
+            Language::Ru => "ru".parse().unwrap(),
+            Language::En => "en".parse().unwrap(),
+        }
+    }
+    fn catalog(&self) -> &'static str {
+        match self {
+            Language::Ru => include_str!("../../../locales/ru.ftl"),
+            Language::En => include_str!("../../../locales/en.ftl"),
+        }
+    }
+}
+
+pub struct Localizer {
+    bundles: HashMap<Language, FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    pub fn new() -> Self {
+        let mut bundles = HashMap::new();
+        for language in Language::ALL {
+            let resource = FluentResource::try_new(language.catalog().to_string())
+                .expect("Failed to parse message catalog");
+            let mut bundle = FluentBundle::new_concurrent(vec![language.identifier()]);
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .expect("Failed to add message catalog");
+            bundles.insert(language, bundle);
+        }
+        Localizer { bundles }
+    }
+
+    pub fn text(&self, language: Language, key: &str, args: Option<&FluentArgs>) -> String {
+        let bundle = &self.bundles[&language];
+        let pattern = match bundle.get_message(key).and_then(|message| message.value()) {
+            Some(pattern) => pattern,
+            None => {
+                log::error!("No message [{}] for language {:?}", key, language);
+                return key.to_string();
+            }
+        };
+        let mut errors = vec![];
+        let text = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            log::error!("Errors formatting message [{}]: {:?}", key, errors);
+        }
+        text.to_string()
+    }
+}
+
+impl Default for Localizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Languages of the chats as stored in the languages file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+struct Languages {
+    /// Chosen with the `/language` command, these win over detected ones.
+    #[serde(default)]
+    selected: HashMap<i64, Language>,
+    /// Detected from Telegram's `language_code`.
+    #[serde(default)]
+    detected: HashMap<i64, Language>,
+}
+
+impl Languages {
+    /// Files written by older versions only hold the selected languages.
+    fn parse(data: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(data).or_else(|_| {
+            serde_json::from_str(data).map(|selected| Languages {
+                selected,
+                detected: HashMap::new(),
+            })
+        })
+    }
+}
+
+/// Keeps the interface language of every chat.
+///
+/// Both the languages chosen with the `/language` command and the ones
+/// detected from Telegram's `language_code` are persisted to a JSON file, so
+/// notifications sent after a restart keep the language of the user.
+pub struct I18n {
+    localizer: Localizer,
+    path: PathBuf,
+    languages: Mutex<Languages>,
+    /// Held while writing the file so that writes land in order.
+    file: Mutex<()>,
+}
+
+impl I18n {
+    pub fn new(path: &str) -> Arc<Self> {
+        let path = PathBuf::from(path);
+        let languages = match fs::read_to_string(&path) {
+            Ok(data) => Languages::parse(&data).unwrap_or_else(|error| {
+                log::error!("Failed to parse languages file {:?}: {}", path, error);
+                Languages::default()
+            }),
+            Err(_) => Languages::default(),
+        };
+        Arc::new(I18n {
+            localizer: Localizer::new(),
+            path,
+            languages: Mutex::new(languages),
+            file: Mutex::new(()),
+        })
+    }
+
+    pub async fn language(&self, chat_id: ChatId) -> Language {
+        let languages = self.languages.lock().await;
+        languages
+            .selected
+            .get(&chat_id.0)
+            .or_else(|| languages.detected.get(&chat_id.0))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Remembers the language of the chat's `language_code`, the file is only
+    /// written when it changes.
+    pub async fn detect(&self, chat_id: ChatId, language_code: Option<&str>) {
+        let language = match language_code.and_then(Language::from_code) {
+            Some(language) => language,
+            None => return,
+        };
+        let previous = self
+            .languages
+            .lock()
+            .await
+            .detected
+            .insert(chat_id.0, language);
+        if previous != Some(language) {
+            if let Err(error) = self.save().await {
+                log::error!("Failed to save languages file {:?}: {}", self.path, error);
+            }
+        }
+    }
+
+    pub async fn set_language(&self, chat_id: ChatId, language: Language) -> io::Result<()> {
+        self.languages
+            .lock()
+            .await
+            .selected
+            .insert(chat_id.0, language);
+        self.save().await
+    }
+
+    async fn save(&self) -> io::Result<()> {
+        let _file = self.file.lock().await;
+        let data = serde_json::to_string(&*self.languages.lock().await)?;
+        tokio::fs::write(&self.path, data).await
+    }
+
+    pub async fn text(&self, chat_id: ChatId, key: &str) -> String {
+        let language = self.language(chat_id).await;
+        self.localizer.text(language, key, None)
+    }
+
+    pub async fn text_with(&self, chat_id: ChatId, key: &str, args: &[(&str, &str)]) -> String {
+        let language = self.language(chat_id).await;
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.to_string());
+        }
+        self.localizer.text(language, key, Some(&fluent_args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_from_code() {
+        assert_eq!(Language::from_code("en-US"), Some(Language::En));
+        assert_eq!(Language::from_code(" RU "), Some(Language::Ru));
+        assert_eq!(Language::from_code("de"), None);
+        assert_eq!(Language::from_code(""), None);
+    }
+
+    #[test]
+    fn languages_file_formats() {
+        let languages =
+            Languages::parse(r#"{"selected":{"1":"En"},"detected":{"2":"Ru"}}"#).unwrap();
+        assert_eq!(languages.selected.get(&1), Some(&Language::En));
+        assert_eq!(languages.detected.get(&2), Some(&Language::Ru));
+
+        let legacy = Languages::parse(r#"{"1":"En"}"#).unwrap();
+        assert_eq!(legacy.selected.get(&1), Some(&Language::En));
+        assert!(legacy.detected.is_empty());
+    }
+}
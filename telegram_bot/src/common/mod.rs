@@ -1,7 +1,12 @@
+pub mod i18n;
 pub mod storage;
 
 pub type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
+/// Telegram limit of a photo or document caption.
+pub const MAX_CAPTION_LENGTH: usize = 1024;
+
+use domain::codec::WireFormat;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone)]
@@ -15,7 +20,69 @@ pub struct Config {
     pub telegram_client_token: String,
     pub telegram_client_url: String,
     pub telegram_client_address: String,
+    /// JSON file keeping the languages of the client chats, `client_languages.json` by default.
+    #[serde(default)]
+    pub telegram_client_languages_path: Option<String>,
+    /// Notifications the client bot sends per second, 30 by default.
+    #[serde(default)]
+    pub telegram_client_delivery_rate: Option<u32>,
+    /// IANA timezone of the dates clients enter, UTC by default.
+    #[serde(default)]
+    pub telegram_client_timezone: Option<String>,
     pub telegram_customer_token: String,
     pub telegram_customer_url: String,
     pub telegram_customer_address: String,
+    /// JSON file keeping the languages of the customer chats, `customer_languages.json` by default.
+    #[serde(default)]
+    pub telegram_customer_languages_path: Option<String>,
+    /// IANA timezone of the times customers enter, UTC by default.
+    #[serde(default)]
+    pub telegram_customer_timezone: Option<String>,
+    #[serde(default)]
+    pub wire_format: WireFormat,
+}
+
+/// Argument of a text command such as `/language en`, `None` if the text is
+/// another command or message. `/command@bot_name` is accepted too.
+pub fn command_argument<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let text = text.trim_start();
+    let (name, argument) = match text.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (text, ""),
+    };
+    let name = name.split_once('@').map_or(name, |(name, _)| name);
+    (name == command).then_some(argument)
+}
+
+const PREVIEW_LENGTH: usize = 40;
+
+/// Beginning of a notification text for one-line lists.
+pub fn preview(text: &str) -> String {
+    let mut preview: String = text.chars().take(PREVIEW_LENGTH).collect();
+    if text.chars().count() > PREVIEW_LENGTH {
+        preview.push('…');
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_argument_matches_whole_command() {
+        assert_eq!(command_argument("/language en", "/language"), Some("en"));
+        assert_eq!(
+            command_argument("  /language   ru  ", "/language"),
+            Some("ru")
+        );
+        assert_eq!(command_argument("/language", "/language"), Some(""));
+        assert_eq!(
+            command_argument("/language@notify_bot en", "/language"),
+            Some("en")
+        );
+        assert_eq!(command_argument("/languagefoo", "/language"), None);
+        assert_eq!(command_argument("/history", "/language"), None);
+        assert_eq!(command_argument("language en", "/language"), None);
+    }
 }
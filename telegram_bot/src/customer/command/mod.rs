@@ -0,0 +1,60 @@
+use domain::models::OperatorRole;
+use serde::{Deserialize, Serialize};
+
+/// Text command setting the webhook of the customer, `/webhook off` removes it.
+pub const WEBHOOK_COMMAND: &str = "/webhook";
+const WEBHOOK_OFF: &str = "off";
+
+/// Url of a `/webhook` command argument, `Some(None)` to remove the webhook.
+pub fn webhook_url(argument: &str) -> Option<Option<String>> {
+    match argument {
+        "" => None,
+        WEBHOOK_OFF => Some(None),
+        url => Some(Some(url.to_string())),
+    }
+}
+
+/// Text command listing the sent notifications, `/history <product>` for one product.
+pub const HISTORY_COMMAND: &str = "/history";
+
+/// Product of a `/history` command argument, `None` for every product.
+pub fn history_product(argument: &str) -> Option<&str> {
+    match argument {
+        "" => None,
+        product => Some(product),
+    }
+}
+
+/// Callback data of the customer bot inline buttons.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Command {
+    AddNotification,
+    ScheduledNotifications,
+    CancelScheduledNotification(i64),
+    RecurringNotifications,
+    PauseRecurringNotification(i64),
+    ResumeRecurringNotification(i64),
+    DeleteRecurringNotification(i64),
+    Operators,
+    InviteOperator(OperatorRole),
+    RevokeOperator(u32),
+    SwitchCustomer,
+    /// Index of the customer in the operator session.
+    SelectCustomer(usize),
+    AddCustomer,
+    /// Sent notifications of every product, from the newest.
+    History,
+    /// Page of the history of the product kept in `State::History`.
+    HistoryPage(u32),
+}
+
+impl From<Command> for String {
+    fn from(command: Command) -> Self {
+        serde_json::to_string(&command).unwrap()
+    }
+}
+impl From<String> for Command {
+    fn from(s: String) -> Self {
+        serde_json::from_str(&s).unwrap()
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use domain::models::{Attachment, LinkButton, NotificationContent, ParseMode, Timestamp};
+use teloxide::types::Message;
+use url::Url;
+
+use crate::common::MAX_CAPTION_LENGTH;
+
+const AT_DIRECTIVE: &str = "/at ";
+const AT_FORMAT: &str = "%Y-%m-%d %H:%M";
+const EVERY_DIRECTIVE: &str = "/every ";
+const CRON_FIELDS: usize = 5;
+const HTML_DIRECTIVE: &str = "/html";
+const MARKDOWN_DIRECTIVE: &str = "/markdown";
+const BUTTON_SEPARATOR: &str = " | ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendAtError {
+    Invalid,
+    InPast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentError {
+    /// Neither text nor attachment.
+    Empty,
+    /// The text of a photo or document is longer than Telegram accepts.
+    CaptionTooLong { length: usize, max_length: usize },
+}
+
+/// Reads the send time from an operator message whose first line is
+/// `/at YYYY-MM-DD HH:MM`, given in `timezone`.
+pub fn send_at(msg: &Message, timezone: Tz) -> Result<Option<Timestamp>, SendAtError> {
+    let raw_text = msg.text().or_else(|| msg.caption()).unwrap_or_default();
+    parse_send_at(raw_text, timezone, Timestamp::from(msg.date))
+}
+
+fn parse_send_at(
+    raw_text: &str,
+    timezone: Tz,
+    now: Timestamp,
+) -> Result<Option<Timestamp>, SendAtError> {
+    let argument = match raw_text
+        .lines()
+        .next()
+        .and_then(|line| line.trim().strip_prefix(AT_DIRECTIVE))
+    {
+        Some(argument) => argument.trim(),
+        None => return Ok(None),
+    };
+    let local =
+        NaiveDateTime::parse_from_str(argument, AT_FORMAT).map_err(|_| SendAtError::Invalid)?;
+    let send_at = match timezone.from_local_datetime(&local).earliest() {
+        Some(send_at) => Timestamp::from(send_at.with_timezone(&chrono::Utc)),
+        None => return Err(SendAtError::Invalid),
+    };
+    if send_at <= now {
+        return Err(SendAtError::InPast);
+    }
+    Ok(Some(send_at))
+}
+
+/// Reads a recurring schedule from an operator message whose first line is
+/// `/every <cron expression> [timezone]`, e.g. `/every 0 9 * * MON-FRI Europe/Moscow`.
+/// Returns the cron expression and the IANA timezone, `timezone` by default.
+/// The expression itself is validated by the repository.
+pub fn recurring_schedule(msg: &Message, timezone: Tz) -> Option<(String, String)> {
+    let raw_text = msg.text().or_else(|| msg.caption()).unwrap_or_default();
+    parse_recurring_schedule(raw_text, timezone)
+}
+
+fn parse_recurring_schedule(raw_text: &str, timezone: Tz) -> Option<(String, String)> {
+    let argument = raw_text
+        .lines()
+        .next()?
+        .trim()
+        .strip_prefix(EVERY_DIRECTIVE)?;
+    let mut fields: Vec<&str> = argument.split_whitespace().collect();
+    let timezone = match fields.last().map(|field| field.parse::<Tz>()) {
+        Some(Ok(timezone)) if fields.len() > CRON_FIELDS => {
+            fields.pop();
+            timezone
+        }
+        _ => timezone,
+    };
+    Some((fields.join(" "), timezone.name().to_string()))
+}
+
+/// Builds notification content from an operator message.
+///
+/// The first line may be an `/at` or `/every` directive (see [`send_at`] and
+/// [`recurring_schedule`]), followed by `/html` or `/markdown` to pick the
+/// parse mode, trailing lines of the form `Text | https://url` become link buttons,
+/// and an attached photo or document is sent along with the text, which then
+/// may not be longer than [`MAX_CAPTION_LENGTH`].
+pub fn notification_content(msg: &Message) -> Result<NotificationContent, ContentError> {
+    let attachment = if let Some(photo) = msg.photo().and_then(|sizes| sizes.last()) {
+        Some(Attachment::Photo {
+            file_id: photo.file_id.clone(),
+        })
+    } else {
+        msg.document().map(|document| Attachment::Document {
+            file_id: document.file_id.clone(),
+        })
+    };
+    let raw_text = msg.text().or_else(|| msg.caption()).unwrap_or_default();
+    parse_content(raw_text, attachment)
+}
+
+fn parse_content(
+    raw_text: &str,
+    attachment: Option<Attachment>,
+) -> Result<NotificationContent, ContentError> {
+    let mut lines: Vec<&str> = raw_text.lines().collect();
+    if lines
+        .first()
+        .map(|line| line.trim())
+        .is_some_and(|line| line.starts_with(AT_DIRECTIVE) || line.starts_with(EVERY_DIRECTIVE))
+    {
+        lines.remove(0);
+    }
+    let parse_mode = match lines.first().map(|line| line.trim()) {
+        Some(HTML_DIRECTIVE) => ParseMode::Html,
+        Some(MARKDOWN_DIRECTIVE) => ParseMode::MarkdownV2,
+        _ => ParseMode::Plain,
+    };
+    if parse_mode != ParseMode::Plain {
+        lines.remove(0);
+    }
+
+    let mut buttons = vec![];
+    while let Some(button) = lines.last().and_then(|line| link_button(line)) {
+        buttons.insert(0, button);
+        lines.pop();
+    }
+
+    let text = lines.join("\n").trim().to_string();
+    if text.is_empty() && attachment.is_none() {
+        return Err(ContentError::Empty);
+    }
+    let length = text.chars().count();
+    if attachment.is_some() && length > MAX_CAPTION_LENGTH {
+        return Err(ContentError::CaptionTooLong {
+            length,
+            max_length: MAX_CAPTION_LENGTH,
+        });
+    }
+
+    Ok(NotificationContent {
+        text,
+        parse_mode,
+        attachment,
+        buttons,
+    })
+}
+
+fn link_button(line: &str) -> Option<LinkButton> {
+    let (text, url) = line.rsplit_once(BUTTON_SEPARATOR)?;
+    let text = text.trim();
+    let url = Url::parse(url.trim()).ok()?;
+    if text.is_empty() || !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(LinkButton {
+        text: text.to_string(),
+        url: url.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn photo() -> Option<Attachment> {
+        Some(Attachment::Photo {
+            file_id: "file".to_string(),
+        })
+    }
+
+    #[test]
+    fn plain_text_is_kept() {
+        let content = parse_content("Hello\nworld", None).unwrap();
+        assert_eq!(content.text, "Hello\nworld");
+        assert_eq!(content.parse_mode, ParseMode::Plain);
+        assert!(content.buttons.is_empty());
+    }
+
+    #[test]
+    fn parse_mode_directive_is_removed() {
+        let content = parse_content("/html\n<b>Hi</b>", None).unwrap();
+        assert_eq!(content.parse_mode, ParseMode::Html);
+        assert_eq!(content.text, "<b>Hi</b>");
+
+        let content = parse_content("/markdown\n*Hi*", None).unwrap();
+        assert_eq!(content.parse_mode, ParseMode::MarkdownV2);
+        assert_eq!(content.text, "*Hi*");
+    }
+
+    #[test]
+    fn schedule_directive_is_removed_before_parse_mode() {
+        let content = parse_content("/at 2030-01-01 10:00\n/html\nHi", None).unwrap();
+        assert_eq!(content.parse_mode, ParseMode::Html);
+        assert_eq!(content.text, "Hi");
+
+        let content = parse_content("/every 0 9 * * *\nHi", None).unwrap();
+        assert_eq!(content.text, "Hi");
+    }
+
+    #[test]
+    fn trailing_link_lines_become_buttons() {
+        let content = parse_content(
+            "Sale!\nShop | https://example.com/shop\nHelp | https://example.com/help",
+            None,
+        )
+        .unwrap();
+        assert_eq!(content.text, "Sale!");
+        assert_eq!(
+            content.buttons,
+            vec![
+                LinkButton {
+                    text: "Shop".to_string(),
+                    url: "https://example.com/shop".to_string(),
+                },
+                LinkButton {
+                    text: "Help".to_string(),
+                    url: "https://example.com/help".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_links_stay_in_text() {
+        let content = parse_content("Sale!\nShop | ftp://example.com", None).unwrap();
+        assert_eq!(content.text, "Sale!\nShop | ftp://example.com");
+        assert!(content.buttons.is_empty());
+
+        let content = parse_content("Sale!\n | https://example.com", None).unwrap();
+        assert!(content.buttons.is_empty());
+    }
+
+    #[test]
+    fn empty_content_is_rejected() {
+        assert_eq!(parse_content("", None), Err(ContentError::Empty));
+        assert_eq!(parse_content("/html\n  ", None), Err(ContentError::Empty));
+    }
+
+    #[test]
+    fn attachment_without_text_is_accepted() {
+        let content = parse_content("", photo()).unwrap();
+        assert!(content.text.is_empty());
+        assert_eq!(content.attachment, photo());
+    }
+
+    #[test]
+    fn caption_length_is_limited() {
+        let caption = "я".repeat(MAX_CAPTION_LENGTH);
+        assert!(parse_content(&caption, photo()).is_ok());
+
+        let caption = "я".repeat(MAX_CAPTION_LENGTH + 1);
+        assert_eq!(
+            parse_content(&caption, photo()),
+            Err(ContentError::CaptionTooLong {
+                length: MAX_CAPTION_LENGTH + 1,
+                max_length: MAX_CAPTION_LENGTH,
+            })
+        );
+        assert!(parse_content(&caption, None).is_ok());
+    }
+
+    #[test]
+    fn send_at_is_read_in_timezone() {
+        let now = Timestamp::from_millis(0).unwrap();
+        let send_at = parse_send_at("/at 2030-01-01 12:00\nHi", chrono_tz::Europe::Moscow, now)
+            .unwrap()
+            .unwrap();
+        let expected = chrono::Utc.with_ymd_and_hms(2030, 1, 1, 9, 0, 0).unwrap();
+        assert_eq!(send_at, Timestamp::from(expected));
+    }
+
+    #[test]
+    fn send_at_errors() {
+        let now = Timestamp::now();
+        assert_eq!(parse_send_at("Hi", Tz::UTC, now), Ok(None));
+        assert_eq!(
+            parse_send_at("/at tomorrow\nHi", Tz::UTC, now),
+            Err(SendAtError::Invalid)
+        );
+        assert_eq!(
+            parse_send_at("/at 2000-01-01 10:00\nHi", Tz::UTC, now),
+            Err(SendAtError::InPast)
+        );
+    }
+
+    #[test]
+    fn recurring_schedule_with_and_without_timezone() {
+        assert_eq!(
+            parse_recurring_schedule("/every 0 9 * * MON-FRI Europe/Moscow\nHi", Tz::UTC),
+            Some(("0 9 * * MON-FRI".to_string(), "Europe/Moscow".to_string()))
+        );
+        assert_eq!(
+            parse_recurring_schedule("/every 0 9 * * *\nHi", chrono_tz::Europe::Berlin),
+            Some(("0 9 * * *".to_string(), "Europe/Berlin".to_string()))
+        );
+        assert_eq!(parse_recurring_schedule("Hi", Tz::UTC), None);
+    }
+}
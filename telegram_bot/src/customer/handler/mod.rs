@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
-use domain::responses::CustomerResponse;
 use amqp::IncomingMessageHandler;
+use domain::{
+    codec::{WireFormat, WireMessage},
+    responses::CustomerResponse,
+};
 use tokio::sync::Mutex;
 
 use crate::customer::CustomerService;
@@ -10,11 +13,18 @@ pub struct MessageHandler {}
 impl MessageHandler {
     pub fn customer_response(
         service: Arc<Mutex<CustomerService>>,
+        wire_format: WireFormat,
     ) -> impl IncomingMessageHandler + 'static {
-        move |message: String| {
+        move |message: Vec<u8>| {
             let service = service.clone();
             async move {
-                let response: CustomerResponse = serde_json::from_str(&message).unwrap();
+                let response = match CustomerResponse::decode(&message, wire_format) {
+                    Ok(response) => response,
+                    Err(error) => {
+                        log::error!("Message handler customer response decode error: {}", error);
+                        return;
+                    }
+                };
                 if let Err(error) = service.lock().await.handle_response(response).await {
                     log::error!("Message handler customer response error: {}", error);
                 }
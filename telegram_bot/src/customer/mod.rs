@@ -1,6 +1,9 @@
+pub mod command;
+mod content;
 mod handler;
 mod service;
 pub mod state;
 
+pub use content::{notification_content, recurring_schedule, send_at, ContentError, SendAtError};
 pub use handler::MessageHandler;
 pub use service::CustomerService;
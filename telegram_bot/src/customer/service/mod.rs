@@ -1,54 +1,95 @@
 use std::{collections::HashMap, sync::Arc};
 
-use domain::{models::Customer, responses::CustomerResponse};
+use chrono_tz::Tz;
+use domain::{
+    models::{
+        CustomerName, ModerationReason, OperatorRole, PastNotification, Quota,
+        RecurringNotification, ScheduledNotification, Timestamp,
+    },
+    responses::CustomerResponse,
+};
 use teloxide::{
     payloads::SendMessageSetters,
     prelude::AutoSend,
     requests::Requester,
-    types::{ChatId, KeyboardButton, KeyboardMarkup},
+    types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup},
     Bot,
 };
 use tokio::sync::Mutex;
 
-use crate::{common::HandlerResult, customer::state::State, storage::StateStorage};
+use crate::{
+    common::{i18n::I18n, preview, HandlerResult},
+    customer::{
+        command::Command,
+        state::{AuthorizedCustomer, OperatorSession, State},
+    },
+    storage::StateStorage,
+};
+
+const SEND_AT_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 pub struct CustomerService {
     bot: AutoSend<Bot>,
     state_storage: Arc<StateStorage<State>>,
-    authorized_customers: Arc<Mutex<HashMap<ChatId, Customer>>>,
+    sessions: Arc<Mutex<HashMap<ChatId, OperatorSession>>>,
+    i18n: Arc<I18n>,
+    timezone: Tz,
 }
 
 impl CustomerService {
     pub fn new(
         bot: AutoSend<Bot>,
         state_storage: Arc<StateStorage<State>>,
-        authorized_customers: Arc<Mutex<HashMap<ChatId, Customer>>>,
+        sessions: Arc<Mutex<HashMap<ChatId, OperatorSession>>>,
+        i18n: Arc<I18n>,
+        timezone: Tz,
     ) -> Self {
         CustomerService {
             bot,
             state_storage,
-            authorized_customers,
+            sessions,
+            i18n,
+            timezone,
         }
     }
     pub async fn handle_response(&mut self, response: CustomerResponse) -> HandlerResult {
         match response {
             CustomerResponse::AuthorizationFailure { user_id } => {
                 let chat_id = ChatId(user_id.0 as i64);
-                self.bot
-                    .send_message(chat_id, "Указан не верный ключ!")
-                    .await?;
+                let text = self.i18n.text(chat_id, "authorization-failure").await;
+                self.bot.send_message(chat_id, text).await?;
                 self.state_storage.set_state(chat_id, State::Start).await;
                 Ok(())
             }
-            CustomerResponse::AuthorizationSuccess { user_id, customer } => {
+            CustomerResponse::AuthorizationLocked { user_id, until } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let until = self.format_send_at(until);
+                let text = self
+                    .i18n
+                    .text_with(chat_id, "authorization-locked", &[("until", &until)])
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::AuthorizationSuccess {
+                user_id,
+                customer,
+                role,
+            } => {
                 let chat_id = ChatId(user_id.0 as i64);
                 let customer_name = customer.name.to_string();
-                self.authorized_customers
+                self.sessions
                     .lock()
                     .await
-                    .insert(chat_id, customer);
+                    .entry(chat_id)
+                    .or_default()
+                    .authorize(AuthorizedCustomer { customer, role });
                 self.state_storage.set_state(chat_id, State::Start).await;
-                let text = format!("Добро пожаловать, {}", customer_name);
+                let text = self
+                    .i18n
+                    .text_with(chat_id, "welcome", &[("customer", &customer_name)])
+                    .await;
                 self.bot.send_message(chat_id, text).await?;
                 Ok(())
             }
@@ -60,9 +101,8 @@ impl CustomerService {
                 let chat_id = ChatId(user_id.0 as i64);
                 match products.len() {
                     0 => {
-                        self.bot
-                            .send_message(chat_id, "Нет подписок на товары!")
-                            .await?;
+                        let text = self.i18n.text(chat_id, "no-subscriptions").await;
+                        self.bot.send_message(chat_id, text).await?;
                         self.state_storage.set_state(chat_id, State::Start).await;
                     }
                     _ => {
@@ -74,8 +114,9 @@ impl CustomerService {
                                 .collect();
                             keyboard.push(row);
                         }
+                        let text = self.i18n.text(chat_id, "choose-goods").await;
                         self.bot
-                            .send_message(chat_id, "Выберите товар:")
+                            .send_message(chat_id, text)
                             .reply_markup(KeyboardMarkup::new(keyboard))
                             .await?;
                         self.state_storage
@@ -88,28 +129,725 @@ impl CustomerService {
             }
             CustomerResponse::NotificationSuccess { user_id } => {
                 let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "notification-success").await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::NotificationFailure { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "notification-failure").await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::NotificationScheduled {
+                user_id,
+                notification,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let id = notification.id.to_string();
+                let send_at = self.format_send_at(notification.send_at);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "notification-scheduled",
+                        &[("id", &id), ("send_at", &send_at)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::ScheduledNotifications {
+                user_id,
+                notifications,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                if notifications.is_empty() {
+                    let text = self.i18n.text(chat_id, "no-scheduled-notifications").await;
+                    self.bot.send_message(chat_id, text).await?;
+                    self.state_storage.set_state(chat_id, State::Start).await;
+                    return Ok(());
+                }
+                let can_edit = self.role(chat_id).await.allows(OperatorRole::Editor);
+                let mut lines = vec![self.i18n.text(chat_id, "scheduled-notifications").await];
+                let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+                for notification in &notifications {
+                    let id = notification.id.to_string();
+                    lines.push(
+                        self.scheduled_notification_line(chat_id, notification)
+                            .await,
+                    );
+                    if !can_edit {
+                        continue;
+                    }
+                    let cancel = self
+                        .i18n
+                        .text_with(chat_id, "cancel-scheduled-notification", &[("id", &id)])
+                        .await;
+                    keyboard.push(vec![InlineKeyboardButton::callback(
+                        cancel,
+                        Command::CancelScheduledNotification(notification.id),
+                    )]);
+                }
                 self.bot
-                    .send_message(chat_id, "Уведомление успешно отправлено!")
+                    .send_message(chat_id, lines.join("\n"))
+                    .reply_markup(InlineKeyboardMarkup::new(keyboard))
                     .await?;
                 self.state_storage.set_state(chat_id, State::Start).await;
                 Ok(())
             }
-            CustomerResponse::NotificationFailure { user_id } => {
+            CustomerResponse::ScheduledNotificationCancelled {
+                user_id,
+                id,
+                success,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let key = match success {
+                    true => "scheduled-notification-cancelled",
+                    false => "scheduled-notification-not-cancelled",
+                };
+                let id = id.to_string();
+                let text = self.i18n.text_with(chat_id, key, &[("id", &id)]).await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::RecurringNotificationCreated {
+                user_id,
+                notification,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let line = self
+                    .recurring_notification_line(chat_id, &notification)
+                    .await;
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "recurring-notification-created",
+                        &[("notification", &line)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::RecurringNotifications {
+                user_id,
+                notifications,
+            } => {
                 let chat_id = ChatId(user_id.0 as i64);
+                if notifications.is_empty() {
+                    let text = self.i18n.text(chat_id, "no-recurring-notifications").await;
+                    self.bot.send_message(chat_id, text).await?;
+                    self.state_storage.set_state(chat_id, State::Start).await;
+                    return Ok(());
+                }
+                let can_edit = self.role(chat_id).await.allows(OperatorRole::Editor);
+                let mut lines = vec![self.i18n.text(chat_id, "recurring-notifications").await];
+                let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+                for notification in &notifications {
+                    let id = notification.id.to_string();
+                    lines.push(
+                        self.recurring_notification_line(chat_id, notification)
+                            .await,
+                    );
+                    if !can_edit {
+                        continue;
+                    }
+                    let (key, command) = match notification.paused {
+                        true => (
+                            "resume-recurring-notification",
+                            Command::ResumeRecurringNotification(notification.id),
+                        ),
+                        false => (
+                            "pause-recurring-notification",
+                            Command::PauseRecurringNotification(notification.id),
+                        ),
+                    };
+                    let toggle = self.i18n.text_with(chat_id, key, &[("id", &id)]).await;
+                    let delete = self
+                        .i18n
+                        .text_with(chat_id, "delete-recurring-notification", &[("id", &id)])
+                        .await;
+                    keyboard.push(vec![
+                        InlineKeyboardButton::callback(toggle, command),
+                        InlineKeyboardButton::callback(
+                            delete,
+                            Command::DeleteRecurringNotification(notification.id),
+                        ),
+                    ]);
+                }
                 self.bot
-                    .send_message(chat_id, "Не удалось отправить уведомление!")
+                    .send_message(chat_id, lines.join("\n"))
+                    .reply_markup(InlineKeyboardMarkup::new(keyboard))
                     .await?;
                 self.state_storage.set_state(chat_id, State::Start).await;
                 Ok(())
             }
+            CustomerResponse::RecurringNotificationPaused {
+                user_id,
+                id,
+                paused,
+                success,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let key = match (success, paused) {
+                    (true, true) => "recurring-notification-paused",
+                    (true, false) => "recurring-notification-resumed",
+                    (false, _) => "recurring-notification-not-found",
+                };
+                let id = id.to_string();
+                let text = self.i18n.text_with(chat_id, key, &[("id", &id)]).await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::RecurringNotificationDeleted {
+                user_id,
+                id,
+                success,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let key = match success {
+                    true => "recurring-notification-deleted",
+                    false => "recurring-notification-not-found",
+                };
+                let id = id.to_string();
+                let text = self.i18n.text_with(chat_id, key, &[("id", &id)]).await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::InvalidSchedule {
+                user_id,
+                schedule,
+                timezone,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "invalid-schedule",
+                        &[("schedule", &schedule), ("timezone", &timezone)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::DeliveryProgress {
+                user_id,
+                published,
+                failed,
+                total,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let key = match published + failed < total {
+                    true => "delivery-progress",
+                    false => "delivery-finished",
+                };
+                let published = published.to_string();
+                let failed = failed.to_string();
+                let total = total.to_string();
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        key,
+                        &[
+                            ("published", &published),
+                            ("failed", &failed),
+                            ("total", &total),
+                        ],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            CustomerResponse::RequestFailure { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "request-failure").await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::ServiceUnavailable { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "service-unavailable").await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::WebhookRegistered {
+                user_id,
+                url,
+                secret,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "webhook-registered",
+                        &[("url", &url), ("secret", &secret)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            CustomerResponse::WebhookRemoved { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "webhook-removed").await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            CustomerResponse::InvalidWebhookUrl { user_id, url } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(chat_id, "invalid-webhook-url", &[("url", &url)])
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            CustomerResponse::QuotaExceeded {
+                user_id,
+                quota,
+                limit,
+                retry_after_secs,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let quota = self.quota_label(chat_id, quota, limit).await;
+                let minutes = retry_after_secs.div_ceil(60).to_string();
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "quota-exceeded",
+                        &[("quota", &quota), ("minutes", &minutes)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::NotificationDeferred {
+                user_id,
+                id,
+                quota,
+                limit,
+                send_at,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let quota = self.quota_label(chat_id, quota, limit).await;
+                let id = id.to_string();
+                let send_at = self.format_send_at(send_at);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "notification-deferred",
+                        &[("id", &id), ("quota", &quota), ("send_at", &send_at)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            CustomerResponse::NotificationRejected { user_id, reason } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = match reason {
+                    ModerationReason::TooLong { length, max_length } => {
+                        let length = length.to_string();
+                        let max_length = max_length.to_string();
+                        self.i18n
+                            .text_with(
+                                chat_id,
+                                "notification-rejected-too-long",
+                                &[("length", &length), ("max_length", &max_length)],
+                            )
+                            .await
+                    }
+                    ModerationReason::BannedWord { word } => {
+                        self.i18n
+                            .text_with(
+                                chat_id,
+                                "notification-rejected-banned-word",
+                                &[("word", &word)],
+                            )
+                            .await
+                    }
+                    ModerationReason::DeniedLink { domain } => {
+                        self.i18n
+                            .text_with(
+                                chat_id,
+                                "notification-rejected-denied-link",
+                                &[("domain", &domain)],
+                            )
+                            .await
+                    }
+                    ModerationReason::Rejected { comment } => {
+                        self.i18n
+                            .text_with(
+                                chat_id,
+                                "notification-rejected-by-moderator",
+                                &[("comment", &comment)],
+                            )
+                            .await
+                    }
+                };
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::NotificationHeld { user_id, id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let id = id.to_string();
+                let text = self
+                    .i18n
+                    .text_with(chat_id, "notification-held", &[("id", &id)])
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
             CustomerResponse::ClientSubscription {
                 user_id, product, ..
             } => {
                 let chat_id = ChatId(user_id.0 as i64);
-                let text = format!("Оформлена подписка на товар [{product}]!");
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "client-subscription",
+                        &[("product", product.as_str())],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                Ok(())
+            }
+            CustomerResponse::UnknownCustomer { user_id, customer } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "unknown-customer",
+                        &[("customer", customer.as_str())],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.remove_customer(chat_id, &customer).await;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::Operators { user_id, operators } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let mut lines = vec![self.i18n.text(chat_id, "operators").await];
+                let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+                for operator in &operators {
+                    let id = operator.user_id.to_string();
+                    let role = self.role_name(chat_id, operator.role).await;
+                    let key = match operator.user_id == user_id.0 {
+                        true => "operator-self",
+                        false => "operator",
+                    };
+                    lines.push(
+                        self.i18n
+                            .text_with(chat_id, key, &[("id", &id), ("role", &role)])
+                            .await,
+                    );
+                    if operator.user_id == user_id.0 {
+                        continue;
+                    }
+                    let revoke = self
+                        .i18n
+                        .text_with(chat_id, "revoke-operator", &[("id", &id)])
+                        .await;
+                    keyboard.push(vec![InlineKeyboardButton::callback(
+                        revoke,
+                        Command::RevokeOperator(operator.user_id),
+                    )]);
+                }
+                for role in [
+                    OperatorRole::Owner,
+                    OperatorRole::Editor,
+                    OperatorRole::Viewer,
+                ] {
+                    let role_name = self.role_name(chat_id, role).await;
+                    let invite = self
+                        .i18n
+                        .text_with(chat_id, "invite-operator", &[("role", &role_name)])
+                        .await;
+                    keyboard.push(vec![InlineKeyboardButton::callback(
+                        invite,
+                        Command::InviteOperator(role),
+                    )]);
+                }
+                self.bot
+                    .send_message(chat_id, lines.join("\n"))
+                    .reply_markup(InlineKeyboardMarkup::new(keyboard))
+                    .await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::OperatorInvited {
+                user_id,
+                role,
+                key,
+                expires_at,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let role = self.role_name(chat_id, role).await;
+                let expires_at = self.format_send_at(expires_at);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "operator-invited",
+                        &[("role", &role), ("key", &key), ("expires_at", &expires_at)],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::OperatorRevoked {
+                user_id,
+                operator_id,
+                success,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let key = match success {
+                    true => "operator-revoked",
+                    false => "operator-not-revoked",
+                };
+                let id = operator_id.to_string();
+                let text = self.i18n.text_with(chat_id, key, &[("id", &id)]).await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::OperatorAccessRevoked { user_id, customer } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                self.remove_customer(chat_id, &customer).await;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "operator-access-revoked",
+                        &[("customer", customer.as_str())],
+                    )
+                    .await;
                 self.bot.send_message(chat_id, text).await?;
                 Ok(())
             }
+            CustomerResponse::PermissionDenied { user_id } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self.i18n.text(chat_id, "permission-denied").await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage.set_state(chat_id, State::Start).await;
+                Ok(())
+            }
+            CustomerResponse::UnknownProduct {
+                user_id,
+                customer,
+                product,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                let text = self
+                    .i18n
+                    .text_with(
+                        chat_id,
+                        "unknown-product",
+                        &[
+                            ("customer", customer.as_str()),
+                            ("product", product.as_str()),
+                        ],
+                    )
+                    .await;
+                self.bot.send_message(chat_id, text).await?;
+                self.state_storage
+                    .set_state(chat_id, State::AddNotification { customer })
+                    .await;
+                Ok(())
+            }
+            CustomerResponse::NotificationHistory {
+                user_id,
+                product,
+                page,
+                notifications,
+                has_more,
+            } => {
+                let chat_id = ChatId(user_id.0 as i64);
+                if notifications.is_empty() {
+                    let text = self.i18n.text(chat_id, "no-sent-notifications").await;
+                    self.bot.send_message(chat_id, text).await?;
+                    return Ok(());
+                }
+                let title = match &product {
+                    Some(product) => {
+                        self.i18n
+                            .text_with(
+                                chat_id,
+                                "sent-notifications-product",
+                                &[("product", product.as_str())],
+                            )
+                            .await
+                    }
+                    None => self.i18n.text(chat_id, "sent-notifications").await,
+                };
+                let mut lines = vec![title];
+                for notification in &notifications {
+                    lines.push(self.sent_notification_line(chat_id, notification).await);
+                }
+                let mut row: Vec<InlineKeyboardButton> = vec![];
+                if page > 0 {
+                    row.push(InlineKeyboardButton::callback(
+                        self.i18n.text(chat_id, "history-newer").await,
+                        Command::HistoryPage(page - 1),
+                    ));
+                }
+                if has_more {
+                    row.push(InlineKeyboardButton::callback(
+                        self.i18n.text(chat_id, "history-older").await,
+                        Command::HistoryPage(page + 1),
+                    ));
+                }
+                self.bot
+                    .send_message(chat_id, lines.join("\n"))
+                    .reply_markup(InlineKeyboardMarkup::new(vec![row]))
+                    .await?;
+                Ok(())
+            }
         }
     }
+    /// Role of the user for the active customer, the least privileged one
+    /// if they are not authorized.
+    async fn role(&self, chat_id: ChatId) -> OperatorRole {
+        self.sessions
+            .lock()
+            .await
+            .get(&chat_id)
+            .and_then(|session| session.active())
+            .map(|authorized| authorized.role)
+            .unwrap_or(OperatorRole::Viewer)
+    }
+    async fn remove_customer(&self, chat_id: ChatId, customer: &CustomerName) {
+        let mut sessions = self.sessions.lock().await;
+        if let Some(session) = sessions.get_mut(&chat_id) {
+            session.remove(customer);
+            if session.is_empty() {
+                sessions.remove(&chat_id);
+            }
+        }
+    }
+    async fn role_name(&self, chat_id: ChatId, role: OperatorRole) -> String {
+        let key = format!("role-{}", role.as_str());
+        self.i18n.text(chat_id, &key).await
+    }
+    async fn quota_label(&self, chat_id: ChatId, quota: Quota, limit: u32) -> String {
+        let key = match quota {
+            Quota::NotificationsPerHour => "quota-notifications-per-hour",
+            Quota::NotificationsPerDay => "quota-notifications-per-day",
+            Quota::RecipientsPerDay => "quota-recipients-per-day",
+        };
+        let limit = limit.to_string();
+        self.i18n
+            .text_with(chat_id, key, &[("limit", &limit)])
+            .await
+    }
+
+    fn format_send_at(&self, send_at: Timestamp) -> String {
+        send_at
+            .as_datetime()
+            .with_timezone(&self.timezone)
+            .format(SEND_AT_FORMAT)
+            .to_string()
+    }
+    async fn recurring_notification_line(
+        &self,
+        chat_id: ChatId,
+        notification: &RecurringNotification,
+    ) -> String {
+        let id = notification.id.to_string();
+        let timezone: Tz = notification.timezone.parse().unwrap_or(self.timezone);
+        let next_send_at = match notification.next_send_at {
+            Some(next_send_at) if !notification.paused => next_send_at
+                .as_datetime()
+                .with_timezone(&timezone)
+                .format(SEND_AT_FORMAT)
+                .to_string(),
+            _ => {
+                self.i18n
+                    .text(chat_id, "recurring-notification-inactive")
+                    .await
+            }
+        };
+        let preview = preview(&notification.content.text);
+        self.i18n
+            .text_with(
+                chat_id,
+                "recurring-notification",
+                &[
+                    ("id", &id),
+                    ("schedule", &notification.schedule),
+                    ("timezone", &notification.timezone),
+                    ("next_send_at", &next_send_at),
+                    ("product", notification.product.as_str()),
+                    ("text", &preview),
+                ],
+            )
+            .await
+    }
+    async fn sent_notification_line(
+        &self,
+        chat_id: ChatId,
+        notification: &PastNotification,
+    ) -> String {
+        let id = notification.id.to_string();
+        let sent_at = match notification.sent_at {
+            Some(sent_at) => self.format_send_at(sent_at),
+            None => self.i18n.text(chat_id, "sent-at-unknown").await,
+        };
+        let preview = preview(&notification.content.text);
+        self.i18n
+            .text_with(
+                chat_id,
+                "sent-notification",
+                &[
+                    ("id", &id),
+                    ("sent_at", &sent_at),
+                    ("product", notification.product.as_str()),
+                    ("text", &preview),
+                ],
+            )
+            .await
+    }
+    async fn scheduled_notification_line(
+        &self,
+        chat_id: ChatId,
+        notification: &ScheduledNotification,
+    ) -> String {
+        let id = notification.id.to_string();
+        let send_at = self.format_send_at(notification.send_at);
+        let preview = preview(&notification.content.text);
+        self.i18n
+            .text_with(
+                chat_id,
+                "scheduled-notification",
+                &[
+                    ("id", &id),
+                    ("send_at", &send_at),
+                    ("product", notification.product.as_str()),
+                    ("text", &preview),
+                ],
+            )
+            .await
+    }
 }
@@ -1,8 +1,85 @@
+use domain::models::{Customer, CustomerName, OperatorRole, ProductName};
+
 #[derive(Debug, Clone)]
 pub enum State {
     Start,
     Authorization,
     Command,
-    AddNotification { customer: String },
-    SendNotification { customer: String, product: String },
+    AddNotification {
+        customer: CustomerName,
+    },
+    SendNotification {
+        customer: CustomerName,
+        product: ProductName,
+    },
+    /// Browsing the sent notifications, of `product` only if it is set.
+    History {
+        product: Option<ProductName>,
+    },
+}
+
+/// Customer the user is authorized for and their role as its operator.
+#[derive(Debug, Clone)]
+pub struct AuthorizedCustomer {
+    pub customer: Customer,
+    pub role: OperatorRole,
+}
+
+/// Customers the user is authorized for, requests are made on behalf of the
+/// active one.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorSession {
+    customers: Vec<AuthorizedCustomer>,
+    active: usize,
+}
+
+impl OperatorSession {
+    pub fn active(&self) -> Option<&AuthorizedCustomer> {
+        self.customers.get(self.active)
+    }
+    pub fn customers(&self) -> &[AuthorizedCustomer] {
+        &self.customers
+    }
+    /// Adds the customer, or updates the role if already authorized, and makes it active.
+    pub fn authorize(&mut self, authorized: AuthorizedCustomer) {
+        let position = self
+            .customers
+            .iter()
+            .position(|existing| existing.customer.name == authorized.customer.name);
+        self.active = match position {
+            Some(position) => {
+                self.customers[position] = authorized;
+                position
+            }
+            None => {
+                self.customers.push(authorized);
+                self.customers.len() - 1
+            }
+        };
+    }
+    /// Makes the customer at `index` of [`Self::customers`] active.
+    pub fn select(&mut self, index: usize) -> Option<&AuthorizedCustomer> {
+        if index < self.customers.len() {
+            self.active = index;
+        }
+        self.customers.get(index)
+    }
+    /// Removes the customer, the first remaining one becomes active if it was.
+    pub fn remove(&mut self, customer: &CustomerName) {
+        let position = self
+            .customers
+            .iter()
+            .position(|existing| &existing.customer.name == customer);
+        if let Some(position) = position {
+            self.customers.remove(position);
+            if position < self.active {
+                self.active -= 1;
+            } else if position == self.active {
+                self.active = 0;
+            }
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.customers.is_empty()
+    }
 }
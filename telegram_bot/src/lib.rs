@@ -2,6 +2,8 @@ pub mod client;
 mod common;
 pub mod customer;
 
+pub use common::command_argument;
+pub use common::i18n;
 pub use common::storage;
 pub use common::Config;
 pub use common::HandlerResult;
@@ -0,0 +1,24 @@
+-- Add migration script here
+
+CREATE TABLE "webhooks" (
+	"customer"	TEXT NOT NULL,
+	"url"	TEXT NOT NULL,
+	"secret"	TEXT NOT NULL,
+	PRIMARY KEY("customer")
+);
+
+CREATE TABLE "webhook_deliveries" (
+	"id"	INTEGER NOT NULL,
+	"customer"	TEXT NOT NULL,
+	"url"	TEXT NOT NULL,
+	"event"	TEXT NOT NULL,
+	"payload"	TEXT NOT NULL,
+	"attempt"	INTEGER NOT NULL,
+	"status"	INTEGER,
+	"error"	TEXT,
+	"success"	BOOLEAN NOT NULL,
+	"sent_at"	INTEGER NOT NULL,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "webhook_deliveries_customer" ON "webhook_deliveries" ("customer", "sent_at");
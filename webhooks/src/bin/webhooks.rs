@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use amqp::RabbitMqManager;
+use dotenv::dotenv;
+use tokio::sync::Mutex;
+use webhooks::{repository::SqliteRepository, Config, MessageHandler, WebhookService};
+
+const MIGRATE_ONLY_FLAG: &str = "--migrate-only";
+
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+    pretty_env_logger::init();
+
+    let config = envy::from_env::<Config>().unwrap();
+
+    SqliteRepository::migrate(&config.webhooks_database_url)
+        .await
+        .unwrap();
+    if std::env::args().any(|arg| arg == MIGRATE_ONLY_FLAG) {
+        log::info!("Migrations applied");
+        return;
+    }
+
+    let repository = SqliteRepository::new(&config.webhooks_database_url)
+        .await
+        .unwrap();
+
+    let mut manager = RabbitMqManager::builder()
+        .build(&config.amqp_address)
+        .await
+        .unwrap();
+    let publisher = manager.get_publisher().await.unwrap();
+    let service = Arc::new(Mutex::new(WebhookService::new(
+        config.clone(),
+        repository,
+        publisher,
+    )));
+
+    manager
+        .add_consumer(
+            &config.webhook_queue,
+            MessageHandler::webhook_request(service, config.wire_format),
+        )
+        .await
+        .unwrap();
+
+    manager.run();
+}
@@ -0,0 +1,22 @@
+use domain::codec::WireFormat;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    pub amqp_address: String,
+    pub exchange: String,
+    pub webhooks_database_url: String,
+    pub webhook_queue: String,
+    pub webhook_response_queue: String,
+    /// Attempts to deliver one event, 5 by default.
+    #[serde(default)]
+    pub webhook_max_attempts: Option<u32>,
+    /// Delay before the first retry in seconds, doubled after every failed attempt, 5 by default.
+    #[serde(default)]
+    pub webhook_retry_delay_secs: Option<u64>,
+    /// Timeout of one delivery attempt in seconds, 10 by default.
+    #[serde(default)]
+    pub webhook_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub wire_format: WireFormat,
+}
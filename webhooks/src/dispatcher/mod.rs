@@ -0,0 +1,359 @@
+use std::{sync::Arc, time::Duration};
+
+use domain::models::{CustomerName, Timestamp};
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+use crate::{
+    repository::{DeliveryAttempt, SqliteRepository, Webhook},
+    Config,
+};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_RETRY_DELAY_SECS: u64 = 5;
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Header with the `sha256=<hex>` HMAC of the body, keyed by the webhook secret.
+pub const SIGNATURE_HEADER: &str = "X-NotifyMe-Signature";
+/// Header with the event name, e.g. `subscription.created`.
+pub const EVENT_HEADER: &str = "X-NotifyMe-Event";
+
+/// Event payload on its way to the webhook of a customer.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub customer: CustomerName,
+    pub webhook: Webhook,
+    pub event: String,
+    pub payload: String,
+}
+
+/// Posts events to the webhooks. Each delivery runs in its own task, so a
+/// slow or failing webhook does not hold up the others. Failed attempts are
+/// retried with exponential backoff on network errors, 408, 429 and 5xx.
+#[derive(Clone)]
+pub struct Dispatcher {
+    client: Client,
+    repository: Arc<Mutex<SqliteRepository>>,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+impl Dispatcher {
+    pub fn new(config: &Config, repository: Arc<Mutex<SqliteRepository>>) -> Self {
+        let timeout = config.webhook_timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let client = Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .unwrap();
+        let max_attempts = config
+            .webhook_max_attempts
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+            .max(1);
+        let retry_delay = config
+            .webhook_retry_delay_secs
+            .unwrap_or(DEFAULT_RETRY_DELAY_SECS);
+        Dispatcher {
+            client,
+            repository,
+            max_attempts,
+            retry_delay: Duration::from_secs(retry_delay),
+        }
+    }
+
+    pub fn dispatch(&self, delivery: Delivery) {
+        tokio::spawn(self.clone().deliver(delivery));
+    }
+
+    async fn deliver(self, delivery: Delivery) {
+        let signature = sign(&delivery.webhook.secret, delivery.payload.as_bytes());
+        let mut delay = self.retry_delay;
+        for attempt in 1..=self.max_attempts {
+            let result = self
+                .client
+                .post(&delivery.webhook.url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, &delivery.event)
+                .header(SIGNATURE_HEADER, &signature)
+                .body(delivery.payload.clone())
+                .send()
+                .await;
+            let (status, error, retry) = match result {
+                Ok(response) => {
+                    let status = response.status();
+                    let error = match status.is_success() {
+                        true => None,
+                        false => Some(format!("Unexpected status {}", status)),
+                    };
+                    (Some(status.as_u16()), error, should_retry(status))
+                }
+                Err(error) => (None, Some(error.to_string()), true),
+            };
+            let success = error.is_none();
+            self.log(&delivery, attempt, status, error).await;
+
+            if success {
+                return;
+            }
+            if !retry || attempt == self.max_attempts {
+                log::warn!(
+                    "Giving up {} webhook of customer [{}] after {} attempts",
+                    delivery.event,
+                    delivery.customer,
+                    attempt
+                );
+                return;
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+
+    async fn log(
+        &self,
+        delivery: &Delivery,
+        attempt: u32,
+        status: Option<u16>,
+        error: Option<String>,
+    ) {
+        if let Some(error) = &error {
+            log::info!(
+                "Attempt {} of {} webhook of customer [{}] failed: {}",
+                attempt,
+                delivery.event,
+                delivery.customer,
+                error
+            );
+        }
+        let attempt = DeliveryAttempt {
+            customer: delivery.customer.clone(),
+            url: delivery.webhook.url.clone(),
+            event: delivery.event.clone(),
+            payload: delivery.payload.clone(),
+            attempt,
+            status,
+            success: error.is_none(),
+            error,
+            sent_at: Timestamp::now(),
+        };
+        if let Err(error) = self
+            .repository
+            .lock()
+            .await
+            .add_delivery_attempt(&attempt)
+            .await
+        {
+            log::error!("Failed to log webhook delivery: {}", error);
+        }
+    }
+}
+
+fn should_retry(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// Signature of the payload in the form sent in `SIGNATURE_HEADER`.
+pub fn sign(secret: &str, payload: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(payload);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    /// Answers one request per status, in order, and returns the requests.
+    async fn serve(listener: TcpListener, statuses: Vec<u16>) -> Vec<String> {
+        let mut requests = Vec::new();
+        for status in statuses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            requests.push(read_request(&mut stream).await);
+            let response =
+                format!("HTTP/1.1 {status} Stub\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        requests
+    }
+
+    async fn read_request(stream: &mut TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length: ")
+                            .map(str::to_owned)
+                    })
+                    .map_or(0, |length| length.parse().unwrap());
+                if body.len() >= length {
+                    return text.into_owned();
+                }
+            }
+            if read == 0 {
+                return text.into_owned();
+            }
+        }
+    }
+
+    /// Dispatcher without retry delays logging to a new database, and the url of the database.
+    async fn dispatcher(max_attempts: u32) -> (Dispatcher, String) {
+        let path = std::env::temp_dir().join(format!("webhooks-{}.db", rand::random::<u64>()));
+        let url = format!("sqlite://{}", path.display());
+        SqliteRepository::migrate(&url).await.unwrap();
+        let repository = SqliteRepository::new(&url).await.unwrap();
+        let config = Config {
+            amqp_address: String::new(),
+            exchange: String::new(),
+            webhooks_database_url: url.clone(),
+            webhook_queue: String::new(),
+            webhook_response_queue: String::new(),
+            webhook_max_attempts: Some(max_attempts),
+            webhook_retry_delay_secs: Some(0),
+            webhook_timeout_secs: Some(5),
+            wire_format: Default::default(),
+        };
+        let dispatcher = Dispatcher::new(&config, Arc::new(Mutex::new(repository)));
+        (dispatcher, url)
+    }
+
+    /// Attempt number, status and success of the logged attempts.
+    async fn logged_attempts(url: &str) -> Vec<(i64, Option<i64>, bool)> {
+        let pool = SqlitePool::connect(url).await.unwrap();
+        sqlx::query_as("SELECT attempt, status, success FROM webhook_deliveries ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+    }
+
+    fn delivery(url: String) -> Delivery {
+        Delivery {
+            customer: CustomerName::parse("shop").unwrap(),
+            webhook: Webhook {
+                url,
+                secret: "secret".to_string(),
+            },
+            event: "subscription.created".to_string(),
+            payload: r#"{"event":"subscription.created"}"#.to_string(),
+        }
+    }
+
+    async fn deliver(
+        max_attempts: u32,
+        statuses: Vec<u16>,
+    ) -> (Vec<String>, Vec<(i64, Option<i64>, bool)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let webhook_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(listener, statuses));
+        let (dispatcher, url) = dispatcher(max_attempts).await;
+        dispatcher.deliver(delivery(webhook_url)).await;
+        let requests = server.await.unwrap();
+        let attempts = logged_attempts(&url).await;
+        let _ = std::fs::remove_file(url.trim_start_matches("sqlite://"));
+        (requests, attempts)
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_webhook_accepts() {
+        let (requests, attempts) = deliver(5, vec![503, 500, 200]).await;
+        assert_eq!(requests.len(), 3);
+        let signature = sign("secret", br#"{"event":"subscription.created"}"#);
+        for request in &requests {
+            assert!(request.starts_with("POST /hook "));
+            assert!(request.to_lowercase().contains(&format!(
+                "{}: {}",
+                SIGNATURE_HEADER.to_lowercase(),
+                signature
+            )));
+            assert!(request.ends_with(r#"{"event":"subscription.created"}"#));
+        }
+        assert_eq!(
+            attempts,
+            vec![
+                (1, Some(503), false),
+                (2, Some(500), false),
+                (3, Some(200), true)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_attempt() {
+        let (requests, attempts) = deliver(3, vec![503, 503, 503]).await;
+        assert_eq!(requests.len(), 3);
+        assert_eq!(
+            attempts,
+            vec![
+                (1, Some(503), false),
+                (2, Some(503), false),
+                (3, Some(503), false)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_rejected_events() {
+        let (requests, attempts) = deliver(5, vec![400]).await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(attempts, vec![(1, Some(400), false)]);
+    }
+
+    #[test]
+    fn sign_matches_reference_vector() {
+        // RFC 4231, test case 2.
+        let signature = sign("Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            signature,
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn signature_verifies_with_the_secret() {
+        let payload = br#"{"event":"subscription.created","customer":"shop"}"#;
+        let signature = sign("secret", payload);
+        let digest = hex::decode(signature.strip_prefix("sha256=").unwrap()).unwrap();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(payload);
+        assert!(mac.verify_slice(&digest).is_ok());
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"other secret").unwrap();
+        mac.update(payload);
+        assert!(mac.verify_slice(&digest).is_err());
+    }
+
+    #[test]
+    fn signature_depends_on_secret_and_payload() {
+        let signature = sign("secret", b"payload");
+        assert_eq!(signature, sign("secret", b"payload"));
+        assert_ne!(signature, sign("other secret", b"payload"));
+        assert_ne!(signature, sign("secret", b"other payload"));
+        assert_eq!(sign("", b"").len(), "sha256=".len() + 64);
+    }
+
+    #[test]
+    fn retries_transient_failures_only() {
+        assert!(should_retry(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(should_retry(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(should_retry(StatusCode::TOO_MANY_REQUESTS));
+        assert!(should_retry(StatusCode::REQUEST_TIMEOUT));
+        assert!(!should_retry(StatusCode::BAD_REQUEST));
+        assert!(!should_retry(StatusCode::NOT_FOUND));
+        assert!(!should_retry(StatusCode::GONE));
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use amqp::IncomingMessageHandler;
+use domain::{
+    codec::{Correlated, WireFormat, WireMessage},
+    requests::WebhookRequest,
+};
+use tokio::sync::Mutex;
+
+use crate::WebhookService;
+
+pub struct MessageHandler {}
+impl MessageHandler {
+    pub fn webhook_request(
+        service: Arc<Mutex<WebhookService>>,
+        wire_format: WireFormat,
+    ) -> impl IncomingMessageHandler + 'static {
+        move |message: Vec<u8>| {
+            let service = service.clone();
+            async move {
+                match Correlated::<WebhookRequest>::decode(&message, wire_format) {
+                    Ok(request) => service.lock().await.handle_request(request).await,
+                    Err(error) => log::error!("Message handler webhook request error: {}", error),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod common;
+pub mod dispatcher;
+mod handler;
+pub mod repository;
+mod service;
+
+pub use common::Config;
+pub use handler::MessageHandler;
+pub use service::WebhookService;
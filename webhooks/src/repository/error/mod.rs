@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DatabaseErrors {
+    #[error("Connection failure: [{0}]")]
+    ConnectionFailure(String),
+    #[error("Request error: [{0}]")]
+    RequestError(String),
+    #[error("Migration error: [{0}]")]
+    MigrationError(String),
+}
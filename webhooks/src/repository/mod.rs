@@ -0,0 +1,5 @@
+mod error;
+mod sqlite;
+
+pub use error::DatabaseErrors;
+pub use sqlite::{DeliveryAttempt, SqliteRepository, Webhook};
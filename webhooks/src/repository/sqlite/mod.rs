@@ -0,0 +1,159 @@
+use domain::models::{CustomerName, Timestamp};
+use sqlx::{migrate::Migrator, sqlite::SqliteConnectOptions, SqlitePool};
+use std::str::FromStr;
+
+use crate::repository::error::DatabaseErrors;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    pub secret: String,
+}
+
+/// One POST of an event to a webhook, kept in the delivery log.
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub customer: CustomerName,
+    pub url: String,
+    pub event: String,
+    pub payload: String,
+    pub attempt: u32,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub success: bool,
+    pub sent_at: Timestamp,
+}
+
+pub struct SqliteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRepository {
+    pub async fn new(url: &str) -> Result<Self, DatabaseErrors> {
+        match SqlitePool::connect(url).await {
+            Ok(pool) => Ok(SqliteRepository { pool }),
+            Err(error) => Err(DatabaseErrors::ConnectionFailure(error.to_string())),
+        }
+    }
+
+    /// Creates the database file if needed and applies pending migrations.
+    pub async fn migrate(url: &str) -> Result<(), DatabaseErrors> {
+        let options = SqliteConnectOptions::from_str(url)
+            .map_err(|error| DatabaseErrors::ConnectionFailure(error.to_string()))?
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options)
+            .await
+            .map_err(|error| DatabaseErrors::ConnectionFailure(error.to_string()))?;
+        MIGRATOR
+            .run(&pool)
+            .await
+            .map_err(|error| DatabaseErrors::MigrationError(error.to_string()))
+    }
+
+    pub async fn set_webhook(
+        &mut self,
+        customer: &CustomerName,
+        webhook: &Webhook,
+    ) -> Result<(), DatabaseErrors> {
+        let customer = customer.as_str();
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO webhooks ( customer, url, secret )
+            VALUES ( ?1, ?2, ?3 )
+            ON CONFLICT ( customer ) DO UPDATE
+            SET url = excluded.url, secret = excluded.secret
+            "#,
+            customer,
+            webhook.url,
+            webhook.secret
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Returns false if the customer had no webhook.
+    pub async fn remove_webhook(
+        &mut self,
+        customer: &CustomerName,
+    ) -> Result<bool, DatabaseErrors> {
+        let customer = customer.as_str();
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM webhooks
+            WHERE customer = ?1
+            "#,
+            customer
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+            Ok(result) => Ok(result.rows_affected() > 0),
+        }
+    }
+
+    pub async fn get_webhook(
+        &mut self,
+        customer: &CustomerName,
+    ) -> Result<Option<Webhook>, DatabaseErrors> {
+        let customer = customer.as_str();
+        let result = sqlx::query!(
+            r#"
+            SELECT url, secret
+            FROM webhooks
+            WHERE customer = ?1
+            "#,
+            customer
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+            Ok(record) => Ok(record.map(|record| Webhook {
+                url: record.url,
+                secret: record.secret,
+            })),
+        }
+    }
+
+    pub async fn add_delivery_attempt(
+        &mut self,
+        attempt: &DeliveryAttempt,
+    ) -> Result<(), DatabaseErrors> {
+        let customer = attempt.customer.as_str();
+        let status = attempt.status.map(i64::from);
+        let sent_at = attempt.sent_at.as_millis();
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO webhook_deliveries
+                ( customer, url, event, payload, attempt, status, error, success, sent_at )
+            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9 )
+            "#,
+            customer,
+            attempt.url,
+            attempt.event,
+            attempt.payload,
+            attempt.attempt,
+            status,
+            attempt.error,
+            attempt.success,
+            sent_at
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Err(error) => Err(DatabaseErrors::RequestError(error.to_string())),
+            Ok(_) => Ok(()),
+        }
+    }
+}
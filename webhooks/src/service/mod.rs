@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use amqp::Publisher;
+use domain::{
+    codec::{Correlated, WireMessage},
+    models::{CustomerName, Timestamp, WebhookEvent},
+    requests::WebhookRequest,
+    responses::WebhookResponse,
+};
+use serde_json::json;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::{
+    dispatcher::{Delivery, Dispatcher},
+    repository::{SqliteRepository, Webhook},
+    Config,
+};
+
+const SECRET_LENGTH: usize = 32;
+
+pub struct WebhookService {
+    config: Config,
+    repository: Arc<Mutex<SqliteRepository>>,
+    dispatcher: Dispatcher,
+    publisher: Publisher,
+}
+
+impl WebhookService {
+    pub fn new(config: Config, repository: SqliteRepository, publisher: Publisher) -> Self {
+        let repository = Arc::new(Mutex::new(repository));
+        let dispatcher = Dispatcher::new(&config, repository.clone());
+        WebhookService {
+            config,
+            repository,
+            dispatcher,
+            publisher,
+        }
+    }
+
+    pub async fn handle_request(&mut self, request: Correlated<WebhookRequest>) {
+        let Correlated {
+            request_id,
+            message: request,
+        } = request;
+        match request {
+            WebhookRequest::Register {
+                user_id,
+                customer,
+                url: Some(url),
+            } => {
+                let response = self.register(user_id, &customer, url).await;
+                self.publish_response(request_id, response).await;
+            }
+            WebhookRequest::Register {
+                user_id,
+                customer,
+                url: None,
+            } => {
+                let response = match self.repository.lock().await.remove_webhook(&customer).await {
+                    Ok(_) => WebhookResponse::Removed { user_id },
+                    Err(error) => {
+                        log::error!("Failed to remove webhook of [{}]: {}", customer, error);
+                        WebhookResponse::Failure { user_id }
+                    }
+                };
+                self.publish_response(request_id, response).await;
+            }
+            WebhookRequest::Event {
+                customer,
+                event,
+                timestamp,
+            } => self.dispatch_event(customer, event, timestamp).await,
+        }
+    }
+
+    /// Registering again replaces the url and issues a new secret.
+    async fn register(
+        &mut self,
+        user_id: u32,
+        customer: &CustomerName,
+        url: String,
+    ) -> WebhookResponse {
+        if !is_webhook_url(&url) {
+            return WebhookResponse::InvalidUrl { user_id, url };
+        }
+        let secret = hex::encode(rand::random::<[u8; SECRET_LENGTH]>());
+        let webhook = Webhook { url, secret };
+        match self
+            .repository
+            .lock()
+            .await
+            .set_webhook(customer, &webhook)
+            .await
+        {
+            Ok(()) => WebhookResponse::Registered {
+                user_id,
+                url: webhook.url,
+                secret: webhook.secret,
+            },
+            Err(error) => {
+                log::error!("Failed to register webhook of [{}]: {}", customer, error);
+                WebhookResponse::Failure { user_id }
+            }
+        }
+    }
+
+    async fn dispatch_event(
+        &mut self,
+        customer: CustomerName,
+        event: WebhookEvent,
+        timestamp: Timestamp,
+    ) {
+        let webhook = match self.repository.lock().await.get_webhook(&customer).await {
+            Ok(Some(webhook)) => webhook,
+            Ok(None) => return,
+            Err(error) => {
+                log::error!("Failed to get webhook of [{}]: {}", customer, error);
+                return;
+            }
+        };
+        let payload = payload(&customer, &event, timestamp);
+        self.dispatcher.dispatch(Delivery {
+            customer,
+            webhook,
+            event: event.name().to_string(),
+            payload,
+        });
+    }
+
+    async fn publish_response(&mut self, request_id: u64, response: WebhookResponse) {
+        if let Err(error) = self
+            .publisher
+            .publish_message(
+                &self.config.exchange,
+                &self.config.webhook_response_queue,
+                Correlated::new(request_id, response).encode(self.config.wire_format),
+            )
+            .await
+        {
+            log::error!("Failed to publish webhook response: {}", error);
+        }
+    }
+}
+
+/// Webhooks must use HTTPS, plain HTTP is accepted for local stubs only.
+fn is_webhook_url(url: &str) -> bool {
+    match Url::parse(url) {
+        Ok(url) => match url.scheme() {
+            "https" => url.host().is_some(),
+            "http" => matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")),
+            _ => false,
+        },
+        Err(_) => false,
+    }
+}
+
+fn payload(customer: &CustomerName, event: &WebhookEvent, timestamp: Timestamp) -> String {
+    let data = match event {
+        WebhookEvent::NewSubscriber { product } => json!({ "product": product }),
+        WebhookEvent::DeliveryFinished {
+            product,
+            published,
+            failed,
+            total,
+        } => json!({
+            "product": product,
+            "published": published,
+            "failed": failed,
+            "total": total,
+        }),
+    };
+    json!({
+        "event": event.name(),
+        "customer": customer,
+        "timestamp": timestamp.as_millis(),
+        "data": data,
+    })
+    .to_string()
+}